@@ -0,0 +1,159 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use tokio_util::{sync::CancellationToken, task::TaskTracker};
+
+use crate::{
+    config::BrokerConfig,
+    metadata::{MetadataCache, MetadataImage, MetadataLoader, MetadataWriter},
+    metrics::Metrics,
+    model::{BrokerRegistration, Listener},
+    network::ConnectionLimits,
+    quota::QuotaManager,
+    security::Authorizer,
+};
+
+// State shared by every connection.
+pub struct Broker {
+    config: BrokerConfig,
+    metadata: Arc<MetadataCache>,
+    metadata_writer: MetadataWriter,
+    authorizer: Authorizer,
+    quotas: QuotaManager,
+    metrics: Metrics,
+    shutdown: CancellationToken,
+    connections: TaskTracker,
+    connection_limits: ConnectionLimits,
+    cluster_id: Option<String>,
+}
+impl Broker {
+    pub fn new(config: BrokerConfig, metadata: Arc<MetadataCache>) -> Self {
+        let metadata_writer = MetadataWriter::new(config.metadata_log_dir());
+        let authorizer = Authorizer::new(&config);
+        let quotas = QuotaManager::new(&config);
+        Self {
+            config,
+            metadata,
+            metadata_writer,
+            authorizer,
+            quotas,
+            metrics: Metrics::new(),
+            shutdown: CancellationToken::new(),
+            connections: TaskTracker::new(),
+            connection_limits: ConnectionLimits::new(),
+            cluster_id: None,
+        }
+    }
+
+    // Polls `loader` after every append, so that changes the broker writes
+    // itself are visible to the next request.
+    pub fn with_metadata_loader(mut self, loader: Arc<Mutex<MetadataLoader>>) -> Self {
+        self.metadata_writer = MetadataWriter::new(self.config.metadata_log_dir())
+            .with_loader(loader, self.metadata.clone());
+        self
+    }
+
+    // The cluster the log directories were formatted for.
+    pub fn with_cluster_id(mut self, cluster_id: &str) -> Self {
+        self.cluster_id = Some(cluster_id.to_string());
+        self
+    }
+
+    pub fn cluster_id(&self) -> Option<&str> {
+        self.cluster_id.as_deref()
+    }
+
+    pub fn config(&self) -> &BrokerConfig {
+        &self.config
+    }
+
+    pub fn metadata(&self) -> &MetadataCache {
+        &self.metadata
+    }
+
+    pub fn metadata_writer(&self) -> &MetadataWriter {
+        &self.metadata_writer
+    }
+
+    pub fn authorizer(&self) -> &Authorizer {
+        &self.authorizer
+    }
+
+    pub fn quotas(&self) -> &QuotaManager {
+        &self.quotas
+    }
+
+    pub fn metrics(&self) -> &Metrics {
+        &self.metrics
+    }
+
+    // Cancelled once the broker starts shutting down, after which connections
+    // stop reading requests.
+    pub fn shutdown(&self) -> &CancellationToken {
+        &self.shutdown
+    }
+
+    // Every open connection, so that a shutdown can wait for them to finish.
+    pub fn connections(&self) -> &TaskTracker {
+        &self.connections
+    }
+
+    pub fn connection_limits(&self) -> &ConnectionLimits {
+        &self.connection_limits
+    }
+
+    // Unfenced brokers of the image, sorted by id. Until the controller has
+    // seen this broker register, it stands in for itself with its advertised
+    // listeners.
+    pub fn live_brokers(&self, image: &MetadataImage) -> Vec<BrokerRegistration> {
+        let mut brokers = image
+            .brokers()
+            .values()
+            .filter(|broker| !broker.fenced())
+            .cloned()
+            .collect::<Vec<_>>();
+        let node_id = self.config.node_id();
+        if node_id >= 0 && !image.brokers().contains_key(&node_id) {
+            brokers.push(self.registration());
+        }
+        brokers.sort_by_key(|broker| broker.id());
+        brokers
+    }
+
+    fn registration(&self) -> BrokerRegistration {
+        let listeners = self
+            .config
+            .advertised_listeners()
+            .iter()
+            .map(|endpoint| {
+                let host = match endpoint.host() {
+                    "" => "localhost",
+                    host => host,
+                };
+                let security_protocol = self
+                    .config
+                    .security_protocol(endpoint.listener_name())
+                    .map(|protocol| protocol.id())
+                    .unwrap_or(0);
+                Listener::new(
+                    endpoint.listener_name(),
+                    host,
+                    endpoint.port(),
+                    security_protocol,
+                )
+            })
+            .collect();
+        BrokerRegistration::new(
+            self.config.node_id(),
+            -1,
+            uuid::Uuid::nil(),
+            listeners,
+            HashMap::new(),
+            self.config.get("broker.rack").map(str::to_string),
+            false,
+            false,
+        )
+    }
+}
@@ -0,0 +1,496 @@
+use std::collections::HashMap;
+
+use crate::model::ConfigResource;
+
+use super::BrokerConfig;
+
+// Where a config value comes from, most specific first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigSource {
+    DynamicTopicConfig,
+    DynamicBrokerConfig,
+    DynamicDefaultBrokerConfig,
+    StaticBrokerConfig,
+    DefaultConfig,
+    DynamicBrokerLoggerConfig,
+}
+impl ConfigSource {
+    pub fn id(&self) -> i8 {
+        match self {
+            ConfigSource::DynamicTopicConfig => 1,
+            ConfigSource::DynamicBrokerConfig => 2,
+            ConfigSource::DynamicDefaultBrokerConfig => 3,
+            ConfigSource::StaticBrokerConfig => 4,
+            ConfigSource::DefaultConfig => 5,
+            ConfigSource::DynamicBrokerLoggerConfig => 6,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigType {
+    Boolean,
+    String,
+    Int,
+    Short,
+    Long,
+    Double,
+    List,
+    Class,
+    Password,
+}
+impl ConfigType {
+    pub fn id(&self) -> i8 {
+        match self {
+            ConfigType::Boolean => 1,
+            ConfigType::String => 2,
+            ConfigType::Int => 3,
+            ConfigType::Short => 4,
+            ConfigType::Long => 5,
+            ConfigType::Double => 6,
+            ConfigType::List => 7,
+            ConfigType::Class => 8,
+            ConfigType::Password => 9,
+        }
+    }
+
+    fn validate(&self, value: &str) -> Result<(), &'static str> {
+        let value = value.trim();
+        let valid = match self {
+            ConfigType::Boolean => {
+                value.eq_ignore_ascii_case("true") || value.eq_ignore_ascii_case("false")
+            }
+            ConfigType::Int => value.parse::<i32>().is_ok(),
+            ConfigType::Short => value.parse::<i16>().is_ok(),
+            ConfigType::Long => value.parse::<i64>().is_ok(),
+            ConfigType::Double => value.parse::<f64>().is_ok(),
+            ConfigType::String | ConfigType::List | ConfigType::Class | ConfigType::Password => {
+                true
+            }
+        };
+        if valid {
+            Ok(())
+        } else {
+            Err(match self {
+                ConfigType::Boolean => "expected true or false",
+                _ => "not a valid number",
+            })
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct ConfigDef {
+    name: &'static str,
+    r#type: ConfigType,
+    default: Option<&'static str>,
+    // Broker configs only; every topic config can be altered.
+    dynamic: bool,
+    // Broker configs this topic config falls back to, with the factor that
+    // turns their value into the topic's unit, e.g. hours into milliseconds.
+    synonyms: &'static [(&'static str, i64)],
+    valid_values: &'static [&'static str],
+    documentation: &'static str,
+}
+impl ConfigDef {
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    pub fn r#type(&self) -> ConfigType {
+        self.r#type
+    }
+
+    pub fn default(&self) -> Option<&'static str> {
+        self.default
+    }
+
+    pub fn documentation(&self) -> &'static str {
+        self.documentation
+    }
+
+    pub fn is_sensitive(&self) -> bool {
+        self.r#type == ConfigType::Password
+    }
+
+    pub fn validate(&self, value: &str) -> Result<(), String> {
+        self.r#type.validate(value).map_err(|reason| {
+            format!(
+                "Invalid value {} for configuration {}: {}",
+                value, self.name, reason
+            )
+        })?;
+        if self.valid_values.is_empty() {
+            return Ok(());
+        }
+
+        let values = match self.r#type {
+            ConfigType::List => value
+                .split(',')
+                .map(str::trim)
+                .filter(|value| !value.is_empty())
+                .collect(),
+            _ => vec![value.trim()],
+        };
+        match values
+            .iter()
+            .find(|value| !self.valid_values.contains(value))
+        {
+            Some(invalid) => Err(format!(
+                "Invalid value {} for configuration {}: String must be one of: {}",
+                invalid,
+                self.name,
+                self.valid_values.join(", ")
+            )),
+            None => Ok(()),
+        }
+    }
+}
+
+macro_rules! config_defs {
+    ($(($name:literal, $type:ident, $default:expr, $dynamic:literal, [$(($synonym:literal, $factor:literal)),*], [$($valid:literal),*], $doc:literal),)*) => {
+        &[$(ConfigDef {
+            name: $name,
+            r#type: ConfigType::$type,
+            default: $default,
+            dynamic: $dynamic,
+            synonyms: &[$(($synonym, $factor)),*],
+            valid_values: &[$($valid),*],
+            documentation: $doc,
+        },)*]
+    };
+}
+
+#[rustfmt::skip]
+pub static TOPIC_CONFIGS: &[ConfigDef] = config_defs![
+    ("cleanup.policy", List, Some("delete"), true, [("log.cleanup.policy", 1)], ["delete", "compact"], "The retention policy to use on log segments."),
+    ("compression.type", String, Some("producer"), true, [("compression.type", 1)], ["uncompressed", "zstd", "lz4", "snappy", "gzip", "producer"], "The final compression type for a given topic."),
+    ("delete.retention.ms", Long, Some("86400000"), true, [("log.cleaner.delete.retention.ms", 1)], [], "The amount of time to retain delete tombstone markers for log compacted topics."),
+    ("file.delete.delay.ms", Long, Some("60000"), true, [("log.segment.delete.delay.ms", 1)], [], "The time to wait before deleting a file from the filesystem."),
+    ("flush.messages", Long, Some("9223372036854775807"), true, [("log.flush.interval.messages", 1)], [], "The number of messages written to a log partition before an fsync is forced."),
+    ("flush.ms", Long, Some("9223372036854775807"), true, [("log.flush.interval.ms", 1)], [], "The time interval after which an fsync of the log is forced."),
+    ("index.interval.bytes", Int, Some("4096"), true, [("log.index.interval.bytes", 1)], [], "How frequently an entry is added to the offset index."),
+    ("max.compaction.lag.ms", Long, Some("9223372036854775807"), true, [("log.cleaner.max.compaction.lag.ms", 1)], [], "The maximum time a message will remain ineligible for compaction in the log."),
+    ("max.message.bytes", Int, Some("1048588"), true, [("message.max.bytes", 1)], [], "The largest record batch size allowed by Kafka."),
+    ("message.timestamp.type", String, Some("CreateTime"), true, [("log.message.timestamp.type", 1)], ["CreateTime", "LogAppendTime"], "Define whether the timestamp in the message is message create time or log append time."),
+    ("min.cleanable.dirty.ratio", Double, Some("0.5"), true, [("log.cleaner.min.cleanable.ratio", 1)], [], "How frequently the log compactor will attempt to clean the log."),
+    ("min.compaction.lag.ms", Long, Some("0"), true, [("log.cleaner.min.compaction.lag.ms", 1)], [], "The minimum time a message will remain uncompacted in the log."),
+    ("min.insync.replicas", Int, Some("1"), true, [("min.insync.replicas", 1)], [], "The minimum number of replicas that must acknowledge a write for it to be considered successful."),
+    ("preallocate", Boolean, Some("false"), true, [("log.preallocate", 1)], [], "True if we should preallocate the file on disk when creating a new log segment."),
+    ("retention.bytes", Long, Some("-1"), true, [("log.retention.bytes", 1)], [], "The maximum size a partition can grow to before old log segments are discarded."),
+    ("retention.ms", Long, Some("604800000"), true, [("log.retention.ms", 1), ("log.retention.minutes", 60000), ("log.retention.hours", 3600000)], [], "The maximum time a log is retained before old log segments are discarded."),
+    ("segment.bytes", Int, Some("1073741824"), true, [("log.segment.bytes", 1)], [], "The segment file size for the log."),
+    ("segment.index.bytes", Int, Some("10485760"), true, [("log.index.size.max.bytes", 1)], [], "The size of the index that maps offsets to file positions."),
+    ("segment.jitter.ms", Long, Some("0"), true, [("log.roll.jitter.ms", 1), ("log.roll.jitter.hours", 3600000)], [], "The maximum random jitter subtracted from the scheduled segment roll time."),
+    ("segment.ms", Long, Some("604800000"), true, [("log.roll.ms", 1), ("log.roll.hours", 3600000)], [], "The period of time after which Kafka will force the log to roll."),
+    ("unclean.leader.election.enable", Boolean, Some("false"), true, [("unclean.leader.election.enable", 1)], [], "Whether replicas not in the ISR may be elected as leader as a last resort."),
+];
+
+#[rustfmt::skip]
+pub static BROKER_CONFIGS: &[ConfigDef] = config_defs![
+    ("advertised.listeners", String, None, false, [], [], "Listeners to publish for clients to use."),
+    ("allow.everyone.if.no.acl.found", Boolean, Some("false"), false, [], [], "If no resource patterns match a resource, allow everyone to access it."),
+    ("authorizer.class.name", String, Some(""), false, [], [], "The fully qualified name of a class that implements the Authorizer interface."),
+    ("auto.create.topics.enable", Boolean, Some("true"), false, [], [], "Enable auto creation of topics on the server."),
+    ("broker.rack", String, None, false, [], [], "Rack of the broker."),
+    ("compression.type", String, Some("producer"), true, [], ["uncompressed", "zstd", "lz4", "snappy", "gzip", "producer"], "The final compression type for a given topic."),
+    ("connections.max.idle.ms", Long, Some("600000"), false, [], [], "Idle connections timeout: the server socket processor threads close the connections that idle more than this."),
+    ("controller.listener.names", String, None, false, [], [], "A comma-separated list of the names of the listeners used by the controller."),
+    ("default.replication.factor", Int, Some("1"), false, [], [], "The default replication factor for automatically created topics."),
+    ("inter.broker.listener.name", String, None, false, [], [], "Name of listener used for communication between brokers."),
+    ("listener.security.protocol.map", String, Some("PLAINTEXT:PLAINTEXT,SSL:SSL,SASL_PLAINTEXT:SASL_PLAINTEXT,SASL_SSL:SASL_SSL"), false, [], [], "Map between listener names and security protocols."),
+    ("listeners", String, Some("PLAINTEXT://:9092"), false, [], [], "Listener List - Comma-separated list of URIs we will listen on and the listener names."),
+    ("log.cleaner.delete.retention.ms", Long, Some("86400000"), true, [], [], "The amount of time to retain tombstone message markers for log compacted topics."),
+    ("log.cleaner.max.compaction.lag.ms", Long, Some("9223372036854775807"), true, [], [], "The maximum time a message will remain ineligible for compaction in the log."),
+    ("log.cleaner.min.cleanable.ratio", Double, Some("0.5"), true, [], [], "The minimum ratio of dirty log to total log for a log to eligible for cleaning."),
+    ("log.cleaner.min.compaction.lag.ms", Long, Some("0"), true, [], [], "The minimum time a message will remain uncompacted in the log."),
+    ("log.cleanup.policy", List, Some("delete"), true, [], ["delete", "compact"], "The default cleanup policy for segments beyond the retention window."),
+    ("log.dirs", String, Some("/tmp/kraft-combined-logs"), false, [], [], "A comma-separated list of the directories where the log data is stored."),
+    ("log.flush.interval.messages", Long, Some("9223372036854775807"), true, [], [], "The number of messages accumulated on a log partition before messages are flushed to disk."),
+    ("log.flush.interval.ms", Long, None, true, [], [], "The maximum time in ms that a message in any topic is kept in memory before flushed to disk."),
+    ("log.index.interval.bytes", Int, Some("4096"), true, [], [], "The interval with which we add an entry to the offset index."),
+    ("log.index.size.max.bytes", Int, Some("10485760"), true, [], [], "The maximum size in bytes of the offset index."),
+    ("log.level", String, Some("INFO"), false, [], ["OFF", "FATAL", "ERROR", "WARN", "INFO", "DEBUG", "TRACE", "ALL"], "The level of the root logger at startup."),
+    ("log.message.timestamp.type", String, Some("CreateTime"), true, [], ["CreateTime", "LogAppendTime"], "Define whether the timestamp in the message is message create time or log append time."),
+    ("log.preallocate", Boolean, Some("false"), true, [], [], "Should pre allocate file when create new segment?"),
+    ("log.retention.bytes", Long, Some("-1"), true, [], [], "The maximum size of the log before deleting it."),
+    ("log.retention.hours", Int, Some("168"), false, [], [], "The number of hours to keep a log file before deleting it."),
+    ("log.retention.minutes", Int, None, false, [], [], "The number of minutes to keep a log file before deleting it."),
+    ("log.retention.ms", Long, None, true, [], [], "The number of milliseconds to keep a log file before deleting it."),
+    ("log.roll.hours", Int, Some("168"), false, [], [], "The maximum time before a new log segment is rolled out (in hours)."),
+    ("log.roll.jitter.hours", Int, Some("0"), false, [], [], "The maximum jitter to subtract from logRollTimeMillis (in hours)."),
+    ("log.roll.jitter.ms", Long, None, true, [], [], "The maximum jitter to subtract from logRollTimeMillis (in milliseconds)."),
+    ("log.roll.ms", Long, None, true, [], [], "The maximum time before a new log segment is rolled out (in milliseconds)."),
+    ("log.segment.bytes", Int, Some("1073741824"), true, [], [], "The maximum size of a single log file."),
+    ("log.segment.delete.delay.ms", Long, Some("60000"), true, [], [], "The amount of time to wait before deleting a file from the filesystem."),
+    ("max.connections", Int, Some("2147483647"), false, [], [], "The maximum number of connections we allow in the broker at any time."),
+    ("max.connections.per.ip", Int, Some("2147483647"), false, [], [], "The maximum number of connections we allow from each ip address."),
+    ("max.connections.per.ip.overrides", String, Some(""), false, [], [], "A comma-separated list of ip:count overrides to the default maximum number of connections per ip."),
+    ("message.max.bytes", Int, Some("1048588"), true, [], [], "The largest record batch size allowed by Kafka."),
+    ("metadata.log.dir", String, None, false, [], [], "The directory in which the metadata log is kept."),
+    ("metadata.log.max.record.bytes.between.snapshots", Long, Some("20971520"), false, [], [], "The maximum number of bytes in the log between the latest snapshot and the high-watermark needed before generating a new snapshot."),
+    ("metrics.host", String, None, false, [], [], "The host the Prometheus metrics endpoint binds to. All interfaces if unset."),
+    ("metrics.port", Int, None, false, [], [], "The port of the Prometheus metrics endpoint. Disabled if unset."),
+    ("min.insync.replicas", Int, Some("1"), true, [], [], "The minimum number of replicas that must acknowledge a write for it to be considered successful."),
+    ("node.id", Int, Some("-1"), false, [], [], "The node ID associated with the roles this process is playing."),
+    ("num.partitions", Int, Some("1"), false, [], [], "The default number of log partitions per topic."),
+    ("process.roles", List, Some("broker"), false, [], ["broker", "controller"], "The roles that this process plays."),
+    ("quota.window.num", Int, Some("11"), false, [], [], "The number of samples to retain in memory for client quotas."),
+    ("quota.window.size.seconds", Int, Some("1"), false, [], [], "The time span of each sample for client quotas."),
+    ("request.log.file", String, None, false, [], [], "The file completed requests are logged to as JSON lines. Standard output if unset."),
+    ("request.log.level", String, Some("WARN"), false, [], ["OFF", "FATAL", "ERROR", "WARN", "INFO", "DEBUG", "TRACE", "ALL"], "The level of kafka.request.logger at startup; DEBUG logs every completed request."),
+    ("sasl.enabled.mechanisms", List, Some("PLAIN,SCRAM-SHA-256,SCRAM-SHA-512"), false, [], ["PLAIN", "SCRAM-SHA-256", "SCRAM-SHA-512"], "The list of SASL mechanisms enabled in the Kafka server."),
+    ("sasl.plain.credentials.file", String, None, false, [], [], "A properties file of user=password pairs that SASL/PLAIN authenticates against."),
+    ("shutdown.timeout.ms", Long, Some("30000"), false, [], [], "The maximum time a shutdown waits for connections to answer the requests they already read."),
+    ("socket.request.max.bytes", Int, Some("104857600"), false, [], [], "The maximum number of bytes in a socket request."),
+    ("ssl.certificate.location", String, None, false, [], [], "The location of the PEM certificate chain presented by SSL and SASL_SSL listeners."),
+    ("ssl.client.auth", String, Some("none"), false, [], ["required", "requested", "none"], "Configures the broker to request client authentication."),
+    ("ssl.key.location", String, None, false, [], [], "The location of the PEM private key for the certificate in ssl.certificate.location."),
+    ("ssl.key.password", Password, None, true, [], [], "The password of the private key in the key store file."),
+    ("ssl.keystore.password", Password, None, true, [], [], "The store password for the key store file."),
+    ("ssl.truststore.location", String, None, false, [], [], "The location of the PEM certificates trusted to sign client certificates."),
+    ("ssl.truststore.password", Password, None, true, [], [], "The password for the trust store file."),
+    ("super.users", String, None, false, [], [], "List of users that are allowed to perform any action on any resource, separated by semicolons."),
+    ("unclean.leader.election.enable", Boolean, Some("false"), true, [], [], "Indicates whether to enable replicas not in the ISR set to be elected as leader as a last resort."),
+];
+
+pub fn topic_config(name: &str) -> Option<&'static ConfigDef> {
+    TOPIC_CONFIGS.iter().find(|def| def.name == name)
+}
+
+pub fn broker_config(name: &str) -> Option<&'static ConfigDef> {
+    BROKER_CONFIGS.iter().find(|def| def.name == name)
+}
+
+// Broker configs that may be changed through the metadata log.
+pub fn is_dynamic_broker_config(name: &str) -> bool {
+    broker_config(name).is_some_and(|def| def.dynamic)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigSynonym {
+    name: String,
+    value: Option<String>,
+    source: ConfigSource,
+}
+impl ConfigSynonym {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn value(&self) -> Option<&str> {
+        self.value.as_deref()
+    }
+
+    pub fn source(&self) -> ConfigSource {
+        self.source
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ConfigEntry {
+    def: &'static ConfigDef,
+    value: Option<String>,
+    source: ConfigSource,
+    read_only: bool,
+    synonyms: Vec<ConfigSynonym>,
+}
+impl ConfigEntry {
+    pub fn name(&self) -> &str {
+        self.def.name
+    }
+
+    pub fn value(&self) -> Option<&str> {
+        self.value.as_deref()
+    }
+
+    pub fn source(&self) -> ConfigSource {
+        self.source
+    }
+
+    pub fn read_only(&self) -> bool {
+        self.read_only
+    }
+
+    pub fn is_default(&self) -> bool {
+        self.source == ConfigSource::DefaultConfig
+    }
+
+    pub fn is_sensitive(&self) -> bool {
+        self.def.is_sensitive()
+    }
+
+    // All values that were considered, the effective one first.
+    pub fn synonyms(&self) -> &[ConfigSynonym] {
+        &self.synonyms
+    }
+
+    pub fn r#type(&self) -> ConfigType {
+        self.def.r#type
+    }
+
+    pub fn documentation(&self) -> &'static str {
+        self.def.documentation
+    }
+}
+
+// Resolves a config through its layers: the topic's own dynamic config, then
+// the broker's dynamic config, the cluster-wide dynamic broker default, the
+// static server.properties and finally the built-in default.
+pub struct ConfigResolver<'a> {
+    config: &'a BrokerConfig,
+    dynamic: &'a HashMap<ConfigResource, HashMap<String, String>>,
+}
+impl<'a> ConfigResolver<'a> {
+    pub fn new(
+        config: &'a BrokerConfig,
+        dynamic: &'a HashMap<ConfigResource, HashMap<String, String>>,
+    ) -> Self {
+        Self { config, dynamic }
+    }
+
+    pub fn topic_configs(&self, topic: &str) -> Vec<ConfigEntry> {
+        TOPIC_CONFIGS
+            .iter()
+            .map(|def| self.resolve_topic(topic, def))
+            .collect()
+    }
+
+    pub fn topic_config(&self, topic: &str, name: &str) -> Option<ConfigEntry> {
+        topic_config(name).map(|def| self.resolve_topic(topic, def))
+    }
+
+    pub fn broker_configs(&self, broker: &str) -> Vec<ConfigEntry> {
+        BROKER_CONFIGS
+            .iter()
+            .map(|def| self.resolve_broker(broker, def))
+            .collect()
+    }
+
+    pub fn broker_config(&self, broker: &str, name: &str) -> Option<ConfigEntry> {
+        broker_config(name).map(|def| self.resolve_broker(broker, def))
+    }
+
+    // The dynamic configs set directly on a resource.
+    pub fn dynamic_configs(&self, resource: &ConfigResource) -> Option<&HashMap<String, String>> {
+        self.dynamic.get(resource)
+    }
+
+    fn resolve_topic(&self, topic: &str, def: &'static ConfigDef) -> ConfigEntry {
+        let mut candidates = Vec::new();
+        if let Some(value) = self.dynamic_value(ConfigResource::TOPIC, topic, def.name) {
+            candidates.push((
+                synonym(def.name, value, ConfigSource::DynamicTopicConfig),
+                1,
+            ));
+        }
+        // Broker synonyms are ranked by source first, so a dynamic
+        // log.retention.hours beats a static log.retention.ms.
+        let node_id = self.config.node_id().to_string();
+        let mut broker_candidates = def
+            .synonyms
+            .iter()
+            .flat_map(|(name, factor)| {
+                self.broker_values(&node_id, name)
+                    .map(|(value, source)| (synonym(name, value, source), *factor))
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        broker_candidates.sort_by_key(|(synonym, _)| synonym.source.id());
+        candidates.extend(broker_candidates);
+
+        let (value, source) = match candidates.first() {
+            Some((first, factor)) => (scale(first.value.as_deref(), *factor), first.source),
+            None => (def.default.map(str::to_string), ConfigSource::DefaultConfig),
+        };
+        let mut synonyms = candidates
+            .into_iter()
+            .map(|(synonym, _)| synonym)
+            .collect::<Vec<_>>();
+        if let Some(default) = def.default {
+            synonyms.push(synonym(def.name, default, ConfigSource::DefaultConfig));
+        }
+
+        ConfigEntry {
+            def,
+            value,
+            source,
+            read_only: false,
+            synonyms,
+        }
+    }
+
+    fn resolve_broker(&self, broker: &str, def: &'static ConfigDef) -> ConfigEntry {
+        let mut synonyms = self
+            .broker_values(broker, def.name)
+            .map(|(value, source)| synonym(def.name, value, source))
+            .collect::<Vec<_>>();
+        if let Some(default) = def.default {
+            synonyms.push(synonym(def.name, default, ConfigSource::DefaultConfig));
+        }
+
+        let (value, source) = synonyms
+            .first()
+            .map(|first| (first.value.clone(), first.source))
+            .unwrap_or((None, ConfigSource::DefaultConfig));
+        ConfigEntry {
+            def,
+            value,
+            source,
+            read_only: !def.dynamic,
+            synonyms,
+        }
+    }
+
+    fn broker_values<'n>(
+        &'n self,
+        broker: &'n str,
+        name: &'n str,
+    ) -> impl Iterator<Item = (&'n str, ConfigSource)> {
+        let static_value = if broker.is_empty() {
+            None
+        } else {
+            self.config.get(name)
+        };
+        [
+            (
+                self.dynamic_value(ConfigResource::BROKER, broker, name)
+                    .filter(|_| !broker.is_empty()),
+                ConfigSource::DynamicBrokerConfig,
+            ),
+            (
+                self.dynamic_value(ConfigResource::BROKER, "", name),
+                ConfigSource::DynamicDefaultBrokerConfig,
+            ),
+            (static_value, ConfigSource::StaticBrokerConfig),
+        ]
+        .into_iter()
+        .filter_map(|(value, source)| value.map(|value| (value, source)))
+    }
+
+    fn dynamic_value(&self, resource_type: i8, name: &str, key: &str) -> Option<&'a str> {
+        self.dynamic
+            .get(&ConfigResource::new(resource_type, name))
+            .and_then(|configs| configs.get(key))
+            .map(String::as_str)
+    }
+}
+
+fn synonym(name: &str, value: &str, source: ConfigSource) -> ConfigSynonym {
+    ConfigSynonym {
+        name: name.to_string(),
+        value: Some(value.to_string()),
+        source,
+    }
+}
+
+fn scale(value: Option<&str>, factor: i64) -> Option<String> {
+    let value = value?;
+    if factor == 1 {
+        return Some(value.to_string());
+    }
+    match value.trim().parse::<i64>() {
+        Ok(value) => Some(value.saturating_mul(factor).to_string()),
+        Err(_) => Some(value.to_string()),
+    }
+}
@@ -0,0 +1,785 @@
+pub mod dynamic;
+mod properties;
+
+use std::{
+    collections::HashMap,
+    fmt,
+    net::IpAddr,
+    path::{Path, PathBuf},
+    str::FromStr,
+    time::Duration,
+};
+
+use crate::security::SaslMechanism;
+
+pub use properties::parse_properties;
+
+pub const ENV_PREFIX: &str = "KAFKA_CFG_";
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("unable to read {path}: {source}")]
+    IOError {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+
+    #[error("invalid value {value:?} for configuration {key}: {reason}")]
+    InvalidValue {
+        key: String,
+        value: String,
+        reason: &'static str,
+    },
+
+    #[error("invalid argument: {0}")]
+    InvalidArgument(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SecurityProtocol {
+    Plaintext,
+    Ssl,
+    SaslPlaintext,
+    SaslSsl,
+}
+impl SecurityProtocol {
+    pub fn name(&self) -> &'static str {
+        match self {
+            SecurityProtocol::Plaintext => "PLAINTEXT",
+            SecurityProtocol::Ssl => "SSL",
+            SecurityProtocol::SaslPlaintext => "SASL_PLAINTEXT",
+            SecurityProtocol::SaslSsl => "SASL_SSL",
+        }
+    }
+
+    // The id used on the wire, e.g. in RegisterBrokerRecord endpoints.
+    pub fn id(&self) -> i16 {
+        match self {
+            SecurityProtocol::Plaintext => 0,
+            SecurityProtocol::Ssl => 1,
+            SecurityProtocol::SaslPlaintext => 2,
+            SecurityProtocol::SaslSsl => 3,
+        }
+    }
+}
+impl FromStr for SecurityProtocol {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_uppercase().as_str() {
+            "PLAINTEXT" => Ok(SecurityProtocol::Plaintext),
+            "SSL" => Ok(SecurityProtocol::Ssl),
+            "SASL_PLAINTEXT" => Ok(SecurityProtocol::SaslPlaintext),
+            "SASL_SSL" => Ok(SecurityProtocol::SaslSsl),
+            _ => Err("unknown security protocol"),
+        }
+    }
+}
+impl fmt::Display for SecurityProtocol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+// Whether SSL listeners ask clients for a certificate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SslClientAuth {
+    None,
+    Requested,
+    Required,
+}
+impl FromStr for SslClientAuth {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "none" => Ok(SslClientAuth::None),
+            "requested" => Ok(SslClientAuth::Requested),
+            "required" => Ok(SslClientAuth::Required),
+            _ => Err("expected required, requested or none"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Endpoint {
+    listener_name: String,
+    host: String,
+    port: u16,
+}
+impl Endpoint {
+    pub fn new(listener_name: &str, host: &str, port: u16) -> Self {
+        Self {
+            listener_name: listener_name.to_string(),
+            host: host.to_string(),
+            port,
+        }
+    }
+
+    pub fn listener_name(&self) -> &str {
+        &self.listener_name
+    }
+
+    pub fn host(&self) -> &str {
+        &self.host
+    }
+
+    pub fn port(&self) -> u16 {
+        self.port
+    }
+
+    // An empty host binds every interface, as Kafka does for `PLAINTEXT://:9092`.
+    pub fn bind_address(&self) -> String {
+        if self.host.is_empty() {
+            format!("0.0.0.0:{}", self.port)
+        } else {
+            format!("{}:{}", self.host, self.port)
+        }
+    }
+}
+impl FromStr for Endpoint {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (listener_name, address) = s
+            .trim()
+            .split_once("://")
+            .ok_or("listener must look like NAME://host:port")?;
+        let (host, port) = address
+            .rsplit_once(':')
+            .ok_or("listener must look like NAME://host:port")?;
+        let host = host.trim_start_matches('[').trim_end_matches(']');
+        let port = port.parse().map_err(|_| "invalid listener port")?;
+        Ok(Endpoint::new(
+            &listener_name.to_ascii_uppercase(),
+            host,
+            port,
+        ))
+    }
+}
+impl fmt::Display for Endpoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}://{}:{}", self.listener_name, self.host, self.port)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct BrokerConfig {
+    node_id: i32,
+    process_roles: Vec<String>,
+    listeners: Vec<Endpoint>,
+    advertised_listeners: Vec<Endpoint>,
+    listener_security_protocol_map: HashMap<String, SecurityProtocol>,
+    inter_broker_listener_name: String,
+    controller_listener_names: Vec<String>,
+    log_dirs: Vec<PathBuf>,
+    metadata_log_dir: PathBuf,
+    metadata_log_max_record_bytes_between_snapshots: u64,
+    num_partitions: i32,
+    default_replication_factor: i16,
+    log_retention_ms: i64,
+    log_retention_bytes: i64,
+    log_segment_bytes: i32,
+    log_cleanup_policy: Vec<String>,
+    socket_request_max_bytes: usize,
+    shutdown_timeout_ms: u64,
+    max_connections: usize,
+    max_connections_per_ip: usize,
+    max_connections_per_ip_overrides: HashMap<IpAddr, usize>,
+    connections_max_idle_ms: u64,
+    quota_window_num: usize,
+    quota_window_size_seconds: u64,
+    metrics_host: String,
+    metrics_port: Option<u16>,
+    log_level: String,
+    request_log_level: String,
+    request_log_file: Option<PathBuf>,
+    authorizer_class_name: Option<String>,
+    super_users: Vec<String>,
+    allow_everyone_if_no_acl_found: bool,
+    sasl_enabled_mechanisms: Vec<String>,
+    sasl_plain_credentials_file: Option<PathBuf>,
+    ssl_certificate_location: Option<PathBuf>,
+    ssl_key_location: Option<PathBuf>,
+    ssl_truststore_location: Option<PathBuf>,
+    ssl_client_auth: SslClientAuth,
+    properties: HashMap<String, String>,
+}
+impl BrokerConfig {
+    // The first positional argument is the properties file; `--override
+    // key=value` pairs win over `KAFKA_CFG_*` environment variables, which win over
+    // the file.
+    pub fn from_args<I: IntoIterator<Item = String>>(args: I) -> Result<Self, Error> {
+        let mut properties = HashMap::new();
+        let mut overrides = HashMap::new();
+
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            if arg == "--override" {
+                let value = args
+                    .next()
+                    .ok_or_else(|| Error::InvalidArgument("--override needs key=value".into()))?;
+                let (key, value) = value
+                    .split_once('=')
+                    .ok_or_else(|| Error::InvalidArgument(format!("--override {}", value)))?;
+                overrides.insert(key.trim().to_string(), value.trim().to_string());
+            } else if let Some(value) = arg.strip_prefix("--override=") {
+                let (key, value) = value
+                    .split_once('=')
+                    .ok_or_else(|| Error::InvalidArgument(format!("--override={}", value)))?;
+                overrides.insert(key.trim().to_string(), value.trim().to_string());
+            } else {
+                properties.extend(Self::read_properties_file(Path::new(&arg))?);
+            }
+        }
+
+        properties.extend(env_overrides(std::env::vars()));
+        properties.extend(overrides);
+        Self::from_properties(properties)
+    }
+
+    pub fn read_properties_file(path: &Path) -> Result<HashMap<String, String>, Error> {
+        let content = std::fs::read_to_string(path).map_err(|source| Error::IOError {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        Ok(parse_properties(&content))
+    }
+
+    pub fn from_properties(properties: HashMap<String, String>) -> Result<Self, Error> {
+        let node_id = if properties.contains_key("node.id") {
+            parse(&properties, "node.id", -1)?
+        } else {
+            parse(&properties, "broker.id", -1)?
+        };
+        let process_roles = list(&properties, "process.roles", "broker");
+
+        let listeners = endpoints(&properties, "listeners", "PLAINTEXT://127.0.0.1:9092")?;
+        let controller_listener_names = list(&properties, "controller.listener.names", "")
+            .into_iter()
+            .map(|name| name.to_ascii_uppercase())
+            .collect::<Vec<_>>();
+        let advertised_listeners = if properties.contains_key("advertised.listeners") {
+            endpoints(&properties, "advertised.listeners", "")?
+        } else {
+            listeners
+                .iter()
+                .filter(|endpoint| !controller_listener_names.contains(&endpoint.listener_name))
+                .cloned()
+                .collect()
+        };
+
+        let mut listener_security_protocol_map = HashMap::from([
+            ("PLAINTEXT".to_string(), SecurityProtocol::Plaintext),
+            ("SSL".to_string(), SecurityProtocol::Ssl),
+            (
+                "SASL_PLAINTEXT".to_string(),
+                SecurityProtocol::SaslPlaintext,
+            ),
+            ("SASL_SSL".to_string(), SecurityProtocol::SaslSsl),
+        ]);
+        if let Some(value) = properties.get("listener.security.protocol.map") {
+            for entry in value.split(',').filter(|entry| !entry.trim().is_empty()) {
+                let (name, protocol) = entry.split_once(':').ok_or_else(|| {
+                    invalid(
+                        "listener.security.protocol.map",
+                        value,
+                        "NAME:PROTOCOL expected",
+                    )
+                })?;
+                let protocol = protocol
+                    .parse()
+                    .map_err(|reason| invalid("listener.security.protocol.map", value, reason))?;
+                listener_security_protocol_map.insert(name.trim().to_ascii_uppercase(), protocol);
+            }
+        }
+        let inter_broker_listener_name = properties
+            .get("inter.broker.listener.name")
+            .map(|name| name.trim().to_ascii_uppercase())
+            .unwrap_or_else(|| "PLAINTEXT".to_string());
+
+        let log_dirs = match properties.get("log.dirs").or(properties.get("log.dir")) {
+            Some(value) => value
+                .split(',')
+                .map(str::trim)
+                .filter(|dir| !dir.is_empty())
+                .map(PathBuf::from)
+                .collect::<Vec<_>>(),
+            None => vec![PathBuf::from("/tmp/kraft-combined-logs")],
+        };
+        if log_dirs.is_empty() {
+            return Err(invalid(
+                "log.dirs",
+                "",
+                "at least one log directory is required",
+            ));
+        }
+        let metadata_log_dir = properties
+            .get("metadata.log.dir")
+            .map(PathBuf::from)
+            .unwrap_or_else(|| log_dirs[0].clone());
+
+        let log_retention_ms = if properties.contains_key("log.retention.ms") {
+            parse(&properties, "log.retention.ms", -1i64)?
+        } else if properties.contains_key("log.retention.minutes") {
+            parse(&properties, "log.retention.minutes", 0i64)? * 60 * 1000
+        } else {
+            parse(&properties, "log.retention.hours", 168i64)? * 60 * 60 * 1000
+        };
+
+        let sasl_enabled_mechanisms = list(
+            &properties,
+            "sasl.enabled.mechanisms",
+            "PLAIN,SCRAM-SHA-256,SCRAM-SHA-512",
+        )
+        .into_iter()
+        .map(|mechanism| mechanism.to_ascii_uppercase())
+        .collect::<Vec<_>>();
+        if let Some(mechanism) = sasl_enabled_mechanisms
+            .iter()
+            .find(|mechanism| SaslMechanism::from_name(mechanism).is_none())
+        {
+            return Err(invalid(
+                "sasl.enabled.mechanisms",
+                mechanism,
+                "unsupported SASL mechanism",
+            ));
+        }
+
+        let max_connections_per_ip =
+            parse(&properties, "max.connections.per.ip", i32::MAX as usize)?;
+        let max_connections_per_ip_overrides = per_ip_overrides(&properties)?;
+        if max_connections_per_ip == 0 && max_connections_per_ip_overrides.is_empty() {
+            return Err(invalid(
+                "max.connections.per.ip",
+                "0",
+                "can be zero only if max.connections.per.ip.overrides is set",
+            ));
+        }
+
+        let config = Self {
+            node_id,
+            process_roles,
+            listeners,
+            advertised_listeners,
+            listener_security_protocol_map,
+            inter_broker_listener_name,
+            controller_listener_names,
+            log_dirs,
+            metadata_log_dir,
+            metadata_log_max_record_bytes_between_snapshots: parse(
+                &properties,
+                "metadata.log.max.record.bytes.between.snapshots",
+                20 * 1024 * 1024,
+            )?,
+            num_partitions: parse(&properties, "num.partitions", 1)?,
+            default_replication_factor: parse(&properties, "default.replication.factor", 1)?,
+            log_retention_ms,
+            log_retention_bytes: parse(&properties, "log.retention.bytes", -1)?,
+            log_segment_bytes: parse(&properties, "log.segment.bytes", 1024 * 1024 * 1024)?,
+            log_cleanup_policy: list(&properties, "log.cleanup.policy", "delete"),
+            socket_request_max_bytes: parse(&properties, "socket.request.max.bytes", 104857600)?,
+            shutdown_timeout_ms: parse(&properties, "shutdown.timeout.ms", 30000)?,
+            max_connections: parse(&properties, "max.connections", i32::MAX as usize)?,
+            max_connections_per_ip,
+            max_connections_per_ip_overrides,
+            connections_max_idle_ms: parse(&properties, "connections.max.idle.ms", 600000)?,
+            quota_window_num: positive(&properties, "quota.window.num", 11)?,
+            quota_window_size_seconds: positive(&properties, "quota.window.size.seconds", 1)?,
+            metrics_host: properties
+                .get("metrics.host")
+                .map(|host| host.trim().to_string())
+                .unwrap_or_default(),
+            metrics_port: match properties.get("metrics.port") {
+                Some(value) => Some(
+                    value
+                        .trim()
+                        .parse()
+                        .map_err(|_| invalid("metrics.port", value, "invalid port"))?,
+                ),
+                None => None,
+            },
+            log_level: properties
+                .get("log.level")
+                .map(|level| level.trim().to_string())
+                .unwrap_or_else(|| "INFO".to_string()),
+            request_log_level: properties
+                .get("request.log.level")
+                .map(|level| level.trim().to_string())
+                .unwrap_or_else(|| "WARN".to_string()),
+            request_log_file: path(&properties, "request.log.file"),
+            authorizer_class_name: properties
+                .get("authorizer.class.name")
+                .map(|name| name.trim().to_string())
+                .filter(|name| !name.is_empty()),
+            // Principals are separated by semicolons since they may contain
+            // commas themselves.
+            super_users: properties
+                .get("super.users")
+                .map(String::as_str)
+                .unwrap_or_default()
+                .split(';')
+                .map(str::trim)
+                .filter(|user| !user.is_empty())
+                .map(str::to_string)
+                .collect(),
+            allow_everyone_if_no_acl_found: parse(
+                &properties,
+                "allow.everyone.if.no.acl.found",
+                false,
+            )?,
+            sasl_enabled_mechanisms,
+            sasl_plain_credentials_file: path(&properties, "sasl.plain.credentials.file"),
+            ssl_certificate_location: path(&properties, "ssl.certificate.location"),
+            ssl_key_location: path(&properties, "ssl.key.location"),
+            ssl_truststore_location: path(&properties, "ssl.truststore.location"),
+            ssl_client_auth: match properties.get("ssl.client.auth") {
+                Some(value) => value
+                    .parse()
+                    .map_err(|reason| invalid("ssl.client.auth", value, reason))?,
+                None => SslClientAuth::None,
+            },
+            properties,
+        };
+        for endpoint in config.listeners.iter() {
+            config.security_protocol(endpoint.listener_name())?;
+        }
+        // Clients are handed the advertised endpoint of the listener they
+        // connected to, so every advertised listener has to be bound.
+        for endpoint in config.advertised_listeners.iter() {
+            let value = endpoint.to_string();
+            if !config
+                .listeners
+                .iter()
+                .any(|listener| listener.listener_name == endpoint.listener_name)
+            {
+                return Err(invalid(
+                    "advertised.listeners",
+                    &value,
+                    "listener is not one of listeners",
+                ));
+            }
+            if endpoint.host == "0.0.0.0" || endpoint.host == "::" {
+                return Err(invalid(
+                    "advertised.listeners",
+                    &value,
+                    "cannot advertise a wildcard address",
+                ));
+            }
+        }
+        Ok(config)
+    }
+
+    pub fn node_id(&self) -> i32 {
+        self.node_id
+    }
+
+    pub fn process_roles(&self) -> &[String] {
+        &self.process_roles
+    }
+
+    pub fn listeners(&self) -> &[Endpoint] {
+        &self.listeners
+    }
+
+    // Listeners that serve clients, i.e. everything but the controller ones.
+    pub fn broker_listeners(&self) -> impl Iterator<Item = &Endpoint> {
+        self.listeners.iter().filter(|endpoint| {
+            !self
+                .controller_listener_names
+                .contains(&endpoint.listener_name)
+        })
+    }
+
+    pub fn advertised_listeners(&self) -> &[Endpoint] {
+        &self.advertised_listeners
+    }
+
+    pub fn security_protocol(&self, listener_name: &str) -> Result<SecurityProtocol, Error> {
+        self.listener_security_protocol_map
+            .get(listener_name)
+            .copied()
+            .ok_or_else(|| {
+                invalid(
+                    "listener.security.protocol.map",
+                    listener_name,
+                    "no security protocol for listener",
+                )
+            })
+    }
+
+    pub fn inter_broker_listener_name(&self) -> &str {
+        &self.inter_broker_listener_name
+    }
+
+    pub fn controller_listener_names(&self) -> &[String] {
+        &self.controller_listener_names
+    }
+
+    pub fn log_dirs(&self) -> &[PathBuf] {
+        &self.log_dirs
+    }
+
+    pub fn metadata_log_dir(&self) -> &Path {
+        &self.metadata_log_dir
+    }
+
+    pub fn metadata_log_max_record_bytes_between_snapshots(&self) -> u64 {
+        self.metadata_log_max_record_bytes_between_snapshots
+    }
+
+    pub fn num_partitions(&self) -> i32 {
+        self.num_partitions
+    }
+
+    pub fn default_replication_factor(&self) -> i16 {
+        self.default_replication_factor
+    }
+
+    pub fn log_retention_ms(&self) -> i64 {
+        self.log_retention_ms
+    }
+
+    pub fn log_retention_bytes(&self) -> i64 {
+        self.log_retention_bytes
+    }
+
+    pub fn log_segment_bytes(&self) -> i32 {
+        self.log_segment_bytes
+    }
+
+    pub fn log_cleanup_policy(&self) -> &[String] {
+        &self.log_cleanup_policy
+    }
+
+    pub fn socket_request_max_bytes(&self) -> usize {
+        self.socket_request_max_bytes
+    }
+
+    // How long a shutdown waits for connections to finish the requests they
+    // already read.
+    pub fn shutdown_timeout(&self) -> Duration {
+        Duration::from_millis(self.shutdown_timeout_ms)
+    }
+
+    // The most connections the broker keeps open at once, over all listeners.
+    pub fn max_connections(&self) -> usize {
+        self.max_connections
+    }
+
+    // The most connections one address may have open, unless it has an
+    // override of its own.
+    pub fn max_connections_per_ip(&self, ip: &IpAddr) -> usize {
+        self.max_connections_per_ip_overrides
+            .get(ip)
+            .copied()
+            .unwrap_or(self.max_connections_per_ip)
+    }
+
+    // Connections without a request for this long are closed.
+    pub fn connections_max_idle(&self) -> Duration {
+        Duration::from_millis(self.connections_max_idle_ms)
+    }
+
+    pub fn quota_window_num(&self) -> usize {
+        self.quota_window_num
+    }
+
+    pub fn quota_window_size(&self) -> Duration {
+        Duration::from_secs(self.quota_window_size_seconds)
+    }
+
+    // Where the Prometheus metrics are served, if anywhere.
+    pub fn metrics_bind_address(&self) -> Option<String> {
+        let port = self.metrics_port?;
+        if self.metrics_host.is_empty() {
+            Some(format!("0.0.0.0:{}", port))
+        } else {
+            Some(format!("{}:{}", self.metrics_host, port))
+        }
+    }
+
+    pub fn log_level(&self) -> &str {
+        &self.log_level
+    }
+
+    pub fn request_log_level(&self) -> &str {
+        &self.request_log_level
+    }
+
+    pub fn request_log_file(&self) -> Option<&Path> {
+        self.request_log_file.as_deref()
+    }
+
+    pub fn authorizer_class_name(&self) -> Option<&str> {
+        self.authorizer_class_name.as_deref()
+    }
+
+    pub fn super_users(&self) -> &[String] {
+        &self.super_users
+    }
+
+    pub fn allow_everyone_if_no_acl_found(&self) -> bool {
+        self.allow_everyone_if_no_acl_found
+    }
+
+    pub fn sasl_enabled_mechanisms(&self) -> &[String] {
+        &self.sasl_enabled_mechanisms
+    }
+
+    pub fn sasl_plain_credentials_file(&self) -> Option<&Path> {
+        self.sasl_plain_credentials_file.as_deref()
+    }
+
+    pub fn ssl_certificate_location(&self) -> Option<&Path> {
+        self.ssl_certificate_location.as_deref()
+    }
+
+    pub fn ssl_key_location(&self) -> Option<&Path> {
+        self.ssl_key_location.as_deref()
+    }
+
+    pub fn ssl_truststore_location(&self) -> Option<&Path> {
+        self.ssl_truststore_location.as_deref()
+    }
+
+    pub fn ssl_client_auth(&self) -> SslClientAuth {
+        self.ssl_client_auth
+    }
+
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.properties.get(key).map(String::as_str)
+    }
+
+    pub fn properties(&self) -> &HashMap<String, String> {
+        &self.properties
+    }
+}
+impl Default for BrokerConfig {
+    fn default() -> Self {
+        Self::from_properties(HashMap::new()).expect("default configuration is valid")
+    }
+}
+
+// `KAFKA_CFG_LOG_DIRS` becomes `log.dirs`: a single underscore maps to a dot,
+// a double one to an underscore and a triple one to a dash. The prefix keeps
+// variables such as `KAFKA_OPTS` or `KAFKA_HOME` out of the config.
+pub fn env_overrides<I: IntoIterator<Item = (String, String)>>(vars: I) -> HashMap<String, String> {
+    vars.into_iter()
+        .filter_map(|(name, value)| {
+            let name = name.strip_prefix(ENV_PREFIX)?;
+            let key = name
+                .to_ascii_lowercase()
+                .replace("___", "-")
+                .replace("__", "\0")
+                .replace('_', ".")
+                .replace('\0', "_");
+            Some((key, value))
+        })
+        .collect()
+}
+
+fn invalid(key: &str, value: &str, reason: &'static str) -> Error {
+    Error::InvalidValue {
+        key: key.to_string(),
+        value: value.to_string(),
+        reason,
+    }
+}
+
+fn parse<T: FromStr>(
+    properties: &HashMap<String, String>,
+    key: &str,
+    default: T,
+) -> Result<T, Error> {
+    match properties.get(key) {
+        Some(value) => value
+            .trim()
+            .parse()
+            .map_err(|_| invalid(key, value, "not a valid number")),
+        None => Ok(default),
+    }
+}
+
+fn positive<T: FromStr + PartialOrd + Default>(
+    properties: &HashMap<String, String>,
+    key: &str,
+    default: T,
+) -> Result<T, Error> {
+    let value = parse(properties, key, default)?;
+    if value <= T::default() {
+        return Err(invalid(key, &properties[key], "must be greater than zero"));
+    }
+    Ok(value)
+}
+
+fn list(properties: &HashMap<String, String>, key: &str, default: &str) -> Vec<String> {
+    properties
+        .get(key)
+        .map(String::as_str)
+        .unwrap_or(default)
+        .split(',')
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+// `ip:count` pairs, e.g. `127.0.0.1:200,::1:200`.
+fn per_ip_overrides(properties: &HashMap<String, String>) -> Result<HashMap<IpAddr, usize>, Error> {
+    let key = "max.connections.per.ip.overrides";
+    list(properties, key, "")
+        .into_iter()
+        .map(|entry| {
+            let (ip, count) = entry
+                .rsplit_once(':')
+                .ok_or_else(|| invalid(key, &entry, "expected ip:count"))?;
+            let ip = ip
+                .trim()
+                .parse()
+                .map_err(|_| invalid(key, &entry, "not an IP address"))?;
+            let count = count
+                .trim()
+                .parse()
+                .map_err(|_| invalid(key, &entry, "not a valid number"))?;
+            Ok((ip, count))
+        })
+        .collect()
+}
+
+fn path(properties: &HashMap<String, String>, key: &str) -> Option<PathBuf> {
+    properties
+        .get(key)
+        .map(|path| path.trim())
+        .filter(|path| !path.is_empty())
+        .map(PathBuf::from)
+}
+
+fn endpoints(
+    properties: &HashMap<String, String>,
+    key: &str,
+    default: &str,
+) -> Result<Vec<Endpoint>, Error> {
+    let value = properties.get(key).map(String::as_str).unwrap_or(default);
+    let endpoints = value
+        .split(',')
+        .filter(|endpoint| !endpoint.trim().is_empty())
+        .map(|endpoint| {
+            endpoint
+                .parse()
+                .map_err(|reason| invalid(key, value, reason))
+        })
+        .collect::<Result<Vec<Endpoint>, _>>()?;
+    for (i, endpoint) in endpoints.iter().enumerate() {
+        if endpoints[..i]
+            .iter()
+            .any(|other| other.listener_name == endpoint.listener_name)
+        {
+            return Err(invalid(key, value, "duplicate listener name"));
+        }
+    }
+    Ok(endpoints)
+}
+
+#[cfg(test)]
+mod tests;
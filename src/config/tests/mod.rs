@@ -0,0 +1,178 @@
+use std::{collections::HashMap, path::PathBuf};
+
+use super::*;
+
+fn properties(entries: &[(&str, &str)]) -> HashMap<String, String> {
+    entries
+        .iter()
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .collect()
+}
+
+#[test]
+fn test_parse_properties() {
+    let content = "# comment\n\
+                   ! another comment\n\
+                   node.id=1\n\
+                   process.roles : broker,controller\n\
+                   log.dirs /var/lib/kafka\n\
+                   listeners=PLAINTEXT://:9092,\\\n    CONTROLLER://:9093\n\
+                   escaped\\=key=tab\\there\n";
+    let properties = parse_properties(content);
+
+    assert_eq!(Some("1"), properties.get("node.id").map(String::as_str));
+    assert_eq!(
+        Some("broker,controller"),
+        properties.get("process.roles").map(String::as_str)
+    );
+    assert_eq!(
+        Some("/var/lib/kafka"),
+        properties.get("log.dirs").map(String::as_str)
+    );
+    assert_eq!(
+        Some("PLAINTEXT://:9092,CONTROLLER://:9093"),
+        properties.get("listeners").map(String::as_str)
+    );
+    assert_eq!(
+        Some("tab\there"),
+        properties.get("escaped=key").map(String::as_str)
+    );
+    assert_eq!(5, properties.len());
+}
+
+#[test]
+fn test_broker_config_defaults() {
+    let config = BrokerConfig::default();
+
+    assert_eq!(-1, config.node_id());
+    assert_eq!(
+        &[Endpoint::new("PLAINTEXT", "127.0.0.1", 9092)],
+        config.listeners()
+    );
+    assert_eq!("127.0.0.1:9092", config.listeners()[0].bind_address());
+    assert_eq!(
+        &[PathBuf::from("/tmp/kraft-combined-logs")],
+        config.log_dirs()
+    );
+    assert_eq!(config.log_dirs()[0], config.metadata_log_dir());
+    assert_eq!(168 * 60 * 60 * 1000, config.log_retention_ms());
+    assert_eq!(104857600, config.socket_request_max_bytes());
+}
+
+#[test]
+fn test_broker_config_listeners() {
+    let config = BrokerConfig::from_properties(properties(&[
+        ("broker.id", "3"),
+        ("listeners", "PLAINTEXT://localhost:9092,CONTROLLER://:9093"),
+        ("controller.listener.names", "CONTROLLER"),
+        (
+            "listener.security.protocol.map",
+            "PLAINTEXT:PLAINTEXT,CONTROLLER:PLAINTEXT",
+        ),
+        ("log.dirs", "/data/a, /data/b"),
+        ("log.retention.minutes", "5"),
+    ]))
+    .unwrap();
+
+    assert_eq!(3, config.node_id());
+    assert_eq!(
+        vec![&Endpoint::new("PLAINTEXT", "localhost", 9092)],
+        config.broker_listeners().collect::<Vec<_>>()
+    );
+    assert_eq!(
+        &[Endpoint::new("PLAINTEXT", "localhost", 9092)],
+        config.advertised_listeners()
+    );
+    assert_eq!(
+        SecurityProtocol::Plaintext,
+        config.security_protocol("CONTROLLER").unwrap()
+    );
+    assert_eq!(
+        &[PathBuf::from("/data/a"), PathBuf::from("/data/b")],
+        config.log_dirs()
+    );
+    assert_eq!(5 * 60 * 1000, config.log_retention_ms());
+}
+
+#[test]
+fn test_broker_config_connection_limits() {
+    let config = BrokerConfig::from_properties(properties(&[
+        ("max.connections.per.ip", "0"),
+        ("max.connections.per.ip.overrides", "127.0.0.1:10, ::1:5"),
+    ]))
+    .unwrap();
+
+    assert_eq!(
+        10,
+        config.max_connections_per_ip(&"127.0.0.1".parse().unwrap())
+    );
+    assert_eq!(5, config.max_connections_per_ip(&"::1".parse().unwrap()));
+    assert_eq!(
+        0,
+        config.max_connections_per_ip(&"10.0.0.1".parse().unwrap())
+    );
+    assert_eq!(i32::MAX as usize, config.max_connections());
+}
+
+#[test]
+fn test_broker_config_invalid_value() {
+    let err = BrokerConfig::from_properties(properties(&[("num.partitions", "many")])).unwrap_err();
+    assert!(matches!(err, Error::InvalidValue { key, .. } if key == "num.partitions"));
+
+    let err =
+        BrokerConfig::from_properties(properties(&[("listeners", "UNKNOWN://:9092")])).unwrap_err();
+    assert!(
+        matches!(err, Error::InvalidValue { key, .. } if key == "listener.security.protocol.map")
+    );
+
+    let err = BrokerConfig::from_properties(properties(&[(
+        "listeners",
+        "PLAINTEXT://:9092,PLAINTEXT://:9093",
+    )]))
+    .unwrap_err();
+    assert!(matches!(err, Error::InvalidValue { key, .. } if key == "listeners"));
+
+    for advertised in ["SSL://localhost:9093", "PLAINTEXT://0.0.0.0:9092"] {
+        let err = BrokerConfig::from_properties(properties(&[
+            ("listeners", "PLAINTEXT://:9092"),
+            ("advertised.listeners", advertised),
+        ]))
+        .unwrap_err();
+        assert!(matches!(err, Error::InvalidValue { key, .. } if key == "advertised.listeners"));
+    }
+
+    let err =
+        BrokerConfig::from_properties(properties(&[("max.connections.per.ip", "0")])).unwrap_err();
+    assert!(matches!(err, Error::InvalidValue { key, .. } if key == "max.connections.per.ip"));
+    let err = BrokerConfig::from_properties(properties(&[(
+        "max.connections.per.ip.overrides",
+        "localhost:10",
+    )]))
+    .unwrap_err();
+    assert!(
+        matches!(err, Error::InvalidValue { key, .. } if key == "max.connections.per.ip.overrides")
+    );
+}
+
+#[test]
+fn test_env_and_cli_overrides() {
+    let overrides = env_overrides(vec![
+        ("KAFKA_CFG_LOG_DIRS".to_string(), "/env/logs".to_string()),
+        ("KAFKA_CFG_SOME__KEY___NAME".to_string(), "x".to_string()),
+        ("KAFKA_OPTS".to_string(), "-Xmx1G".to_string()),
+        ("PATH".to_string(), "/usr/bin".to_string()),
+    ]);
+    assert_eq!(
+        properties(&[("log.dirs", "/env/logs"), ("some_key-name", "x")]),
+        overrides
+    );
+
+    let config = BrokerConfig::from_args(vec![
+        "--override".to_string(),
+        "node.id=7".to_string(),
+        "--override=num.partitions=4".to_string(),
+    ])
+    .unwrap();
+    assert_eq!(7, config.node_id());
+    assert_eq!(4, config.num_partitions());
+}
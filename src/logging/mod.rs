@@ -0,0 +1,191 @@
+use std::{
+    collections::BTreeMap,
+    fs::OpenOptions,
+    io,
+    sync::{LazyLock, Mutex},
+};
+
+use tracing::level_filters::LevelFilter;
+use tracing_subscriber::{
+    filter::{filter_fn, Targets},
+    fmt,
+    layer::SubscriberExt,
+    reload,
+    util::SubscriberInitExt,
+    Layer, Registry,
+};
+
+use crate::config::BrokerConfig;
+
+pub const ROOT_LOGGER: &str = "root";
+// Completed requests are logged to this target at DEBUG, one JSON object per
+// line, like Kafka's request logger.
+pub const REQUEST_LOGGER: &str = "kafka.request.logger";
+// Every module of the broker logs under the crate's name.
+const CRATE_LOGGER: &str = "codecrafters_kafka";
+
+pub const LOG_LEVELS: [&str; 8] = [
+    "OFF", "FATAL", "ERROR", "WARN", "INFO", "DEBUG", "TRACE", "ALL",
+];
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("io error: {0}")]
+    IOError(#[from] io::Error),
+
+    #[error("{0}")]
+    InvalidLevel(String),
+
+    #[error("failed to install logger: {0}")]
+    Init(String),
+}
+
+// The level of every logger that has one of its own; the others inherit the
+// level of the root logger. Once installed, changes take effect immediately.
+struct Loggers {
+    levels: BTreeMap<String, String>,
+    reload: Option<reload::Handle<Targets, Registry>>,
+}
+impl Loggers {
+    fn targets(&self) -> Targets {
+        let mut targets = Targets::new();
+        for (logger, level) in self.levels.iter() {
+            let filter = level_filter(level).unwrap_or(LevelFilter::INFO);
+            if logger == ROOT_LOGGER {
+                targets = targets.with_default(filter);
+            } else {
+                targets = targets.with_target(logger.clone(), filter);
+            }
+        }
+        targets
+    }
+
+    fn apply(&self) {
+        if let Some(reload) = &self.reload {
+            let _ = reload.reload(self.targets());
+        }
+    }
+}
+
+static LOGGERS: LazyLock<Mutex<Loggers>> = LazyLock::new(|| {
+    Mutex::new(Loggers {
+        levels: BTreeMap::from([
+            (ROOT_LOGGER.to_string(), "INFO".to_string()),
+            (REQUEST_LOGGER.to_string(), "WARN".to_string()),
+        ]),
+        reload: None,
+    })
+});
+
+// Installs the global subscriber: readable lines on stdout for the broker's
+// own logs and JSON lines for the request log, which goes to
+// request.log.file if one is configured. The request log sees every span so
+// that its lines carry the request they belong to.
+pub fn init(config: &BrokerConfig) -> Result<(), Error> {
+    let mut loggers = LOGGERS.lock().unwrap();
+    loggers.levels.insert(
+        ROOT_LOGGER.to_string(),
+        validate(ROOT_LOGGER, config.log_level())?,
+    );
+    loggers.levels.insert(
+        REQUEST_LOGGER.to_string(),
+        validate(REQUEST_LOGGER, config.request_log_level())?,
+    );
+
+    let (filter, reload) = reload::Layer::new(loggers.targets());
+    let request_log = fmt::layer()
+        .json()
+        .with_current_span(true)
+        .with_span_list(false);
+    let request_log = match config.request_log_file() {
+        Some(path) => {
+            let file = OpenOptions::new().create(true).append(true).open(path)?;
+            request_log.with_writer(Mutex::new(file)).boxed()
+        }
+        None => request_log.boxed(),
+    };
+    tracing_subscriber::registry()
+        .with(filter)
+        .with(fmt::layer().with_filter(filter_fn(|metadata| metadata.target() != REQUEST_LOGGER)))
+        .with(request_log.with_filter(filter_fn(|metadata| {
+            metadata.is_span() || metadata.target() == REQUEST_LOGGER
+        })))
+        .try_init()
+        .map_err(|err| Error::Init(err.to_string()))?;
+    loggers.reload = Some(reload);
+    Ok(())
+}
+
+// Loggers with a level of their own, the root logger first.
+pub fn loggers() -> Vec<(String, String)> {
+    let loggers = LOGGERS.lock().unwrap();
+    let mut levels = loggers
+        .levels
+        .iter()
+        .map(|(logger, level)| (logger.clone(), level.clone()))
+        .collect::<Vec<_>>();
+    levels.sort_by_key(|(logger, _)| logger != ROOT_LOGGER);
+    levels
+}
+
+// Gives the logger a level of its own, or with None lets it inherit the root
+// logger's again.
+pub fn set_level(logger: &str, level: Option<&str>) -> Result<(), Error> {
+    let level = check(logger, level)?;
+    let mut loggers = LOGGERS.lock().unwrap();
+    match level {
+        Some(level) => loggers.levels.insert(logger.to_string(), level),
+        None => loggers.levels.remove(logger),
+    };
+    loggers.apply();
+    Ok(())
+}
+
+// Validates what set_level would do without doing it.
+pub fn check(logger: &str, level: Option<&str>) -> Result<Option<String>, Error> {
+    match level {
+        Some(level) => validate(logger, level).map(Some),
+        None if logger == ROOT_LOGGER => Err(Error::InvalidLevel(
+            "Removing the log level of the root logger is not allowed".to_string(),
+        )),
+        None => validate(logger, "INFO").map(|_| None),
+    }
+}
+
+fn validate(logger: &str, level: &str) -> Result<String, Error> {
+    let known = logger == ROOT_LOGGER
+        || logger == REQUEST_LOGGER
+        || logger == CRATE_LOGGER
+        || logger.starts_with(&format!("{}::", CRATE_LOGGER));
+    if !known {
+        return Err(Error::InvalidLevel(format!(
+            "Logger {} does not exist",
+            logger
+        )));
+    }
+    let level = level.trim().to_ascii_uppercase();
+    if level_filter(&level).is_none() {
+        return Err(Error::InvalidLevel(format!(
+            "Cannot set the log level of {} to {} as it is not a supported log level. Valid log levels are {}",
+            logger,
+            level,
+            LOG_LEVELS.join(", ")
+        )));
+    }
+    Ok(level)
+}
+
+fn level_filter(level: &str) -> Option<LevelFilter> {
+    match level {
+        "OFF" => Some(LevelFilter::OFF),
+        "FATAL" | "ERROR" => Some(LevelFilter::ERROR),
+        "WARN" => Some(LevelFilter::WARN),
+        "INFO" => Some(LevelFilter::INFO),
+        "DEBUG" => Some(LevelFilter::DEBUG),
+        "TRACE" | "ALL" => Some(LevelFilter::TRACE),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests;
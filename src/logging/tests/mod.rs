@@ -0,0 +1,23 @@
+use super::{check, loggers, set_level, ROOT_LOGGER};
+
+#[test]
+fn test_set_and_remove_logger_level() {
+    let logger = "codecrafters_kafka::logging::tests";
+    set_level(logger, Some("debug")).unwrap();
+    assert!(loggers().contains(&(logger.to_string(), "DEBUG".to_string())));
+    assert_eq!(loggers()[0].0, ROOT_LOGGER);
+
+    set_level(logger, None).unwrap();
+    assert!(!loggers().iter().any(|(name, _)| name == logger));
+}
+
+#[test]
+fn test_check_rejects_invalid_changes() {
+    assert!(check(ROOT_LOGGER, None).is_err());
+    assert!(check("org.apache.kafka", Some("INFO")).is_err());
+    assert!(check("codecrafters_kafka", Some("VERBOSE")).is_err());
+    assert_eq!(
+        check("codecrafters_kafka::network", Some("warn")).unwrap(),
+        Some("WARN".to_string())
+    );
+}
@@ -1,105 +1,204 @@
-#![allow(unused_imports)]
 use std::{
-    collections::HashMap,
-    fs::File,
-    io::{BufRead, BufReader, Read, Write},
-    net::{TcpListener, TcpStream},
-    sync::Arc,
-    thread,
+    io::IsTerminal,
+    sync::{Arc, Mutex},
 };
 
-use bytes::{Buf, BufMut, Bytes, BytesMut};
+use anyhow::Context;
 use codecrafters_kafka::{
-    metadata,
-    model::{self, Topic},
-    protocol::{
-        self,
-        message::{process_request, read_request, write_response},
-        r#type::TaggedFields,
-        Readable, Writable,
-    },
+    broker::Broker,
+    config::{BrokerConfig, SecurityProtocol},
+    logging,
+    metadata::{MetadataCache, MetadataLoader},
+    metrics, network,
+    security::tls,
+    storage,
+    tools::{dump_log::DumpLog, format::Format, metadata_shell::MetadataShell},
 };
+use tokio::{
+    net::TcpListener,
+    signal::unix::{signal, SignalKind},
+};
+use tokio_util::sync::CancellationToken;
 
-fn main() -> anyhow::Result<()> {
-    // You can use print statements as follows for debugging, they'll be visible when running tests.
-    println!("Logs from your program will appear here!");
-
-    let listener = TcpListener::bind("127.0.0.1:9092").unwrap();
-
-    for stream in listener.incoming() {
-        match stream {
-            Ok(stream) => {
-                println!("accepted new connection");
-                thread::spawn({
-                    move || {
-                        if let Err(err) = handle(stream) {
-                            println!("error while handle stream: {}", err);
-                        }
-                    }
-                });
-            }
-            Err(err) => {
-                println!("error while listening: {}", err);
-            }
-        }
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    // Subcommands run in place of the broker.
+    let mut args = std::env::args().skip(1).peekable();
+    if args.next_if_eq("format").is_some() {
+        return format(args);
+    }
+    if args.next_if_eq("dump-log").is_some() {
+        DumpLog::from_args(args)?.run(&mut std::io::stdout().lock())?;
+        return Ok(());
+    }
+    if args.next_if_eq("metadata-shell").is_some() {
+        let stdin = std::io::stdin();
+        let prompt = stdin.is_terminal();
+        MetadataShell::from_args(args)?.run(stdin.lock(), &mut std::io::stdout().lock(), prompt)?;
+        return Ok(());
     }
 
-    Ok(())
-}
-
-#[derive(Debug, thiserror::Error)]
-enum KafkaError {
-    #[error("io error: {0}")]
-    IOError(#[from] std::io::Error),
+    let config = BrokerConfig::from_args(args)?;
+    if config.broker_listeners().next().is_none() {
+        anyhow::bail!("no broker listener configured");
+    }
+    logging::init(&config)?;
 
-    #[error("protocol error: {0}")]
-    ProtocolError(#[from] protocol::Error),
-}
+    // Log directories formatted for another cluster or node must not be
+    // served; ones that were never formatted are.
+    let meta_properties = storage::meta_properties::load(&config)?;
+    match &meta_properties {
+        Some(properties) => tracing::info!("cluster id {}", properties.cluster_id()),
+        None => tracing::warn!("no meta.properties found in the log directories"),
+    }
 
-fn handle(mut stream: TcpStream) -> Result<(), KafkaError> {
-    let mut filler = vec![0u8; 1024];
-    let mut stream_buffer = Vec::with_capacity(8192);
-    loop {
-        let read_bytes = stream.read(&mut filler)?;
-        if read_bytes == 0 {
-            return Ok(());
+    // A directory that was not closed cleanly may end in a torn batch, which
+    // has to go before anything reads or appends to it.
+    for log_dir in storage::log_dirs(&config) {
+        let recovered = storage::recover(&log_dir)?;
+        if recovered > 0 {
+            tracing::info!(
+                "recovered {} partitions in {} after an unclean shutdown",
+                recovered,
+                log_dir.display()
+            );
         }
-        stream_buffer.put_slice(&(&filler)[..read_bytes]);
+    }
 
-        let mut read_buffer: &[u8] = stream_buffer.as_mut();
+    // Load what is already on disk before accepting connections, then keep
+    // tailing; errors are reported and retried by the background loader.
+    let metadata = Arc::new(MetadataCache::new());
+    let mut loader = MetadataLoader::new(
+        config.metadata_log_dir(),
+        config.metadata_log_max_record_bytes_between_snapshots(),
+    );
+    if let Err(err) = loader.poll(&metadata) {
+        tracing::error!("error while loading metadata: {}", err);
+    }
+    let loader = Arc::new(Mutex::new(loader));
+    let loader_stop = CancellationToken::new();
+    let loader_thread =
+        MetadataLoader::spawn(loader.clone(), metadata.clone(), loader_stop.clone())?;
 
-        let (request_header, request) = read_request(&mut read_buffer)?;
-        if read_buffer.is_empty() {
-            stream_buffer.clear();
-        } else {
-            let read_buffer_remaining = read_buffer.remaining();
-            let lower_index = stream_buffer.len() - read_buffer_remaining;
-            stream_buffer.copy_within(lower_index.., 0);
-            stream_buffer.truncate(read_buffer_remaining);
+    // Every listener is bound before any is served, so that a port that is
+    // taken fails startup instead of leaving the broker half reachable.
+    let mut listeners = Vec::new();
+    for endpoint in config.broker_listeners() {
+        let security_protocol = config.security_protocol(endpoint.listener_name())?;
+        let acceptor = match security_protocol {
+            SecurityProtocol::Ssl | SecurityProtocol::SaslSsl => Some(tls::acceptor(&config)?),
+            SecurityProtocol::Plaintext | SecurityProtocol::SaslPlaintext => None,
+        };
+        let listener = TcpListener::bind(endpoint.bind_address())
+            .await
+            .with_context(|| format!("failed to bind {}", endpoint))?;
+        tracing::info!("listening on {} ({})", endpoint, security_protocol);
+        listeners.push((
+            listener,
+            endpoint.listener_name().to_string(),
+            security_protocol,
+            acceptor,
+        ));
+    }
+    let metrics_listener = match config.metrics_bind_address() {
+        Some(address) => {
+            let listener = TcpListener::bind(&address)
+                .await
+                .with_context(|| format!("failed to bind metrics endpoint {}", address))?;
+            tracing::info!("serving metrics on http://{}/metrics", address);
+            Some(listener)
         }
+        None => None,
+    };
 
-        let response = process_request(request)?;
+    let mut broker = Broker::new(config, metadata).with_metadata_loader(loader);
+    if let Some(properties) = &meta_properties {
+        broker = broker.with_cluster_id(properties.cluster_id());
+    }
+    let broker = Arc::new(broker);
+    if let Some(listener) = metrics_listener {
+        let broker = broker.clone();
+        tokio::spawn(async move {
+            if let Err(err) = metrics::serve(listener, broker).await {
+                tracing::error!("error while serving metrics: {}", err);
+            }
+        });
+    }
+    let servers =
+        listeners
+            .into_iter()
+            .map(|(listener, listener_name, security_protocol, acceptor)| {
+                network::serve(
+                    listener,
+                    broker.clone(),
+                    listener_name,
+                    security_protocol,
+                    acceptor,
+                )
+            });
+    let servers = futures::future::try_join_all(servers);
+    tokio::select! {
+        result = servers => {
+            result?;
+        }
+        result = shutdown_signal() => {
+            result?;
+            tracing::info!("shutting down");
+        }
+    }
 
-        let response_data = {
-            let mut data = BytesMut::with_capacity(64);
-            write_response(&mut data, request_header, response)?;
-            data.freeze()
-        };
+    // Listeners stop accepting and connections stop reading; whatever they
+    // read already is answered unless that takes longer than the timeout.
+    broker.shutdown().cancel();
+    broker.connections().close();
+    let timeout = broker.config().shutdown_timeout();
+    if tokio::time::timeout(timeout, broker.connections().wait())
+        .await
+        .is_err()
+    {
+        // Requests still in flight may be appending, so the log directories
+        // are left to recovery on the next start rather than closed under them.
+        tracing::warn!(
+            "closing {} connections that did not finish within {:?}, skipping the clean shutdown",
+            broker.connections().len(),
+            timeout
+        );
+        return Ok(());
+    }
 
-        let message_size = response_data.len() as i32;
+    // Snapshots are written by the loader, so it has to be done before the
+    // metadata log directory is closed.
+    loader_stop.cancel();
+    tokio::task::spawn_blocking(move || loader_thread.join())
+        .await?
+        .map_err(|_| anyhow::anyhow!("metadata loader panicked"))?;
+    for log_dir in storage::log_dirs(broker.config()) {
+        storage::close(&log_dir)?;
+    }
+    tracing::info!("shut down cleanly");
+    Ok(())
+}
 
-        let header_data = {
-            let mut data = BytesMut::with_capacity(4);
-            message_size.write(&mut data);
-            data.freeze()
-        };
+fn format(args: impl Iterator<Item = String>) -> anyhow::Result<()> {
+    let format = Format::from_args(args)?;
+    let formatted = format.run()?;
+    if formatted.is_empty() {
+        println!("All of the log directories are already formatted.");
+    }
+    for log_dir in formatted {
+        println!(
+            "Formatted {} with metadata.version {}.",
+            log_dir.display(),
+            format.metadata_version()
+        );
+    }
+    Ok(())
+}
 
-        stream
-            .write_all(&header_data)
-            .map_err(KafkaError::IOError)?;
-        stream
-            .write_all(&response_data)
-            .map_err(KafkaError::IOError)?;
-        stream.flush().map_err(KafkaError::IOError)?;
+async fn shutdown_signal() -> std::io::Result<()> {
+    let mut terminate = signal(SignalKind::terminate())?;
+    tokio::select! {
+        result = tokio::signal::ctrl_c() => result,
+        _ = terminate.recv() => Ok(()),
     }
 }
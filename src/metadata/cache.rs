@@ -0,0 +1,54 @@
+use std::sync::Arc;
+
+use arc_swap::ArcSwap;
+use tokio::sync::broadcast;
+use uuid::Uuid;
+
+use super::MetadataImage;
+
+const CHANGES_CAPACITY: usize = 1024;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MetadataChange {
+    TopicCreated { topic_id: Uuid, name: String },
+    TopicDeleted { topic_id: Uuid, name: String },
+    PartitionChanged { topic_id: Uuid, partition_id: i32 },
+    BrokerChanged { broker_id: i32 },
+}
+
+// Request handlers load the current image without locking; the loader swaps
+// in a new one after every poll that applied records.
+pub struct MetadataCache {
+    image: ArcSwap<MetadataImage>,
+    changes: broadcast::Sender<MetadataChange>,
+}
+impl MetadataCache {
+    pub fn new() -> Self {
+        let (changes, _) = broadcast::channel(CHANGES_CAPACITY);
+        Self {
+            image: ArcSwap::from_pointee(MetadataImage::default()),
+            changes,
+        }
+    }
+
+    pub fn image(&self) -> Arc<MetadataImage> {
+        self.image.load_full()
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<MetadataChange> {
+        self.changes.subscribe()
+    }
+
+    pub(crate) fn publish(&self, image: MetadataImage, changes: Vec<MetadataChange>) {
+        self.image.store(Arc::new(image));
+        for change in changes {
+            // Nobody listening is fine, the image is what matters.
+            let _ = self.changes.send(change);
+        }
+    }
+}
+impl Default for MetadataCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,409 @@
+use std::{collections::HashMap, sync::Arc};
+
+use uuid::Uuid;
+
+use crate::model::{
+    Acl, BrokerRegistration, ClientQuotaEntity, ConfigResource, Listener, Partition,
+    ScramCredential, Topic,
+};
+
+use super::{
+    records::{
+        broker_registration_change_record, partition_change_record, ClientQuotaRecord,
+        ConfigRecord, MetadataRecord, PartitionChangeRecord, PartitionRecord, RegisterBrokerRecord,
+    },
+    MetadataChange, MetadataImage,
+};
+
+// Records replayed on top of an image. Sections are copied from the image the
+// first time a record touches them; `apply` shares the untouched ones.
+pub struct MetadataDelta {
+    image: Arc<MetadataImage>,
+    last_offset: Option<i64>,
+    features: Option<HashMap<String, i16>>,
+    brokers: Option<HashMap<i32, BrokerRegistration>>,
+    topics: Option<HashMap<Uuid, Topic>>,
+    configs: Option<HashMap<ConfigResource, HashMap<String, String>>>,
+    client_quotas: Option<HashMap<ClientQuotaEntity, HashMap<String, f64>>>,
+    acls: Option<HashMap<Uuid, Acl>>,
+    scram_credentials: Option<HashMap<(String, i8), ScramCredential>>,
+    next_producer_id: Option<i64>,
+    changes: Vec<MetadataChange>,
+}
+impl MetadataDelta {
+    pub fn new(image: Arc<MetadataImage>) -> Self {
+        Self {
+            last_offset: image.last_offset(),
+            image,
+            features: None,
+            brokers: None,
+            topics: None,
+            configs: None,
+            client_quotas: None,
+            acls: None,
+            scram_credentials: None,
+            next_producer_id: None,
+            changes: Vec::new(),
+        }
+    }
+
+    pub fn image(&self) -> &MetadataImage {
+        &self.image
+    }
+
+    pub fn last_offset(&self) -> Option<i64> {
+        self.last_offset
+    }
+
+    pub fn set_last_offset(&mut self, last_offset: i64) {
+        self.last_offset = Some(last_offset);
+    }
+
+    pub fn replay(&mut self, record: MetadataRecord) {
+        match record {
+            MetadataRecord::RegisterBroker(record) => self.replay_register_broker(record),
+            MetadataRecord::UnregisterBroker(record) => {
+                let brokers = self.brokers();
+                if brokers
+                    .get(&record.broker_id())
+                    .is_some_and(|broker| broker.epoch() == record.broker_epoch())
+                {
+                    brokers.remove(&record.broker_id());
+                    self.changes.push(MetadataChange::BrokerChanged {
+                        broker_id: record.broker_id(),
+                    });
+                }
+            }
+            MetadataRecord::Topic(record) => {
+                let topic_id = record.topic_id();
+                let name = String::from_utf8_lossy(record.name()).to_string();
+                self.topics()
+                    .entry(topic_id)
+                    .or_insert_with(|| Topic::new(topic_id, name.clone()));
+                self.changes
+                    .push(MetadataChange::TopicCreated { topic_id, name });
+            }
+            MetadataRecord::Partition(record) => self.replay_partition(record),
+            MetadataRecord::Config(record) => self.replay_config(record),
+            MetadataRecord::PartitionChange(record) => self.replay_partition_change(record),
+            MetadataRecord::AccessControlEntry(record) => {
+                let acl = Acl::new(
+                    record.id(),
+                    record.resource_type(),
+                    record.resource_name(),
+                    record.pattern_type(),
+                    record.principal(),
+                    record.host(),
+                    record.operation(),
+                    record.permission_type(),
+                );
+                self.acls().insert(record.id(), acl);
+            }
+            MetadataRecord::RemoveAccessControlEntry(record) => {
+                self.acls().remove(&record.id());
+            }
+            MetadataRecord::FenceBroker(record) => {
+                self.update_broker(record.id(), record.epoch(), |broker| {
+                    broker.set_fenced(true)
+                });
+            }
+            MetadataRecord::UnfenceBroker(record) => {
+                self.update_broker(record.id(), record.epoch(), |broker| {
+                    broker.set_fenced(false)
+                });
+            }
+            MetadataRecord::RemoveTopic(record) => {
+                if let Some(topic) = self.topics().remove(&record.topic_id()) {
+                    let resource = ConfigResource::new(ConfigResource::TOPIC, topic.name());
+                    self.configs().remove(&resource);
+                    self.changes.push(MetadataChange::TopicDeleted {
+                        topic_id: topic.id(),
+                        name: topic.name().to_string(),
+                    });
+                }
+            }
+            MetadataRecord::UserScramCredential(record) => {
+                let credential = ScramCredential::new(
+                    record.salt().clone(),
+                    record.stored_key().clone(),
+                    record.server_key().clone(),
+                    record.iterations(),
+                );
+                self.scram_credentials()
+                    .insert((record.name().to_string(), record.mechanism()), credential);
+            }
+            MetadataRecord::RemoveUserScramCredential(record) => {
+                self.scram_credentials()
+                    .remove(&(record.name().to_string(), record.mechanism()));
+            }
+            MetadataRecord::FeatureLevel(record) => {
+                let features = self.features();
+                if record.feature_level() == 0 {
+                    features.remove(record.name());
+                } else {
+                    features.insert(record.name().to_string(), record.feature_level());
+                }
+            }
+            MetadataRecord::ClientQuota(record) => self.replay_client_quota(record),
+            MetadataRecord::ProducerIds(record) => {
+                self.next_producer_id = Some(record.next_producer_id());
+            }
+            MetadataRecord::BrokerRegistrationChange(record) => {
+                let fenced = record.fenced();
+                let in_controlled_shutdown = record.in_controlled_shutdown();
+                self.update_broker(record.broker_id(), record.broker_epoch(), |broker| {
+                    match fenced {
+                        broker_registration_change_record::SET => broker.set_fenced(true),
+                        broker_registration_change_record::CLEAR => broker.set_fenced(false),
+                        _ => {}
+                    }
+                    match in_controlled_shutdown {
+                        broker_registration_change_record::SET => {
+                            broker.set_in_controlled_shutdown(true)
+                        }
+                        broker_registration_change_record::CLEAR => {
+                            broker.set_in_controlled_shutdown(false)
+                        }
+                        _ => {}
+                    }
+                });
+            }
+            MetadataRecord::NoOp | MetadataRecord::Unknown { .. } => {}
+        }
+    }
+
+    pub fn apply(self) -> (MetadataImage, Vec<MetadataChange>) {
+        let image = self.image;
+        let next = MetadataImage {
+            last_offset: self.last_offset,
+            features: self
+                .features
+                .map(Arc::new)
+                .unwrap_or_else(|| image.features.clone()),
+            brokers: self
+                .brokers
+                .map(Arc::new)
+                .unwrap_or_else(|| image.brokers.clone()),
+            topics: self
+                .topics
+                .map(Arc::new)
+                .unwrap_or_else(|| image.topics.clone()),
+            configs: self
+                .configs
+                .map(Arc::new)
+                .unwrap_or_else(|| image.configs.clone()),
+            client_quotas: self
+                .client_quotas
+                .map(Arc::new)
+                .unwrap_or_else(|| image.client_quotas.clone()),
+            acls: self
+                .acls
+                .map(Arc::new)
+                .unwrap_or_else(|| image.acls.clone()),
+            scram_credentials: self
+                .scram_credentials
+                .map(Arc::new)
+                .unwrap_or_else(|| image.scram_credentials.clone()),
+            next_producer_id: self.next_producer_id.unwrap_or(image.next_producer_id),
+        };
+        (next, self.changes)
+    }
+
+    fn replay_register_broker(&mut self, record: RegisterBrokerRecord) {
+        let listeners = record
+            .end_points()
+            .iter()
+            .map(|endpoint| {
+                Listener::new(
+                    endpoint.name(),
+                    endpoint.host(),
+                    endpoint.port(),
+                    endpoint.security_protocol(),
+                )
+            })
+            .collect();
+        let supported_features = record
+            .features()
+            .iter()
+            .map(|feature| {
+                (
+                    feature.name().to_string(),
+                    (
+                        feature.min_supported_version(),
+                        feature.max_supported_version(),
+                    ),
+                )
+            })
+            .collect();
+        let broker = BrokerRegistration::new(
+            record.broker_id(),
+            record.broker_epoch(),
+            record.incarnation_id(),
+            listeners,
+            supported_features,
+            record.rack().map(str::to_string),
+            record.fenced(),
+            record.in_controlled_shutdown(),
+        );
+        self.brokers().insert(record.broker_id(), broker);
+        self.changes.push(MetadataChange::BrokerChanged {
+            broker_id: record.broker_id(),
+        });
+    }
+
+    fn replay_partition(&mut self, record: PartitionRecord) {
+        let topic_id = record.topic_id();
+        let partition = Partition::new(
+            record.partition_id(),
+            record.leader(),
+            record.leader_epoch(),
+            record.partition_epoch(),
+            record.replicas().to_vec(),
+            record.isr().to_vec(),
+            record.eligible_leader_replicas().map(|v| v.to_vec()),
+            record.last_known_elr().map(|v| v.to_vec()),
+        );
+        if let Some(topic) = self.topics().get_mut(&topic_id) {
+            topic.add_partition(partition);
+            self.changes.push(MetadataChange::PartitionChanged {
+                topic_id,
+                partition_id: record.partition_id(),
+            });
+        }
+    }
+
+    fn replay_partition_change(&mut self, record: PartitionChangeRecord) {
+        let topic_id = record.topic_id();
+        let Some(partition) = self
+            .topics()
+            .get_mut(&topic_id)
+            .and_then(|topic| topic.partition_mut(record.partition_id()))
+        else {
+            return;
+        };
+
+        // A new leader starts a new leader epoch; every change bumps the
+        // partition epoch.
+        if record.leader() != partition_change_record::NO_LEADER_CHANGE {
+            partition.set_leader(record.leader());
+            partition.set_leader_epoch(partition.leader_epoch() + 1);
+        }
+        if let Some(isr) = record.isr() {
+            partition.set_isr(isr.to_vec());
+        }
+        if let Some(replicas) = record.replicas() {
+            partition.set_replicas(replicas.to_vec());
+        }
+        if let Some(eligible_leader_replicas) = record.eligible_leader_replicas() {
+            partition.set_eligible_leader_replicas(Some(eligible_leader_replicas.to_vec()));
+        }
+        if let Some(last_known_elr) = record.last_known_elr() {
+            partition.set_last_known_elr(Some(last_known_elr.to_vec()));
+        }
+        partition.set_partition_epoch(partition.partition_epoch() + 1);
+        self.changes.push(MetadataChange::PartitionChanged {
+            topic_id,
+            partition_id: record.partition_id(),
+        });
+    }
+
+    fn replay_config(&mut self, record: ConfigRecord) {
+        let resource = ConfigResource::new(record.resource_type(), record.resource_name());
+        let configs = self.configs();
+        match record.value() {
+            Some(value) => {
+                configs
+                    .entry(resource)
+                    .or_default()
+                    .insert(record.name().to_string(), value.to_string());
+            }
+            None => {
+                if let Some(entries) = configs.get_mut(&resource) {
+                    entries.remove(record.name());
+                    if entries.is_empty() {
+                        configs.remove(&resource);
+                    }
+                }
+            }
+        }
+    }
+
+    fn replay_client_quota(&mut self, record: ClientQuotaRecord) {
+        let entity = record
+            .entity()
+            .iter()
+            .map(|data| {
+                (
+                    data.entity_type().to_string(),
+                    data.entity_name().map(str::to_string),
+                )
+            })
+            .collect::<ClientQuotaEntity>();
+        let client_quotas = self.client_quotas();
+        if record.remove() {
+            if let Some(quotas) = client_quotas.get_mut(&entity) {
+                quotas.remove(record.key());
+                if quotas.is_empty() {
+                    client_quotas.remove(&entity);
+                }
+            }
+        } else {
+            client_quotas
+                .entry(entity)
+                .or_default()
+                .insert(record.key().to_string(), record.value());
+        }
+    }
+
+    fn update_broker<F: FnOnce(&mut BrokerRegistration)>(
+        &mut self,
+        broker_id: i32,
+        broker_epoch: i64,
+        f: F,
+    ) {
+        let broker = self
+            .brokers()
+            .get_mut(&broker_id)
+            .filter(|broker| broker.epoch() == broker_epoch);
+        if let Some(broker) = broker {
+            f(broker);
+            self.changes
+                .push(MetadataChange::BrokerChanged { broker_id });
+        }
+    }
+
+    fn features(&mut self) -> &mut HashMap<String, i16> {
+        self.features
+            .get_or_insert_with(|| self.image.features.as_ref().clone())
+    }
+
+    fn brokers(&mut self) -> &mut HashMap<i32, BrokerRegistration> {
+        self.brokers
+            .get_or_insert_with(|| self.image.brokers.as_ref().clone())
+    }
+
+    fn topics(&mut self) -> &mut HashMap<Uuid, Topic> {
+        self.topics
+            .get_or_insert_with(|| self.image.topics.as_ref().clone())
+    }
+
+    fn configs(&mut self) -> &mut HashMap<ConfigResource, HashMap<String, String>> {
+        self.configs
+            .get_or_insert_with(|| self.image.configs.as_ref().clone())
+    }
+
+    fn client_quotas(&mut self) -> &mut HashMap<ClientQuotaEntity, HashMap<String, f64>> {
+        self.client_quotas
+            .get_or_insert_with(|| self.image.client_quotas.as_ref().clone())
+    }
+
+    fn acls(&mut self) -> &mut HashMap<Uuid, Acl> {
+        self.acls
+            .get_or_insert_with(|| self.image.acls.as_ref().clone())
+    }
+
+    fn scram_credentials(&mut self) -> &mut HashMap<(String, i8), ScramCredential> {
+        self.scram_credentials
+            .get_or_insert_with(|| self.image.scram_credentials.as_ref().clone())
+    }
+}
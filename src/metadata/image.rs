@@ -0,0 +1,65 @@
+use std::{collections::HashMap, sync::Arc};
+
+use uuid::Uuid;
+
+use crate::model::{
+    Acl, BrokerRegistration, ClientQuotaEntity, ConfigResource, ScramCredential, Topic,
+};
+
+// An immutable view of the cluster metadata up to `last_offset`. Every section
+// is shared between images, so a delta only copies the sections it touches.
+#[derive(Debug, Clone, Default)]
+pub struct MetadataImage {
+    pub(super) last_offset: Option<i64>,
+    pub(super) features: Arc<HashMap<String, i16>>,
+    pub(super) brokers: Arc<HashMap<i32, BrokerRegistration>>,
+    pub(super) topics: Arc<HashMap<Uuid, Topic>>,
+    pub(super) configs: Arc<HashMap<ConfigResource, HashMap<String, String>>>,
+    pub(super) client_quotas: Arc<HashMap<ClientQuotaEntity, HashMap<String, f64>>>,
+    pub(super) acls: Arc<HashMap<Uuid, Acl>>,
+    pub(super) scram_credentials: Arc<HashMap<(String, i8), ScramCredential>>,
+    pub(super) next_producer_id: i64,
+}
+impl MetadataImage {
+    pub fn last_offset(&self) -> Option<i64> {
+        self.last_offset
+    }
+
+    // Finalized feature levels, e.g. `metadata.version`.
+    pub fn features(&self) -> &HashMap<String, i16> {
+        &self.features
+    }
+
+    pub fn brokers(&self) -> &HashMap<i32, BrokerRegistration> {
+        &self.brokers
+    }
+
+    pub fn topics(&self) -> &HashMap<Uuid, Topic> {
+        &self.topics
+    }
+
+    pub fn topic_by_name(&self, name: &str) -> Option<&Topic> {
+        self.topics.values().find(|topic| topic.name() == name)
+    }
+
+    pub fn configs(&self) -> &HashMap<ConfigResource, HashMap<String, String>> {
+        &self.configs
+    }
+
+    pub fn client_quotas(&self) -> &HashMap<ClientQuotaEntity, HashMap<String, f64>> {
+        &self.client_quotas
+    }
+
+    pub fn acls(&self) -> &HashMap<Uuid, Acl> {
+        &self.acls
+    }
+
+    // Keyed by user name and SCRAM mechanism.
+    pub fn scram_credentials(&self) -> &HashMap<(String, i8), ScramCredential> {
+        &self.scram_credentials
+    }
+
+    pub fn next_producer_id(&self) -> i64 {
+        self.next_producer_id
+    }
+}
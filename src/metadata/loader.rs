@@ -0,0 +1,263 @@
+use std::{
+    fs::File,
+    io::{self, Read, Seek, SeekFrom},
+    panic::{self, AssertUnwindSafe},
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    thread,
+    time::Duration,
+};
+
+use tokio_util::sync::CancellationToken;
+
+use crate::protocol::{self, Readable, ReadableResult};
+
+use super::{
+    records::MetadataRecord, snapshot, MetadataCache, MetadataDelta, MetadataValue, Record,
+    RecordBatch,
+};
+
+pub const METADATA_PARTITION_DIR: &str = "__cluster_metadata-0";
+pub const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+// Tails the `__cluster_metadata-0` segments, starting from the latest
+// snapshot if there is one. The read position only moves past complete
+// batches, so a partial read is simply retried on the next poll, while a
+// complete batch or record that cannot be decoded is reported and skipped.
+// Once enough record bytes were applied, the image is written out as a new
+// snapshot to bound the next startup.
+pub struct MetadataLoader {
+    partition_dir: PathBuf,
+    segment: Option<u64>,
+    position: u64,
+    epoch: i32,
+    max_bytes_between_snapshots: u64,
+    bytes_since_snapshot: u64,
+}
+impl MetadataLoader {
+    pub fn new(metadata_log_dir: &Path, max_bytes_between_snapshots: u64) -> Self {
+        Self {
+            partition_dir: metadata_log_dir.join(METADATA_PARTITION_DIR),
+            segment: None,
+            position: 0,
+            epoch: 0,
+            max_bytes_between_snapshots,
+            bytes_since_snapshot: 0,
+        }
+    }
+
+    // Applies everything appended since the last call and returns the number
+    // of batches that were applied.
+    pub fn poll(&mut self, cache: &MetadataCache) -> Result<usize, protocol::Error> {
+        let mut delta = MetadataDelta::new(cache.image());
+        let mut applied = 0;
+        if self.segment.is_none() && delta.last_offset().is_none() {
+            applied += self.load_snapshot(&mut delta)?;
+        }
+
+        let mut epoch = self.epoch;
+        let mut bytes = 0;
+        let result = self.read_batches(|batch, size| {
+            let batch_last_offset = batch.base_offset() + batch.last_offset_delta() as i64;
+            if delta
+                .last_offset()
+                .is_some_and(|offset| batch_last_offset <= offset)
+            {
+                return;
+            }
+            delta.set_last_offset(batch_last_offset);
+            epoch = batch.partition_leader_epoch();
+            bytes += size;
+            applied += 1;
+            if batch.is_control_batch() {
+                return;
+            }
+
+            for record in batch.records() {
+                if let Record::Value(value_record) = record {
+                    let offset = batch.base_offset() + value_record.offset_delta() as i64;
+                    let value = match MetadataValue::read_result(&mut value_record.value().clone())
+                    {
+                        Ok(value) => value,
+                        Err(err) => {
+                            tracing::warn!(
+                                "skipping metadata record at offset {}: {}",
+                                offset,
+                                err
+                            );
+                            continue;
+                        }
+                    };
+                    match MetadataRecord::read(&value) {
+                        Ok(record) => delta.replay(record),
+                        Err(err) => tracing::warn!(
+                            "skipping metadata record type {} version {} at offset {}: {}",
+                            value.r#type(),
+                            value.version(),
+                            offset,
+                            err
+                        ),
+                    }
+                }
+            }
+        });
+
+        self.epoch = epoch;
+        self.bytes_since_snapshot += bytes;
+        if applied > 0 {
+            let (image, changes) = delta.apply();
+            cache.publish(image, changes);
+        }
+        if self.bytes_since_snapshot >= self.max_bytes_between_snapshots {
+            match snapshot::write_snapshot(&self.partition_dir, &cache.image(), self.epoch) {
+                Ok(id) => {
+                    if let Some(id) = id {
+                        tracing::info!("wrote metadata snapshot {}", id.file_name());
+                    }
+                    self.bytes_since_snapshot = 0;
+                }
+                Err(err) => tracing::error!("error while writing metadata snapshot: {}", err),
+            }
+        }
+        result.map(|_| applied)
+    }
+
+    // Replays the latest snapshot and skips the segments it covers.
+    fn load_snapshot(&mut self, delta: &mut MetadataDelta) -> Result<usize, protocol::Error> {
+        let id = snapshot::latest_snapshot(&self.partition_dir)
+            .map_err(|err| protocol::Error::IOError(err.to_string()))?;
+        let Some(id) = id else {
+            return Ok(0);
+        };
+
+        snapshot::read_snapshot(&self.partition_dir, id, delta)?;
+        self.epoch = id.epoch();
+        self.segment = self
+            .segments()?
+            .into_iter()
+            .filter(|base_offset| *base_offset as i64 <= id.end_offset())
+            .max();
+        self.position = 0;
+        Ok(1)
+    }
+
+    // The loader is shared with the `MetadataWriter`, which polls it right
+    // after an append. The thread finishes its current poll and exits once
+    // `stop` is cancelled.
+    pub fn spawn(
+        loader: Arc<Mutex<MetadataLoader>>,
+        cache: Arc<MetadataCache>,
+        stop: CancellationToken,
+    ) -> io::Result<thread::JoinHandle<()>> {
+        thread::Builder::new()
+            .name("metadata-loader".to_string())
+            .spawn(move || {
+                let mut last_error = None;
+                while !stop.is_cancelled() {
+                    // A decoding bug must not stop metadata updates for good,
+                    // so a panic is reported like any other failed poll.
+                    let mut loader = loader.lock().unwrap_or_else(|err| err.into_inner());
+                    let result = panic::catch_unwind(AssertUnwindSafe(|| loader.poll(&cache)))
+                        .unwrap_or(Err(protocol::Error::IllegalArgument(
+                            "metadata loader panicked",
+                        )));
+                    match result {
+                        Ok(_) => last_error = None,
+                        Err(err) => {
+                            let err = err.to_string();
+                            if last_error.as_ref() != Some(&err) {
+                                tracing::error!("error while loading metadata: {}", err);
+                                last_error = Some(err);
+                            }
+                        }
+                    }
+                    drop(loader);
+                    thread::sleep(POLL_INTERVAL);
+                }
+            })
+    }
+
+    fn read_batches<F: FnMut(&RecordBatch, u64)>(
+        &mut self,
+        mut f: F,
+    ) -> Result<(), protocol::Error> {
+        let segments = self.segments()?;
+        let Some(mut segment) = self.segment.or(segments.first().copied()) else {
+            return Ok(());
+        };
+        self.segment = Some(segment);
+
+        loop {
+            let data = read_from(&self.segment_path(segment), self.position)
+                .map_err(|err| protocol::Error::IOError(err.to_string()))?;
+
+            let mut read_buffer = data.as_slice();
+            while !read_buffer.is_empty() {
+                let mut cursor = read_buffer;
+                match RecordBatch::read_result(&mut cursor) {
+                    Ok(batch) => {
+                        let size = (read_buffer.len() - cursor.len()) as u64;
+                        self.position += size;
+                        read_buffer = cursor;
+                        f(&batch, size);
+                    }
+                    Err(protocol::Error::BufferUnderflow) => break,
+                    // The whole batch is there but cannot be decoded, and it
+                    // never will be, so it is stepped over instead of being
+                    // read again on every poll.
+                    Err(err) => {
+                        let mut header = read_buffer;
+                        let base_offset = i64::read(&mut header);
+                        let size = 12 + u32::read(&mut header) as usize;
+                        tracing::warn!(
+                            "skipping metadata batch at offset {} in {}: {}",
+                            base_offset,
+                            self.segment_path(segment).display(),
+                            err
+                        );
+                        self.position += size as u64;
+                        read_buffer = &read_buffer[size..];
+                    }
+                }
+            }
+
+            // Kafka only rolls to a new segment after the old one is complete.
+            match segments.iter().find(|base_offset| **base_offset > segment) {
+                Some(next) if read_buffer.is_empty() => {
+                    segment = *next;
+                    self.segment = Some(segment);
+                    self.position = 0;
+                }
+                _ => return Ok(()),
+            }
+        }
+    }
+
+    fn segments(&self) -> Result<Vec<u64>, protocol::Error> {
+        list_segments(&self.partition_dir).map_err(|err| protocol::Error::IOError(err.to_string()))
+    }
+
+    fn segment_path(&self, base_offset: u64) -> PathBuf {
+        self.partition_dir.join(format!("{:020}.log", base_offset))
+    }
+}
+
+// Base offsets of the `*.log` segments, oldest first.
+pub(crate) fn list_segments(partition_dir: &Path) -> io::Result<Vec<u64>> {
+    let mut segments = std::fs::read_dir(partition_dir)?
+        .filter_map(|entry| {
+            let name = entry.ok()?.file_name();
+            name.to_str()?.strip_suffix(".log")?.parse().ok()
+        })
+        .collect::<Vec<u64>>();
+    segments.sort_unstable();
+    Ok(segments)
+}
+
+fn read_from(path: &Path, position: u64) -> io::Result<Vec<u8>> {
+    let mut file = File::open(path)?;
+    file.seek(SeekFrom::Start(position))?;
+    let mut data = Vec::new();
+    file.read_to_end(&mut data)?;
+    Ok(data)
+}
@@ -1,7 +1,20 @@
 #![allow(clippy::too_many_arguments)]
 use bytes::{Buf, BufMut, Bytes, BytesMut};
 
+pub(crate) mod cache;
+pub(crate) mod delta;
+pub(crate) mod image;
+pub(crate) mod loader;
 pub mod records;
+pub(crate) mod snapshot;
+pub(crate) mod writer;
+
+pub use cache::{MetadataCache, MetadataChange};
+pub use delta::MetadataDelta;
+pub use image::MetadataImage;
+pub use loader::MetadataLoader;
+pub use snapshot::SnapshotId;
+pub use writer::MetadataWriter;
 
 use crate::protocol::{
     self,
@@ -9,6 +22,12 @@ use crate::protocol::{
     Readable, ReadableResult, Writable,
 };
 
+const COMPRESSION_MASK: u16 = 0x07;
+// What follows the batch length up to the records: the leader epoch, magic,
+// CRC, attributes, last offset delta, both timestamps, the producer id, epoch
+// and base sequence, and the record count.
+const BATCH_HEADER_LENGTH: usize = 49;
+
 #[derive(Debug, Clone)]
 pub struct Value {}
 
@@ -36,9 +55,9 @@ impl Header {
 impl ReadableResult for Header {
     fn read_result<B: Buf>(buffer: &mut B) -> Result<Self, protocol::Error> {
         let key_length = VarInt::read_result(buffer)?.value() as usize;
-        let key = buffer.copy_to_bytes(key_length);
+        let key = read_bytes(buffer, key_length)?;
         let value_length = VarInt::read_result(buffer)?.value() as usize;
-        let value = buffer.copy_to_bytes(value_length);
+        let value = read_bytes(buffer, value_length)?;
         Ok(Self { key, value })
     }
 }
@@ -51,14 +70,21 @@ impl Writable for Header {
     }
 }
 
-#[derive(Debug, Clone, Copy)]
+// The key of a control record holds its version and type, the value the
+// type-specific payload, e.g. a SnapshotHeaderRecord.
+#[derive(Debug, Clone)]
 pub struct ControlRecord {
     version: i16,
     r#type: i16,
+    value: Bytes,
 }
 impl ControlRecord {
-    pub fn new(version: i16, r#type: i16) -> Self {
-        Self { version, r#type }
+    pub fn new(version: i16, r#type: i16, value: Bytes) -> Self {
+        Self {
+            version,
+            r#type,
+            value,
+        }
     }
 
     pub fn version(&self) -> i16 {
@@ -68,18 +94,45 @@ impl ControlRecord {
     pub fn r#type(&self) -> i16 {
         self.r#type
     }
+
+    pub fn value(&self) -> &Bytes {
+        &self.value
+    }
 }
-impl Readable for ControlRecord {
-    fn read<B: Buf>(buffer: &mut B) -> Self {
-        let version = i16::read(buffer);
-        let r#type = i16::read(buffer);
-        Self { version, r#type }
+impl ReadableResult for ControlRecord {
+    fn read_result<B: Buf>(buffer: &mut B) -> Result<Self, protocol::Error> {
+        let record = ValueRecord::read_result(buffer)?;
+        let mut key = match record.key {
+            Some(key) if key.len() >= 4 => key,
+            _ => {
+                return Err(protocol::Error::IllegalArgument(
+                    "invalid key for ControlRecord",
+                ))
+            }
+        };
+        let version = i16::read_result(&mut key)?;
+        let r#type = i16::read_result(&mut key)?;
+        Ok(Self {
+            version,
+            r#type,
+            value: record.value,
+        })
     }
 }
 impl Writable for ControlRecord {
     fn write<B: BufMut>(&self, buffer: &mut B) {
-        self.version.write(buffer);
-        self.r#type.write(buffer);
+        let mut key = BytesMut::with_capacity(4);
+        self.version.write(&mut key);
+        self.r#type.write(&mut key);
+        ValueRecord {
+            attributes: 0,
+            timestamp_delta: 0,
+            offset_delta: 0,
+            key: Some(key.freeze()),
+            value: self.value.clone(),
+            headers: vec![],
+        }
+        .write(buffer);
     }
 }
 
@@ -116,21 +169,18 @@ impl MetadataValue {
         &self.data
     }
 }
-impl Readable for MetadataValue {
-    fn read<B: Buf>(buffer: &mut B) -> Self {
-        let frame_version = u8::read(buffer);
-        let r#type = u8::read(buffer);
-        let version = u8::read(buffer);
-        let data = Bytes::copy_from_slice({
-            let len = buffer.remaining();
-            &buffer.copy_to_bytes(len)
-        });
-        Self {
+impl ReadableResult for MetadataValue {
+    fn read_result<B: Buf>(buffer: &mut B) -> Result<Self, protocol::Error> {
+        let frame_version = u8::read_result(buffer)?;
+        let r#type = u8::read_result(buffer)?;
+        let version = u8::read_result(buffer)?;
+        let data = buffer.copy_to_bytes(buffer.remaining());
+        Ok(Self {
             frame_version,
             r#type,
             version,
             data,
-        }
+        })
     }
 }
 impl Writable for MetadataValue {
@@ -197,9 +247,9 @@ impl ValueRecord {
 impl ReadableResult for ValueRecord {
     fn read_result<B: Buf>(buffer: &mut B) -> Result<Self, protocol::Error> {
         let length = VarInt::read_result_inner(buffer)? as usize;
-        let mut inner_buffer = buffer.copy_to_bytes(length);
+        let mut inner_buffer = read_bytes(buffer, length)?;
 
-        let attributes = u8::read(&mut inner_buffer);
+        let attributes = u8::read_result(&mut inner_buffer)?;
         let timestamp_delta = VarLong::read_result_inner(&mut inner_buffer)?;
         let offset_delta = VarInt::read_result_inner(&mut inner_buffer)?;
         let key = {
@@ -207,16 +257,16 @@ impl ReadableResult for ValueRecord {
             if key_length < 0 {
                 None
             } else {
-                Some(inner_buffer.copy_to_bytes(key_length as usize))
+                Some(read_bytes(&mut inner_buffer, key_length as usize)?)
             }
         };
         let value = {
             let value_length = VarInt::read_result_inner(&mut inner_buffer)? as usize;
-            inner_buffer.copy_to_bytes(value_length)
+            read_bytes(&mut inner_buffer, value_length)?
         };
         let headers = {
             let length = VarInt::read_result_inner(&mut inner_buffer)?;
-            let mut result = Vec::with_capacity(length as usize);
+            let mut result = Vec::new();
             for _ in 0..length {
                 result.push(Header::read_result(&mut inner_buffer)?);
             }
@@ -236,8 +286,8 @@ impl Writable for ValueRecord {
     fn write<B: BufMut>(&self, buffer: &mut B) {
         let mut inner_buffer = BytesMut::with_capacity(64);
         self.attributes.write(&mut inner_buffer);
-        self.timestamp_delta.write(&mut inner_buffer);
-        self.offset_delta.write(&mut inner_buffer);
+        VarLong::write_inner(&mut inner_buffer, self.timestamp_delta);
+        VarInt::write_inner(&mut inner_buffer, self.offset_delta);
         {
             if let Some(key) = &self.key {
                 VarInt::write_inner(&mut inner_buffer, key.len() as i32);
@@ -364,9 +414,16 @@ impl RecordBatch {
     pub fn records(&self) -> &[Record] {
         self.records.as_ref()
     }
-}
-impl ReadableResult for RecordBatch {
-    fn read_result<B: Buf>(buffer: &mut B) -> Result<Self, protocol::Error> {
+
+    // 0 for none, then gzip, snappy, lz4 and zstd.
+    pub fn compression(&self) -> u16 {
+        self.attributes & COMPRESSION_MASK
+    }
+
+    // Decodes the batch at the start of `buffer` and returns it with its
+    // stored CRC and whether that matched. The records are only decoded when
+    // it did and the batch is not compressed.
+    fn read_unchecked<B: Buf>(buffer: &mut B) -> Result<(Self, u32, bool), protocol::Error> {
         if buffer.remaining() < 12 {
             return Err(protocol::Error::BufferUnderflow);
         }
@@ -378,40 +435,56 @@ impl ReadableResult for RecordBatch {
             }
             buffer.copy_to_bytes(batch_length)
         };
-
-        let partition_leader_epoch = i32::read(&mut inner_buffer);
-        let magic_byte = u8::read(&mut inner_buffer);
-        let crc_read = u32::read(&mut inner_buffer);
-        let crc_check = crc32c::crc32c(&inner_buffer);
-        if crc_read != crc_check {
-            return Err(protocol::Error::IllegalArgument("crc mismatch"));
+        if inner_buffer.len() < BATCH_HEADER_LENGTH {
+            return Err(protocol::Error::IllegalArgument("record batch too short"));
         }
 
-        let attributes = u16::read(&mut inner_buffer);
-        let last_offset_delta = i32::read(&mut inner_buffer);
-        let base_timestamp = i64::read(&mut inner_buffer);
-        let max_timestamp = i64::read(&mut inner_buffer);
-        let producer_id = i64::read(&mut inner_buffer);
-        let producer_epoch = i16::read(&mut inner_buffer);
-        let base_sequence = i32::read(&mut inner_buffer);
+        // The batch is complete, so running out of data inside it means it is
+        // corrupt rather than still being written.
+        Self::read_body(base_offset, &mut inner_buffer).map_err(|err| match err {
+            protocol::Error::BufferUnderflow => {
+                protocol::Error::IllegalArgument("record batch is shorter than its records")
+            }
+            err => err,
+        })
+    }
+
+    fn read_body(
+        base_offset: i64,
+        inner_buffer: &mut Bytes,
+    ) -> Result<(Self, u32, bool), protocol::Error> {
+        let partition_leader_epoch = i32::read_result(inner_buffer)?;
+        let magic_byte = u8::read_result(inner_buffer)?;
+        let crc = u32::read_result(inner_buffer)?;
+        let crc_valid = crc == crc32c::crc32c(inner_buffer);
+
+        let attributes = u16::read_result(inner_buffer)?;
+        let last_offset_delta = i32::read_result(inner_buffer)?;
+        let base_timestamp = i64::read_result(inner_buffer)?;
+        let max_timestamp = i64::read_result(inner_buffer)?;
+        let producer_id = i64::read_result(inner_buffer)?;
+        let producer_epoch = i16::read_result(inner_buffer)?;
+        let base_sequence = i32::read_result(inner_buffer)?;
         let records = {
-            let records_length = i32::read(&mut inner_buffer) as usize;
-            if attributes & 0x10 != 0 {
+            let records_length = i32::read_result(inner_buffer)? as usize;
+            if !crc_valid || attributes & COMPRESSION_MASK != 0 {
+                vec![]
+            } else if attributes & 0x10 != 0 {
                 if records_length != 1 {
                     return Err(protocol::Error::IllegalArgument(
                         "invalid records length for ControlBatch",
                     ));
                 }
-                vec![Record::Control(ControlRecord::read(&mut inner_buffer))]
+                vec![Record::Control(ControlRecord::read_result(inner_buffer)?)]
             } else {
-                let mut records = Vec::with_capacity(records_length);
+                let mut records = Vec::new();
                 for _ in 0..records_length {
-                    records.push(Record::Value(ValueRecord::read_result(&mut inner_buffer)?));
+                    records.push(Record::Value(ValueRecord::read_result(inner_buffer)?));
                 }
                 records
             }
         };
-        Ok(Self {
+        let batch = Self {
             base_offset,
             partition_leader_epoch,
             magic_byte,
@@ -423,7 +496,22 @@ impl ReadableResult for RecordBatch {
             producer_epoch,
             base_sequence,
             records,
-        })
+        };
+        Ok((batch, crc, crc_valid))
+    }
+}
+impl ReadableResult for RecordBatch {
+    fn read_result<B: Buf>(buffer: &mut B) -> Result<Self, protocol::Error> {
+        let (batch, _, crc_valid) = Self::read_unchecked(buffer)?;
+        if !crc_valid {
+            return Err(protocol::Error::IllegalArgument("crc mismatch"));
+        }
+        if batch.compression() != 0 {
+            return Err(protocol::Error::IllegalArgument(
+                "compressed record batches are not supported",
+            ));
+        }
+        Ok(batch)
     }
 }
 impl Writable for RecordBatch {
@@ -449,7 +537,8 @@ impl Writable for RecordBatch {
             let data = inner_buffer.as_ref();
             crc32c::crc32c(data)
         };
-        let batch_length = inner_buffer.len() as i32;
+        // The length also covers partition_leader_epoch, magic_byte and crc.
+        let batch_length = inner_buffer.len() as i32 + 9;
         self.base_offset.write(buffer);
         batch_length.write(buffer);
         self.partition_leader_epoch.write(buffer);
@@ -458,3 +547,76 @@ impl Writable for RecordBatch {
         buffer.put_slice(inner_buffer.as_ref());
     }
 }
+
+// A batch as a segment stores it, for tools that inspect logs.
+#[derive(Debug, Clone)]
+pub struct StoredBatch {
+    position: usize,
+    size: usize,
+    crc: u32,
+    crc_valid: bool,
+    batch: RecordBatch,
+}
+impl StoredBatch {
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    pub fn crc(&self) -> u32 {
+        self.crc
+    }
+
+    pub fn crc_valid(&self) -> bool {
+        self.crc_valid
+    }
+
+    pub fn batch(&self) -> &RecordBatch {
+        &self.batch
+    }
+}
+
+// The batches of a segment up to the first that is cut short or can't be
+// decoded. Unlike `RecordBatch::read_result` this keeps batches whose CRC
+// doesn't match, without their records, so that they can be looked at.
+pub fn read_record_batches(data: &[u8]) -> Vec<StoredBatch> {
+    let mut batches = Vec::new();
+    let mut read_buffer = data;
+    while !read_buffer.is_empty() {
+        let mut next = read_buffer;
+        let Ok((batch, crc, crc_valid)) = RecordBatch::read_unchecked(&mut next) else {
+            break;
+        };
+        batches.push(StoredBatch {
+            position: data.len() - read_buffer.len(),
+            size: read_buffer.len() - next.len(),
+            crc,
+            crc_valid,
+            batch,
+        });
+        read_buffer = next;
+    }
+    batches
+}
+
+fn read_bytes<B: Buf>(buffer: &mut B, length: usize) -> Result<Bytes, protocol::Error> {
+    if buffer.remaining() < length {
+        return Err(protocol::Error::BufferUnderflow);
+    }
+    Ok(buffer.copy_to_bytes(length))
+}
+
+// Frames a record the way it is stored in a metadata log batch.
+pub(crate) fn metadata_value<W: Writable>(r#type: i16, version: i16, record: &W) -> Bytes {
+    let mut data = BytesMut::new();
+    record.write(&mut data);
+    let mut value = BytesMut::new();
+    MetadataValue::new(1, r#type as u8, version as u8, data.freeze()).write(&mut value);
+    value.freeze()
+}
+
+#[cfg(test)]
+mod tests;
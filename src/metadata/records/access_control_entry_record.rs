@@ -0,0 +1,125 @@
+use bytes::{Buf, BufMut};
+use uuid::Uuid;
+
+use crate::protocol::{
+    self,
+    r#type::{CompactKafkaString, TaggedFields},
+    ReadableResult, ReadableVersion, Writable,
+};
+
+use super::read_string;
+
+pub(crate) const API_KEY: i16 = 6;
+
+#[derive(Debug, Clone)]
+pub struct AccessControlEntryRecord {
+    id: Uuid,
+    resource_type: i8,
+    resource_name: String,
+    pattern_type: i8,
+    principal: String,
+    host: String,
+    operation: i8,
+    permission_type: i8,
+}
+impl AccessControlEntryRecord {
+    pub fn new(
+        id: Uuid,
+        resource_type: i8,
+        resource_name: &str,
+        pattern_type: i8,
+        principal: &str,
+        host: &str,
+        operation: i8,
+        permission_type: i8,
+    ) -> Self {
+        Self {
+            id,
+            resource_type,
+            resource_name: resource_name.to_string(),
+            pattern_type,
+            principal: principal.to_string(),
+            host: host.to_string(),
+            operation,
+            permission_type,
+        }
+    }
+
+    pub fn id(&self) -> Uuid {
+        self.id
+    }
+
+    pub fn resource_type(&self) -> i8 {
+        self.resource_type
+    }
+
+    pub fn resource_name(&self) -> &str {
+        &self.resource_name
+    }
+
+    pub fn pattern_type(&self) -> i8 {
+        self.pattern_type
+    }
+
+    pub fn principal(&self) -> &str {
+        &self.principal
+    }
+
+    pub fn host(&self) -> &str {
+        &self.host
+    }
+
+    pub fn operation(&self) -> i8 {
+        self.operation
+    }
+
+    pub fn permission_type(&self) -> i8 {
+        self.permission_type
+    }
+}
+impl ReadableVersion for AccessControlEntryRecord {
+    fn read_version<B: Buf>(buffer: &mut B, version: i16) -> Result<Self, protocol::Error> {
+        if version != 0 {
+            return Err(protocol::Error::UnsupportedVersion);
+        }
+
+        let id = Uuid::read_result(buffer)?;
+        let resource_type = i8::read_result(buffer)?;
+        let resource_name = read_string(
+            buffer,
+            "non-nullable field resource_name was serialized as null",
+        )?;
+        let pattern_type = i8::read_result(buffer)?;
+        let principal = read_string(
+            buffer,
+            "non-nullable field principal was serialized as null",
+        )?;
+        let host = read_string(buffer, "non-nullable field host was serialized as null")?;
+        let operation = i8::read_result(buffer)?;
+        let permission_type = i8::read_result(buffer)?;
+        let _tagged_fields = TaggedFields::read_result_inner(buffer)?;
+        Ok(Self {
+            id,
+            resource_type,
+            resource_name,
+            pattern_type,
+            principal,
+            host,
+            operation,
+            permission_type,
+        })
+    }
+}
+impl Writable for AccessControlEntryRecord {
+    fn write<B: BufMut>(&self, buffer: &mut B) {
+        self.id.write(buffer);
+        self.resource_type.write(buffer);
+        CompactKafkaString::write_inner(buffer, Some(self.resource_name.as_bytes()));
+        self.pattern_type.write(buffer);
+        CompactKafkaString::write_inner(buffer, Some(self.principal.as_bytes()));
+        CompactKafkaString::write_inner(buffer, Some(self.host.as_bytes()));
+        self.operation.write(buffer);
+        self.permission_type.write(buffer);
+        TaggedFields::write_empty(buffer);
+    }
+}
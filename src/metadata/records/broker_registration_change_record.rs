@@ -0,0 +1,120 @@
+use bytes::{Buf, BufMut, BytesMut};
+use uuid::Uuid;
+
+use crate::protocol::{
+    self,
+    r#type::{CompactArray, TaggedField, TaggedFields},
+    ReadableResult, ReadableVersion, Writable,
+};
+
+pub(crate) const API_KEY: i16 = 17;
+
+// The tri-state used by `fenced` and `in_controlled_shutdown`.
+pub const NO_CHANGE: i8 = 0;
+pub const SET: i8 = 1;
+pub const CLEAR: i8 = -1;
+
+#[derive(Debug, Clone)]
+pub struct BrokerRegistrationChangeRecord {
+    version: i16,
+    broker_id: i32,
+    broker_epoch: i64,
+    fenced: i8,
+    in_controlled_shutdown: i8,
+    log_dirs: Option<Vec<Uuid>>,
+}
+impl BrokerRegistrationChangeRecord {
+    pub fn new(
+        version: i16,
+        broker_id: i32,
+        broker_epoch: i64,
+        fenced: i8,
+        in_controlled_shutdown: i8,
+        log_dirs: Option<Vec<Uuid>>,
+    ) -> Self {
+        Self {
+            version,
+            broker_id,
+            broker_epoch,
+            fenced,
+            in_controlled_shutdown,
+            log_dirs,
+        }
+    }
+
+    pub fn broker_id(&self) -> i32 {
+        self.broker_id
+    }
+
+    pub fn broker_epoch(&self) -> i64 {
+        self.broker_epoch
+    }
+
+    pub fn fenced(&self) -> i8 {
+        self.fenced
+    }
+
+    pub fn in_controlled_shutdown(&self) -> i8 {
+        self.in_controlled_shutdown
+    }
+
+    pub fn log_dirs(&self) -> Option<&[Uuid]> {
+        self.log_dirs.as_deref()
+    }
+}
+impl ReadableVersion for BrokerRegistrationChangeRecord {
+    fn read_version<B: Buf>(buffer: &mut B, version: i16) -> Result<Self, protocol::Error> {
+        if !(0..=2).contains(&version) {
+            return Err(protocol::Error::UnsupportedVersion);
+        }
+
+        let broker_id = i32::read_result(buffer)?;
+        let broker_epoch = i64::read_result(buffer)?;
+        let mut fenced = NO_CHANGE;
+        let mut in_controlled_shutdown = NO_CHANGE;
+        let mut log_dirs = None;
+        for tf in TaggedFields::read_result_inner(buffer)? {
+            let mut inner_buffer = tf.data;
+            match tf.key {
+                0 => fenced = i8::read_result(&mut inner_buffer)?,
+                1 => in_controlled_shutdown = i8::read_result(&mut inner_buffer)?,
+                2 => log_dirs = CompactArray::<Uuid>::read_result_inner(&mut inner_buffer)?,
+                _ => continue,
+            }
+        }
+        Ok(Self {
+            version,
+            broker_id,
+            broker_epoch,
+            fenced,
+            in_controlled_shutdown,
+            log_dirs,
+        })
+    }
+}
+impl Writable for BrokerRegistrationChangeRecord {
+    fn write<B: BufMut>(&self, buffer: &mut B) {
+        self.broker_id.write(buffer);
+        self.broker_epoch.write(buffer);
+
+        let mut tagged_fields = Vec::with_capacity(3);
+        if self.fenced != NO_CHANGE {
+            let mut data = BytesMut::with_capacity(1);
+            self.fenced.write(&mut data);
+            tagged_fields.push(TaggedField::new(0, data.freeze()));
+        }
+        if self.version >= 1 && self.in_controlled_shutdown != NO_CHANGE {
+            let mut data = BytesMut::with_capacity(1);
+            self.in_controlled_shutdown.write(&mut data);
+            tagged_fields.push(TaggedField::new(1, data.freeze()));
+        }
+        if self.version >= 2 {
+            if let Some(log_dirs) = self.log_dirs() {
+                let mut data = BytesMut::with_capacity(1 + 16 * log_dirs.len());
+                CompactArray::write_inner(&mut data, Some(log_dirs));
+                tagged_fields.push(TaggedField::new(2, data.freeze()));
+            }
+        }
+        TaggedFields::write_inner(buffer, &tagged_fields);
+    }
+}
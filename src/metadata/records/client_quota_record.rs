@@ -0,0 +1,119 @@
+use bytes::{Buf, BufMut};
+
+use crate::protocol::{
+    self,
+    r#type::{CompactArray, CompactKafkaString, TaggedFields},
+    ReadableResult, ReadableVersion, Writable,
+};
+
+use super::{read_nullable_string, read_string};
+
+pub(crate) const API_KEY: i16 = 14;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EntityData {
+    entity_type: String,
+    entity_name: Option<String>,
+}
+impl EntityData {
+    pub fn new(entity_type: &str, entity_name: Option<&str>) -> Self {
+        Self {
+            entity_type: entity_type.to_string(),
+            entity_name: entity_name.map(str::to_string),
+        }
+    }
+
+    pub fn entity_type(&self) -> &str {
+        &self.entity_type
+    }
+
+    // Null is the default entity of that type.
+    pub fn entity_name(&self) -> Option<&str> {
+        self.entity_name.as_deref()
+    }
+}
+impl ReadableResult for EntityData {
+    fn read_result<B: Buf>(buffer: &mut B) -> Result<Self, protocol::Error> {
+        let entity_type = read_string(
+            buffer,
+            "non-nullable field entity_type was serialized as null",
+        )?;
+        let entity_name = read_nullable_string(buffer)?;
+        let _tagged_fields = TaggedFields::read_result_inner(buffer)?;
+        Ok(Self {
+            entity_type,
+            entity_name,
+        })
+    }
+}
+impl Writable for EntityData {
+    fn write<B: BufMut>(&self, buffer: &mut B) {
+        CompactKafkaString::write_inner(buffer, Some(self.entity_type.as_bytes()));
+        CompactKafkaString::write_inner(buffer, self.entity_name().map(str::as_bytes));
+        TaggedFields::write_empty(buffer);
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ClientQuotaRecord {
+    entity: Vec<EntityData>,
+    key: String,
+    value: f64,
+    remove: bool,
+}
+impl ClientQuotaRecord {
+    pub fn new(entity: Vec<EntityData>, key: &str, value: f64, remove: bool) -> Self {
+        Self {
+            entity,
+            key: key.to_string(),
+            value,
+            remove,
+        }
+    }
+
+    pub fn entity(&self) -> &[EntityData] {
+        &self.entity
+    }
+
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    pub fn value(&self) -> f64 {
+        self.value
+    }
+
+    pub fn remove(&self) -> bool {
+        self.remove
+    }
+}
+impl ReadableVersion for ClientQuotaRecord {
+    fn read_version<B: Buf>(buffer: &mut B, version: i16) -> Result<Self, protocol::Error> {
+        if version != 0 {
+            return Err(protocol::Error::UnsupportedVersion);
+        }
+
+        let entity = CompactArray::<EntityData>::read_result_inner(buffer)?.ok_or(
+            protocol::Error::IllegalArgument("non-nullable field entity was serialized as null"),
+        )?;
+        let key = read_string(buffer, "non-nullable field key was serialized as null")?;
+        let value = f64::read_result(buffer)?;
+        let remove = bool::read_result(buffer)?;
+        let _tagged_fields = TaggedFields::read_result_inner(buffer)?;
+        Ok(Self {
+            entity,
+            key,
+            value,
+            remove,
+        })
+    }
+}
+impl Writable for ClientQuotaRecord {
+    fn write<B: BufMut>(&self, buffer: &mut B) {
+        CompactArray::write_inner(buffer, Some(self.entity()));
+        CompactKafkaString::write_inner(buffer, Some(self.key.as_bytes()));
+        self.value.write(buffer);
+        self.remove.write(buffer);
+        TaggedFields::write_empty(buffer);
+    }
+}
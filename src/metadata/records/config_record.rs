@@ -0,0 +1,77 @@
+use bytes::{Buf, BufMut};
+
+use crate::protocol::{
+    self,
+    r#type::{CompactKafkaString, TaggedFields},
+    ReadableResult, ReadableVersion, Writable,
+};
+
+use super::{read_nullable_string, read_string};
+
+pub(crate) const API_KEY: i16 = 4;
+
+#[derive(Debug, Clone)]
+pub struct ConfigRecord {
+    resource_type: i8,
+    resource_name: String,
+    name: String,
+    value: Option<String>,
+}
+impl ConfigRecord {
+    pub fn new(resource_type: i8, resource_name: &str, name: &str, value: Option<&str>) -> Self {
+        Self {
+            resource_type,
+            resource_name: resource_name.to_string(),
+            name: name.to_string(),
+            value: value.map(str::to_string),
+        }
+    }
+
+    pub fn resource_type(&self) -> i8 {
+        self.resource_type
+    }
+
+    pub fn resource_name(&self) -> &str {
+        &self.resource_name
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    // A null value removes the config.
+    pub fn value(&self) -> Option<&str> {
+        self.value.as_deref()
+    }
+}
+impl ReadableVersion for ConfigRecord {
+    fn read_version<B: Buf>(buffer: &mut B, version: i16) -> Result<Self, protocol::Error> {
+        if version != 0 {
+            return Err(protocol::Error::UnsupportedVersion);
+        }
+
+        let resource_type = i8::read_result(buffer)?;
+        let resource_name = read_string(
+            buffer,
+            "non-nullable field resource_name was serialized as null",
+        )?;
+        let name = read_string(buffer, "non-nullable field name was serialized as null")?;
+        let value = read_nullable_string(buffer)?;
+        let _tagged_fields = TaggedFields::read_result_inner(buffer)?;
+        Ok(Self {
+            resource_type,
+            resource_name,
+            name,
+            value,
+        })
+    }
+}
+impl Writable for ConfigRecord {
+    fn write<B: BufMut>(&self, buffer: &mut B) {
+        self.resource_type.write(buffer);
+        CompactKafkaString::write_inner(buffer, Some(self.resource_name.as_bytes()));
+        CompactKafkaString::write_inner(buffer, Some(self.name.as_bytes()));
+        CompactKafkaString::write_inner(buffer, self.value().map(str::as_bytes));
+        TaggedFields::write_empty(buffer);
+    }
+}
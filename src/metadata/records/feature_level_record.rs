@@ -0,0 +1,56 @@
+use bytes::{Buf, BufMut};
+
+use crate::protocol::{
+    self,
+    r#type::{CompactKafkaString, TaggedFields},
+    ReadableResult, ReadableVersion, Writable,
+};
+
+use super::read_string;
+
+pub(crate) const API_KEY: i16 = 12;
+
+#[derive(Debug, Clone)]
+pub struct FeatureLevelRecord {
+    name: String,
+    feature_level: i16,
+}
+impl FeatureLevelRecord {
+    pub fn new(name: &str, feature_level: i16) -> Self {
+        Self {
+            name: name.to_string(),
+            feature_level,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    // Level 0 removes the feature.
+    pub fn feature_level(&self) -> i16 {
+        self.feature_level
+    }
+}
+impl ReadableVersion for FeatureLevelRecord {
+    fn read_version<B: Buf>(buffer: &mut B, version: i16) -> Result<Self, protocol::Error> {
+        if version != 0 {
+            return Err(protocol::Error::UnsupportedVersion);
+        }
+
+        let name = read_string(buffer, "non-nullable field name was serialized as null")?;
+        let feature_level = i16::read_result(buffer)?;
+        let _tagged_fields = TaggedFields::read_result_inner(buffer)?;
+        Ok(Self {
+            name,
+            feature_level,
+        })
+    }
+}
+impl Writable for FeatureLevelRecord {
+    fn write<B: BufMut>(&self, buffer: &mut B) {
+        CompactKafkaString::write_inner(buffer, Some(self.name.as_bytes()));
+        self.feature_level.write(buffer);
+        TaggedFields::write_empty(buffer);
+    }
+}
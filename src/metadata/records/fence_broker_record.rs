@@ -0,0 +1,43 @@
+use bytes::{Buf, BufMut};
+
+use crate::protocol::{self, r#type::TaggedFields, ReadableResult, ReadableVersion, Writable};
+
+pub(crate) const API_KEY: i16 = 8;
+
+#[derive(Debug, Clone)]
+pub struct FenceBrokerRecord {
+    id: i32,
+    epoch: i64,
+}
+impl FenceBrokerRecord {
+    pub fn new(id: i32, epoch: i64) -> Self {
+        Self { id, epoch }
+    }
+
+    pub fn id(&self) -> i32 {
+        self.id
+    }
+
+    pub fn epoch(&self) -> i64 {
+        self.epoch
+    }
+}
+impl ReadableVersion for FenceBrokerRecord {
+    fn read_version<B: Buf>(buffer: &mut B, version: i16) -> Result<Self, protocol::Error> {
+        if version != 0 {
+            return Err(protocol::Error::UnsupportedVersion);
+        }
+
+        let id = i32::read_result(buffer)?;
+        let epoch = i64::read_result(buffer)?;
+        let _tagged_fields = TaggedFields::read_result_inner(buffer)?;
+        Ok(Self { id, epoch })
+    }
+}
+impl Writable for FenceBrokerRecord {
+    fn write<B: BufMut>(&self, buffer: &mut B) {
+        self.id.write(buffer);
+        self.epoch.write(buffer);
+        TaggedFields::write_empty(buffer);
+    }
+}
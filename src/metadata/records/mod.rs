@@ -1,5 +1,151 @@
+use bytes::{Buf, Bytes};
+
+use crate::protocol::{self, r#type::CompactKafkaString, ReadableVersion};
+
+use super::MetadataValue;
+
+pub(crate) mod access_control_entry_record;
+pub(crate) mod broker_registration_change_record;
+pub(crate) mod client_quota_record;
+pub(crate) mod config_record;
+pub(crate) mod feature_level_record;
+pub(crate) mod fence_broker_record;
+pub(crate) mod partition_change_record;
 pub(crate) mod partition_record;
+pub(crate) mod producer_ids_record;
+pub(crate) mod register_broker_record;
+pub(crate) mod remove_access_control_entry_record;
+pub(crate) mod remove_topic_record;
+pub(crate) mod remove_user_scram_credential_record;
 pub(crate) mod topic_record;
+pub(crate) mod unfence_broker_record;
+pub(crate) mod unregister_broker_record;
+pub(crate) mod user_scram_credential_record;
 
+pub use access_control_entry_record::AccessControlEntryRecord;
+pub use broker_registration_change_record::BrokerRegistrationChangeRecord;
+pub use client_quota_record::{ClientQuotaRecord, EntityData};
+pub use config_record::ConfigRecord;
+pub use feature_level_record::FeatureLevelRecord;
+pub use fence_broker_record::FenceBrokerRecord;
+pub use partition_change_record::PartitionChangeRecord;
 pub use partition_record::PartitionRecord;
+pub use producer_ids_record::ProducerIdsRecord;
+pub use register_broker_record::{BrokerEndpoint, BrokerFeature, RegisterBrokerRecord};
+pub use remove_access_control_entry_record::RemoveAccessControlEntryRecord;
+pub use remove_topic_record::RemoveTopicRecord;
+pub use remove_user_scram_credential_record::RemoveUserScramCredentialRecord;
 pub use topic_record::TopicRecord;
+pub use unfence_broker_record::UnfenceBrokerRecord;
+pub use unregister_broker_record::UnregisterBrokerRecord;
+pub use user_scram_credential_record::UserScramCredentialRecord;
+
+pub(crate) const NO_OP_RECORD_API_KEY: i16 = 20;
+
+#[derive(Debug, Clone)]
+pub enum MetadataRecord {
+    RegisterBroker(RegisterBrokerRecord),
+    UnregisterBroker(UnregisterBrokerRecord),
+    Topic(TopicRecord),
+    Partition(PartitionRecord),
+    Config(ConfigRecord),
+    PartitionChange(PartitionChangeRecord),
+    AccessControlEntry(AccessControlEntryRecord),
+    RemoveAccessControlEntry(RemoveAccessControlEntryRecord),
+    FenceBroker(FenceBrokerRecord),
+    UnfenceBroker(UnfenceBrokerRecord),
+    RemoveTopic(RemoveTopicRecord),
+    UserScramCredential(UserScramCredentialRecord),
+    FeatureLevel(FeatureLevelRecord),
+    ClientQuota(ClientQuotaRecord),
+    ProducerIds(ProducerIdsRecord),
+    BrokerRegistrationChange(BrokerRegistrationChangeRecord),
+    NoOp,
+    RemoveUserScramCredential(RemoveUserScramCredentialRecord),
+    // Types the broker does not need, e.g. delegation tokens or transactions.
+    Unknown { r#type: i16, version: i16 },
+}
+impl MetadataRecord {
+    pub fn read(value: &MetadataValue) -> Result<Self, protocol::Error> {
+        let version = value.version() as i16;
+        let buffer = &mut value.data().clone();
+        let record = match value.r#type() as i16 {
+            register_broker_record::API_KEY => {
+                Self::RegisterBroker(RegisterBrokerRecord::read_version(buffer, version)?)
+            }
+            unregister_broker_record::API_KEY => {
+                Self::UnregisterBroker(UnregisterBrokerRecord::read_version(buffer, version)?)
+            }
+            topic_record::API_KEY => Self::Topic(TopicRecord::read_version(buffer, version)?),
+            partition_record::API_KEY => {
+                Self::Partition(PartitionRecord::read_version(buffer, version)?)
+            }
+            config_record::API_KEY => Self::Config(ConfigRecord::read_version(buffer, version)?),
+            partition_change_record::API_KEY => {
+                Self::PartitionChange(PartitionChangeRecord::read_version(buffer, version)?)
+            }
+            access_control_entry_record::API_KEY => {
+                Self::AccessControlEntry(AccessControlEntryRecord::read_version(buffer, version)?)
+            }
+            remove_access_control_entry_record::API_KEY => Self::RemoveAccessControlEntry(
+                RemoveAccessControlEntryRecord::read_version(buffer, version)?,
+            ),
+            fence_broker_record::API_KEY => {
+                Self::FenceBroker(FenceBrokerRecord::read_version(buffer, version)?)
+            }
+            unfence_broker_record::API_KEY => {
+                Self::UnfenceBroker(UnfenceBrokerRecord::read_version(buffer, version)?)
+            }
+            remove_topic_record::API_KEY => {
+                Self::RemoveTopic(RemoveTopicRecord::read_version(buffer, version)?)
+            }
+            user_scram_credential_record::API_KEY => {
+                Self::UserScramCredential(UserScramCredentialRecord::read_version(buffer, version)?)
+            }
+            feature_level_record::API_KEY => {
+                Self::FeatureLevel(FeatureLevelRecord::read_version(buffer, version)?)
+            }
+            client_quota_record::API_KEY => {
+                Self::ClientQuota(ClientQuotaRecord::read_version(buffer, version)?)
+            }
+            producer_ids_record::API_KEY => {
+                Self::ProducerIds(ProducerIdsRecord::read_version(buffer, version)?)
+            }
+            broker_registration_change_record::API_KEY => Self::BrokerRegistrationChange(
+                BrokerRegistrationChangeRecord::read_version(buffer, version)?,
+            ),
+            NO_OP_RECORD_API_KEY => Self::NoOp,
+            remove_user_scram_credential_record::API_KEY => Self::RemoveUserScramCredential(
+                RemoveUserScramCredentialRecord::read_version(buffer, version)?,
+            ),
+            r#type => Self::Unknown { r#type, version },
+        };
+        Ok(record)
+    }
+}
+
+pub(crate) fn read_string<B: Buf>(
+    buffer: &mut B,
+    null_error: &'static str,
+) -> Result<String, protocol::Error> {
+    let value = CompactKafkaString::read_result_inner(buffer)?
+        .ok_or(protocol::Error::IllegalArgument(null_error))?;
+    Ok(std::str::from_utf8(&value)?.to_string())
+}
+
+pub(crate) fn read_nullable_string<B: Buf>(
+    buffer: &mut B,
+) -> Result<Option<String>, protocol::Error> {
+    match CompactKafkaString::read_result_inner(buffer)? {
+        Some(value) => Ok(Some(std::str::from_utf8(&value)?.to_string())),
+        None => Ok(None),
+    }
+}
+
+pub(crate) fn read_bytes<B: Buf>(
+    buffer: &mut B,
+    null_error: &'static str,
+) -> Result<Bytes, protocol::Error> {
+    CompactKafkaString::read_result_inner(buffer)?
+        .ok_or(protocol::Error::IllegalArgument(null_error))
+}
@@ -0,0 +1,263 @@
+use bytes::{Buf, BufMut, BytesMut};
+use uuid::Uuid;
+
+use crate::protocol::{
+    self,
+    r#type::{CompactArray, TaggedField, TaggedFields},
+    ReadableResult, ReadableVersion, Writable,
+};
+
+pub(crate) const API_KEY: i16 = 5;
+
+// Leader value meaning the leader did not change.
+pub const NO_LEADER_CHANGE: i32 = -2;
+
+// Every field past the ids is tagged; an absent field means "unchanged".
+// Version 1 adds the ELR fields (KIP-966), version 2 the directories (KIP-858).
+#[derive(Debug, Clone)]
+pub struct PartitionChangeRecord {
+    version: i16,
+    partition_id: i32,
+    topic_id: Uuid,
+    isr: Option<Vec<i32>>,
+    leader: i32,
+    replicas: Option<Vec<i32>>,
+    removing_replicas: Option<Vec<i32>>,
+    adding_replicas: Option<Vec<i32>>,
+    leader_recovery_state: i8,
+    eligible_leader_replicas: Option<Vec<i32>>,
+    last_known_elr: Option<Vec<i32>>,
+    directories: Option<Vec<Uuid>>,
+}
+impl PartitionChangeRecord {
+    pub fn v0(
+        partition_id: i32,
+        topic_id: Uuid,
+        isr: Option<Vec<i32>>,
+        leader: i32,
+        replicas: Option<Vec<i32>>,
+        removing_replicas: Option<Vec<i32>>,
+        adding_replicas: Option<Vec<i32>>,
+        leader_recovery_state: i8,
+    ) -> Self {
+        Self {
+            version: 0,
+            partition_id,
+            topic_id,
+            isr,
+            leader,
+            replicas,
+            removing_replicas,
+            adding_replicas,
+            leader_recovery_state,
+            eligible_leader_replicas: None,
+            last_known_elr: None,
+            directories: None,
+        }
+    }
+
+    pub fn v1(
+        partition_id: i32,
+        topic_id: Uuid,
+        isr: Option<Vec<i32>>,
+        leader: i32,
+        replicas: Option<Vec<i32>>,
+        removing_replicas: Option<Vec<i32>>,
+        adding_replicas: Option<Vec<i32>>,
+        leader_recovery_state: i8,
+        eligible_leader_replicas: Option<Vec<i32>>,
+        last_known_elr: Option<Vec<i32>>,
+    ) -> Self {
+        Self {
+            version: 1,
+            partition_id,
+            topic_id,
+            isr,
+            leader,
+            replicas,
+            removing_replicas,
+            adding_replicas,
+            leader_recovery_state,
+            eligible_leader_replicas,
+            last_known_elr,
+            directories: None,
+        }
+    }
+
+    pub fn v2(
+        partition_id: i32,
+        topic_id: Uuid,
+        isr: Option<Vec<i32>>,
+        leader: i32,
+        replicas: Option<Vec<i32>>,
+        removing_replicas: Option<Vec<i32>>,
+        adding_replicas: Option<Vec<i32>>,
+        leader_recovery_state: i8,
+        eligible_leader_replicas: Option<Vec<i32>>,
+        last_known_elr: Option<Vec<i32>>,
+        directories: Option<Vec<Uuid>>,
+    ) -> Self {
+        Self {
+            version: 2,
+            partition_id,
+            topic_id,
+            isr,
+            leader,
+            replicas,
+            removing_replicas,
+            adding_replicas,
+            leader_recovery_state,
+            eligible_leader_replicas,
+            last_known_elr,
+            directories,
+        }
+    }
+
+    pub fn version(&self) -> i16 {
+        self.version
+    }
+
+    pub fn partition_id(&self) -> i32 {
+        self.partition_id
+    }
+
+    pub fn topic_id(&self) -> Uuid {
+        self.topic_id
+    }
+
+    pub fn isr(&self) -> Option<&[i32]> {
+        self.isr.as_deref()
+    }
+
+    pub fn leader(&self) -> i32 {
+        self.leader
+    }
+
+    pub fn replicas(&self) -> Option<&[i32]> {
+        self.replicas.as_deref()
+    }
+
+    pub fn removing_replicas(&self) -> Option<&[i32]> {
+        self.removing_replicas.as_deref()
+    }
+
+    pub fn adding_replicas(&self) -> Option<&[i32]> {
+        self.adding_replicas.as_deref()
+    }
+
+    pub fn leader_recovery_state(&self) -> i8 {
+        self.leader_recovery_state
+    }
+
+    pub fn eligible_leader_replicas(&self) -> Option<&[i32]> {
+        self.eligible_leader_replicas.as_deref()
+    }
+
+    pub fn last_known_elr(&self) -> Option<&[i32]> {
+        self.last_known_elr.as_deref()
+    }
+
+    pub fn directories(&self) -> Option<&[Uuid]> {
+        self.directories.as_deref()
+    }
+}
+impl ReadableVersion for PartitionChangeRecord {
+    fn read_version<B: Buf>(buffer: &mut B, version: i16) -> Result<Self, protocol::Error> {
+        if !(0..=2).contains(&version) {
+            return Err(protocol::Error::UnsupportedVersion);
+        }
+
+        let partition_id = i32::read_result(buffer)?;
+        let topic_id = Uuid::read_result(buffer)?;
+        let mut isr = None;
+        let mut leader = NO_LEADER_CHANGE;
+        let mut replicas = None;
+        let mut removing_replicas = None;
+        let mut adding_replicas = None;
+        let mut leader_recovery_state = -1;
+        let mut eligible_leader_replicas = None;
+        let mut last_known_elr = None;
+        let mut directories = None;
+        for tf in TaggedFields::read_result_inner(buffer)? {
+            let mut inner_buffer = tf.data;
+            match tf.key {
+                0 => isr = CompactArray::<i32>::read_result_inner(&mut inner_buffer)?,
+                1 => leader = i32::read_result(&mut inner_buffer)?,
+                2 => replicas = CompactArray::<i32>::read_result_inner(&mut inner_buffer)?,
+                3 => removing_replicas = CompactArray::<i32>::read_result_inner(&mut inner_buffer)?,
+                4 => adding_replicas = CompactArray::<i32>::read_result_inner(&mut inner_buffer)?,
+                5 => leader_recovery_state = i8::read_result(&mut inner_buffer)?,
+                6 if version >= 1 => {
+                    eligible_leader_replicas =
+                        CompactArray::<i32>::read_result_inner(&mut inner_buffer)?
+                }
+                7 if version >= 1 => {
+                    last_known_elr = CompactArray::<i32>::read_result_inner(&mut inner_buffer)?
+                }
+                8 if version >= 2 => {
+                    directories = CompactArray::<Uuid>::read_result_inner(&mut inner_buffer)?
+                }
+                _ => continue,
+            }
+        }
+
+        Ok(Self {
+            version,
+            partition_id,
+            topic_id,
+            isr,
+            leader,
+            replicas,
+            removing_replicas,
+            adding_replicas,
+            leader_recovery_state,
+            eligible_leader_replicas,
+            last_known_elr,
+            directories,
+        })
+    }
+}
+impl Writable for PartitionChangeRecord {
+    fn write<B: BufMut>(&self, buffer: &mut B) {
+        self.partition_id.write(buffer);
+        self.topic_id.write(buffer);
+
+        let mut tagged_fields = Vec::with_capacity(9);
+        let mut arrays = vec![
+            (0, self.isr()),
+            (2, self.replicas()),
+            (3, self.removing_replicas()),
+            (4, self.adding_replicas()),
+        ];
+        if self.version >= 1 {
+            arrays.push((6, self.eligible_leader_replicas()));
+            arrays.push((7, self.last_known_elr()));
+        }
+        for (key, value) in arrays {
+            if let Some(value) = value {
+                let mut data = BytesMut::with_capacity(1 + 4 * value.len());
+                CompactArray::write_inner(&mut data, Some(value));
+                tagged_fields.push(TaggedField::new(key, data.freeze()));
+            }
+        }
+        if self.leader != NO_LEADER_CHANGE {
+            let mut data = BytesMut::with_capacity(4);
+            self.leader.write(&mut data);
+            tagged_fields.push(TaggedField::new(1, data.freeze()));
+        }
+        if self.leader_recovery_state != -1 {
+            let mut data = BytesMut::with_capacity(1);
+            self.leader_recovery_state.write(&mut data);
+            tagged_fields.push(TaggedField::new(5, data.freeze()));
+        }
+        if self.version >= 2 {
+            if let Some(directories) = self.directories() {
+                let mut data = BytesMut::with_capacity(1 + 16 * directories.len());
+                CompactArray::write_inner(&mut data, Some(directories));
+                tagged_fields.push(TaggedField::new(8, data.freeze()));
+            }
+        }
+
+        TaggedFields::write_inner(buffer, &tagged_fields);
+    }
+}
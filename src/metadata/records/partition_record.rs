@@ -4,7 +4,7 @@ use uuid::Uuid;
 use crate::protocol::{
     self,
     r#type::{CompactArray, TaggedField, TaggedFields},
-    Readable, ReadableVersion, Writable,
+    ReadableResult, ReadableVersion, Writable,
 };
 
 pub(crate) const API_KEY: i16 = 3;
@@ -176,31 +176,31 @@ impl PartitionRecord {
 impl ReadableVersion for PartitionRecord {
     fn read_version<B: Buf>(buffer: &mut B, version: i16) -> Result<Self, protocol::Error> {
         if (0..=2).contains(&version) {
-            let partition_id = buffer.get_i32();
-            let topic_id = Uuid::read(buffer);
-            let replicas = CompactArray::<i32>::read_inner(buffer)?.ok_or(
+            let partition_id = i32::read_result(buffer)?;
+            let topic_id = Uuid::read_result(buffer)?;
+            let replicas = CompactArray::<i32>::read_result_inner(buffer)?.ok_or(
                 protocol::Error::IllegalArgument(
                     "non-nullable field replicas was serialized as null",
                 ),
             )?;
-            let isr = CompactArray::<i32>::read_inner(buffer)?.ok_or(
+            let isr = CompactArray::<i32>::read_result_inner(buffer)?.ok_or(
                 protocol::Error::IllegalArgument("non-nullable field isr was serialized as null"),
             )?;
-            let removing_replicas = CompactArray::<i32>::read_inner(buffer)?.ok_or(
+            let removing_replicas = CompactArray::<i32>::read_result_inner(buffer)?.ok_or(
                 protocol::Error::IllegalArgument(
                     "non-nullable field removing_replicas was serialized as null",
                 ),
             )?;
-            let adding_replicas = CompactArray::<i32>::read_inner(buffer)?.ok_or(
+            let adding_replicas = CompactArray::<i32>::read_result_inner(buffer)?.ok_or(
                 protocol::Error::IllegalArgument(
                     "non-nullable field adding_replicas was serialized as null",
                 ),
             )?;
-            let leader = buffer.get_i32();
-            let leader_epoch = buffer.get_i32();
-            let partition_epoch = buffer.get_i32();
+            let leader = i32::read_result(buffer)?;
+            let leader_epoch = i32::read_result(buffer)?;
+            let partition_epoch = i32::read_result(buffer)?;
             let directories = if version >= 1 {
-                CompactArray::<Uuid>::read_inner(buffer)?.ok_or(
+                CompactArray::<Uuid>::read_result_inner(buffer)?.ok_or(
                     protocol::Error::IllegalArgument(
                         "non-nullable field directories was serialized as null",
                     ),
@@ -217,11 +217,12 @@ impl ReadableVersion for PartitionRecord {
                 let mut inner_buffer = tf.data;
                 match tf.key {
                     0 => {
-                        leader_recovery_state = u8::read(&mut inner_buffer);
+                        leader_recovery_state = u8::read_result(&mut inner_buffer)?;
                     }
                     1 => {
                         if version >= 2 {
-                            if let Some(value) = CompactArray::<i32>::read_inner(&mut inner_buffer)?
+                            if let Some(value) =
+                                CompactArray::<i32>::read_result_inner(&mut inner_buffer)?
                             {
                                 eligible_leader_replicas.replace(value);
                             }
@@ -229,13 +230,14 @@ impl ReadableVersion for PartitionRecord {
                     }
                     2 => {
                         if version >= 2 {
-                            if let Some(value) = CompactArray::<i32>::read_inner(&mut inner_buffer)?
+                            if let Some(value) =
+                                CompactArray::<i32>::read_result_inner(&mut inner_buffer)?
                             {
                                 last_known_elr.replace(value);
                             }
                         }
                     }
-                    _ => unreachable!(),
+                    _ => continue,
                 }
             }
 
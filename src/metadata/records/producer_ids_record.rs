@@ -0,0 +1,58 @@
+use bytes::{Buf, BufMut};
+
+use crate::protocol::{self, r#type::TaggedFields, ReadableResult, ReadableVersion, Writable};
+
+pub(crate) const API_KEY: i16 = 15;
+
+#[derive(Debug, Clone)]
+pub struct ProducerIdsRecord {
+    broker_id: i32,
+    broker_epoch: i64,
+    next_producer_id: i64,
+}
+impl ProducerIdsRecord {
+    pub fn new(broker_id: i32, broker_epoch: i64, next_producer_id: i64) -> Self {
+        Self {
+            broker_id,
+            broker_epoch,
+            next_producer_id,
+        }
+    }
+
+    pub fn broker_id(&self) -> i32 {
+        self.broker_id
+    }
+
+    pub fn broker_epoch(&self) -> i64 {
+        self.broker_epoch
+    }
+
+    pub fn next_producer_id(&self) -> i64 {
+        self.next_producer_id
+    }
+}
+impl ReadableVersion for ProducerIdsRecord {
+    fn read_version<B: Buf>(buffer: &mut B, version: i16) -> Result<Self, protocol::Error> {
+        if version != 0 {
+            return Err(protocol::Error::UnsupportedVersion);
+        }
+
+        let broker_id = i32::read_result(buffer)?;
+        let broker_epoch = i64::read_result(buffer)?;
+        let next_producer_id = i64::read_result(buffer)?;
+        let _tagged_fields = TaggedFields::read_result_inner(buffer)?;
+        Ok(Self {
+            broker_id,
+            broker_epoch,
+            next_producer_id,
+        })
+    }
+}
+impl Writable for ProducerIdsRecord {
+    fn write<B: BufMut>(&self, buffer: &mut B) {
+        self.broker_id.write(buffer);
+        self.broker_epoch.write(buffer);
+        self.next_producer_id.write(buffer);
+        TaggedFields::write_empty(buffer);
+    }
+}
@@ -0,0 +1,270 @@
+use bytes::{Buf, BufMut};
+use uuid::Uuid;
+
+use crate::protocol::{
+    self,
+    r#type::{CompactArray, CompactKafkaString, TaggedFields},
+    ReadableResult, ReadableVersion, Writable,
+};
+
+use super::{read_nullable_string, read_string};
+
+pub(crate) const API_KEY: i16 = 0;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BrokerEndpoint {
+    name: String,
+    host: String,
+    port: u16,
+    security_protocol: i16,
+}
+impl BrokerEndpoint {
+    pub fn new(name: &str, host: &str, port: u16, security_protocol: i16) -> Self {
+        Self {
+            name: name.to_string(),
+            host: host.to_string(),
+            port,
+            security_protocol,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn host(&self) -> &str {
+        &self.host
+    }
+
+    pub fn port(&self) -> u16 {
+        self.port
+    }
+
+    pub fn security_protocol(&self) -> i16 {
+        self.security_protocol
+    }
+}
+impl ReadableResult for BrokerEndpoint {
+    fn read_result<B: Buf>(buffer: &mut B) -> Result<Self, protocol::Error> {
+        let name = read_string(buffer, "non-nullable field name was serialized as null")?;
+        let host = read_string(buffer, "non-nullable field host was serialized as null")?;
+        let port = u16::read_result(buffer)?;
+        let security_protocol = i16::read_result(buffer)?;
+        let _tagged_fields = TaggedFields::read_result_inner(buffer)?;
+        Ok(Self {
+            name,
+            host,
+            port,
+            security_protocol,
+        })
+    }
+}
+impl Writable for BrokerEndpoint {
+    fn write<B: BufMut>(&self, buffer: &mut B) {
+        CompactKafkaString::write_inner(buffer, Some(self.name.as_bytes()));
+        CompactKafkaString::write_inner(buffer, Some(self.host.as_bytes()));
+        self.port.write(buffer);
+        self.security_protocol.write(buffer);
+        TaggedFields::write_empty(buffer);
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BrokerFeature {
+    name: String,
+    min_supported_version: i16,
+    max_supported_version: i16,
+}
+impl BrokerFeature {
+    pub fn new(name: &str, min_supported_version: i16, max_supported_version: i16) -> Self {
+        Self {
+            name: name.to_string(),
+            min_supported_version,
+            max_supported_version,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn min_supported_version(&self) -> i16 {
+        self.min_supported_version
+    }
+
+    pub fn max_supported_version(&self) -> i16 {
+        self.max_supported_version
+    }
+}
+impl ReadableResult for BrokerFeature {
+    fn read_result<B: Buf>(buffer: &mut B) -> Result<Self, protocol::Error> {
+        let name = read_string(buffer, "non-nullable field name was serialized as null")?;
+        let min_supported_version = i16::read_result(buffer)?;
+        let max_supported_version = i16::read_result(buffer)?;
+        let _tagged_fields = TaggedFields::read_result_inner(buffer)?;
+        Ok(Self {
+            name,
+            min_supported_version,
+            max_supported_version,
+        })
+    }
+}
+impl Writable for BrokerFeature {
+    fn write<B: BufMut>(&self, buffer: &mut B) {
+        CompactKafkaString::write_inner(buffer, Some(self.name.as_bytes()));
+        self.min_supported_version.write(buffer);
+        self.max_supported_version.write(buffer);
+        TaggedFields::write_empty(buffer);
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RegisterBrokerRecord {
+    version: i16,
+    broker_id: i32,
+    is_migrating_zk_broker: bool,
+    incarnation_id: Uuid,
+    broker_epoch: i64,
+    end_points: Vec<BrokerEndpoint>,
+    features: Vec<BrokerFeature>,
+    rack: Option<String>,
+    fenced: bool,
+    in_controlled_shutdown: bool,
+    log_dirs: Vec<Uuid>,
+}
+impl RegisterBrokerRecord {
+    pub fn new(
+        version: i16,
+        broker_id: i32,
+        incarnation_id: Uuid,
+        broker_epoch: i64,
+        end_points: Vec<BrokerEndpoint>,
+        features: Vec<BrokerFeature>,
+        rack: Option<&str>,
+        fenced: bool,
+        in_controlled_shutdown: bool,
+        log_dirs: Vec<Uuid>,
+    ) -> Self {
+        Self {
+            version,
+            broker_id,
+            is_migrating_zk_broker: false,
+            incarnation_id,
+            broker_epoch,
+            end_points,
+            features,
+            rack: rack.map(str::to_string),
+            fenced,
+            in_controlled_shutdown,
+            log_dirs,
+        }
+    }
+
+    pub fn version(&self) -> i16 {
+        self.version
+    }
+
+    pub fn broker_id(&self) -> i32 {
+        self.broker_id
+    }
+
+    pub fn is_migrating_zk_broker(&self) -> bool {
+        self.is_migrating_zk_broker
+    }
+
+    pub fn incarnation_id(&self) -> Uuid {
+        self.incarnation_id
+    }
+
+    pub fn broker_epoch(&self) -> i64 {
+        self.broker_epoch
+    }
+
+    pub fn end_points(&self) -> &[BrokerEndpoint] {
+        &self.end_points
+    }
+
+    pub fn features(&self) -> &[BrokerFeature] {
+        &self.features
+    }
+
+    pub fn rack(&self) -> Option<&str> {
+        self.rack.as_deref()
+    }
+
+    pub fn fenced(&self) -> bool {
+        self.fenced
+    }
+
+    pub fn in_controlled_shutdown(&self) -> bool {
+        self.in_controlled_shutdown
+    }
+
+    pub fn log_dirs(&self) -> &[Uuid] {
+        &self.log_dirs
+    }
+}
+impl ReadableVersion for RegisterBrokerRecord {
+    fn read_version<B: Buf>(buffer: &mut B, version: i16) -> Result<Self, protocol::Error> {
+        if !(0..=3).contains(&version) {
+            return Err(protocol::Error::UnsupportedVersion);
+        }
+
+        let broker_id = i32::read_result(buffer)?;
+        let is_migrating_zk_broker = version >= 2 && bool::read_result(buffer)?;
+        let incarnation_id = Uuid::read_result(buffer)?;
+        let broker_epoch = i64::read_result(buffer)?;
+        let end_points = CompactArray::<BrokerEndpoint>::read_result_inner(buffer)?.ok_or(
+            protocol::Error::IllegalArgument(
+                "non-nullable field end_points was serialized as null",
+            ),
+        )?;
+        let features = CompactArray::<BrokerFeature>::read_result_inner(buffer)?.ok_or(
+            protocol::Error::IllegalArgument("non-nullable field features was serialized as null"),
+        )?;
+        let rack = read_nullable_string(buffer)?;
+        let fenced = bool::read_result(buffer)?;
+        let in_controlled_shutdown = version >= 1 && bool::read_result(buffer)?;
+        let log_dirs = if version >= 3 {
+            CompactArray::<Uuid>::read_result_inner(buffer)?.unwrap_or_default()
+        } else {
+            vec![]
+        };
+        let _tagged_fields = TaggedFields::read_result_inner(buffer)?;
+
+        Ok(Self {
+            version,
+            broker_id,
+            is_migrating_zk_broker,
+            incarnation_id,
+            broker_epoch,
+            end_points,
+            features,
+            rack,
+            fenced,
+            in_controlled_shutdown,
+            log_dirs,
+        })
+    }
+}
+impl Writable for RegisterBrokerRecord {
+    fn write<B: BufMut>(&self, buffer: &mut B) {
+        self.broker_id.write(buffer);
+        if self.version >= 2 {
+            self.is_migrating_zk_broker.write(buffer);
+        }
+        self.incarnation_id.write(buffer);
+        self.broker_epoch.write(buffer);
+        CompactArray::write_inner(buffer, Some(self.end_points()));
+        CompactArray::write_inner(buffer, Some(self.features()));
+        CompactKafkaString::write_inner(buffer, self.rack().map(str::as_bytes));
+        self.fenced.write(buffer);
+        if self.version >= 1 {
+            self.in_controlled_shutdown.write(buffer);
+        }
+        if self.version >= 3 {
+            CompactArray::write_inner(buffer, Some(self.log_dirs()));
+        }
+        TaggedFields::write_empty(buffer);
+    }
+}
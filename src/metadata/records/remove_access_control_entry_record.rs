@@ -0,0 +1,37 @@
+use bytes::{Buf, BufMut};
+use uuid::Uuid;
+
+use crate::protocol::{self, r#type::TaggedFields, ReadableResult, ReadableVersion, Writable};
+
+pub(crate) const API_KEY: i16 = 7;
+
+#[derive(Debug, Clone)]
+pub struct RemoveAccessControlEntryRecord {
+    id: Uuid,
+}
+impl RemoveAccessControlEntryRecord {
+    pub fn new(id: Uuid) -> Self {
+        Self { id }
+    }
+
+    pub fn id(&self) -> Uuid {
+        self.id
+    }
+}
+impl ReadableVersion for RemoveAccessControlEntryRecord {
+    fn read_version<B: Buf>(buffer: &mut B, version: i16) -> Result<Self, protocol::Error> {
+        if version != 0 {
+            return Err(protocol::Error::UnsupportedVersion);
+        }
+
+        let id = Uuid::read_result(buffer)?;
+        let _tagged_fields = TaggedFields::read_result_inner(buffer)?;
+        Ok(Self { id })
+    }
+}
+impl Writable for RemoveAccessControlEntryRecord {
+    fn write<B: BufMut>(&self, buffer: &mut B) {
+        self.id.write(buffer);
+        TaggedFields::write_empty(buffer);
+    }
+}
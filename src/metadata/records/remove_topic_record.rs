@@ -0,0 +1,37 @@
+use bytes::{Buf, BufMut};
+use uuid::Uuid;
+
+use crate::protocol::{self, r#type::TaggedFields, ReadableResult, ReadableVersion, Writable};
+
+pub(crate) const API_KEY: i16 = 10;
+
+#[derive(Debug, Clone)]
+pub struct RemoveTopicRecord {
+    topic_id: Uuid,
+}
+impl RemoveTopicRecord {
+    pub fn new(topic_id: Uuid) -> Self {
+        Self { topic_id }
+    }
+
+    pub fn topic_id(&self) -> Uuid {
+        self.topic_id
+    }
+}
+impl ReadableVersion for RemoveTopicRecord {
+    fn read_version<B: Buf>(buffer: &mut B, version: i16) -> Result<Self, protocol::Error> {
+        if version != 0 {
+            return Err(protocol::Error::UnsupportedVersion);
+        }
+
+        let topic_id = Uuid::read_result(buffer)?;
+        let _tagged_fields = TaggedFields::read_result_inner(buffer)?;
+        Ok(Self { topic_id })
+    }
+}
+impl Writable for RemoveTopicRecord {
+    fn write<B: BufMut>(&self, buffer: &mut B) {
+        self.topic_id.write(buffer);
+        TaggedFields::write_empty(buffer);
+    }
+}
@@ -0,0 +1,52 @@
+use bytes::{Buf, BufMut};
+
+use crate::protocol::{
+    self,
+    r#type::{CompactKafkaString, TaggedFields},
+    ReadableResult, ReadableVersion, Writable,
+};
+
+use super::read_string;
+
+pub(crate) const API_KEY: i16 = 22;
+
+#[derive(Debug, Clone)]
+pub struct RemoveUserScramCredentialRecord {
+    name: String,
+    mechanism: i8,
+}
+impl RemoveUserScramCredentialRecord {
+    pub fn new(name: &str, mechanism: i8) -> Self {
+        Self {
+            name: name.to_string(),
+            mechanism,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn mechanism(&self) -> i8 {
+        self.mechanism
+    }
+}
+impl ReadableVersion for RemoveUserScramCredentialRecord {
+    fn read_version<B: Buf>(buffer: &mut B, version: i16) -> Result<Self, protocol::Error> {
+        if version != 0 {
+            return Err(protocol::Error::UnsupportedVersion);
+        }
+
+        let name = read_string(buffer, "non-nullable field name was serialized as null")?;
+        let mechanism = i8::read_result(buffer)?;
+        let _tagged_fields = TaggedFields::read_result_inner(buffer)?;
+        Ok(Self { name, mechanism })
+    }
+}
+impl Writable for RemoveUserScramCredentialRecord {
+    fn write<B: BufMut>(&self, buffer: &mut B) {
+        CompactKafkaString::write_inner(buffer, Some(self.name.as_bytes()));
+        self.mechanism.write(buffer);
+        TaggedFields::write_empty(buffer);
+    }
+}
@@ -4,7 +4,7 @@ use uuid::Uuid;
 use crate::protocol::{
     self,
     r#type::{CompactKafkaString, TaggedFields},
-    Readable, ReadableVersion, Writable,
+    ReadableResult, ReadableVersion, Writable,
 };
 
 pub(crate) const API_KEY: i16 = 2;
@@ -39,7 +39,7 @@ impl ReadableVersion for TopicRecord {
         let name = CompactKafkaString::read_result_inner(buffer)?.ok_or(
             protocol::Error::IllegalArgument("non-nullable field name was serialized as null"),
         )?;
-        let topic_id = Uuid::read(buffer);
+        let topic_id = Uuid::read_result(buffer)?;
         let _tagged_fields = TaggedFields::read_result_inner(buffer)?;
         Ok(TopicRecord { name, topic_id })
     }
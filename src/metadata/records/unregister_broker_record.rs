@@ -0,0 +1,49 @@
+use bytes::{Buf, BufMut};
+
+use crate::protocol::{self, r#type::TaggedFields, ReadableResult, ReadableVersion, Writable};
+
+pub(crate) const API_KEY: i16 = 1;
+
+#[derive(Debug, Clone)]
+pub struct UnregisterBrokerRecord {
+    broker_id: i32,
+    broker_epoch: i64,
+}
+impl UnregisterBrokerRecord {
+    pub fn new(broker_id: i32, broker_epoch: i64) -> Self {
+        Self {
+            broker_id,
+            broker_epoch,
+        }
+    }
+
+    pub fn broker_id(&self) -> i32 {
+        self.broker_id
+    }
+
+    pub fn broker_epoch(&self) -> i64 {
+        self.broker_epoch
+    }
+}
+impl ReadableVersion for UnregisterBrokerRecord {
+    fn read_version<B: Buf>(buffer: &mut B, version: i16) -> Result<Self, protocol::Error> {
+        if version != 0 {
+            return Err(protocol::Error::UnsupportedVersion);
+        }
+
+        let broker_id = i32::read_result(buffer)?;
+        let broker_epoch = i64::read_result(buffer)?;
+        let _tagged_fields = TaggedFields::read_result_inner(buffer)?;
+        Ok(Self {
+            broker_id,
+            broker_epoch,
+        })
+    }
+}
+impl Writable for UnregisterBrokerRecord {
+    fn write<B: BufMut>(&self, buffer: &mut B) {
+        self.broker_id.write(buffer);
+        self.broker_epoch.write(buffer);
+        TaggedFields::write_empty(buffer);
+    }
+}
@@ -0,0 +1,104 @@
+use bytes::{Buf, BufMut, Bytes};
+
+use crate::protocol::{
+    self,
+    r#type::{CompactKafkaString, TaggedFields},
+    ReadableResult, ReadableVersion, Writable,
+};
+
+use super::{read_bytes, read_string};
+
+pub(crate) const API_KEY: i16 = 11;
+
+#[derive(Debug, Clone)]
+pub struct UserScramCredentialRecord {
+    name: String,
+    mechanism: i8,
+    salt: Bytes,
+    stored_key: Bytes,
+    server_key: Bytes,
+    iterations: i32,
+}
+impl UserScramCredentialRecord {
+    pub fn new(
+        name: &str,
+        mechanism: i8,
+        salt: Bytes,
+        stored_key: Bytes,
+        server_key: Bytes,
+        iterations: i32,
+    ) -> Self {
+        Self {
+            name: name.to_string(),
+            mechanism,
+            salt,
+            stored_key,
+            server_key,
+            iterations,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn mechanism(&self) -> i8 {
+        self.mechanism
+    }
+
+    pub fn salt(&self) -> &Bytes {
+        &self.salt
+    }
+
+    pub fn stored_key(&self) -> &Bytes {
+        &self.stored_key
+    }
+
+    pub fn server_key(&self) -> &Bytes {
+        &self.server_key
+    }
+
+    pub fn iterations(&self) -> i32 {
+        self.iterations
+    }
+}
+impl ReadableVersion for UserScramCredentialRecord {
+    fn read_version<B: Buf>(buffer: &mut B, version: i16) -> Result<Self, protocol::Error> {
+        if version != 0 {
+            return Err(protocol::Error::UnsupportedVersion);
+        }
+
+        let name = read_string(buffer, "non-nullable field name was serialized as null")?;
+        let mechanism = i8::read_result(buffer)?;
+        let salt = read_bytes(buffer, "non-nullable field salt was serialized as null")?;
+        let stored_key = read_bytes(
+            buffer,
+            "non-nullable field stored_key was serialized as null",
+        )?;
+        let server_key = read_bytes(
+            buffer,
+            "non-nullable field server_key was serialized as null",
+        )?;
+        let iterations = i32::read_result(buffer)?;
+        let _tagged_fields = TaggedFields::read_result_inner(buffer)?;
+        Ok(Self {
+            name,
+            mechanism,
+            salt,
+            stored_key,
+            server_key,
+            iterations,
+        })
+    }
+}
+impl Writable for UserScramCredentialRecord {
+    fn write<B: BufMut>(&self, buffer: &mut B) {
+        CompactKafkaString::write_inner(buffer, Some(self.name.as_bytes()));
+        self.mechanism.write(buffer);
+        CompactKafkaString::write_inner(buffer, Some(&self.salt));
+        CompactKafkaString::write_inner(buffer, Some(&self.stored_key));
+        CompactKafkaString::write_inner(buffer, Some(&self.server_key));
+        self.iterations.write(buffer);
+        TaggedFields::write_empty(buffer);
+    }
+}
@@ -0,0 +1,415 @@
+use std::{
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use bytes::{Bytes, BytesMut};
+
+use crate::protocol::{self, ReadableResult, Writable};
+
+use super::{
+    metadata_value,
+    records::{
+        access_control_entry_record, client_quota_record, config_record, feature_level_record,
+        partition_record, producer_ids_record, register_broker_record, topic_record,
+        user_scram_credential_record, AccessControlEntryRecord, BrokerEndpoint, BrokerFeature,
+        ClientQuotaRecord, ConfigRecord, EntityData, FeatureLevelRecord, MetadataRecord,
+        PartitionRecord, ProducerIdsRecord, RegisterBrokerRecord, TopicRecord,
+        UserScramCredentialRecord,
+    },
+    ControlRecord, MetadataDelta, MetadataImage, MetadataValue, Record, RecordBatch, ValueRecord,
+};
+
+pub const SNAPSHOT_SUFFIX: &str = ".checkpoint";
+const PARTIAL_SNAPSHOT_SUFFIX: &str = ".checkpoint.part";
+
+const SNAPSHOT_HEADER_TYPE: i16 = 4;
+const SNAPSHOT_FOOTER_TYPE: i16 = 5;
+const CONTROL_BATCH: u16 = 0x10;
+const RECORDS_PER_BATCH: usize = 1024;
+
+// A snapshot holds every record below `end_offset`; its file is named
+// `<end_offset>-<epoch>.checkpoint`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct SnapshotId {
+    end_offset: i64,
+    epoch: i32,
+}
+impl SnapshotId {
+    pub fn new(end_offset: i64, epoch: i32) -> Self {
+        Self { end_offset, epoch }
+    }
+
+    pub fn end_offset(&self) -> i64 {
+        self.end_offset
+    }
+
+    pub fn epoch(&self) -> i32 {
+        self.epoch
+    }
+
+    pub fn file_name(&self) -> String {
+        format!(
+            "{:020}-{:010}{}",
+            self.end_offset, self.epoch, SNAPSHOT_SUFFIX
+        )
+    }
+
+    fn parse(file_name: &str) -> Option<Self> {
+        let (end_offset, epoch) = file_name.strip_suffix(SNAPSHOT_SUFFIX)?.split_once('-')?;
+        Some(Self::new(end_offset.parse().ok()?, epoch.parse().ok()?))
+    }
+}
+
+pub fn latest_snapshot(partition_dir: &Path) -> io::Result<Option<SnapshotId>> {
+    let latest = fs::read_dir(partition_dir)?
+        .filter_map(|entry| SnapshotId::parse(entry.ok()?.file_name().to_str()?))
+        .max();
+    Ok(latest)
+}
+
+// Replays the snapshot into `delta`; its last offset becomes the one before
+// the snapshot's end offset.
+pub fn read_snapshot(
+    partition_dir: &Path,
+    id: SnapshotId,
+    delta: &mut MetadataDelta,
+) -> Result<(), protocol::Error> {
+    let data = fs::read(partition_dir.join(id.file_name()))
+        .map_err(|err| protocol::Error::IOError(err.to_string()))?;
+
+    let mut read_buffer = data.as_slice();
+    let mut footer = false;
+    while !read_buffer.is_empty() {
+        let batch = RecordBatch::read_result(&mut read_buffer)?;
+        for record in batch.records() {
+            match record {
+                Record::Control(control) => footer |= control.r#type() == SNAPSHOT_FOOTER_TYPE,
+                Record::Value(value_record) => {
+                    let value = MetadataValue::read_result(&mut value_record.value().clone())?;
+                    delta.replay(MetadataRecord::read(&value)?);
+                }
+            }
+        }
+    }
+    if !footer {
+        return Err(protocol::Error::IllegalArgument(
+            "snapshot has no SnapshotFooterRecord",
+        ));
+    }
+
+    delta.set_last_offset(id.end_offset() - 1);
+    Ok(())
+}
+
+// Writes the image as a snapshot next to the log. The file only gets its
+// final name once complete, so a crash never leaves a truncated snapshot, and
+// older snapshots are only removed after that.
+pub fn write_snapshot(
+    partition_dir: &Path,
+    image: &MetadataImage,
+    epoch: i32,
+) -> io::Result<Option<SnapshotId>> {
+    let Some(last_offset) = image.last_offset() else {
+        return Ok(None);
+    };
+    let id = SnapshotId::new(last_offset + 1, epoch);
+    let path = partition_dir.join(id.file_name());
+    if path.exists() {
+        return Ok(None);
+    }
+
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_millis() as i64)
+        .unwrap_or_default();
+    let mut data = BytesMut::new();
+    let mut offset = 0;
+
+    let mut header = BytesMut::with_capacity(11);
+    0i16.write(&mut header);
+    timestamp.write(&mut header);
+    0u8.write(&mut header);
+    control_batch(
+        offset,
+        epoch,
+        timestamp,
+        SNAPSHOT_HEADER_TYPE,
+        header.freeze(),
+    )
+    .write(&mut data);
+    offset += 1;
+
+    for values in image_records(image).chunks(RECORDS_PER_BATCH) {
+        let records = values
+            .iter()
+            .enumerate()
+            .map(|(offset_delta, value)| {
+                Record::Value(ValueRecord::new(
+                    0,
+                    0,
+                    offset_delta as i32,
+                    None,
+                    value.clone(),
+                    vec![],
+                ))
+            })
+            .collect();
+        RecordBatch::new(
+            offset,
+            epoch,
+            2,
+            0,
+            values.len() as i32 - 1,
+            timestamp,
+            timestamp,
+            -1,
+            -1,
+            -1,
+            records,
+        )
+        .write(&mut data);
+        offset += values.len() as i64;
+    }
+
+    let mut footer = BytesMut::with_capacity(3);
+    0i16.write(&mut footer);
+    0u8.write(&mut footer);
+    control_batch(
+        offset,
+        epoch,
+        timestamp,
+        SNAPSHOT_FOOTER_TYPE,
+        footer.freeze(),
+    )
+    .write(&mut data);
+
+    let partial_path = partial_path(partition_dir, id);
+    let mut file = fs::File::create(&partial_path)?;
+    file.write_all(&data)?;
+    file.sync_all()?;
+    fs::rename(&partial_path, &path)?;
+    delete_snapshots_before(partition_dir, id)?;
+    Ok(Some(id))
+}
+
+fn delete_snapshots_before(partition_dir: &Path, id: SnapshotId) -> io::Result<()> {
+    for entry in fs::read_dir(partition_dir)? {
+        let entry = entry?;
+        let older = entry
+            .file_name()
+            .to_str()
+            .and_then(SnapshotId::parse)
+            .is_some_and(|other| other < id);
+        if older {
+            fs::remove_file(entry.path())?;
+        }
+    }
+    Ok(())
+}
+
+fn partial_path(partition_dir: &Path, id: SnapshotId) -> PathBuf {
+    partition_dir.join(format!(
+        "{:020}-{:010}{}",
+        id.end_offset(),
+        id.epoch(),
+        PARTIAL_SNAPSHOT_SUFFIX
+    ))
+}
+
+fn control_batch(
+    base_offset: i64,
+    epoch: i32,
+    timestamp: i64,
+    r#type: i16,
+    value: Bytes,
+) -> RecordBatch {
+    RecordBatch::new(
+        base_offset,
+        epoch,
+        2,
+        CONTROL_BATCH,
+        0,
+        timestamp,
+        timestamp,
+        -1,
+        -1,
+        -1,
+        vec![Record::Control(ControlRecord::new(0, r#type, value))],
+    )
+}
+
+// The records that rebuild `image` from scratch, in replay order.
+pub(crate) fn image_records(image: &MetadataImage) -> Vec<Bytes> {
+    let mut values = Vec::new();
+
+    let mut features = image.features().iter().collect::<Vec<_>>();
+    features.sort();
+    for (name, level) in features {
+        values.push(metadata_value(
+            feature_level_record::API_KEY,
+            0,
+            &FeatureLevelRecord::new(name, *level),
+        ));
+    }
+
+    let mut brokers = image.brokers().values().collect::<Vec<_>>();
+    brokers.sort_by_key(|broker| broker.id());
+    for broker in brokers {
+        let mut features = broker
+            .supported_features()
+            .iter()
+            .map(|(name, (min, max))| BrokerFeature::new(name, *min, *max))
+            .collect::<Vec<_>>();
+        features.sort_by(|a, b| a.name().cmp(b.name()));
+        let record = RegisterBrokerRecord::new(
+            3,
+            broker.id(),
+            broker.incarnation_id(),
+            broker.epoch(),
+            broker
+                .listeners()
+                .iter()
+                .map(|listener| {
+                    BrokerEndpoint::new(
+                        listener.name(),
+                        listener.host(),
+                        listener.port(),
+                        listener.security_protocol(),
+                    )
+                })
+                .collect(),
+            features,
+            broker.rack(),
+            broker.fenced(),
+            broker.in_controlled_shutdown(),
+            vec![],
+        );
+        values.push(metadata_value(register_broker_record::API_KEY, 3, &record));
+    }
+
+    let mut topics = image.topics().values().collect::<Vec<_>>();
+    topics.sort_by(|a, b| a.name().cmp(b.name()));
+    for topic in topics {
+        values.push(metadata_value(
+            topic_record::API_KEY,
+            0,
+            &TopicRecord::new(topic.name(), topic.id()),
+        ));
+        for partition in topic.partitions() {
+            let elr = partition.eligible_leader_replicas().map(<[i32]>::to_vec);
+            let last_known_elr = partition.last_known_elr().map(<[i32]>::to_vec);
+            let (version, record) = if elr.is_some() || last_known_elr.is_some() {
+                (
+                    2,
+                    PartitionRecord::v2(
+                        partition.id(),
+                        topic.id(),
+                        partition.replicas().to_vec(),
+                        partition.isr().to_vec(),
+                        vec![],
+                        vec![],
+                        partition.leader(),
+                        partition.leader_epoch(),
+                        partition.partition_epoch(),
+                        vec![],
+                        0,
+                        elr,
+                        last_known_elr,
+                    ),
+                )
+            } else {
+                (
+                    0,
+                    PartitionRecord::v0(
+                        partition.id(),
+                        topic.id(),
+                        partition.replicas().to_vec(),
+                        partition.isr().to_vec(),
+                        vec![],
+                        vec![],
+                        partition.leader(),
+                        partition.leader_epoch(),
+                        partition.partition_epoch(),
+                        0,
+                    ),
+                )
+            };
+            values.push(metadata_value(partition_record::API_KEY, version, &record));
+        }
+    }
+
+    let mut configs = image.configs().iter().collect::<Vec<_>>();
+    configs.sort_by_key(|(resource, _)| (resource.resource_type(), resource.name()));
+    for (resource, entries) in configs {
+        let mut entries = entries.iter().collect::<Vec<_>>();
+        entries.sort();
+        for (name, value) in entries {
+            let record =
+                ConfigRecord::new(resource.resource_type(), resource.name(), name, Some(value));
+            values.push(metadata_value(config_record::API_KEY, 0, &record));
+        }
+    }
+
+    let mut client_quotas = image.client_quotas().iter().collect::<Vec<_>>();
+    client_quotas.sort_by_key(|(key, _)| *key);
+    for (entity, quotas) in client_quotas {
+        let entity = entity
+            .iter()
+            .map(|(entity_type, entity_name)| EntityData::new(entity_type, entity_name.as_deref()))
+            .collect::<Vec<_>>();
+        let mut quotas = quotas.iter().collect::<Vec<_>>();
+        quotas.sort_by_key(|(key, _)| *key);
+        for (key, value) in quotas {
+            let record = ClientQuotaRecord::new(entity.clone(), key, *value, false);
+            values.push(metadata_value(client_quota_record::API_KEY, 0, &record));
+        }
+    }
+
+    let mut acls = image.acls().values().collect::<Vec<_>>();
+    acls.sort_by_key(|acl| acl.id());
+    for acl in acls {
+        let record = AccessControlEntryRecord::new(
+            acl.id(),
+            acl.resource_type(),
+            acl.resource_name(),
+            acl.pattern_type(),
+            acl.principal(),
+            acl.host(),
+            acl.operation(),
+            acl.permission_type(),
+        );
+        values.push(metadata_value(
+            access_control_entry_record::API_KEY,
+            0,
+            &record,
+        ));
+    }
+
+    let mut scram_credentials = image.scram_credentials().iter().collect::<Vec<_>>();
+    scram_credentials.sort_by_key(|(key, _)| *key);
+    for ((name, mechanism), credential) in scram_credentials {
+        let record = UserScramCredentialRecord::new(
+            name,
+            *mechanism,
+            credential.salt().clone(),
+            credential.stored_key().clone(),
+            credential.server_key().clone(),
+            credential.iterations(),
+        );
+        values.push(metadata_value(
+            user_scram_credential_record::API_KEY,
+            0,
+            &record,
+        ));
+    }
+
+    if image.next_producer_id() > 0 {
+        let record = ProducerIdsRecord::new(-1, -1, image.next_producer_id());
+        values.push(metadata_value(producer_ids_record::API_KEY, 0, &record));
+    }
+
+    values
+}
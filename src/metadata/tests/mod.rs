@@ -0,0 +1,382 @@
+use std::{fs, io::Write, path::PathBuf, sync::Arc};
+
+use bytes::{Bytes, BytesMut};
+use uuid::Uuid;
+
+use crate::protocol::{ReadableResult, Writable};
+
+use super::{
+    records::{
+        BrokerEndpoint, BrokerFeature, ClientQuotaRecord, ConfigRecord, EntityData,
+        FeatureLevelRecord, PartitionChangeRecord, PartitionRecord, ProducerIdsRecord,
+        RegisterBrokerRecord, RemoveTopicRecord, TopicRecord, UnfenceBrokerRecord,
+    },
+    *,
+};
+
+fn metadata_value<W: Writable>(r#type: u8, version: u8, record: &W) -> Bytes {
+    let mut data = BytesMut::new();
+    record.write(&mut data);
+    let mut value = BytesMut::new();
+    MetadataValue::new(1, r#type, version, data.freeze()).write(&mut value);
+    value.freeze()
+}
+
+fn record_batch(base_offset: i64, values: Vec<Bytes>) -> Bytes {
+    let last_offset_delta = values.len() as i32 - 1;
+    let records = values
+        .into_iter()
+        .enumerate()
+        .map(|(offset_delta, value)| {
+            Record::Value(ValueRecord::new(
+                0,
+                0,
+                offset_delta as i32,
+                None,
+                value,
+                vec![],
+            ))
+        })
+        .collect();
+    let batch = RecordBatch::new(
+        base_offset,
+        0,
+        2,
+        0,
+        last_offset_delta,
+        0,
+        0,
+        -1,
+        -1,
+        -1,
+        records,
+    );
+    let mut data = BytesMut::new();
+    batch.write(&mut data);
+    data.freeze()
+}
+
+fn append(path: &PathBuf, data: &[u8]) {
+    let mut file = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .unwrap();
+    file.write_all(data).unwrap();
+}
+
+#[test]
+fn test_loader_tails_metadata_log() {
+    let log_dir = std::env::temp_dir().join(format!("metadata-loader-{}", Uuid::new_v4()));
+    let partition_dir = log_dir.join(loader::METADATA_PARTITION_DIR);
+    let cache = MetadataCache::new();
+    let mut changes = cache.subscribe();
+    let mut loader = MetadataLoader::new(&log_dir, u64::MAX);
+
+    // The log does not exist yet: an error, but nothing is cached.
+    assert!(loader.poll(&cache).is_err());
+    assert!(cache.image().topics().is_empty());
+
+    fs::create_dir_all(&partition_dir).unwrap();
+    let topic_id = Uuid::new_v4();
+    let first_segment = partition_dir.join("00000000000000000000.log");
+    append(
+        &first_segment,
+        &record_batch(
+            0,
+            vec![metadata_value(2, 0, &TopicRecord::new("foo", topic_id))],
+        ),
+    );
+    assert_eq!(1, loader.poll(&cache).unwrap());
+    assert_eq!("foo", cache.image().topics()[&topic_id].name());
+    assert_eq!(
+        MetadataChange::TopicCreated {
+            topic_id,
+            name: "foo".to_string()
+        },
+        changes.try_recv().unwrap()
+    );
+
+    // A batch that is only partially written is picked up once complete.
+    let partition = PartitionRecord::v0(0, topic_id, vec![1], vec![1], vec![], vec![], 1, 0, 0, 0);
+    let batch = record_batch(1, vec![metadata_value(3, 0, &partition)]);
+    append(&first_segment, &batch[..10]);
+    assert_eq!(0, loader.poll(&cache).unwrap());
+    assert!(cache.image().topics()[&topic_id].partitions().is_empty());
+    append(&first_segment, &batch[10..]);
+    assert_eq!(1, loader.poll(&cache).unwrap());
+    assert_eq!(1, cache.image().topics()[&topic_id].partitions().len());
+    assert_eq!(Some(1), cache.image().last_offset());
+
+    // Rolled segments are followed.
+    let other_id = Uuid::new_v4();
+    append(
+        &partition_dir.join("00000000000000000002.log"),
+        &record_batch(
+            2,
+            vec![metadata_value(2, 0, &TopicRecord::new("bar", other_id))],
+        ),
+    );
+    assert_eq!(1, loader.poll(&cache).unwrap());
+    assert_eq!(2, cache.image().topics().len());
+
+    // A truncated value only loses its own record, and a corrupt batch is
+    // skipped rather than read again on every poll.
+    let second_segment = partition_dir.join("00000000000000000002.log");
+    let truncated = metadata_value(2, 0, &TopicRecord::new("baz", Uuid::new_v4()));
+    let truncated = truncated.slice(..truncated.len() - 8);
+    append(
+        &second_segment,
+        &record_batch(
+            3,
+            vec![
+                truncated,
+                metadata_value(2, 0, &TopicRecord::new("qux", Uuid::new_v4())),
+            ],
+        ),
+    );
+    let mut corrupt = BytesMut::from(
+        &record_batch(
+            5,
+            vec![metadata_value(
+                2,
+                0,
+                &TopicRecord::new("quux", Uuid::new_v4()),
+            )],
+        )[..],
+    );
+    let last = corrupt.len() - 1;
+    corrupt[last] ^= 0xff;
+    append(&second_segment, &corrupt);
+    append(
+        &second_segment,
+        &record_batch(
+            6,
+            vec![metadata_value(
+                2,
+                0,
+                &TopicRecord::new("corge", Uuid::new_v4()),
+            )],
+        ),
+    );
+    assert_eq!(2, loader.poll(&cache).unwrap());
+    assert_eq!(0, loader.poll(&cache).unwrap());
+    assert_eq!(4, cache.image().topics().len());
+    assert_eq!(Some(6), cache.image().last_offset());
+
+    fs::remove_dir_all(&log_dir).unwrap();
+}
+
+fn replay<W: Writable>(delta: &mut MetadataDelta, r#type: u8, version: u8, record: &W) {
+    let mut value = metadata_value(r#type, version, record);
+    let value = MetadataValue::read_result(&mut value).unwrap();
+    delta.replay(records::MetadataRecord::read(&value).unwrap());
+}
+
+#[test]
+fn test_delta_replays_record_types() {
+    let topic_id = Uuid::new_v4();
+    let mut delta = MetadataDelta::new(Arc::new(MetadataImage::default()));
+
+    let register = RegisterBrokerRecord::new(
+        3,
+        1,
+        Uuid::new_v4(),
+        7,
+        vec![BrokerEndpoint::new("PLAINTEXT", "localhost", 9092, 0)],
+        vec![BrokerFeature::new("metadata.version", 1, 20)],
+        Some("rack-a"),
+        true,
+        false,
+        vec![],
+    );
+    replay(&mut delta, 0, 3, &register);
+    replay(&mut delta, 9, 0, &UnfenceBrokerRecord::new(1, 7));
+    replay(
+        &mut delta,
+        12,
+        0,
+        &FeatureLevelRecord::new("metadata.version", 20),
+    );
+    replay(&mut delta, 2, 0, &TopicRecord::new("foo", topic_id));
+    replay(
+        &mut delta,
+        4,
+        0,
+        &ConfigRecord::new(2, "foo", "retention.ms", Some("1000")),
+    );
+    replay(&mut delta, 4, 0, &ConfigRecord::new(4, "1", "a", Some("b")));
+    replay(&mut delta, 4, 0, &ConfigRecord::new(4, "1", "a", None));
+    replay(
+        &mut delta,
+        14,
+        0,
+        &ClientQuotaRecord::new(
+            vec![EntityData::new("user", Some("alice"))],
+            "producer_byte_rate",
+            1024.0,
+            false,
+        ),
+    );
+    replay(&mut delta, 15, 0, &ProducerIdsRecord::new(1, 7, 2000));
+
+    let (image, changes) = delta.apply();
+    let broker = &image.brokers()[&1];
+    assert!(!broker.fenced());
+    assert_eq!(Some("rack-a"), broker.rack());
+    assert_eq!(9092, broker.listener("PLAINTEXT").unwrap().port());
+    assert_eq!(Some(&20), image.features().get("metadata.version"));
+    assert_eq!(1, image.configs().len());
+    assert_eq!(1, image.client_quotas().len());
+    assert_eq!(2000, image.next_producer_id());
+    assert!(changes.contains(&MetadataChange::BrokerChanged { broker_id: 1 }));
+
+    let image = Arc::new(image);
+    let mut delta = MetadataDelta::new(image.clone());
+    replay(&mut delta, 10, 0, &RemoveTopicRecord::new(topic_id));
+    let (next, changes) = delta.apply();
+    assert!(next.topics().is_empty());
+    assert!(next.configs().is_empty());
+    assert_eq!(
+        vec![MetadataChange::TopicDeleted {
+            topic_id,
+            name: "foo".to_string()
+        }],
+        changes
+    );
+    // Untouched sections are shared with the previous image.
+    assert!(Arc::ptr_eq(&image.brokers, &next.brokers));
+}
+
+#[test]
+fn test_partition_change_updates_partition() {
+    let topic_id = Uuid::new_v4();
+    let mut delta = MetadataDelta::new(Arc::new(MetadataImage::default()));
+    replay(&mut delta, 2, 0, &TopicRecord::new("foo", topic_id));
+    let partition = PartitionRecord::v0(
+        0,
+        topic_id,
+        vec![1, 2, 3],
+        vec![1, 2, 3],
+        vec![],
+        vec![],
+        1,
+        4,
+        9,
+        0,
+    );
+    replay(&mut delta, 3, 0, &partition);
+
+    // ISR shrink only: the leader epoch stays, the partition epoch moves.
+    let change = PartitionChangeRecord::v0(0, topic_id, Some(vec![1, 2]), -2, None, None, None, -1);
+    replay(&mut delta, 5, 0, &change);
+    let change = PartitionChangeRecord::v1(
+        0,
+        topic_id,
+        Some(vec![2]),
+        2,
+        None,
+        None,
+        None,
+        -1,
+        Some(vec![3]),
+        Some(vec![1]),
+    );
+    replay(&mut delta, 5, 1, &change);
+
+    let (image, changes) = delta.apply();
+    let partition = &image.topics()[&topic_id].partitions()[0];
+    assert_eq!(2, partition.leader());
+    assert_eq!(5, partition.leader_epoch());
+    assert_eq!(11, partition.partition_epoch());
+    assert_eq!(&[2], partition.isr());
+    assert_eq!(&[1, 2, 3], partition.replicas());
+    assert_eq!(Some(&[3][..]), partition.eligible_leader_replicas());
+    assert_eq!(Some(&[1][..]), partition.last_known_elr());
+    assert_eq!(
+        3,
+        changes
+            .iter()
+            .filter(|change| matches!(change, MetadataChange::PartitionChanged { .. }))
+            .count()
+    );
+}
+
+#[test]
+fn test_loader_writes_and_loads_snapshots() {
+    let log_dir = std::env::temp_dir().join(format!("metadata-snapshot-{}", Uuid::new_v4()));
+    let partition_dir = log_dir.join(loader::METADATA_PARTITION_DIR);
+    fs::create_dir_all(&partition_dir).unwrap();
+
+    let topic_id = Uuid::new_v4();
+    let partition = PartitionRecord::v0(0, topic_id, vec![1], vec![1], vec![], vec![], 1, 3, 4, 0);
+    let first_segment = partition_dir.join("00000000000000000000.log");
+    append(
+        &first_segment,
+        &record_batch(
+            0,
+            vec![metadata_value(2, 0, &TopicRecord::new("foo", topic_id))],
+        ),
+    );
+    append(
+        &first_segment,
+        &record_batch(1, vec![metadata_value(3, 0, &partition)]),
+    );
+
+    // Any applied byte crosses the threshold.
+    let cache = MetadataCache::new();
+    assert_eq!(2, MetadataLoader::new(&log_dir, 1).poll(&cache).unwrap());
+    let id = SnapshotId::new(2, 0);
+    assert!(partition_dir.join(id.file_name()).exists());
+
+    // Startup only needs the snapshot and the log after it.
+    fs::remove_file(&first_segment).unwrap();
+    let other_id = Uuid::new_v4();
+    append(
+        &partition_dir.join("00000000000000000002.log"),
+        &record_batch(
+            2,
+            vec![metadata_value(2, 0, &TopicRecord::new("bar", other_id))],
+        ),
+    );
+    let cache = MetadataCache::new();
+    assert_eq!(
+        2,
+        MetadataLoader::new(&log_dir, u64::MAX)
+            .poll(&cache)
+            .unwrap()
+    );
+    let image = cache.image();
+    assert_eq!(Some(2), image.last_offset());
+    let partitions = image.topics()[&topic_id].partitions();
+    assert_eq!(1, partitions.len());
+    assert_eq!(3, partitions[0].leader_epoch());
+    assert_eq!(4, partitions[0].partition_epoch());
+    assert_eq!("bar", image.topics()[&other_id].name());
+
+    // Only the newest snapshot is kept.
+    MetadataLoader::new(&log_dir, 1)
+        .poll(&MetadataCache::new())
+        .unwrap();
+    assert!(partition_dir
+        .join(SnapshotId::new(3, 0).file_name())
+        .exists());
+    assert!(!partition_dir.join(id.file_name()).exists());
+
+    fs::remove_dir_all(&log_dir).unwrap();
+}
+
+#[test]
+fn test_loader_thread_stops_when_cancelled() {
+    let log_dir = std::env::temp_dir().join(format!("metadata-loader-{}", Uuid::new_v4()));
+    let cache = Arc::new(MetadataCache::new());
+    let loader = Arc::new(std::sync::Mutex::new(MetadataLoader::new(
+        &log_dir,
+        u64::MAX,
+    )));
+    let stop = tokio_util::sync::CancellationToken::new();
+    let thread = MetadataLoader::spawn(loader, cache, stop.clone()).unwrap();
+
+    stop.cancel();
+    thread.join().unwrap();
+}
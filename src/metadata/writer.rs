@@ -0,0 +1,165 @@
+use std::{
+    fs::{self, File},
+    io::{self, Read, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use bytes::{Bytes, BytesMut};
+
+use crate::protocol::{self, Readable, Writable};
+
+use super::{
+    loader::{list_segments, METADATA_PARTITION_DIR},
+    snapshot, MetadataCache, MetadataLoader, Record, RecordBatch, ValueRecord, BATCH_HEADER_LENGTH,
+};
+
+// The base offset and length of a batch, followed by its header up to
+// lastOffsetDelta.
+const BATCH_PREFIX_LENGTH: usize = 27;
+
+// Appends records the broker itself produces, e.g. from AlterConfigs, to the
+// metadata log. The loader applies them like any other batch; with a loader
+// attached, it is polled right after the append so that a request which
+// follows already sees the change.
+pub struct MetadataWriter {
+    partition_dir: PathBuf,
+    lock: Mutex<()>,
+    loader: Option<(Arc<Mutex<MetadataLoader>>, Arc<MetadataCache>)>,
+}
+impl MetadataWriter {
+    pub fn new(metadata_log_dir: &Path) -> Self {
+        Self {
+            partition_dir: metadata_log_dir.join(METADATA_PARTITION_DIR),
+            lock: Mutex::new(()),
+            loader: None,
+        }
+    }
+
+    pub fn with_loader(
+        mut self,
+        loader: Arc<Mutex<MetadataLoader>>,
+        cache: Arc<MetadataCache>,
+    ) -> Self {
+        self.loader = Some((loader, cache));
+        self
+    }
+
+    // Writes `values` as a single batch after the last one of the log and
+    // returns its base offset.
+    pub fn append(&self, values: Vec<Bytes>) -> Result<i64, protocol::Error> {
+        let _guard = self.lock.lock().unwrap_or_else(|err| err.into_inner());
+        let io_error = |err: std::io::Error| protocol::Error::IOError(err.to_string());
+
+        fs::create_dir_all(&self.partition_dir).map_err(io_error)?;
+        let (segment, base_offset, epoch) =
+            match list_segments(&self.partition_dir).map_err(io_error)?.last() {
+                Some(segment) => {
+                    let path = self.segment_path(*segment);
+                    let (next_offset, epoch) = next_offset(&path, *segment as i64)?;
+                    (path, next_offset, epoch)
+                }
+                None => {
+                    let start = snapshot::latest_snapshot(&self.partition_dir)
+                        .map_err(io_error)?
+                        .map(|id| (id.end_offset(), id.epoch()))
+                        .unwrap_or((0, 0));
+                    (self.segment_path(start.0 as u64), start.0, start.1)
+                }
+            };
+
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_millis() as i64)
+            .unwrap_or_default();
+        let last_offset_delta = values.len() as i32 - 1;
+        let records = values
+            .into_iter()
+            .enumerate()
+            .map(|(offset_delta, value)| {
+                Record::Value(ValueRecord::new(
+                    0,
+                    0,
+                    offset_delta as i32,
+                    None,
+                    value,
+                    vec![],
+                ))
+            })
+            .collect();
+        let mut data = BytesMut::new();
+        RecordBatch::new(
+            base_offset,
+            epoch,
+            2,
+            0,
+            last_offset_delta,
+            timestamp,
+            timestamp,
+            -1,
+            -1,
+            -1,
+            records,
+        )
+        .write(&mut data);
+
+        let mut file = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(segment)
+            .map_err(io_error)?;
+        file.write_all(&data).map_err(io_error)?;
+        file.sync_data().map_err(io_error)?;
+
+        // The batch is durable at this point, so a failed poll is only
+        // reported; the background loader retries it.
+        if let Some((loader, cache)) = &self.loader {
+            let mut loader = loader.lock().unwrap_or_else(|err| err.into_inner());
+            if let Err(err) = loader.poll(cache) {
+                tracing::error!("error while loading metadata: {}", err);
+            }
+        }
+        Ok(base_offset)
+    }
+
+    fn segment_path(&self, base_offset: u64) -> PathBuf {
+        self.partition_dir.join(format!("{:020}.log", base_offset))
+    }
+}
+
+// The offset after the last complete batch of a segment, and that batch's
+// leader epoch. Only the batch headers are read, so large segments are cheap
+// to walk and compressed batches count like any other.
+pub(crate) fn next_offset(path: &Path, base_offset: i64) -> Result<(i64, i32), protocol::Error> {
+    let io_error = |err: io::Error| protocol::Error::IOError(err.to_string());
+    let mut file = File::open(path).map_err(io_error)?;
+    let len = file.metadata().map_err(io_error)?.len();
+    let mut header = [0u8; BATCH_PREFIX_LENGTH];
+    let mut position = 0;
+    let mut next = (base_offset, 0);
+    while position + BATCH_PREFIX_LENGTH as u64 <= len {
+        file.seek(SeekFrom::Start(position)).map_err(io_error)?;
+        file.read_exact(&mut header).map_err(io_error)?;
+        let mut header = header.as_slice();
+        let batch_base_offset = i64::read(&mut header);
+        let batch_length = u32::read(&mut header) as u64;
+        let partition_leader_epoch = i32::read(&mut header);
+        let _magic_byte = u8::read(&mut header);
+        let _crc = u32::read(&mut header);
+        let _attributes = u16::read(&mut header);
+        let last_offset_delta = i32::read(&mut header);
+        if batch_length < BATCH_HEADER_LENGTH as u64 {
+            return Err(protocol::Error::IllegalArgument("record batch too short"));
+        }
+        if position + 12 + batch_length > len {
+            break;
+        }
+        next = (
+            batch_base_offset + last_offset_delta as i64 + 1,
+            partition_leader_epoch,
+        );
+        position += 12 + batch_length;
+    }
+    Ok(next)
+}
@@ -0,0 +1,101 @@
+use std::{sync::Arc, time::Duration};
+
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+    time::{sleep, timeout},
+};
+
+use crate::broker::Broker;
+
+// Requests larger than this are not scrapes.
+const MAX_REQUEST_BYTES: usize = 8192;
+// How long a client has to send its request.
+const READ_TIMEOUT: Duration = Duration::from_secs(10);
+// How long to back off after a failed accept, e.g. when out of file
+// descriptors.
+const ACCEPT_BACKOFF: Duration = Duration::from_millis(100);
+
+// Serves the metrics over plain HTTP/1.1 on GET /metrics. Every scrape gets
+// its own connection, which is closed after the response.
+pub async fn serve(listener: TcpListener, broker: Arc<Broker>) -> std::io::Result<()> {
+    loop {
+        let stream = match listener.accept().await {
+            Ok((stream, _)) => stream,
+            Err(err) => {
+                tracing::warn!("error while accepting a metrics connection: {}", err);
+                sleep(ACCEPT_BACKOFF).await;
+                continue;
+            }
+        };
+        let broker = broker.clone();
+        tokio::spawn(async move {
+            if let Err(err) = handle(stream, broker).await {
+                tracing::warn!("error while serving metrics: {}", err);
+            }
+        });
+    }
+}
+
+async fn handle(mut stream: TcpStream, broker: Arc<Broker>) -> std::io::Result<()> {
+    let request = match timeout(READ_TIMEOUT, read_request(&mut stream)).await {
+        Ok(request) => request?,
+        Err(_) => return respond(&mut stream, "408 Request Timeout", "").await,
+    };
+    let Some(request) = request else {
+        return Ok(());
+    };
+    if request.len() > MAX_REQUEST_BYTES {
+        return respond(&mut stream, "413 Content Too Large", "").await;
+    }
+
+    let request_line = String::from_utf8_lossy(&request);
+    let mut parts = request_line.split_whitespace();
+    let (method, path) = (parts.next(), parts.next());
+    let path = path.map(|path| path.split('?').next().unwrap_or(path));
+    match (method, path) {
+        (Some("GET"), Some("/metrics")) => {
+            // Rendering reads the partition logs, which is no work for the
+            // runtime's worker threads.
+            let body = tokio::task::spawn_blocking(move || {
+                broker
+                    .metrics()
+                    .render(&broker.metadata().image(), broker.config())
+            })
+            .await
+            .map_err(std::io::Error::other)?;
+            respond(&mut stream, "200 OK", &body).await
+        }
+        (Some("GET"), _) => respond(&mut stream, "404 Not Found", "").await,
+        _ => respond(&mut stream, "405 Method Not Allowed", "").await,
+    }
+}
+
+// Reads up to the end of the request headers, or past MAX_REQUEST_BYTES.
+// None if the client closed the connection first.
+async fn read_request(stream: &mut TcpStream) -> std::io::Result<Option<Vec<u8>>> {
+    let mut request = Vec::new();
+    let mut buffer = [0u8; 1024];
+    while !request.windows(4).any(|window| window == b"\r\n\r\n") {
+        let n = stream.read(&mut buffer).await?;
+        if n == 0 {
+            return Ok(None);
+        }
+        request.extend_from_slice(&buffer[..n]);
+        if request.len() > MAX_REQUEST_BYTES {
+            break;
+        }
+    }
+    Ok(Some(request))
+}
+
+async fn respond(stream: &mut TcpStream, status: &str, body: &str) -> std::io::Result<()> {
+    let response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: text/plain; version=0.0.4; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    );
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await
+}
@@ -0,0 +1,330 @@
+mod http;
+
+use std::{
+    collections::BTreeMap,
+    fmt::Write,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Mutex,
+    },
+    time::Duration,
+};
+
+use crate::{
+    config::BrokerConfig,
+    metadata::{loader::list_segments, writer::next_offset, MetadataImage},
+    protocol::ErrorCode,
+};
+
+pub use http::serve;
+
+// Upper bounds of the request latency buckets, in seconds.
+const LATENCY_BUCKETS: [f64; 14] = [
+    0.0005, 0.001, 0.0025, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+];
+
+#[derive(Debug, Default)]
+struct Histogram {
+    buckets: [u64; LATENCY_BUCKETS.len()],
+    sum: f64,
+    count: u64,
+}
+impl Histogram {
+    fn observe(&mut self, value: f64) {
+        for (bucket, bound) in self.buckets.iter_mut().zip(LATENCY_BUCKETS) {
+            if value <= bound {
+                *bucket += 1;
+            }
+        }
+        self.sum += value;
+        self.count += 1;
+    }
+}
+
+// Everything the broker counts as it serves requests. Partition metrics are
+// not kept here but read from the logs whenever the metrics are scraped.
+#[derive(Debug, Default)]
+pub struct Metrics {
+    requests: Mutex<BTreeMap<&'static str, Histogram>>,
+    errors: Mutex<BTreeMap<(&'static str, &'static str), u64>>,
+    // Bytes out per topic are counted by Fetch. There is no bytes in per
+    // topic: the broker serves no Produce API, so nothing writes to a topic.
+    topic_bytes_out: Mutex<BTreeMap<String, u64>>,
+    connections: Mutex<BTreeMap<String, i64>>,
+    bytes_in: AtomicU64,
+    bytes_out: AtomicU64,
+}
+impl Metrics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn connection_opened(&self, listener_name: &str) {
+        let mut connections = self.connections.lock().unwrap();
+        *connections.entry(listener_name.to_string()).or_default() += 1;
+    }
+
+    pub fn connection_closed(&self, listener_name: &str) {
+        let mut connections = self.connections.lock().unwrap();
+        *connections.entry(listener_name.to_string()).or_default() -= 1;
+    }
+
+    pub fn record_request(
+        &self,
+        api_name: &'static str,
+        latency: Duration,
+        request_size: usize,
+        response_size: usize,
+        error_codes: &[ErrorCode],
+    ) {
+        self.requests
+            .lock()
+            .unwrap()
+            .entry(api_name)
+            .or_default()
+            .observe(latency.as_secs_f64());
+        if !error_codes.is_empty() {
+            let mut errors = self.errors.lock().unwrap();
+            for error_code in error_codes {
+                *errors.entry((api_name, error_code.name())).or_default() += 1;
+            }
+        }
+        self.bytes_in
+            .fetch_add(request_size as u64, Ordering::Relaxed);
+        self.bytes_out
+            .fetch_add(response_size as u64, Ordering::Relaxed);
+    }
+
+    pub fn record_topic_bytes_out(&self, topic: &str, bytes: usize) {
+        let mut topic_bytes_out = self.topic_bytes_out.lock().unwrap();
+        *topic_bytes_out.entry(topic.to_string()).or_default() += bytes as u64;
+    }
+
+    // The Prometheus text exposition format, version 0.0.4.
+    pub fn render(&self, image: &MetadataImage, config: &BrokerConfig) -> String {
+        let mut out = String::new();
+
+        header(
+            &mut out,
+            "kafka_network_requests_total",
+            "counter",
+            "Requests handled, by API.",
+        );
+        let requests = self.requests.lock().unwrap();
+        for (api_name, histogram) in requests.iter() {
+            sample(
+                &mut out,
+                "kafka_network_requests_total",
+                &[("request", api_name)],
+                histogram.count,
+            );
+        }
+
+        header(
+            &mut out,
+            "kafka_network_request_duration_seconds",
+            "histogram",
+            "Time from reading a request to having its response ready, by API.",
+        );
+        for (api_name, histogram) in requests.iter() {
+            let name = "kafka_network_request_duration_seconds";
+            for (count, bound) in histogram.buckets.iter().zip(LATENCY_BUCKETS) {
+                sample(
+                    &mut out,
+                    &format!("{}_bucket", name),
+                    &[("request", api_name), ("le", &bound.to_string())],
+                    count,
+                );
+            }
+            sample(
+                &mut out,
+                &format!("{}_bucket", name),
+                &[("request", api_name), ("le", "+Inf")],
+                histogram.count,
+            );
+            sample(
+                &mut out,
+                &format!("{}_sum", name),
+                &[("request", api_name)],
+                histogram.sum,
+            );
+            sample(
+                &mut out,
+                &format!("{}_count", name),
+                &[("request", api_name)],
+                histogram.count,
+            );
+        }
+        drop(requests);
+
+        header(
+            &mut out,
+            "kafka_network_errors_total",
+            "counter",
+            "Error codes returned, by API and error.",
+        );
+        for ((api_name, error), count) in self.errors.lock().unwrap().iter() {
+            sample(
+                &mut out,
+                "kafka_network_errors_total",
+                &[("request", api_name), ("error", error)],
+                count,
+            );
+        }
+
+        header(
+            &mut out,
+            "kafka_network_bytes_in_total",
+            "counter",
+            "Bytes of requests read.",
+        );
+        sample(
+            &mut out,
+            "kafka_network_bytes_in_total",
+            &[],
+            self.bytes_in.load(Ordering::Relaxed),
+        );
+        header(
+            &mut out,
+            "kafka_network_bytes_out_total",
+            "counter",
+            "Bytes of responses written.",
+        );
+        sample(
+            &mut out,
+            "kafka_network_bytes_out_total",
+            &[],
+            self.bytes_out.load(Ordering::Relaxed),
+        );
+
+        header(
+            &mut out,
+            "kafka_network_active_connections",
+            "gauge",
+            "Open client connections, by listener.",
+        );
+        for (listener_name, count) in self.connections.lock().unwrap().iter() {
+            sample(
+                &mut out,
+                "kafka_network_active_connections",
+                &[("listener", listener_name)],
+                count,
+            );
+        }
+
+        header(
+            &mut out,
+            "kafka_server_topic_bytes_out_total",
+            "counter",
+            "Record bytes fetched, by topic.",
+        );
+        for (topic, bytes) in self.topic_bytes_out.lock().unwrap().iter() {
+            sample(
+                &mut out,
+                "kafka_server_topic_bytes_out_total",
+                &[("topic", topic)],
+                bytes,
+            );
+        }
+
+        let mut partitions = image
+            .topics()
+            .values()
+            .flat_map(|topic| {
+                topic
+                    .partitions()
+                    .iter()
+                    .map(move |partition| (topic.name(), partition.id()))
+            })
+            .filter_map(|(topic, partition)| {
+                let stats = partition_log_stats(config, topic, partition)?;
+                Some((topic, partition, stats))
+            })
+            .collect::<Vec<_>>();
+        partitions.sort_by(|a, b| (a.0, a.1).cmp(&(b.0, b.1)));
+
+        header(
+            &mut out,
+            "kafka_log_log_end_offset",
+            "gauge",
+            "The offset after the last record of the partition.",
+        );
+        for (topic, partition, (log_end_offset, _)) in partitions.iter() {
+            sample(
+                &mut out,
+                "kafka_log_log_end_offset",
+                &[("topic", topic), ("partition", &partition.to_string())],
+                log_end_offset,
+            );
+        }
+        header(
+            &mut out,
+            "kafka_log_size_bytes",
+            "gauge",
+            "Bytes of all log segments of the partition.",
+        );
+        for (topic, partition, (_, size)) in partitions.iter() {
+            sample(
+                &mut out,
+                "kafka_log_size_bytes",
+                &[("topic", topic), ("partition", &partition.to_string())],
+                size,
+            );
+        }
+
+        out
+    }
+}
+
+// The log end offset and size of a partition this broker has a log for.
+fn partition_log_stats(config: &BrokerConfig, topic: &str, partition: i32) -> Option<(i64, u64)> {
+    let partition_dir = config
+        .log_dirs()
+        .iter()
+        .map(|log_dir| log_dir.join(format!("{}-{}", topic, partition)))
+        .find(|dir| dir.is_dir())?;
+    let segments = list_segments(&partition_dir).ok()?;
+    let segment_path = |base_offset: u64| partition_dir.join(format!("{:020}.log", base_offset));
+
+    let size = segments
+        .iter()
+        .filter_map(|segment| std::fs::metadata(segment_path(*segment)).ok())
+        .map(|metadata| metadata.len())
+        .sum();
+    let log_end_offset = match segments.last() {
+        Some(segment) => {
+            next_offset(&segment_path(*segment), *segment as i64)
+                .ok()?
+                .0
+        }
+        None => 0,
+    };
+    Some((log_end_offset, size))
+}
+
+fn header(out: &mut String, name: &str, metric_type: &str, help: &str) {
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} {}", name, metric_type);
+}
+
+fn sample<V: std::fmt::Display>(out: &mut String, name: &str, labels: &[(&str, &str)], value: V) {
+    out.push_str(name);
+    if !labels.is_empty() {
+        let labels = labels
+            .iter()
+            .map(|(key, value)| format!("{}=\"{}\"", key, escape(value)))
+            .collect::<Vec<_>>();
+        let _ = write!(out, "{{{}}}", labels.join(","));
+    }
+    let _ = writeln!(out, " {}", value);
+}
+
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,156 @@
+use std::{collections::HashMap, sync::Arc, time::Duration};
+
+use bytes::{Bytes, BytesMut};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+use crate::{
+    broker::Broker,
+    config::BrokerConfig,
+    metadata::{
+        records::{MetadataRecord, PartitionRecord, TopicRecord},
+        MetadataCache, MetadataDelta, Record, RecordBatch, ValueRecord,
+    },
+    protocol::{ErrorCode, Writable},
+};
+
+use super::Metrics;
+
+fn record_batch(base_offset: i64, attributes: u16, count: usize) -> BytesMut {
+    let records = (0..count)
+        .map(|offset_delta| {
+            Record::Value(ValueRecord::new(
+                0,
+                0,
+                offset_delta as i32,
+                None,
+                Bytes::from_static(b"value"),
+                vec![],
+            ))
+        })
+        .collect();
+    let mut data = BytesMut::new();
+    RecordBatch::new(
+        base_offset,
+        0,
+        2,
+        attributes,
+        count as i32 - 1,
+        0,
+        0,
+        -1,
+        -1,
+        -1,
+        records,
+    )
+    .write(&mut data);
+    data
+}
+
+#[test]
+fn test_render_request_and_partition_metrics() {
+    let log_dir = std::env::temp_dir().join(format!("metrics-{}", uuid::Uuid::new_v4()));
+    let partition_dir = log_dir.join("foo\"bar-0");
+    std::fs::create_dir_all(&partition_dir).unwrap();
+    // The last batch claims gzip, which only its header is read for.
+    let mut segment = record_batch(0, 0, 2);
+    segment.extend_from_slice(&record_batch(2, 1, 3));
+    std::fs::write(partition_dir.join("00000000000000000000.log"), &segment).unwrap();
+    let config = BrokerConfig::from_properties(HashMap::from([(
+        "log.dirs".to_string(),
+        log_dir.display().to_string(),
+    )]))
+    .unwrap();
+
+    let metadata = MetadataCache::new();
+    let topic_id = uuid::Uuid::new_v4();
+    let mut delta = MetadataDelta::new(metadata.image());
+    delta.replay(MetadataRecord::Topic(TopicRecord::new(
+        "foo\"bar", topic_id,
+    )));
+    for partition in 0..2 {
+        delta.replay(MetadataRecord::Partition(PartitionRecord::v0(
+            partition,
+            topic_id,
+            vec![1],
+            vec![1],
+            vec![],
+            vec![],
+            1,
+            0,
+            0,
+            0,
+        )));
+    }
+    let (image, changes) = delta.apply();
+    metadata.publish(image, changes);
+
+    let metrics = Metrics::new();
+    metrics.connection_opened("PLAINTEXT");
+    metrics.record_request("Metadata", Duration::from_millis(3), 40, 100, &[]);
+    metrics.record_request(
+        "Fetch",
+        Duration::from_secs(20),
+        60,
+        30,
+        &[ErrorCode::UnknownTopicId, ErrorCode::UnknownTopicId],
+    );
+    let text = metrics.render(&metadata.image(), &config);
+    let lines = text.lines().collect::<Vec<_>>();
+
+    for expected in [
+        "kafka_network_requests_total{request=\"Fetch\"} 1",
+        "kafka_network_request_duration_seconds_bucket{request=\"Metadata\",le=\"0.0025\"} 0",
+        "kafka_network_request_duration_seconds_bucket{request=\"Metadata\",le=\"0.005\"} 1",
+        "kafka_network_request_duration_seconds_bucket{request=\"Fetch\",le=\"10\"} 0",
+        "kafka_network_request_duration_seconds_bucket{request=\"Fetch\",le=\"+Inf\"} 1",
+        "kafka_network_request_duration_seconds_count{request=\"Fetch\"} 1",
+        "kafka_network_errors_total{request=\"Fetch\",error=\"UNKNOWN_TOPIC_ID\"} 2",
+        "kafka_network_bytes_in_total 100",
+        "kafka_network_bytes_out_total 130",
+        "kafka_network_active_connections{listener=\"PLAINTEXT\"} 1",
+        // Only partitions with a log on this broker are reported.
+        "kafka_log_log_end_offset{topic=\"foo\\\"bar\",partition=\"0\"} 5",
+        &format!(
+            "kafka_log_size_bytes{{topic=\"foo\\\"bar\",partition=\"0\"}} {}",
+            segment.len()
+        ),
+    ] {
+        assert!(
+            lines.contains(&expected),
+            "{} missing in\n{}",
+            expected,
+            text
+        );
+    }
+    assert!(!text.contains("partition=\"1\""));
+}
+
+#[tokio::test]
+async fn test_scrape_over_http() {
+    let broker = Arc::new(Broker::new(
+        BrokerConfig::default(),
+        Arc::new(MetadataCache::new()),
+    ));
+    broker.metrics().connection_opened("PLAINTEXT");
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(super::serve(listener, broker));
+
+    let scrape = |path: &'static str| async move {
+        let mut stream = tokio::net::TcpStream::connect(addr).await.unwrap();
+        stream
+            .write_all(format!("GET {} HTTP/1.1\r\nHost: localhost\r\n\r\n", path).as_bytes())
+            .await
+            .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        response
+    };
+
+    let response = scrape("/metrics").await;
+    assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+    assert!(response.contains("Content-Type: text/plain; version=0.0.4"));
+    assert!(response.contains("kafka_network_active_connections{listener=\"PLAINTEXT\"} 1\n"));
+
+    assert!(scrape("/").await.starts_with("HTTP/1.1 404 Not Found\r\n"));
+}
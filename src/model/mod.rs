@@ -1,3 +1,9 @@
+#![allow(clippy::too_many_arguments)]
+use std::collections::{BTreeMap, HashMap};
+
+use bytes::Bytes;
+
+#[derive(Debug, Clone)]
 pub struct Topic {
     id: uuid::Uuid,
     name: String,
@@ -24,15 +30,25 @@ impl Topic {
         &self.partitions
     }
 
+    pub fn partition_mut(&mut self, id: i32) -> Option<&mut Partition> {
+        self.partitions.iter_mut().find(|p| p.id == id)
+    }
+
+    // A later record for a known partition replaces it.
     pub fn add_partition(&mut self, partition: Partition) {
-        self.partitions.push(partition);
+        match self.partitions.iter_mut().find(|p| p.id == partition.id) {
+            Some(existing) => *existing = partition,
+            None => self.partitions.push(partition),
+        }
     }
 }
 
+#[derive(Debug, Clone)]
 pub struct Partition {
     id: i32,
     leader: i32,
     leader_epoch: i32,
+    partition_epoch: i32,
     replicas: Vec<i32>,
     isr: Vec<i32>,
     eligible_leader_replicas: Option<Vec<i32>>,
@@ -43,6 +59,7 @@ impl Partition {
         id: i32,
         leader: i32,
         leader_epoch: i32,
+        partition_epoch: i32,
         replicas: Vec<i32>,
         isr: Vec<i32>,
         eligible_leader_replicas: Option<Vec<i32>>,
@@ -52,6 +69,7 @@ impl Partition {
             id,
             leader,
             leader_epoch,
+            partition_epoch,
             replicas,
             isr,
             eligible_leader_replicas,
@@ -71,6 +89,10 @@ impl Partition {
         self.leader_epoch
     }
 
+    pub fn partition_epoch(&self) -> i32 {
+        self.partition_epoch
+    }
+
     pub fn replicas(&self) -> &[i32] {
         &self.replicas
     }
@@ -86,4 +108,276 @@ impl Partition {
     pub fn last_known_elr(&self) -> Option<&[i32]> {
         self.last_known_elr.as_deref()
     }
+    pub fn set_leader(&mut self, leader: i32) {
+        self.leader = leader;
+    }
+
+    pub fn set_leader_epoch(&mut self, leader_epoch: i32) {
+        self.leader_epoch = leader_epoch;
+    }
+
+    pub fn set_replicas(&mut self, replicas: Vec<i32>) {
+        self.replicas = replicas;
+    }
+
+    pub fn set_isr(&mut self, isr: Vec<i32>) {
+        self.isr = isr;
+    }
+
+    pub fn set_partition_epoch(&mut self, partition_epoch: i32) {
+        self.partition_epoch = partition_epoch;
+    }
+
+    pub fn set_eligible_leader_replicas(&mut self, eligible_leader_replicas: Option<Vec<i32>>) {
+        self.eligible_leader_replicas = eligible_leader_replicas;
+    }
+
+    pub fn set_last_known_elr(&mut self, last_known_elr: Option<Vec<i32>>) {
+        self.last_known_elr = last_known_elr;
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Listener {
+    name: String,
+    host: String,
+    port: u16,
+    security_protocol: i16,
+}
+impl Listener {
+    pub fn new(name: &str, host: &str, port: u16, security_protocol: i16) -> Self {
+        Listener {
+            name: name.to_string(),
+            host: host.to_string(),
+            port,
+            security_protocol,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn host(&self) -> &str {
+        &self.host
+    }
+
+    pub fn port(&self) -> u16 {
+        self.port
+    }
+
+    pub fn security_protocol(&self) -> i16 {
+        self.security_protocol
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct BrokerRegistration {
+    id: i32,
+    epoch: i64,
+    incarnation_id: uuid::Uuid,
+    listeners: Vec<Listener>,
+    supported_features: HashMap<String, (i16, i16)>,
+    rack: Option<String>,
+    fenced: bool,
+    in_controlled_shutdown: bool,
+}
+impl BrokerRegistration {
+    pub fn new(
+        id: i32,
+        epoch: i64,
+        incarnation_id: uuid::Uuid,
+        listeners: Vec<Listener>,
+        supported_features: HashMap<String, (i16, i16)>,
+        rack: Option<String>,
+        fenced: bool,
+        in_controlled_shutdown: bool,
+    ) -> Self {
+        BrokerRegistration {
+            id,
+            epoch,
+            incarnation_id,
+            listeners,
+            supported_features,
+            rack,
+            fenced,
+            in_controlled_shutdown,
+        }
+    }
+
+    pub fn id(&self) -> i32 {
+        self.id
+    }
+
+    pub fn epoch(&self) -> i64 {
+        self.epoch
+    }
+
+    pub fn incarnation_id(&self) -> uuid::Uuid {
+        self.incarnation_id
+    }
+
+    pub fn listeners(&self) -> &[Listener] {
+        &self.listeners
+    }
+
+    pub fn listener(&self, name: &str) -> Option<&Listener> {
+        self.listeners.iter().find(|listener| listener.name == name)
+    }
+
+    // Feature name to its (min, max) supported level.
+    pub fn supported_features(&self) -> &HashMap<String, (i16, i16)> {
+        &self.supported_features
+    }
+
+    pub fn rack(&self) -> Option<&str> {
+        self.rack.as_deref()
+    }
+
+    pub fn fenced(&self) -> bool {
+        self.fenced
+    }
+
+    pub fn set_fenced(&mut self, fenced: bool) {
+        self.fenced = fenced;
+    }
+
+    pub fn in_controlled_shutdown(&self) -> bool {
+        self.in_controlled_shutdown
+    }
+
+    pub fn set_in_controlled_shutdown(&mut self, in_controlled_shutdown: bool) {
+        self.in_controlled_shutdown = in_controlled_shutdown;
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ConfigResource {
+    resource_type: i8,
+    name: String,
+}
+impl ConfigResource {
+    pub const TOPIC: i8 = 2;
+    pub const BROKER: i8 = 4;
+    pub const BROKER_LOGGER: i8 = 8;
+
+    pub fn new(resource_type: i8, name: &str) -> Self {
+        ConfigResource {
+            resource_type,
+            name: name.to_string(),
+        }
+    }
+
+    pub fn resource_type(&self) -> i8 {
+        self.resource_type
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Acl {
+    id: uuid::Uuid,
+    resource_type: i8,
+    resource_name: String,
+    pattern_type: i8,
+    principal: String,
+    host: String,
+    operation: i8,
+    permission_type: i8,
+}
+impl Acl {
+    pub fn new(
+        id: uuid::Uuid,
+        resource_type: i8,
+        resource_name: &str,
+        pattern_type: i8,
+        principal: &str,
+        host: &str,
+        operation: i8,
+        permission_type: i8,
+    ) -> Self {
+        Acl {
+            id,
+            resource_type,
+            resource_name: resource_name.to_string(),
+            pattern_type,
+            principal: principal.to_string(),
+            host: host.to_string(),
+            operation,
+            permission_type,
+        }
+    }
+
+    pub fn id(&self) -> uuid::Uuid {
+        self.id
+    }
+
+    pub fn resource_type(&self) -> i8 {
+        self.resource_type
+    }
+
+    pub fn resource_name(&self) -> &str {
+        &self.resource_name
+    }
+
+    pub fn pattern_type(&self) -> i8 {
+        self.pattern_type
+    }
+
+    pub fn principal(&self) -> &str {
+        &self.principal
+    }
+
+    pub fn host(&self) -> &str {
+        &self.host
+    }
+
+    pub fn operation(&self) -> i8 {
+        self.operation
+    }
+
+    pub fn permission_type(&self) -> i8 {
+        self.permission_type
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ScramCredential {
+    salt: Bytes,
+    stored_key: Bytes,
+    server_key: Bytes,
+    iterations: i32,
 }
+impl ScramCredential {
+    pub fn new(salt: Bytes, stored_key: Bytes, server_key: Bytes, iterations: i32) -> Self {
+        ScramCredential {
+            salt,
+            stored_key,
+            server_key,
+            iterations,
+        }
+    }
+
+    pub fn salt(&self) -> &Bytes {
+        &self.salt
+    }
+
+    pub fn stored_key(&self) -> &Bytes {
+        &self.stored_key
+    }
+
+    pub fn server_key(&self) -> &Bytes {
+        &self.server_key
+    }
+
+    pub fn iterations(&self) -> i32 {
+        self.iterations
+    }
+}
+
+// Entity type (`user`, `client-id`, `ip`) to name; a `None` name is the
+// default entity of that type.
+pub type ClientQuotaEntity = BTreeMap<String, Option<String>>;
@@ -0,0 +1,67 @@
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+use tokio_util::codec::{Decoder, Encoder};
+
+use super::Error;
+
+const SIZE_PREFIX_LENGTH: usize = 4;
+
+pub const DEFAULT_SOCKET_REQUEST_MAX_BYTES: usize = 100 * 1024 * 1024;
+
+#[derive(Debug, Clone)]
+pub struct KafkaCodec {
+    max_request_size: usize,
+}
+impl KafkaCodec {
+    pub fn new(max_request_size: usize) -> Self {
+        Self { max_request_size }
+    }
+
+    pub fn max_request_size(&self) -> usize {
+        self.max_request_size
+    }
+}
+impl Default for KafkaCodec {
+    fn default() -> Self {
+        Self::new(DEFAULT_SOCKET_REQUEST_MAX_BYTES)
+    }
+}
+impl Decoder for KafkaCodec {
+    type Item = Bytes;
+    type Error = Error;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        if src.len() < SIZE_PREFIX_LENGTH {
+            return Ok(None);
+        }
+
+        let size = (&src[..SIZE_PREFIX_LENGTH]).get_i32();
+        if size < 0 {
+            return Err(Error::InvalidRequestSize(size));
+        }
+        let size = size as usize;
+        if size > self.max_request_size {
+            return Err(Error::RequestTooLarge {
+                size,
+                max: self.max_request_size,
+            });
+        }
+
+        if src.len() < SIZE_PREFIX_LENGTH + size {
+            src.reserve(SIZE_PREFIX_LENGTH + size - src.len());
+            return Ok(None);
+        }
+
+        src.advance(SIZE_PREFIX_LENGTH);
+        Ok(Some(src.split_to(size).freeze()))
+    }
+}
+impl Encoder<Bytes> for KafkaCodec {
+    type Error = Error;
+
+    fn encode(&mut self, item: Bytes, dst: &mut BytesMut) -> Result<(), Self::Error> {
+        dst.reserve(SIZE_PREFIX_LENGTH + item.len());
+        dst.put_i32(item.len() as i32);
+        dst.put_slice(&item);
+        Ok(())
+    }
+}
@@ -0,0 +1,435 @@
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use bytes::{Bytes, BytesMut};
+use futures::{SinkExt, StreamExt};
+use tokio::{
+    io::{AsyncRead, AsyncWrite},
+    net::TcpStream,
+    sync::mpsc,
+};
+use tokio_rustls::TlsAcceptor;
+use tokio_util::codec::{FramedRead, FramedWrite};
+
+use crate::{
+    broker::Broker,
+    config::SecurityProtocol,
+    logging::REQUEST_LOGGER,
+    protocol::{
+        message::{
+            api_name, error_response, fetch, process_request, read_request_body, write_response,
+            KafkaResponse, RequestHeader,
+        },
+        ErrorCode, ReadableResult,
+    },
+    quota::QuotaType,
+    security::{tls, Session},
+};
+
+use super::{Error, KafkaCodec, REQUEST_QUEUE_SIZE, RESPONSE_QUEUE_SIZE};
+
+// How long a connection over a limit is kept open, so that its client backs
+// off instead of reconnecting right away.
+const REJECTED_CONNECTION_CLOSE_DELAY: Duration = Duration::from_secs(1);
+
+// Completes the TLS handshake on SSL and SASL_SSL listeners and decides who
+// the client is before any request is read. Clients on SASL listeners have to
+// authenticate first, on SSL listeners a verified certificate names the
+// principal, and everyone else is anonymous.
+pub async fn accept(
+    stream: TcpStream,
+    broker: Arc<Broker>,
+    listener_name: Arc<str>,
+    security_protocol: SecurityProtocol,
+    tls: Option<TlsAcceptor>,
+) -> Result<(), Error> {
+    let host = stream.peer_addr()?.ip().to_string();
+    let session = |principal: Option<String>| match security_protocol {
+        SecurityProtocol::SaslPlaintext | SecurityProtocol::SaslSsl => {
+            Session::unauthenticated(&host)
+        }
+        SecurityProtocol::Plaintext | SecurityProtocol::Ssl => match principal {
+            Some(principal) => Session::new(&principal, &host),
+            None => Session::anonymous(&host),
+        },
+    };
+    match tls {
+        Some(acceptor) => {
+            // A client that never finishes the handshake is treated like an
+            // idle one, and shutting down does not wait for it either.
+            let max_idle = broker.config().connections_max_idle();
+            let handshake = tokio::select! {
+                handshake = tokio::time::timeout(max_idle, acceptor.accept(stream)) => handshake,
+                _ = broker.shutdown().cancelled() => return Ok(()),
+            };
+            let Ok(stream) = handshake else {
+                tracing::debug!(
+                    "closing connection without a TLS handshake after {:?}",
+                    max_idle
+                );
+                return Ok(());
+            };
+            let stream = stream?;
+            let principal = tls::principal(stream.get_ref().1.peer_certificates());
+            handle_connection(stream, broker, listener_name, session(principal)).await
+        }
+        None => handle_connection(stream, broker, listener_name, session(None)).await,
+    }
+}
+
+// Turns away a connection over one of the connection limits. Its first
+// request is answered with NETWORK_EXCEPTION, so that the client sees why it
+// is disconnected, and the connection is closed once the delay is over. TLS
+// connections are only closed late, as answering them would take a handshake
+// for a client that is not served anyway.
+pub(super) async fn reject(mut stream: TcpStream, broker: Arc<Broker>, tls: bool) {
+    let deadline = tokio::time::Instant::now() + REJECTED_CONNECTION_CLOSE_DELAY;
+    let answer = async {
+        if tls {
+            return Ok(());
+        }
+        let codec = KafkaCodec::new(broker.config().socket_request_max_bytes());
+        let (read_half, write_half) = stream.split();
+        let Some(frame) = FramedRead::new(read_half, codec.clone()).next().await else {
+            return Ok(());
+        };
+        let mut frame = frame?;
+        let request_header = RequestHeader::read_result(&mut frame)?;
+        let response = error_response(&request_header, ErrorCode::NetworkException);
+        let mut data = BytesMut::with_capacity(64);
+        write_response(&mut data, &request_header, &response)?;
+        FramedWrite::new(write_half, codec)
+            .send(data.freeze())
+            .await?;
+        Ok::<_, Error>(())
+    };
+    let shutdown = broker.shutdown();
+    tokio::select! {
+        result = tokio::time::timeout_at(deadline, answer) => {
+            if let Ok(Err(err)) = result {
+                tracing::debug!("error while rejecting connection: {}", err);
+            }
+        }
+        _ = shutdown.cancelled() => return,
+    }
+    tokio::select! {
+        _ = tokio::time::sleep_until(deadline) => {}
+        _ = shutdown.cancelled() => {}
+    }
+}
+
+// Every connection runs a reader, a processor and a writer joined by bounded
+// queues. The processor handles one request at a time, so responses leave in
+// the same order the requests arrived even when the client pipelines them.
+pub async fn handle_connection<S>(
+    stream: S,
+    broker: Arc<Broker>,
+    listener_name: Arc<str>,
+    mut session: Session,
+) -> Result<(), Error>
+where
+    S: AsyncRead + AsyncWrite + Send + 'static,
+{
+    let (read_half, write_half) = tokio::io::split(stream);
+    let codec = KafkaCodec::new(broker.config().socket_request_max_bytes());
+    let mut frames = FramedRead::new(read_half, codec.clone());
+    let mut sink = FramedWrite::new(write_half, codec);
+
+    let (request_tx, mut request_rx) = mpsc::channel::<Bytes>(REQUEST_QUEUE_SIZE);
+    let (response_tx, mut response_rx) = mpsc::channel::<Bytes>(RESPONSE_QUEUE_SIZE);
+
+    // Once the broker shuts down no more requests are read, but those already
+    // read are still answered before the connection closes. The same goes for
+    // connections that sent nothing for connections.max.idle.ms. The reader
+    // also stops once the processor has, instead of waiting for a client that
+    // is about to be disconnected to send another request.
+    let shutdown = broker.shutdown().clone();
+    let max_idle = broker.config().connections_max_idle();
+    let reader = async move {
+        loop {
+            let frame = tokio::select! {
+                frame = frames.next() => frame,
+                _ = request_tx.closed() => break,
+                _ = shutdown.cancelled() => break,
+                _ = tokio::time::sleep(max_idle) => {
+                    tracing::debug!("closing connection idle for more than {:?}", max_idle);
+                    break;
+                }
+            };
+            let Some(frame) = frame else {
+                break;
+            };
+            if request_tx.send(frame?).await.is_err() {
+                break;
+            }
+        }
+        Ok::<_, Error>(())
+    };
+
+    let _open = OpenConnection::new(broker.clone(), listener_name.clone());
+    let processor = async move {
+        while let Some(frame) = request_rx.recv().await {
+            let broker = broker.clone();
+            let listener_name = listener_name.clone();
+            // The session moves into the blocking task and back, since
+            // SaslHandshake and SaslAuthenticate change it.
+            let (response, next) = tokio::task::spawn_blocking(move || {
+                let response = handle_request(frame, &broker, &listener_name, &mut session);
+                (response, session)
+            })
+            .await?;
+            session = next;
+            let (response, throttle_time_ms) = response?;
+            if response_tx.send(response).await.is_err() {
+                break;
+            }
+            // Dropping the response queue lets the writer send what is queued
+            // and then close the connection.
+            if session.authentication_failed() {
+                break;
+            }
+            // A throttled client is muted: none of its requests are handled until
+            // the throttle time has passed.
+            if throttle_time_ms > 0 {
+                tokio::time::sleep(Duration::from_millis(throttle_time_ms as u64)).await;
+            }
+        }
+        Ok::<_, Error>(())
+    };
+
+    let writer = async move {
+        while let Some(response) = response_rx.recv().await {
+            sink.send(response).await?;
+        }
+        Ok::<_, Error>(())
+    };
+
+    // A frame the reader cannot decode ends the reader, and with it the
+    // request queue, but the requests read before it are still answered
+    // before its error is reported.
+    let (read, processed, written) = tokio::join!(reader, processor, writer);
+    processed?;
+    written?;
+    read
+}
+
+// Counts the connection as active for as long as it is alive, however it ends.
+struct OpenConnection {
+    broker: Arc<Broker>,
+    listener_name: Arc<str>,
+}
+impl OpenConnection {
+    fn new(broker: Arc<Broker>, listener_name: Arc<str>) -> Self {
+        broker.metrics().connection_opened(&listener_name);
+        Self {
+            broker,
+            listener_name,
+        }
+    }
+}
+impl Drop for OpenConnection {
+    fn drop(&mut self) {
+        self.broker.metrics().connection_closed(&self.listener_name);
+    }
+}
+
+// Only a header that cannot be read closes the connection: without a
+// correlation id there is nothing to answer, and the stream is no longer
+// trustworthy. Anything after the header is answered with an error response,
+// including requests that the session's SASL state does not allow yet.
+//
+// Returns the response along with how long the channel has to stay muted
+// because the client went over one of its quotas. Everything logged while
+// handling the request is in its span, and the request log gets a line for
+// every completed request.
+pub(super) fn handle_request(
+    mut frame: Bytes,
+    broker: &Broker,
+    listener_name: &str,
+    session: &mut Session,
+) -> Result<(Bytes, i32), Error> {
+    let start = Instant::now();
+    let request_size = frame.len();
+    let request_header = RequestHeader::read_result(&mut frame)?;
+    let span = tracing::info_span!(
+        "request",
+        api_key = request_header.request_api_key(),
+        api_version = request_header.request_api_version(),
+        correlation_id = request_header.correlation_id(),
+        client_id = %String::from_utf8_lossy(request_header.client_id().unwrap_or_default()),
+        client_software_name = tracing::field::Empty,
+        client_software_version = tracing::field::Empty,
+    );
+    let _entered = span.enter();
+
+    let (data, throttle_time_ms) = respond(
+        frame,
+        &request_header,
+        broker,
+        listener_name,
+        session,
+        request_size,
+        start,
+    )?;
+    // Clients only name their software in ApiVersions, so the first request of
+    // a connection learns it while being handled.
+    if let (Some(name), Some(version)) = (
+        session.client_software_name(),
+        session.client_software_version(),
+    ) {
+        span.record("client_software_name", name);
+        span.record("client_software_version", version);
+    }
+    tracing::debug!(
+        target: REQUEST_LOGGER,
+        api = api_name(request_header.request_api_key()),
+        principal = session.principal(),
+        listener = listener_name,
+        host = session.host(),
+        request_size,
+        response_size = data.len(),
+        throttle_time_ms,
+        duration_ms = start.elapsed().as_secs_f64() * 1000.0,
+        "Completed request"
+    );
+    Ok((data, throttle_time_ms))
+}
+
+// Reads the body, processes the request and encodes its response, throttled
+// if the client went over a quota.
+fn respond(
+    mut frame: Bytes,
+    request_header: &RequestHeader,
+    broker: &Broker,
+    listener_name: &str,
+    session: &mut Session,
+    request_size: usize,
+    start: Instant,
+) -> Result<(Bytes, i32), Error> {
+    if !session.permits(request_header.request_api_key()) {
+        let response = error_response(request_header, ErrorCode::IllegalSaslState);
+        let mut data = BytesMut::with_capacity(64);
+        write_response(&mut data, request_header, &response)?;
+        record_metrics(
+            broker,
+            request_header,
+            &response,
+            request_size,
+            data.len(),
+            start,
+        );
+        return Ok((data.freeze(), 0));
+    }
+    // Clients still authenticating have no principal to hold a quota against.
+    let quota_bound = session.is_authenticated();
+    let mut response = read_request_body(request_header, &mut frame)
+        .and_then(|request| process_request(request, broker, listener_name, session))
+        .unwrap_or_else(|err| {
+            tracing::warn!("error while processing request: {}", err);
+            error_response(request_header, ErrorCode::from(&err))
+        });
+
+    let mut data = BytesMut::with_capacity(64);
+    write_response(&mut data, request_header, &response)?;
+    let mut throttle_time_ms = 0;
+    if quota_bound {
+        throttle_time_ms = record_quotas(broker, session, request_header, data.len(), start);
+        if throttle_time_ms > 0 {
+            response.set_throttle_time_ms(throttle_time_ms);
+            data.clear();
+            write_response(&mut data, request_header, &response)?;
+        }
+    }
+    record_metrics(
+        broker,
+        request_header,
+        &response,
+        request_size,
+        data.len(),
+        start,
+    );
+    Ok((data.freeze(), throttle_time_ms))
+}
+
+// Fetched record bytes are attributed to their topic; newer Fetch versions
+// only name topics by id.
+fn record_metrics(
+    broker: &Broker,
+    request_header: &RequestHeader,
+    response: &KafkaResponse,
+    request_size: usize,
+    response_size: usize,
+    start: Instant,
+) {
+    let metrics = broker.metrics();
+    metrics.record_request(
+        api_name(request_header.request_api_key()),
+        start.elapsed(),
+        request_size,
+        response_size,
+        &response.error_codes(),
+    );
+    if let KafkaResponse::Fetch(response) = response {
+        let metadata = broker.metadata().image();
+        for topic in response.responses() {
+            let name = match topic.topic() {
+                Some(name) => String::from_utf8_lossy(name).into_owned(),
+                None => match metadata.topics().get(&topic.topic_id()) {
+                    Some(topic) => topic.name().to_string(),
+                    None => continue,
+                },
+            };
+            let bytes = topic
+                .partitions()
+                .iter()
+                .filter_map(|partition| partition.records())
+                .map(|records| records.len())
+                .sum::<usize>();
+            if bytes > 0 {
+                metrics.record_topic_bytes_out(&name, bytes);
+            }
+        }
+    }
+}
+
+// Every request counts against the request quota with the time it took to
+// handle, as a percentage of a second, and fetches also count against the
+// fetch quota with the size of the response. Produce quotas are kept in the
+// metadata but there are no produce requests to hold them against yet.
+fn record_quotas(
+    broker: &Broker,
+    session: &Session,
+    request_header: &RequestHeader,
+    response_size: usize,
+    start: Instant,
+) -> i32 {
+    let metadata = broker.metadata().image();
+    let client_quotas = metadata.client_quotas();
+    let principal = session.principal();
+    let user = principal.strip_prefix("User:").unwrap_or(principal);
+    let client_id = String::from_utf8_lossy(request_header.client_id().unwrap_or_default());
+    let now = Instant::now();
+
+    let request_time = now.duration_since(start).as_secs_f64() * 100.0;
+    let mut throttle_time_ms = broker.quotas().record(
+        QuotaType::Request,
+        client_quotas,
+        user,
+        &client_id,
+        request_time,
+        now,
+    );
+    if request_header.request_api_key() == fetch::API_KEY {
+        throttle_time_ms = throttle_time_ms.max(broker.quotas().record(
+            QuotaType::Fetch,
+            client_quotas,
+            user,
+            &client_id,
+            response_size as f64,
+            now,
+        ));
+    }
+    throttle_time_ms
+}
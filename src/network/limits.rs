@@ -0,0 +1,72 @@
+use std::{
+    collections::HashMap,
+    net::IpAddr,
+    sync::{Arc, Mutex},
+};
+
+use crate::config::BrokerConfig;
+
+use super::Error;
+
+// Open connections, in total and by client address, counted against
+// max.connections and max.connections.per.ip.
+#[derive(Debug, Default)]
+pub struct ConnectionLimits {
+    counts: Arc<Mutex<Counts>>,
+}
+
+#[derive(Debug, Default)]
+struct Counts {
+    total: usize,
+    per_ip: HashMap<IpAddr, usize>,
+}
+
+impl ConnectionLimits {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Takes a slot for a new connection from `ip`, which is given back when
+    // the returned slot is dropped.
+    pub fn acquire(&self, config: &BrokerConfig, ip: IpAddr) -> Result<ConnectionSlot, Error> {
+        let mut counts = self.counts.lock().unwrap();
+        let max_per_ip = config.max_connections_per_ip(&ip);
+        if counts.per_ip.get(&ip).copied().unwrap_or_default() >= max_per_ip {
+            return Err(Error::TooManyConnectionsFromIp {
+                ip,
+                max: max_per_ip,
+            });
+        }
+        if counts.total >= config.max_connections() {
+            return Err(Error::TooManyConnections(config.max_connections()));
+        }
+        counts.total += 1;
+        *counts.per_ip.entry(ip).or_default() += 1;
+        Ok(ConnectionSlot {
+            counts: self.counts.clone(),
+            ip,
+        })
+    }
+
+    pub fn count(&self, ip: &IpAddr) -> usize {
+        let counts = self.counts.lock().unwrap();
+        counts.per_ip.get(ip).copied().unwrap_or_default()
+    }
+}
+
+pub struct ConnectionSlot {
+    counts: Arc<Mutex<Counts>>,
+    ip: IpAddr,
+}
+impl Drop for ConnectionSlot {
+    fn drop(&mut self) {
+        let mut counts = self.counts.lock().unwrap();
+        counts.total -= 1;
+        if let Some(count) = counts.per_ip.get_mut(&self.ip) {
+            *count -= 1;
+            if *count == 0 {
+                counts.per_ip.remove(&self.ip);
+            }
+        }
+    }
+}
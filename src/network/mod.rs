@@ -0,0 +1,103 @@
+mod codec;
+mod connection;
+mod limits;
+
+use std::{net::IpAddr, sync::Arc, time::Duration};
+
+use tokio::net::TcpListener;
+use tokio_rustls::TlsAcceptor;
+
+use crate::{broker::Broker, config::SecurityProtocol, protocol};
+
+pub use codec::{KafkaCodec, DEFAULT_SOCKET_REQUEST_MAX_BYTES};
+use connection::reject;
+pub use connection::{accept, handle_connection};
+pub use limits::{ConnectionLimits, ConnectionSlot};
+
+pub(crate) const REQUEST_QUEUE_SIZE: usize = 16;
+pub(crate) const RESPONSE_QUEUE_SIZE: usize = 16;
+const ACCEPT_ERROR_BACKOFF: Duration = Duration::from_millis(100);
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("io error: {0}")]
+    IOError(#[from] std::io::Error),
+
+    #[error("protocol error: {0}")]
+    ProtocolError(#[from] protocol::Error),
+
+    #[error("invalid request size: {0}")]
+    InvalidRequestSize(i32),
+
+    #[error("request size {size} is larger than socket.request.max.bytes {max}")]
+    RequestTooLarge { size: usize, max: usize },
+
+    #[error("too many connections, at most {0} are allowed")]
+    TooManyConnections(usize),
+
+    #[error("too many connections from {ip}, at most {max} are allowed")]
+    TooManyConnectionsFromIp { ip: IpAddr, max: usize },
+
+    #[error("task error: {0}")]
+    TaskError(#[from] tokio::task::JoinError),
+}
+
+// Accepts connections on one listener until the broker shuts down. Every
+// listener is served on its own, and connections remember which one they
+// came in on.
+pub async fn serve(
+    listener: TcpListener,
+    broker: Arc<Broker>,
+    listener_name: String,
+    security_protocol: SecurityProtocol,
+    tls: Option<TlsAcceptor>,
+) -> Result<(), Error> {
+    let listener_name = Arc::<str>::from(listener_name);
+    loop {
+        let accepted = tokio::select! {
+            accepted = listener.accept() => accepted,
+            _ = broker.shutdown().cancelled() => return Ok(()),
+        };
+        match accepted {
+            Ok((stream, addr)) => {
+                let slot = match broker
+                    .connection_limits()
+                    .acquire(broker.config(), addr.ip())
+                {
+                    Ok(slot) => slot,
+                    Err(err) => {
+                        tracing::info!("rejecting connection from {}: {}", addr, err);
+                        tokio::spawn(reject(stream, broker.clone(), tls.is_some()));
+                        continue;
+                    }
+                };
+                tracing::debug!("accepted new connection from {} on {}", addr, listener_name);
+                let connection = accept(
+                    stream,
+                    broker.clone(),
+                    listener_name.clone(),
+                    security_protocol,
+                    tls.clone(),
+                );
+                broker.connections().spawn(async move {
+                    if let Err(err) = connection.await {
+                        tracing::warn!("error while handle stream: {}", err);
+                    }
+                    drop(slot);
+                });
+            }
+            // Errors such as running out of file descriptors tend to last a
+            // moment, so accepting again right away would only spin.
+            Err(err) => {
+                tracing::error!("error while listening on {}: {}", listener_name, err);
+                tokio::select! {
+                    _ = tokio::time::sleep(ACCEPT_ERROR_BACKOFF) => {}
+                    _ = broker.shutdown().cancelled() => return Ok(()),
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests;
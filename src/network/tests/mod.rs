@@ -0,0 +1,1283 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+use futures::{SinkExt, StreamExt};
+use tokio_util::codec::{Decoder, Encoder, Framed};
+
+use crate::{
+    broker::Broker,
+    config::{BrokerConfig, SecurityProtocol},
+    metadata::{
+        records::{
+            BrokerEndpoint, FeatureLevelRecord, MetadataRecord, RegisterBrokerRecord, TopicRecord,
+        },
+        MetadataCache, MetadataDelta, MetadataLoader,
+    },
+    model::ConfigResource,
+    protocol::{
+        message::{
+            alter_client_quotas::{EntryData as QuotaEntryData, OpData},
+            alter_user_scram_credentials::ScramCredentialUpsertion,
+            create_acls::AclCreation,
+            describe_client_quotas::{
+                ComponentData, EntityData as QuotaEntityData, MATCH_TYPE_SPECIFIED,
+            },
+            describe_cluster::{ENDPOINT_TYPE_BROKERS, ENDPOINT_TYPE_CONTROLLERS},
+            describe_configs::DescribeConfigsResource,
+            incremental_alter_configs::{AlterConfigsResource, AlterableConfig},
+            read_request, AlterClientQuotasRequest, AlterClientQuotasResponse,
+            AlterUserScramCredentialsRequest, AlterUserScramCredentialsResponse,
+            ApiVersionsRequest, ApiVersionsResponse, CreateAclsRequest, CreateAclsResponse,
+            DescribeAclsRequest, DescribeAclsResponse, DescribeClientQuotasRequest,
+            DescribeClientQuotasResponse, DescribeClusterRequest, DescribeClusterResponse,
+            DescribeConfigsRequest, DescribeConfigsResponse, IncrementalAlterConfigsRequest,
+            IncrementalAlterConfigsResponse, KafkaRequest, MetadataRequest, MetadataResponse,
+            RequestHeader, ResponseHeader, SaslAuthenticateRequest, SaslAuthenticateResponse,
+            SaslHandshakeRequest, SaslHandshakeResponse,
+        },
+        ErrorCode, ReadableVersion, Writable,
+    },
+    security::{
+        tls, AclOperation, AclPermissionType, PatternType, ResourceType, ScramMechanism, Session,
+    },
+};
+
+use super::{Error, KafkaCodec};
+
+fn test_broker() -> Broker {
+    Broker::new(BrokerConfig::default(), Arc::new(MetadataCache::new()))
+}
+
+fn test_session() -> Session {
+    Session::anonymous("127.0.0.1")
+}
+
+fn api_versions_frame(correlation_id: i32) -> Bytes {
+    let mut body = BytesMut::new();
+    RequestHeader::v1(18, 0, correlation_id, Some("kafka-cli")).write(&mut body);
+
+    let mut frame = BytesMut::new();
+    KafkaCodec::default()
+        .encode(body.freeze(), &mut frame)
+        .unwrap();
+    frame.freeze()
+}
+
+#[test]
+fn test_decode_partial_frame() {
+    let frame = api_versions_frame(7);
+    let mut codec = KafkaCodec::default();
+    let mut buffer = BytesMut::new();
+
+    buffer.put_slice(&frame[..2]);
+    assert!(
+        codec.decode(&mut buffer).unwrap().is_none(),
+        "size prefix split"
+    );
+
+    buffer.put_slice(&frame[2..frame.len() - 1]);
+    assert!(codec.decode(&mut buffer).unwrap().is_none(), "body split");
+
+    buffer.put_slice(&frame[frame.len() - 1..]);
+    let decoded = codec.decode(&mut buffer).unwrap().unwrap();
+    assert_eq!(&frame[4..], decoded.as_ref());
+    assert!(buffer.is_empty());
+}
+
+#[test]
+fn test_decode_pipelined_frames() {
+    let mut codec = KafkaCodec::default();
+    let mut buffer = BytesMut::new();
+    for correlation_id in 0..3 {
+        buffer.put_slice(&api_versions_frame(correlation_id));
+    }
+    let trailing = api_versions_frame(3);
+    buffer.put_slice(&trailing[..6]);
+
+    let mut decoded = Vec::new();
+    while let Some(frame) = codec.decode(&mut buffer).unwrap() {
+        decoded.push(frame);
+    }
+    assert_eq!(3, decoded.len());
+    assert_eq!(&trailing[..6], buffer.as_ref());
+}
+
+#[test]
+fn test_decode_rejects_oversized_frame() {
+    let mut codec = KafkaCodec::new(16);
+    let mut buffer = BytesMut::new();
+    buffer.put_i32(17);
+
+    match codec.decode(&mut buffer) {
+        Err(Error::RequestTooLarge { size, max }) => {
+            assert_eq!(17, size);
+            assert_eq!(16, max);
+        }
+        other => panic!("expected RequestTooLarge, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_decode_rejects_negative_size() {
+    let mut buffer = BytesMut::new();
+    buffer.put_i32(-1);
+    assert!(matches!(
+        KafkaCodec::default().decode(&mut buffer),
+        Err(Error::InvalidRequestSize(-1))
+    ));
+}
+
+#[test]
+fn test_read_request_keeps_partial_frame() {
+    let frame = api_versions_frame(11);
+    let mut read_buffer = &frame[..frame.len() - 1];
+    assert!(read_request(&mut read_buffer).is_err());
+    assert_eq!(frame.len() - 1, read_buffer.len(), "nothing consumed");
+
+    let mut read_buffer = frame.as_ref();
+    let (header, request) = read_request(&mut read_buffer).unwrap();
+    assert_eq!(11, header.correlation_id());
+    assert!(matches!(request, KafkaRequest::ApiVersions(_)));
+}
+
+#[test]
+fn test_unsupported_request_gets_error_response() {
+    let mut frame = BytesMut::new();
+    RequestHeader::v2(1, 99, 42, Some("kafka-cli")).write(&mut frame);
+
+    let mut response = super::connection::handle_request(
+        frame.freeze(),
+        &test_broker(),
+        "PLAINTEXT",
+        &mut test_session(),
+    )
+    .unwrap()
+    .0;
+    assert_eq!(42, response.get_i32(), "correlation id");
+    assert_eq!(0, response.get_u8(), "flexible response header");
+    assert_eq!(35, response.get_i16(), "UNSUPPORTED_VERSION");
+    assert!(
+        !response.has_remaining(),
+        "no Fetch body for an unknown version"
+    );
+
+    let mut frame = BytesMut::new();
+    RequestHeader::v1(1, 3, 44, Some("kafka-cli")).write(&mut frame);
+
+    let mut response = super::connection::handle_request(
+        frame.freeze(),
+        &test_broker(),
+        "PLAINTEXT",
+        &mut test_session(),
+    )
+    .unwrap()
+    .0;
+    assert_eq!(44, response.get_i32(), "correlation id");
+    assert_eq!(35, response.get_i16(), "UNSUPPORTED_VERSION");
+    assert!(!response.has_remaining(), "no v4 body for a v3 request");
+
+    let mut frame = BytesMut::new();
+    RequestHeader::v1(1000, 0, 43, None).write(&mut frame);
+
+    let mut response = super::connection::handle_request(
+        frame.freeze(),
+        &test_broker(),
+        "PLAINTEXT",
+        &mut test_session(),
+    )
+    .unwrap()
+    .0;
+    assert_eq!(43, response.get_i32(), "correlation id");
+    assert_eq!(42, response.get_i16(), "INVALID_REQUEST");
+}
+
+#[tokio::test]
+async fn test_framing_error_answers_pipelined_requests() {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let mut client = tokio::net::TcpStream::connect(listener.local_addr().unwrap())
+        .await
+        .unwrap();
+    let (server, _) = listener.accept().await.unwrap();
+    let mut frames = BytesMut::from(&api_versions_frame(1)[..]);
+    frames.extend_from_slice(&api_versions_frame(2));
+    frames.put_i32(-1);
+    client.write_all(&frames).await.unwrap();
+
+    assert!(matches!(
+        super::handle_connection(
+            server,
+            Arc::new(test_broker()),
+            Arc::from("PLAINTEXT"),
+            test_session()
+        )
+        .await,
+        Err(Error::InvalidRequestSize(-1))
+    ));
+
+    let mut responses = Vec::new();
+    client.read_to_end(&mut responses).await.unwrap();
+    let mut responses = Bytes::from(responses);
+    for correlation_id in [1, 2] {
+        let size = responses.get_i32() as usize;
+        let mut response = responses.split_to(size);
+        assert_eq!(correlation_id, response.get_i32());
+    }
+    assert!(responses.is_empty());
+}
+
+#[test]
+fn test_api_versions_reports_features() {
+    let metadata = Arc::new(MetadataCache::new());
+    let mut delta = MetadataDelta::new(metadata.image());
+    delta.replay(MetadataRecord::FeatureLevel(FeatureLevelRecord::new(
+        "metadata.version",
+        20,
+    )));
+    delta.replay(MetadataRecord::FeatureLevel(FeatureLevelRecord::new(
+        "kraft.version",
+        1,
+    )));
+    delta.set_last_offset(5);
+    let (image, changes) = delta.apply();
+    metadata.publish(image, changes);
+    let broker = Broker::new(BrokerConfig::default(), metadata);
+
+    let mut frame = BytesMut::new();
+    RequestHeader::v2(18, 3, 7, Some("kafka-cli")).write(&mut frame);
+    ApiVersionsRequest::v3("kafka-cli", "1.0").write(&mut frame);
+    let mut response = super::connection::handle_request(
+        frame.freeze(),
+        &broker,
+        "PLAINTEXT",
+        &mut test_session(),
+    )
+    .unwrap()
+    .0;
+    ResponseHeader::read_version(&mut response, 0).unwrap();
+    let response = ApiVersionsResponse::read_version(&mut response, 3).unwrap();
+    assert_eq!(ErrorCode::None, response.error_code());
+
+    let mut supported = response
+        .supported_features()
+        .unwrap()
+        .iter()
+        .map(|feature| {
+            (
+                feature.name().to_vec(),
+                feature.min_version(),
+                feature.max_version(),
+            )
+        })
+        .collect::<Vec<_>>();
+    supported.sort();
+    assert_eq!(
+        vec![
+            (b"kraft.version".to_vec(), 0, 1),
+            (b"metadata.version".to_vec(), 1, 21)
+        ],
+        supported
+    );
+    let mut finalized = response
+        .finalized_features()
+        .unwrap()
+        .iter()
+        .map(|feature| {
+            (
+                feature.name().to_vec(),
+                feature.min_version_level(),
+                feature.max_version_level(),
+            )
+        })
+        .collect::<Vec<_>>();
+    finalized.sort();
+    assert_eq!(
+        vec![
+            (b"kraft.version".to_vec(), 1, 1),
+            (b"metadata.version".to_vec(), 20, 20)
+        ],
+        finalized
+    );
+    assert_eq!(Some(5), response.finalized_features_epoch());
+}
+
+#[test]
+fn test_metadata_lists_registered_brokers() {
+    let metadata = Arc::new(MetadataCache::new());
+    let mut delta = MetadataDelta::new(metadata.image());
+    for (broker_id, fenced) in [(1, false), (2, true)] {
+        delta.replay(MetadataRecord::RegisterBroker(RegisterBrokerRecord::new(
+            3,
+            broker_id,
+            uuid::Uuid::new_v4(),
+            10,
+            vec![BrokerEndpoint::new("PLAINTEXT", "kafka-1", 9092, 0)],
+            vec![],
+            Some("rack-a"),
+            fenced,
+            false,
+            vec![],
+        )));
+    }
+    let (image, changes) = delta.apply();
+    metadata.publish(image, changes);
+    let broker = Broker::new(BrokerConfig::default(), metadata);
+
+    let mut frame = BytesMut::new();
+    RequestHeader::v2(3, 12, 5, Some("kafka-cli")).write(&mut frame);
+    MetadataRequest::v12(Some(vec![]), false, false).write(&mut frame);
+
+    let mut response = super::connection::handle_request(
+        frame.freeze(),
+        &broker,
+        "PLAINTEXT",
+        &mut test_session(),
+    )
+    .unwrap()
+    .0;
+    let header = ResponseHeader::read_version(&mut response, 1).unwrap();
+    assert_eq!(5, header.correlation_id());
+    let response = MetadataResponse::read_version(&mut response, 12).unwrap();
+    assert_eq!(1, response.brokers().len(), "fenced broker left out");
+    assert_eq!(1, response.brokers()[0].node_id());
+    assert_eq!(b"kafka-1", response.brokers()[0].host());
+    assert_eq!(Some(&b"rack-a"[..]), response.brokers()[0].rack());
+    assert_eq!(1, response.controller_id());
+    assert!(response.topics().is_empty());
+}
+
+#[test]
+fn test_describe_cluster() {
+    let metadata = Arc::new(MetadataCache::new());
+    let mut delta = MetadataDelta::new(metadata.image());
+    for broker_id in [2, 1] {
+        delta.replay(MetadataRecord::RegisterBroker(RegisterBrokerRecord::new(
+            3,
+            broker_id,
+            uuid::Uuid::new_v4(),
+            10,
+            vec![BrokerEndpoint::new(
+                "PLAINTEXT",
+                &format!("kafka-{}", broker_id),
+                9092,
+                0,
+            )],
+            vec![],
+            None,
+            false,
+            false,
+            vec![],
+        )));
+    }
+    let (image, changes) = delta.apply();
+    metadata.publish(image, changes);
+    let broker =
+        Broker::new(BrokerConfig::default(), metadata).with_cluster_id("MkU3OEVBNTcwNTJENDM2Qk");
+
+    let describe_cluster = |endpoint_type: i8| {
+        let mut frame = BytesMut::new();
+        RequestHeader::v2(60, 1, 7, Some("kafka-cli")).write(&mut frame);
+        DescribeClusterRequest::v1(true, endpoint_type).write(&mut frame);
+        let mut response = super::connection::handle_request(
+            frame.freeze(),
+            &broker,
+            "PLAINTEXT",
+            &mut test_session(),
+        )
+        .unwrap()
+        .0;
+        ResponseHeader::read_version(&mut response, 1).unwrap();
+        DescribeClusterResponse::read_version(&mut response, 1).unwrap()
+    };
+
+    let response = describe_cluster(ENDPOINT_TYPE_BROKERS);
+    assert_eq!(ErrorCode::None, response.error_code());
+    assert_eq!(b"MkU3OEVBNTcwNTJENDM2Qk", response.cluster_id());
+    assert_eq!(1, response.controller_id());
+    let mut brokers = response
+        .brokers()
+        .iter()
+        .map(|broker| (broker.broker_id(), broker.host().to_vec()))
+        .collect::<Vec<_>>();
+    brokers.sort();
+    assert_eq!(
+        vec![(1, b"kafka-1".to_vec()), (2, b"kafka-2".to_vec())],
+        brokers
+    );
+    assert_ne!(i32::MIN, response.cluster_authorized_operations());
+
+    let response = describe_cluster(ENDPOINT_TYPE_CONTROLLERS);
+    assert_eq!(ErrorCode::UnsupportedEndpointType, response.error_code());
+    assert!(response.brokers().is_empty());
+}
+
+#[test]
+fn test_incremental_alter_configs_then_describe() {
+    let log_dir = std::env::temp_dir().join(format!("dynamic-configs-{}", uuid::Uuid::new_v4()));
+    let config = BrokerConfig::from_properties(HashMap::from([
+        ("node.id".to_string(), "1".to_string()),
+        (
+            "metadata.log.dir".to_string(),
+            log_dir.display().to_string(),
+        ),
+        ("log.retention.hours".to_string(), "24".to_string()),
+    ]))
+    .unwrap();
+    let metadata = Arc::new(MetadataCache::new());
+    let mut delta = MetadataDelta::new(metadata.image());
+    delta.replay(MetadataRecord::Topic(TopicRecord::new(
+        "foo",
+        uuid::Uuid::new_v4(),
+    )));
+    let (image, changes) = delta.apply();
+    metadata.publish(image, changes);
+    let loader = MetadataLoader::new(&log_dir, u64::MAX);
+    let broker =
+        Broker::new(config, metadata.clone()).with_metadata_loader(Arc::new(Mutex::new(loader)));
+
+    let mut frame = BytesMut::new();
+    RequestHeader::v2(44, 1, 3, Some("kafka-cli")).write(&mut frame);
+    IncrementalAlterConfigsRequest::v1(
+        vec![
+            AlterConfigsResource::v1(
+                ConfigResource::TOPIC,
+                "foo",
+                vec![
+                    AlterableConfig::v1("cleanup.policy", 2, Some("compact")),
+                    AlterableConfig::v1("retention.ms", 0, Some("abc")),
+                ],
+            ),
+            AlterConfigsResource::v1(
+                ConfigResource::TOPIC,
+                "foo",
+                vec![AlterableConfig::v1("cleanup.policy", 2, Some("compact"))],
+            ),
+            AlterConfigsResource::v1(
+                ConfigResource::BROKER,
+                "",
+                vec![AlterableConfig::v1("log.retention.ms", 0, Some("1000"))],
+            ),
+        ],
+        false,
+    )
+    .write(&mut frame);
+    let mut response = super::connection::handle_request(
+        frame.freeze(),
+        &broker,
+        "PLAINTEXT",
+        &mut test_session(),
+    )
+    .unwrap()
+    .0;
+    ResponseHeader::read_version(&mut response, 1).unwrap();
+    let response = IncrementalAlterConfigsResponse::read_version(&mut response, 1).unwrap();
+    let error_codes = response
+        .responses()
+        .iter()
+        .map(|response| response.error_code())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        vec![ErrorCode::InvalidConfig, ErrorCode::None, ErrorCode::None],
+        error_codes,
+        "a bad value rejects the whole resource"
+    );
+
+    // Altered configs are visible as soon as the alter request is answered.
+    let mut frame = BytesMut::new();
+    RequestHeader::v2(32, 4, 4, Some("kafka-cli")).write(&mut frame);
+    DescribeConfigsRequest::v4(
+        vec![DescribeConfigsResource::v4(
+            ConfigResource::TOPIC,
+            "foo",
+            Some(vec!["cleanup.policy", "retention.ms"]),
+        )],
+        true,
+        false,
+    )
+    .write(&mut frame);
+    let mut response = super::connection::handle_request(
+        frame.freeze(),
+        &broker,
+        "PLAINTEXT",
+        &mut test_session(),
+    )
+    .unwrap()
+    .0;
+    ResponseHeader::read_version(&mut response, 1).unwrap();
+    let response = DescribeConfigsResponse::read_version(&mut response, 4).unwrap();
+    let configs = response.results()[0].configs();
+    assert_eq!(2, configs.len());
+
+    assert_eq!(b"cleanup.policy", configs[0].name());
+    assert_eq!(Some(&b"delete,compact"[..]), configs[0].value());
+    assert_eq!(1, configs[0].config_source());
+
+    // The dynamic cluster default wins over the static hours setting.
+    assert_eq!(b"retention.ms", configs[1].name());
+    assert_eq!(Some(&b"1000"[..]), configs[1].value());
+    assert_eq!(3, configs[1].config_source());
+    let synonyms = configs[1]
+        .synonyms()
+        .iter()
+        .map(|synonym| (synonym.name(), synonym.value(), synonym.source()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        vec![
+            (&b"log.retention.ms"[..], Some(&b"1000"[..]), 3),
+            (&b"log.retention.hours"[..], Some(&b"24"[..]), 4),
+            (&b"retention.ms"[..], Some(&b"604800000"[..]), 5),
+        ],
+        synonyms
+    );
+}
+
+#[test]
+fn test_alter_and_describe_broker_loggers() {
+    let config =
+        BrokerConfig::from_properties(HashMap::from([("node.id".to_string(), "1".to_string())]))
+            .unwrap();
+    let broker = Broker::new(config, Arc::new(MetadataCache::new()));
+    let logger = "codecrafters_kafka::network::tests";
+
+    let mut frame = BytesMut::new();
+    RequestHeader::v2(44, 1, 3, Some("kafka-cli")).write(&mut frame);
+    IncrementalAlterConfigsRequest::v1(
+        vec![
+            AlterConfigsResource::v1(
+                ConfigResource::BROKER_LOGGER,
+                "1",
+                vec![AlterableConfig::v1(logger, 0, Some("debug"))],
+            ),
+            AlterConfigsResource::v1(
+                ConfigResource::BROKER_LOGGER,
+                "1",
+                vec![AlterableConfig::v1("root", 1, None)],
+            ),
+            AlterConfigsResource::v1(
+                ConfigResource::BROKER_LOGGER,
+                "1",
+                vec![AlterableConfig::v1(logger, 2, Some("INFO"))],
+            ),
+        ],
+        false,
+    )
+    .write(&mut frame);
+    let mut response = super::connection::handle_request(
+        frame.freeze(),
+        &broker,
+        "PLAINTEXT",
+        &mut test_session(),
+    )
+    .unwrap()
+    .0;
+    ResponseHeader::read_version(&mut response, 1).unwrap();
+    let response = IncrementalAlterConfigsResponse::read_version(&mut response, 1).unwrap();
+    let error_codes = response
+        .responses()
+        .iter()
+        .map(|response| response.error_code())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        vec![
+            ErrorCode::None,
+            ErrorCode::InvalidConfig,
+            ErrorCode::InvalidRequest
+        ],
+        error_codes
+    );
+
+    let mut frame = BytesMut::new();
+    RequestHeader::v2(32, 4, 4, Some("kafka-cli")).write(&mut frame);
+    DescribeConfigsRequest::v4(
+        vec![DescribeConfigsResource::v4(
+            ConfigResource::BROKER_LOGGER,
+            "1",
+            Some(vec![logger]),
+        )],
+        false,
+        false,
+    )
+    .write(&mut frame);
+    let mut response = super::connection::handle_request(
+        frame.freeze(),
+        &broker,
+        "PLAINTEXT",
+        &mut test_session(),
+    )
+    .unwrap()
+    .0;
+    ResponseHeader::read_version(&mut response, 1).unwrap();
+    let response = DescribeConfigsResponse::read_version(&mut response, 4).unwrap();
+    let configs = response.results()[0].configs();
+    assert_eq!(1, configs.len());
+    assert_eq!(logger.as_bytes(), configs[0].name());
+    assert_eq!(Some(&b"DEBUG"[..]), configs[0].value());
+    assert_eq!(6, configs[0].config_source());
+}
+
+#[test]
+fn test_acls_restrict_topics() {
+    let log_dir = std::env::temp_dir().join(format!("acls-{}", uuid::Uuid::new_v4()));
+    let config = BrokerConfig::from_properties(HashMap::from([
+        ("node.id".to_string(), "1".to_string()),
+        (
+            "metadata.log.dir".to_string(),
+            log_dir.display().to_string(),
+        ),
+        (
+            "authorizer.class.name".to_string(),
+            "org.apache.kafka.metadata.authorizer.StandardAuthorizer".to_string(),
+        ),
+        ("super.users".to_string(), "User:admin".to_string()),
+    ]))
+    .unwrap();
+    let metadata = Arc::new(MetadataCache::new());
+    let mut delta = MetadataDelta::new(metadata.image());
+    for name in ["foo-1", "bar"] {
+        delta.replay(MetadataRecord::Topic(TopicRecord::new(
+            name,
+            uuid::Uuid::new_v4(),
+        )));
+    }
+    let (image, changes) = delta.apply();
+    metadata.publish(image, changes);
+    let loader = MetadataLoader::new(&log_dir, u64::MAX);
+    let broker =
+        Broker::new(config, metadata.clone()).with_metadata_loader(Arc::new(Mutex::new(loader)));
+    let mut admin = Session::new("User:admin", "127.0.0.1");
+
+    let mut frame = BytesMut::new();
+    RequestHeader::v2(30, 3, 6, Some("kafka-cli")).write(&mut frame);
+    CreateAclsRequest::v3(vec![
+        AclCreation::v3(
+            ResourceType::Topic.id(),
+            "foo-",
+            PatternType::Prefixed.id(),
+            "User:ANONYMOUS",
+            "*",
+            AclOperation::Read.id(),
+            AclPermissionType::Allow.id(),
+        ),
+        AclCreation::v3(
+            ResourceType::Cluster.id(),
+            "other-cluster",
+            PatternType::Literal.id(),
+            "User:ANONYMOUS",
+            "*",
+            AclOperation::Describe.id(),
+            AclPermissionType::Allow.id(),
+        ),
+    ])
+    .write(&mut frame);
+    let mut response =
+        super::connection::handle_request(frame.freeze(), &broker, "PLAINTEXT", &mut admin)
+            .unwrap()
+            .0;
+    ResponseHeader::read_version(&mut response, 1).unwrap();
+    let response = CreateAclsResponse::read_version(&mut response, 3).unwrap();
+    let error_codes = response
+        .results()
+        .iter()
+        .map(|result| result.error_code())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        vec![ErrorCode::None, ErrorCode::InvalidRequest],
+        error_codes
+    );
+
+    // Read on the prefix implies Describe, so only foo-1 is listed.
+    let mut frame = BytesMut::new();
+    RequestHeader::v2(3, 12, 7, Some("kafka-cli")).write(&mut frame);
+    MetadataRequest::v12(None, false, true).write(&mut frame);
+    let mut response = super::connection::handle_request(
+        frame.freeze(),
+        &broker,
+        "PLAINTEXT",
+        &mut test_session(),
+    )
+    .unwrap()
+    .0;
+    ResponseHeader::read_version(&mut response, 1).unwrap();
+    let response = MetadataResponse::read_version(&mut response, 12).unwrap();
+    assert_eq!(1, response.topics().len());
+    assert_eq!(Some(&b"foo-1"[..]), response.topics()[0].name());
+
+    let describe_frame = || {
+        let mut frame = BytesMut::new();
+        RequestHeader::v2(29, 3, 8, Some("kafka-cli")).write(&mut frame);
+        DescribeAclsRequest::v3(
+            ResourceType::Any.id(),
+            None,
+            PatternType::Any.id(),
+            None,
+            None,
+            AclOperation::Any.id(),
+            AclPermissionType::Any.id(),
+        )
+        .write(&mut frame);
+        frame.freeze()
+    };
+    let mut response = super::connection::handle_request(
+        describe_frame(),
+        &broker,
+        "PLAINTEXT",
+        &mut test_session(),
+    )
+    .unwrap()
+    .0;
+    ResponseHeader::read_version(&mut response, 1).unwrap();
+    let response = DescribeAclsResponse::read_version(&mut response, 3).unwrap();
+    assert_eq!(ErrorCode::ClusterAuthorizationFailed, response.error_code());
+
+    let mut response =
+        super::connection::handle_request(describe_frame(), &broker, "PLAINTEXT", &mut admin)
+            .unwrap()
+            .0;
+    ResponseHeader::read_version(&mut response, 1).unwrap();
+    let response = DescribeAclsResponse::read_version(&mut response, 3).unwrap();
+    assert_eq!(ErrorCode::None, response.error_code());
+    assert_eq!(1, response.resources().len());
+    assert_eq!(b"foo-", response.resources()[0].resource_name());
+    assert_eq!(1, response.resources()[0].acls().len());
+}
+
+fn sasl_handshake(broker: &Broker, session: &mut Session, mechanism: &str) -> ErrorCode {
+    let mut frame = BytesMut::new();
+    RequestHeader::v1(17, 1, 1, Some("kafka-cli")).write(&mut frame);
+    SaslHandshakeRequest::v1(mechanism).write(&mut frame);
+    let mut response =
+        super::connection::handle_request(frame.freeze(), broker, "PLAINTEXT", session)
+            .unwrap()
+            .0;
+    ResponseHeader::read_version(&mut response, 0).unwrap();
+    SaslHandshakeResponse::read_version(&mut response, 1)
+        .unwrap()
+        .error_code()
+}
+
+fn sasl_authenticate(
+    broker: &Broker,
+    session: &mut Session,
+    token: &[u8],
+) -> SaslAuthenticateResponse {
+    let mut frame = BytesMut::new();
+    RequestHeader::v2(36, 2, 2, Some("kafka-cli")).write(&mut frame);
+    SaslAuthenticateRequest::v2(token).write(&mut frame);
+    let mut response =
+        super::connection::handle_request(frame.freeze(), broker, "PLAINTEXT", session)
+            .unwrap()
+            .0;
+    ResponseHeader::read_version(&mut response, 1).unwrap();
+    SaslAuthenticateResponse::read_version(&mut response, 2).unwrap()
+}
+
+#[tokio::test]
+async fn test_sasl_authentication() {
+    use base64::{engine::general_purpose::STANDARD, Engine};
+    use hmac::{Hmac, Mac};
+    use sha2::{Digest, Sha256};
+
+    let dir = std::env::temp_dir().join(format!("sasl-{}", uuid::Uuid::new_v4()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let credentials_file = dir.join("plain.properties");
+    std::fs::write(&credentials_file, "alice=alice-secret\n").unwrap();
+    let log_dir = dir.join("metadata");
+    let config = BrokerConfig::from_properties(HashMap::from([
+        ("node.id".to_string(), "1".to_string()),
+        (
+            "metadata.log.dir".to_string(),
+            log_dir.display().to_string(),
+        ),
+        (
+            "sasl.plain.credentials.file".to_string(),
+            credentials_file.display().to_string(),
+        ),
+    ]))
+    .unwrap();
+    let metadata = Arc::new(MetadataCache::new());
+    let loader = MetadataLoader::new(&log_dir, u64::MAX);
+    let broker = Arc::new(
+        Broker::new(config, metadata.clone()).with_metadata_loader(Arc::new(Mutex::new(loader))),
+    );
+
+    // Nothing but ApiVersions and SaslHandshake before authenticating.
+    let mut session = Session::unauthenticated("127.0.0.1");
+    let metadata_frame = || {
+        let mut frame = BytesMut::new();
+        RequestHeader::v2(3, 12, 3, Some("kafka-cli")).write(&mut frame);
+        MetadataRequest::v12(None, false, false).write(&mut frame);
+        frame.freeze()
+    };
+    let mut response =
+        super::connection::handle_request(metadata_frame(), &broker, "PLAINTEXT", &mut session)
+            .unwrap()
+            .0;
+    ResponseHeader::read_version(&mut response, 1).unwrap();
+    let response = MetadataResponse::read_version(&mut response, 12).unwrap();
+    assert_eq!(
+        ErrorCode::IllegalSaslState,
+        response.topics()[0].error_code()
+    );
+
+    assert_eq!(
+        ErrorCode::UnsupportedSaslMechanism,
+        sasl_handshake(&broker, &mut session, "GSSAPI")
+    );
+    assert_eq!(
+        ErrorCode::None,
+        sasl_handshake(&broker, &mut session, "PLAIN")
+    );
+    let response = sasl_authenticate(&broker, &mut session, b"\0alice\0wrong");
+    assert_eq!(ErrorCode::SaslAuthenticationFailed, response.error_code());
+    assert!(!session.is_authenticated());
+    assert!(session.authentication_failed());
+
+    // On a connection, the failure is answered and the connection closed.
+    {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let mut client = tokio::net::TcpStream::connect(listener.local_addr().unwrap())
+            .await
+            .unwrap();
+        let (server, _) = listener.accept().await.unwrap();
+        let connection = tokio::spawn(super::handle_connection(
+            server,
+            broker.clone(),
+            Arc::from("PLAINTEXT"),
+            Session::unauthenticated("127.0.0.1"),
+        ));
+
+        let mut handshake = BytesMut::new();
+        RequestHeader::v1(17, 1, 1, Some("kafka-cli")).write(&mut handshake);
+        SaslHandshakeRequest::v1("PLAIN").write(&mut handshake);
+        let mut authenticate = BytesMut::new();
+        RequestHeader::v2(36, 2, 2, Some("kafka-cli")).write(&mut authenticate);
+        SaslAuthenticateRequest::v2(b"\0alice\0wrong").write(&mut authenticate);
+        let mut frames = BytesMut::new();
+        for body in [handshake, authenticate] {
+            KafkaCodec::default()
+                .encode(body.freeze(), &mut frames)
+                .unwrap();
+        }
+        client.write_all(&frames).await.unwrap();
+
+        let mut responses = Vec::new();
+        client.read_to_end(&mut responses).await.unwrap();
+        let mut responses = Bytes::from(responses);
+        let size = responses.get_i32() as usize;
+        responses.advance(size);
+        let size = responses.get_i32() as usize;
+        let mut response = responses.split_to(size);
+        assert_eq!(
+            2,
+            ResponseHeader::read_version(&mut response, 1)
+                .unwrap()
+                .correlation_id()
+        );
+        assert_eq!(
+            ErrorCode::SaslAuthenticationFailed,
+            SaslAuthenticateResponse::read_version(&mut response, 2)
+                .unwrap()
+                .error_code()
+        );
+        assert!(responses.is_empty());
+        connection.await.unwrap().unwrap();
+    }
+
+    let mut session = Session::unauthenticated("127.0.0.1");
+    assert_eq!(
+        ErrorCode::None,
+        sasl_handshake(&broker, &mut session, "PLAIN")
+    );
+    let response = sasl_authenticate(&broker, &mut session, b"\0alice\0alice-secret");
+    assert_eq!(ErrorCode::None, response.error_code());
+    assert_eq!("User:alice", session.principal());
+    let mut response =
+        super::connection::handle_request(metadata_frame(), &broker, "PLAINTEXT", &mut session)
+            .unwrap()
+            .0;
+    ResponseHeader::read_version(&mut response, 1).unwrap();
+    assert!(MetadataResponse::read_version(&mut response, 12)
+        .unwrap()
+        .topics()
+        .is_empty());
+
+    // Store a SCRAM credential for bob and log in with it.
+    let salt = b"bob-salt";
+    let mut salted_password = [0u8; 32];
+    pbkdf2::pbkdf2_hmac::<Sha256>(b"bob-secret", salt, 4096, &mut salted_password);
+    let mut frame = BytesMut::new();
+    RequestHeader::v2(51, 0, 4, Some("kafka-cli")).write(&mut frame);
+    AlterUserScramCredentialsRequest::v0(
+        vec![],
+        vec![ScramCredentialUpsertion::v0(
+            "bob",
+            ScramMechanism::Sha256.id(),
+            4096,
+            salt,
+            &salted_password,
+        )],
+    )
+    .write(&mut frame);
+    let mut response =
+        super::connection::handle_request(frame.freeze(), &broker, "PLAINTEXT", &mut session)
+            .unwrap()
+            .0;
+    ResponseHeader::read_version(&mut response, 1).unwrap();
+    let response = AlterUserScramCredentialsResponse::read_version(&mut response, 0).unwrap();
+    assert_eq!(ErrorCode::None, response.results()[0].error_code());
+
+    let hmac = |key: &[u8], message: &[u8]| {
+        let mut mac = Hmac::<Sha256>::new_from_slice(key).unwrap();
+        mac.update(message);
+        mac.finalize().into_bytes().to_vec()
+    };
+    let mut session = Session::unauthenticated("127.0.0.1");
+    assert_eq!(
+        ErrorCode::None,
+        sasl_handshake(&broker, &mut session, "SCRAM-SHA-256")
+    );
+    let client_first_bare = "n=bob,r=client-nonce";
+    let response = sasl_authenticate(
+        &broker,
+        &mut session,
+        format!("n,,{}", client_first_bare).as_bytes(),
+    );
+    assert_eq!(ErrorCode::None, response.error_code());
+    let server_first = std::str::from_utf8(response.auth_bytes())
+        .unwrap()
+        .to_string();
+    let nonce = server_first
+        .split(',')
+        .find_map(|attribute| attribute.strip_prefix("r="))
+        .unwrap();
+    assert!(nonce.starts_with("client-nonce"));
+
+    let without_proof = format!("c=biws,r={}", nonce);
+    let auth_message = format!("{},{},{}", client_first_bare, server_first, without_proof);
+    let client_key = hmac(&salted_password, b"Client Key");
+    let client_signature = hmac(&Sha256::digest(&client_key), auth_message.as_bytes());
+    let proof = client_key
+        .iter()
+        .zip(&client_signature)
+        .map(|(k, s)| k ^ s)
+        .collect::<Vec<_>>();
+    let client_final = format!("{},p={}", without_proof, STANDARD.encode(proof));
+    let response = sasl_authenticate(&broker, &mut session, client_final.as_bytes());
+    assert_eq!(ErrorCode::None, response.error_code());
+    let server_signature = hmac(
+        &hmac(&salted_password, b"Server Key"),
+        auth_message.as_bytes(),
+    );
+    assert_eq!(
+        format!("v={}", STANDARD.encode(server_signature)).as_bytes(),
+        response.auth_bytes()
+    );
+    assert_eq!("User:bob", session.principal());
+}
+
+#[tokio::test]
+async fn test_tls_client_certificate_names_principal() {
+    use rcgen::{BasicConstraints, CertificateParams, DnType, IsCa, KeyPair};
+    use tokio_rustls::{
+        rustls::{crypto::ring, pki_types::ServerName, ClientConfig, RootCertStore},
+        TlsConnector,
+    };
+
+    let dir = std::env::temp_dir().join(format!("tls-{}", uuid::Uuid::new_v4()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let mut ca_params = CertificateParams::default();
+    ca_params.is_ca = IsCa::Ca(BasicConstraints::Unconstrained);
+    ca_params
+        .distinguished_name
+        .push(DnType::CommonName, "test-ca");
+    let ca_key = KeyPair::generate().unwrap();
+    let ca = ca_params.self_signed(&ca_key).unwrap();
+    let server_key = KeyPair::generate().unwrap();
+    let server = CertificateParams::new(vec!["localhost".to_string()])
+        .unwrap()
+        .signed_by(&server_key, &ca, &ca_key)
+        .unwrap();
+    let mut client_params = CertificateParams::default();
+    client_params
+        .distinguished_name
+        .push(DnType::CommonName, "alice");
+    let client_key = KeyPair::generate().unwrap();
+    let client = client_params.signed_by(&client_key, &ca, &ca_key).unwrap();
+    std::fs::write(dir.join("ca.pem"), ca.pem()).unwrap();
+    std::fs::write(dir.join("server.pem"), server.pem()).unwrap();
+    std::fs::write(dir.join("server.key"), server_key.serialize_pem()).unwrap();
+
+    let path = |name: &str| dir.join(name).display().to_string();
+    let config = BrokerConfig::from_properties(HashMap::from([
+        ("node.id".to_string(), "1".to_string()),
+        ("metadata.log.dir".to_string(), path("metadata")),
+        ("ssl.certificate.location".to_string(), path("server.pem")),
+        ("ssl.key.location".to_string(), path("server.key")),
+        ("ssl.truststore.location".to_string(), path("ca.pem")),
+        ("ssl.client.auth".to_string(), "required".to_string()),
+        (
+            "authorizer.class.name".to_string(),
+            "org.apache.kafka.metadata.authorizer.StandardAuthorizer".to_string(),
+        ),
+        ("super.users".to_string(), "User:CN=alice".to_string()),
+        ("connections.max.idle.ms".to_string(), "2000".to_string()),
+    ]))
+    .unwrap();
+    let acceptor = tls::acceptor(&config).unwrap();
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let broker = Arc::new(Broker::new(config, Arc::new(MetadataCache::new())));
+    tokio::spawn(super::serve(
+        listener,
+        broker,
+        "SSL".to_string(),
+        SecurityProtocol::Ssl,
+        Some(acceptor),
+    ));
+
+    let mut roots = RootCertStore::empty();
+    roots.add(ca.der().clone()).unwrap();
+    let client_config = ClientConfig::builder_with_provider(Arc::new(ring::default_provider()))
+        .with_safe_default_protocol_versions()
+        .unwrap()
+        .with_root_certificates(roots)
+        .with_client_auth_cert(
+            vec![client.der().clone()],
+            client_key.serialize_der().try_into().unwrap(),
+        )
+        .unwrap();
+    let stream = tokio::net::TcpStream::connect(addr).await.unwrap();
+    let stream = TlsConnector::from(Arc::new(client_config))
+        .connect(ServerName::try_from("localhost").unwrap(), stream)
+        .await
+        .unwrap();
+    let mut framed = Framed::new(stream, KafkaCodec::default());
+
+    // Only the certificate's subject makes this client a super user.
+    let mut frame = BytesMut::new();
+    RequestHeader::v2(29, 3, 9, Some("kafka-cli")).write(&mut frame);
+    DescribeAclsRequest::v3(
+        ResourceType::Any.id(),
+        None,
+        PatternType::Any.id(),
+        None,
+        None,
+        AclOperation::Any.id(),
+        AclPermissionType::Any.id(),
+    )
+    .write(&mut frame);
+    framed.send(frame.freeze()).await.unwrap();
+    let mut response = framed.next().await.unwrap().unwrap();
+    ResponseHeader::read_version(&mut response, 1).unwrap();
+    let response = DescribeAclsResponse::read_version(&mut response, 3).unwrap();
+    assert_eq!(ErrorCode::None, response.error_code());
+
+    // A client that never starts the handshake is closed once it was idle
+    // for connections.max.idle.ms.
+    let mut silent = tokio::net::TcpStream::connect(addr).await.unwrap();
+    let read = tokio::time::timeout(
+        std::time::Duration::from_secs(10),
+        tokio::io::AsyncReadExt::read(&mut silent, &mut [0; 1]),
+    )
+    .await;
+    assert!(matches!(read, Ok(Ok(0)) | Ok(Err(_))));
+}
+
+#[test]
+fn test_client_quotas_throttle_requests() {
+    let log_dir = std::env::temp_dir().join(format!("quotas-{}", uuid::Uuid::new_v4()));
+    let config = BrokerConfig::from_properties(HashMap::from([
+        ("node.id".to_string(), "1".to_string()),
+        (
+            "metadata.log.dir".to_string(),
+            log_dir.display().to_string(),
+        ),
+    ]))
+    .unwrap();
+    let metadata = Arc::new(MetadataCache::new());
+    let loader = MetadataLoader::new(&log_dir, u64::MAX);
+    let broker =
+        Broker::new(config, metadata.clone()).with_metadata_loader(Arc::new(Mutex::new(loader)));
+
+    // Next to nothing of the request threads for the anonymous user, and an
+    // invalid key that is rejected on its own.
+    let mut frame = BytesMut::new();
+    RequestHeader::v2(49, 1, 9, Some("kafka-cli")).write(&mut frame);
+    AlterClientQuotasRequest::v1(
+        vec![
+            QuotaEntryData::v1(
+                vec![QuotaEntityData::v1("user", Some("ANONYMOUS"))],
+                vec![OpData::v1("request_percentage", 1e-9, false)],
+            ),
+            QuotaEntryData::v1(
+                vec![QuotaEntityData::v1("client-id", None)],
+                vec![OpData::v1("unknown_rate", 1.0, false)],
+            ),
+        ],
+        false,
+    )
+    .write(&mut frame);
+    let (mut response, throttle_time_ms) = super::connection::handle_request(
+        frame.freeze(),
+        &broker,
+        "PLAINTEXT",
+        &mut test_session(),
+    )
+    .unwrap();
+    assert!(
+        throttle_time_ms > 0,
+        "the new quota is in effect once the request is answered"
+    );
+    ResponseHeader::read_version(&mut response, 1).unwrap();
+    let response = AlterClientQuotasResponse::read_version(&mut response, 1).unwrap();
+    let error_codes = response
+        .entries()
+        .iter()
+        .map(|entry| entry.error_code())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        vec![ErrorCode::None, ErrorCode::InvalidRequest],
+        error_codes
+    );
+
+    let mut frame = BytesMut::new();
+    RequestHeader::v2(48, 1, 10, Some("kafka-cli")).write(&mut frame);
+    DescribeClientQuotasRequest::v1(
+        vec![ComponentData::v1("user", MATCH_TYPE_SPECIFIED, None)],
+        false,
+    )
+    .write(&mut frame);
+    let (mut response, throttle_time_ms) = super::connection::handle_request(
+        frame.freeze(),
+        &broker,
+        "PLAINTEXT",
+        &mut test_session(),
+    )
+    .unwrap();
+    assert!(throttle_time_ms > 0);
+    ResponseHeader::read_version(&mut response, 1).unwrap();
+    let response = DescribeClientQuotasResponse::read_version(&mut response, 1).unwrap();
+    assert_eq!(throttle_time_ms, response.throttle_time_ms());
+    let entries = response.entries().unwrap();
+    assert_eq!(1, entries.len());
+    assert_eq!(
+        Some(&b"ANONYMOUS"[..]),
+        entries[0].entity()[0].entity_name()
+    );
+    assert_eq!(b"request_percentage", entries[0].values()[0].key());
+
+    // Other users are not held to it.
+    let mut frame = BytesMut::new();
+    RequestHeader::v2(3, 12, 11, Some("kafka-cli")).write(&mut frame);
+    MetadataRequest::v12(None, false, false).write(&mut frame);
+    let (_, throttle_time_ms) = super::connection::handle_request(
+        frame.freeze(),
+        &broker,
+        "PLAINTEXT",
+        &mut Session::new("User:alice", "127.0.0.1"),
+    )
+    .unwrap();
+    assert_eq!(0, throttle_time_ms);
+}
+
+#[test]
+fn test_metadata_advertises_listener_of_request() {
+    let config = BrokerConfig::from_properties(HashMap::from([
+        ("node.id".to_string(), "1".to_string()),
+        (
+            "listeners".to_string(),
+            "INTERNAL://10.0.0.1:9092,EXTERNAL://:9093".to_string(),
+        ),
+        (
+            "advertised.listeners".to_string(),
+            "INTERNAL://10.0.0.1:9092,EXTERNAL://kafka.example.com:443".to_string(),
+        ),
+        (
+            "listener.security.protocol.map".to_string(),
+            "INTERNAL:PLAINTEXT,EXTERNAL:SASL_SSL".to_string(),
+        ),
+    ]))
+    .unwrap();
+    let broker = Broker::new(config, Arc::new(MetadataCache::new()));
+
+    for (listener_name, host, port) in [
+        ("INTERNAL", &b"10.0.0.1"[..], 9092),
+        ("EXTERNAL", &b"kafka.example.com"[..], 443),
+    ] {
+        let mut frame = BytesMut::new();
+        RequestHeader::v2(3, 12, 1, Some("kafka-cli")).write(&mut frame);
+        MetadataRequest::v12(None, false, false).write(&mut frame);
+        let mut response = super::connection::handle_request(
+            frame.freeze(),
+            &broker,
+            listener_name,
+            &mut test_session(),
+        )
+        .unwrap()
+        .0;
+        ResponseHeader::read_version(&mut response, 1).unwrap();
+        let response = MetadataResponse::read_version(&mut response, 12).unwrap();
+        assert_eq!(1, response.brokers().len());
+        assert_eq!(host, response.brokers()[0].host());
+        assert_eq!(port, response.brokers()[0].port());
+    }
+}
+
+#[tokio::test]
+async fn test_connection_limits_and_idle_timeout() {
+    use std::time::Duration;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let config = BrokerConfig::from_properties(HashMap::from([
+        ("max.connections.per.ip".to_string(), "1".to_string()),
+        ("connections.max.idle.ms".to_string(), "200".to_string()),
+    ]))
+    .unwrap();
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let broker = Arc::new(Broker::new(config, Arc::new(MetadataCache::new())));
+    tokio::spawn(super::serve(
+        listener,
+        broker.clone(),
+        "PLAINTEXT".to_string(),
+        SecurityProtocol::Plaintext,
+        None,
+    ));
+    // The error code of the response, which follows its correlation id.
+    let api_versions = |mut stream: tokio::net::TcpStream| async move {
+        stream.write_all(&api_versions_frame(1)).await.unwrap();
+        let size = stream.read_i32().await.unwrap();
+        assert!(size > 0);
+        let mut response = vec![0; size as usize];
+        stream.read_exact(&mut response).await.unwrap();
+        let error_code = (&response[4..]).get_i16();
+        (stream, error_code)
+    };
+    let closed = |mut stream: tokio::net::TcpStream| async move {
+        let mut buffer = [0; 1];
+        let read = tokio::time::timeout(Duration::from_secs(5), stream.read(&mut buffer)).await;
+        matches!(read, Ok(Ok(0)) | Ok(Err(_)))
+    };
+
+    let (first, error_code) =
+        api_versions(tokio::net::TcpStream::connect(addr).await.unwrap()).await;
+    assert_eq!(ErrorCode::None.code(), error_code);
+    // A connection over the limit still has its first request answered.
+    let (second, error_code) =
+        api_versions(tokio::net::TcpStream::connect(addr).await.unwrap()).await;
+    assert_eq!(ErrorCode::NetworkException.code(), error_code);
+    assert!(closed(second).await, "over max.connections.per.ip");
+
+    assert!(closed(first).await, "idle for longer than 200ms");
+    let ip = addr.ip();
+    while broker.connection_limits().count(&ip) > 0 {
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    api_versions(tokio::net::TcpStream::connect(addr).await.unwrap()).await;
+}
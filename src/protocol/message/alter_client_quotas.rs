@@ -0,0 +1,564 @@
+use std::collections::{HashMap, HashSet};
+
+use bytes::{Buf, BufMut, Bytes};
+
+use crate::{
+    metadata::{
+        metadata_value,
+        records::{self, client_quota_record, ClientQuotaRecord},
+        MetadataWriter,
+    },
+    model::ClientQuotaEntity,
+    protocol::{
+        self,
+        r#type::{Array, CompactArray, TaggedFields},
+        ErrorCode, Readable, ReadableVersion, Writable,
+    },
+    quota::{self, QuotaType},
+    security::{acl::CLUSTER_NAME, AclOperation, Authorization, ResourceType},
+};
+
+use super::{
+    alter_configs::append,
+    describe_client_quotas::{read_string, write_string, EntityData},
+};
+
+pub(crate) const API_KEY: i16 = 49;
+
+#[derive(Debug, Clone)]
+pub struct Request {
+    version: i16,
+    entries: Vec<EntryData>,
+    validate_only: bool,
+}
+impl Request {
+    fn new(version: i16, entries: Vec<EntryData>, validate_only: bool) -> Self {
+        Self {
+            version,
+            entries,
+            validate_only,
+        }
+    }
+
+    pub fn v0(entries: Vec<EntryData>, validate_only: bool) -> Self {
+        Self::new(0, entries, validate_only)
+    }
+
+    pub fn v1(entries: Vec<EntryData>, validate_only: bool) -> Self {
+        Self::new(1, entries, validate_only)
+    }
+
+    pub fn entries(&self) -> &[EntryData] {
+        &self.entries
+    }
+
+    pub fn validate_only(&self) -> bool {
+        self.validate_only
+    }
+}
+impl ReadableVersion for Request {
+    fn read_version<B: Buf>(buffer: &mut B, version: i16) -> Result<Self, protocol::Error> {
+        if !(0..=1).contains(&version) {
+            return Err(protocol::Error::UnsupportedVersion);
+        }
+
+        let entries = if version == 0 {
+            Array::<EntryData>::read_version_inner(buffer, version)
+        } else {
+            CompactArray::<EntryData>::read_version_inner(buffer, version)
+        }?
+        .ok_or(protocol::Error::IllegalArgument(
+            "non-nullable field entries was serialized as null",
+        ))?;
+        let validate_only = bool::read(buffer);
+        if version >= 1 {
+            let _tagged_fields = TaggedFields::read_result_inner(buffer)?;
+        }
+
+        Ok(Self {
+            version,
+            entries,
+            validate_only,
+        })
+    }
+}
+impl Writable for Request {
+    fn write<B: BufMut>(&self, buffer: &mut B) {
+        if self.version == 0 {
+            Array::write_inner(buffer, Some(self.entries()));
+        } else {
+            CompactArray::write_inner(buffer, Some(self.entries()));
+        }
+        self.validate_only.write(buffer);
+        if self.version >= 1 {
+            TaggedFields::write_empty(buffer);
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct EntryData {
+    version: i16,
+    entity: Vec<EntityData>,
+    ops: Vec<OpData>,
+}
+impl EntryData {
+    fn new(version: i16, entity: Vec<EntityData>, ops: Vec<OpData>) -> Self {
+        Self {
+            version,
+            entity,
+            ops,
+        }
+    }
+
+    pub fn v0(entity: Vec<EntityData>, ops: Vec<OpData>) -> Self {
+        Self::new(0, entity, ops)
+    }
+
+    pub fn v1(entity: Vec<EntityData>, ops: Vec<OpData>) -> Self {
+        Self::new(1, entity, ops)
+    }
+
+    pub fn entity(&self) -> &[EntityData] {
+        &self.entity
+    }
+
+    pub fn ops(&self) -> &[OpData] {
+        &self.ops
+    }
+}
+impl ReadableVersion for EntryData {
+    fn read_version<B: Buf>(buffer: &mut B, version: i16) -> Result<Self, protocol::Error> {
+        if !(0..=1).contains(&version) {
+            return Err(protocol::Error::UnsupportedVersion);
+        }
+
+        let entity = if version == 0 {
+            Array::<EntityData>::read_version_inner(buffer, version)
+        } else {
+            CompactArray::<EntityData>::read_version_inner(buffer, version)
+        }?
+        .ok_or(protocol::Error::IllegalArgument(
+            "non-nullable field entity was serialized as null",
+        ))?;
+        let ops = if version == 0 {
+            Array::<OpData>::read_version_inner(buffer, version)
+        } else {
+            CompactArray::<OpData>::read_version_inner(buffer, version)
+        }?
+        .ok_or(protocol::Error::IllegalArgument(
+            "non-nullable field ops was serialized as null",
+        ))?;
+        if version >= 1 {
+            let _tagged_fields = TaggedFields::read_result_inner(buffer)?;
+        }
+
+        Ok(Self {
+            version,
+            entity,
+            ops,
+        })
+    }
+}
+impl Writable for EntryData {
+    fn write<B: BufMut>(&self, buffer: &mut B) {
+        if self.version == 0 {
+            Array::write_inner(buffer, Some(self.entity()));
+            Array::write_inner(buffer, Some(self.ops()));
+        } else {
+            CompactArray::write_inner(buffer, Some(self.entity()));
+            CompactArray::write_inner(buffer, Some(self.ops()));
+            TaggedFields::write_empty(buffer);
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct OpData {
+    version: i16,
+    key: Bytes,
+    value: f64,
+    remove: bool,
+}
+impl OpData {
+    fn new(version: i16, key: &str, value: f64, remove: bool) -> Self {
+        Self {
+            version,
+            key: Bytes::copy_from_slice(key.as_bytes()),
+            value,
+            remove,
+        }
+    }
+
+    pub fn v0(key: &str, value: f64, remove: bool) -> Self {
+        Self::new(0, key, value, remove)
+    }
+
+    pub fn v1(key: &str, value: f64, remove: bool) -> Self {
+        Self::new(1, key, value, remove)
+    }
+
+    pub fn key(&self) -> &[u8] {
+        &self.key
+    }
+
+    pub fn value(&self) -> f64 {
+        self.value
+    }
+
+    pub fn remove(&self) -> bool {
+        self.remove
+    }
+}
+impl ReadableVersion for OpData {
+    fn read_version<B: Buf>(buffer: &mut B, version: i16) -> Result<Self, protocol::Error> {
+        if !(0..=1).contains(&version) {
+            return Err(protocol::Error::UnsupportedVersion);
+        }
+
+        let key = read_string(buffer, version)?.ok_or(protocol::Error::IllegalArgument(
+            "non-nullable field key was serialized as null",
+        ))?;
+        let value = f64::read(buffer);
+        let remove = bool::read(buffer);
+        if version >= 1 {
+            let _tagged_fields = TaggedFields::read_result_inner(buffer)?;
+        }
+
+        Ok(Self {
+            version,
+            key,
+            value,
+            remove,
+        })
+    }
+}
+impl Writable for OpData {
+    fn write<B: BufMut>(&self, buffer: &mut B) {
+        write_string(buffer, self.version, Some(self.key()));
+        self.value.write(buffer);
+        self.remove.write(buffer);
+        if self.version >= 1 {
+            TaggedFields::write_empty(buffer);
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Response {
+    version: i16,
+    throttle_time_ms: i32,
+    entries: Vec<AlterClientQuotasResponseEntry>,
+}
+impl Response {
+    fn new(
+        version: i16,
+        throttle_time_ms: i32,
+        entries: Vec<AlterClientQuotasResponseEntry>,
+    ) -> Self {
+        Self {
+            version,
+            throttle_time_ms,
+            entries,
+        }
+    }
+
+    pub fn v0(throttle_time_ms: i32, entries: Vec<AlterClientQuotasResponseEntry>) -> Self {
+        Self::new(0, throttle_time_ms, entries)
+    }
+
+    pub fn v1(throttle_time_ms: i32, entries: Vec<AlterClientQuotasResponseEntry>) -> Self {
+        Self::new(1, throttle_time_ms, entries)
+    }
+
+    pub fn throttle_time_ms(&self) -> i32 {
+        self.throttle_time_ms
+    }
+
+    pub fn set_throttle_time_ms(&mut self, throttle_time_ms: i32) {
+        self.throttle_time_ms = throttle_time_ms;
+    }
+
+    pub fn entries(&self) -> &[AlterClientQuotasResponseEntry] {
+        &self.entries
+    }
+}
+impl ReadableVersion for Response {
+    fn read_version<B: Buf>(buffer: &mut B, version: i16) -> Result<Self, protocol::Error> {
+        if !(0..=1).contains(&version) {
+            return Err(protocol::Error::UnsupportedVersion);
+        }
+
+        let throttle_time_ms = i32::read(buffer);
+        let entries = if version == 0 {
+            Array::<AlterClientQuotasResponseEntry>::read_version_inner(buffer, version)
+        } else {
+            CompactArray::<AlterClientQuotasResponseEntry>::read_version_inner(buffer, version)
+        }?
+        .ok_or(protocol::Error::IllegalArgument(
+            "non-nullable field entries was serialized as null",
+        ))?;
+        if version >= 1 {
+            let _tagged_fields = TaggedFields::read_result_inner(buffer)?;
+        }
+
+        Ok(Self {
+            version,
+            throttle_time_ms,
+            entries,
+        })
+    }
+}
+impl Writable for Response {
+    fn write<B: BufMut>(&self, buffer: &mut B) {
+        self.throttle_time_ms.write(buffer);
+        if self.version == 0 {
+            Array::write_inner(buffer, Some(self.entries()));
+        } else {
+            CompactArray::write_inner(buffer, Some(self.entries()));
+            TaggedFields::write_empty(buffer);
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct AlterClientQuotasResponseEntry {
+    version: i16,
+    error_code: ErrorCode,
+    error_message: Option<Bytes>,
+    entity: Vec<EntityData>,
+}
+impl AlterClientQuotasResponseEntry {
+    fn new(
+        version: i16,
+        error_code: ErrorCode,
+        error_message: Option<&str>,
+        entity: Vec<EntityData>,
+    ) -> Self {
+        Self {
+            version,
+            error_code,
+            error_message: error_message.map(|m| Bytes::copy_from_slice(m.as_bytes())),
+            entity,
+        }
+    }
+
+    pub fn v0(error_code: ErrorCode, error_message: Option<&str>, entity: Vec<EntityData>) -> Self {
+        Self::new(0, error_code, error_message, entity)
+    }
+
+    pub fn v1(error_code: ErrorCode, error_message: Option<&str>, entity: Vec<EntityData>) -> Self {
+        Self::new(1, error_code, error_message, entity)
+    }
+
+    pub fn error_code(&self) -> ErrorCode {
+        self.error_code
+    }
+
+    pub fn error_message(&self) -> Option<&[u8]> {
+        self.error_message.as_deref()
+    }
+
+    pub fn entity(&self) -> &[EntityData] {
+        &self.entity
+    }
+}
+impl ReadableVersion for AlterClientQuotasResponseEntry {
+    fn read_version<B: Buf>(buffer: &mut B, version: i16) -> Result<Self, protocol::Error> {
+        if !(0..=1).contains(&version) {
+            return Err(protocol::Error::UnsupportedVersion);
+        }
+
+        let error_code = ErrorCode::read(buffer);
+        let error_message = read_string(buffer, version)?;
+        let entity = if version == 0 {
+            Array::<EntityData>::read_version_inner(buffer, version)
+        } else {
+            CompactArray::<EntityData>::read_version_inner(buffer, version)
+        }?
+        .ok_or(protocol::Error::IllegalArgument(
+            "non-nullable field entity was serialized as null",
+        ))?;
+        if version >= 1 {
+            let _tagged_fields = TaggedFields::read_result_inner(buffer)?;
+        }
+
+        Ok(Self {
+            version,
+            error_code,
+            error_message,
+            entity,
+        })
+    }
+}
+impl Writable for AlterClientQuotasResponseEntry {
+    fn write<B: BufMut>(&self, buffer: &mut B) {
+        self.error_code.write(buffer);
+        write_string(buffer, self.version, self.error_message());
+        if self.version == 0 {
+            Array::write_inner(buffer, Some(self.entity()));
+        } else {
+            CompactArray::write_inner(buffer, Some(self.entity()));
+            TaggedFields::write_empty(buffer);
+        }
+    }
+}
+
+// Entries are independent: an invalid one is rejected on its own while the
+// valid ones are written together.
+pub fn process_request(
+    request: Request,
+    client_quotas: &HashMap<ClientQuotaEntity, HashMap<String, f64>>,
+    writer: &MetadataWriter,
+    auth: &Authorization,
+) -> Result<Response, protocol::Error> {
+    let version = request.version;
+    let authorized = auth.authorize(
+        AclOperation::AlterConfigs,
+        ResourceType::Cluster,
+        CLUSTER_NAME,
+    );
+
+    let mut results = Vec::with_capacity(request.entries().len());
+    let mut records = Vec::new();
+    for entry in request.entries() {
+        let entity = entry
+            .entity()
+            .iter()
+            .map(|entity| {
+                EntityData::new(
+                    version,
+                    &String::from_utf8_lossy(entity.entity_type()),
+                    entity.entity_name().map(String::from_utf8_lossy).as_deref(),
+                )
+            })
+            .collect::<Vec<_>>();
+        if !authorized {
+            results.push((
+                entity,
+                Some((
+                    ErrorCode::ClusterAuthorizationFailed,
+                    ErrorCode::ClusterAuthorizationFailed.message(),
+                )),
+            ));
+            continue;
+        }
+        match validate(entry, client_quotas) {
+            Ok(entry_records) => {
+                records.extend(entry_records);
+                results.push((entity, None));
+            }
+            Err(message) => results.push((entity, Some((ErrorCode::InvalidRequest, message)))),
+        }
+    }
+
+    let outcome = if request.validate_only() {
+        Ok(())
+    } else {
+        append(writer, records)
+    };
+    let entries = results
+        .into_iter()
+        .map(|(entity, error)| match (error, &outcome) {
+            (Some((error_code, message)), _) => {
+                AlterClientQuotasResponseEntry::new(version, error_code, Some(message), entity)
+            }
+            (None, Ok(())) => {
+                AlterClientQuotasResponseEntry::new(version, ErrorCode::None, None, entity)
+            }
+            (None, Err((error_code, message))) => {
+                AlterClientQuotasResponseEntry::new(version, *error_code, Some(message), entity)
+            }
+        })
+        .collect();
+
+    Ok(Response::new(version, 0, entries))
+}
+
+fn validate(
+    entry: &EntryData,
+    client_quotas: &HashMap<ClientQuotaEntity, HashMap<String, f64>>,
+) -> Result<Vec<Bytes>, &'static str> {
+    let mut entity = ClientQuotaEntity::new();
+    for data in entry.entity() {
+        let entity_type =
+            std::str::from_utf8(data.entity_type()).map_err(|_| "Invalid entity type")?;
+        let entity_name = data
+            .entity_name()
+            .map(std::str::from_utf8)
+            .transpose()
+            .map_err(|_| "Invalid entity name")?;
+        if !quota::ENTITY_TYPES.contains(&entity_type) {
+            return Err("Custom entity types are not supported");
+        }
+        if entity_name == Some("") {
+            return Err("Entity names must not be empty");
+        }
+        if entity
+            .insert(entity_type.to_string(), entity_name.map(str::to_string))
+            .is_some()
+        {
+            return Err("Duplicate entity type");
+        }
+    }
+    if entity.is_empty() {
+        return Err("Invalid empty client quota entity");
+    }
+    let is_ip = entity.contains_key(quota::IP);
+    if is_ip && entity.len() > 1 {
+        return Err("IP quotas cannot be combined with user or client-id quotas");
+    }
+
+    let existing = client_quotas.get(&entity);
+    let mut keys = HashSet::new();
+    let mut records = Vec::with_capacity(entry.ops().len());
+    for op in entry.ops() {
+        let key = std::str::from_utf8(op.key()).map_err(|_| "Invalid quota key")?;
+        let valid_key = if is_ip {
+            key == quota::CONNECTION_CREATION_RATE
+        } else {
+            QuotaType::from_key(key).is_some()
+        };
+        if !valid_key {
+            return Err("Invalid quota key for this entity");
+        }
+        if !keys.insert(key) {
+            return Err("Duplicate quota key");
+        }
+        if op.remove() {
+            if !existing.is_some_and(|quotas| quotas.contains_key(key)) {
+                continue;
+            }
+        } else if !op.value().is_finite() || op.value() <= 0.0 {
+            return Err("Quota values must be positive");
+        } else if key != QuotaType::Request.key() && op.value().fract() != 0.0 {
+            return Err("Rate quotas must be whole numbers");
+        }
+
+        let record = ClientQuotaRecord::new(
+            entity
+                .iter()
+                .map(|(entity_type, name)| records::EntityData::new(entity_type, name.as_deref()))
+                .collect(),
+            key,
+            if op.remove() { 0.0 } else { op.value() },
+            op.remove(),
+        );
+        records.push(metadata_value(client_quota_record::API_KEY, 0, &record));
+    }
+    Ok(records)
+}
+
+pub(crate) fn error_response(version: i16, error_code: ErrorCode) -> Response {
+    let version = version.clamp(0, 1);
+    Response::new(
+        version,
+        0,
+        vec![AlterClientQuotasResponseEntry::new(
+            version,
+            error_code,
+            Some(error_code.message()),
+            vec![],
+        )],
+    )
+}
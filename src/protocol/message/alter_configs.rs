@@ -0,0 +1,648 @@
+use std::collections::HashMap;
+
+use bytes::{Buf, BufMut, Bytes};
+use uuid::Uuid;
+
+use crate::{
+    config::dynamic::{self, ConfigDef, ConfigResolver},
+    metadata::{
+        metadata_value,
+        records::{config_record, ConfigRecord},
+        MetadataWriter,
+    },
+    model::{self, ConfigResource},
+    protocol::{
+        self,
+        r#type::{Array, CompactArray, CompactKafkaString, KafkaString, TaggedFields},
+        ErrorCode, Readable, ReadableVersion, Writable,
+    },
+    security::{acl::CLUSTER_NAME, AclOperation, Authorization, ResourceType},
+};
+
+use super::topic_by_name;
+
+pub(crate) const API_KEY: i16 = 33;
+
+#[derive(Debug, Clone)]
+pub struct Request {
+    version: i16,
+    resources: Vec<AlterConfigsResource>,
+    validate_only: bool,
+}
+impl Request {
+    fn new(version: i16, resources: Vec<AlterConfigsResource>, validate_only: bool) -> Self {
+        Self {
+            version,
+            resources,
+            validate_only,
+        }
+    }
+
+    pub fn v0(resources: Vec<AlterConfigsResource>, validate_only: bool) -> Self {
+        Self::new(0, resources, validate_only)
+    }
+
+    pub fn v1(resources: Vec<AlterConfigsResource>, validate_only: bool) -> Self {
+        Self::new(1, resources, validate_only)
+    }
+
+    pub fn v2(resources: Vec<AlterConfigsResource>, validate_only: bool) -> Self {
+        Self::new(2, resources, validate_only)
+    }
+
+    pub fn resources(&self) -> &[AlterConfigsResource] {
+        &self.resources
+    }
+
+    pub fn validate_only(&self) -> bool {
+        self.validate_only
+    }
+}
+impl ReadableVersion for Request {
+    fn read_version<B: Buf>(buffer: &mut B, version: i16) -> Result<Self, protocol::Error> {
+        if !(0..=2).contains(&version) {
+            return Err(protocol::Error::UnsupportedVersion);
+        }
+
+        let resources = if version <= 1 {
+            Array::<AlterConfigsResource>::read_version_inner(buffer, version)
+        } else {
+            CompactArray::<AlterConfigsResource>::read_version_inner(buffer, version)
+        }?
+        .ok_or(protocol::Error::IllegalArgument(
+            "non-nullable field resources was serialized as null",
+        ))?;
+        let validate_only = bool::read(buffer);
+        if version >= 2 {
+            let _tagged_fields = TaggedFields::read_result_inner(buffer)?;
+        }
+
+        Ok(Self {
+            version,
+            resources,
+            validate_only,
+        })
+    }
+}
+impl Writable for Request {
+    fn write<B: BufMut>(&self, buffer: &mut B) {
+        if self.version <= 1 {
+            Array::write_inner(buffer, Some(self.resources()));
+            self.validate_only.write(buffer);
+        } else {
+            CompactArray::write_inner(buffer, Some(self.resources()));
+            self.validate_only.write(buffer);
+            TaggedFields::write_empty(buffer);
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct AlterConfigsResource {
+    version: i16,
+    resource_type: i8,
+    resource_name: Bytes,
+    configs: Vec<AlterableConfig>,
+}
+impl AlterConfigsResource {
+    fn new(
+        version: i16,
+        resource_type: i8,
+        resource_name: &str,
+        configs: Vec<AlterableConfig>,
+    ) -> Self {
+        Self {
+            version,
+            resource_type,
+            resource_name: Bytes::copy_from_slice(resource_name.as_bytes()),
+            configs,
+        }
+    }
+
+    pub fn v0(resource_type: i8, resource_name: &str, configs: Vec<AlterableConfig>) -> Self {
+        Self::new(0, resource_type, resource_name, configs)
+    }
+
+    pub fn v1(resource_type: i8, resource_name: &str, configs: Vec<AlterableConfig>) -> Self {
+        Self::new(1, resource_type, resource_name, configs)
+    }
+
+    pub fn v2(resource_type: i8, resource_name: &str, configs: Vec<AlterableConfig>) -> Self {
+        Self::new(2, resource_type, resource_name, configs)
+    }
+
+    pub fn resource_type(&self) -> i8 {
+        self.resource_type
+    }
+
+    pub fn resource_name(&self) -> &[u8] {
+        &self.resource_name
+    }
+
+    pub fn configs(&self) -> &[AlterableConfig] {
+        &self.configs
+    }
+}
+impl ReadableVersion for AlterConfigsResource {
+    fn read_version<B: Buf>(buffer: &mut B, version: i16) -> Result<Self, protocol::Error> {
+        if !(0..=2).contains(&version) {
+            return Err(protocol::Error::UnsupportedVersion);
+        }
+
+        let resource_type = i8::read(buffer);
+        let resource_name = if version <= 1 {
+            KafkaString::read_inner(buffer)
+        } else {
+            CompactKafkaString::read_result_inner(buffer)?
+        }
+        .ok_or(protocol::Error::IllegalArgument(
+            "non-nullable field resourceName was serialized as null",
+        ))?;
+        let configs = if version <= 1 {
+            Array::<AlterableConfig>::read_version_inner(buffer, version)
+        } else {
+            CompactArray::<AlterableConfig>::read_version_inner(buffer, version)
+        }?
+        .ok_or(protocol::Error::IllegalArgument(
+            "non-nullable field configs was serialized as null",
+        ))?;
+        if version >= 2 {
+            let _tagged_fields = TaggedFields::read_result_inner(buffer)?;
+        }
+
+        Ok(Self {
+            version,
+            resource_type,
+            resource_name,
+            configs,
+        })
+    }
+}
+impl Writable for AlterConfigsResource {
+    fn write<B: BufMut>(&self, buffer: &mut B) {
+        self.resource_type.write(buffer);
+        if self.version <= 1 {
+            KafkaString::write_inner(buffer, Some(self.resource_name()));
+            Array::write_inner(buffer, Some(self.configs()));
+        } else {
+            CompactKafkaString::write_inner(buffer, Some(self.resource_name()));
+            CompactArray::write_inner(buffer, Some(self.configs()));
+            TaggedFields::write_empty(buffer);
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct AlterableConfig {
+    version: i16,
+    name: Bytes,
+    value: Option<Bytes>,
+}
+impl AlterableConfig {
+    fn new(version: i16, name: &str, value: Option<&str>) -> Self {
+        Self {
+            version,
+            name: Bytes::copy_from_slice(name.as_bytes()),
+            value: value.map(|v| Bytes::copy_from_slice(v.as_bytes())),
+        }
+    }
+
+    pub fn v0(name: &str, value: Option<&str>) -> Self {
+        Self::new(0, name, value)
+    }
+
+    pub fn v1(name: &str, value: Option<&str>) -> Self {
+        Self::new(1, name, value)
+    }
+
+    pub fn v2(name: &str, value: Option<&str>) -> Self {
+        Self::new(2, name, value)
+    }
+
+    pub fn name(&self) -> &[u8] {
+        &self.name
+    }
+
+    pub fn value(&self) -> Option<&[u8]> {
+        self.value.as_deref()
+    }
+}
+impl ReadableVersion for AlterableConfig {
+    fn read_version<B: Buf>(buffer: &mut B, version: i16) -> Result<Self, protocol::Error> {
+        if !(0..=2).contains(&version) {
+            return Err(protocol::Error::UnsupportedVersion);
+        }
+
+        let (name, value) = if version <= 1 {
+            (
+                KafkaString::read_inner(buffer),
+                KafkaString::read_inner(buffer),
+            )
+        } else {
+            (
+                CompactKafkaString::read_result_inner(buffer)?,
+                CompactKafkaString::read_result_inner(buffer)?,
+            )
+        };
+        let name = name.ok_or(protocol::Error::IllegalArgument(
+            "non-nullable field name was serialized as null",
+        ))?;
+        if version >= 2 {
+            let _tagged_fields = TaggedFields::read_result_inner(buffer)?;
+        }
+
+        Ok(Self {
+            version,
+            name,
+            value,
+        })
+    }
+}
+impl Writable for AlterableConfig {
+    fn write<B: BufMut>(&self, buffer: &mut B) {
+        if self.version <= 1 {
+            KafkaString::write_inner(buffer, Some(self.name()));
+            KafkaString::write_inner(buffer, self.value());
+        } else {
+            CompactKafkaString::write_inner(buffer, Some(self.name()));
+            CompactKafkaString::write_inner(buffer, self.value());
+            TaggedFields::write_empty(buffer);
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Response {
+    version: i16,
+    throttle_time_ms: i32,
+    responses: Vec<AlterConfigsResourceResponse>,
+}
+impl Response {
+    fn new(
+        version: i16,
+        throttle_time_ms: i32,
+        responses: Vec<AlterConfigsResourceResponse>,
+    ) -> Self {
+        Self {
+            version,
+            throttle_time_ms,
+            responses,
+        }
+    }
+
+    pub fn v0(throttle_time_ms: i32, responses: Vec<AlterConfigsResourceResponse>) -> Self {
+        Self::new(0, throttle_time_ms, responses)
+    }
+
+    pub fn v1(throttle_time_ms: i32, responses: Vec<AlterConfigsResourceResponse>) -> Self {
+        Self::new(1, throttle_time_ms, responses)
+    }
+
+    pub fn v2(throttle_time_ms: i32, responses: Vec<AlterConfigsResourceResponse>) -> Self {
+        Self::new(2, throttle_time_ms, responses)
+    }
+
+    pub fn throttle_time_ms(&self) -> i32 {
+        self.throttle_time_ms
+    }
+
+    pub fn set_throttle_time_ms(&mut self, throttle_time_ms: i32) {
+        self.throttle_time_ms = throttle_time_ms;
+    }
+
+    pub fn responses(&self) -> &[AlterConfigsResourceResponse] {
+        &self.responses
+    }
+}
+impl ReadableVersion for Response {
+    fn read_version<B: Buf>(buffer: &mut B, version: i16) -> Result<Self, protocol::Error> {
+        if !(0..=2).contains(&version) {
+            return Err(protocol::Error::UnsupportedVersion);
+        }
+
+        let throttle_time_ms = i32::read(buffer);
+        let responses = if version <= 1 {
+            Array::<AlterConfigsResourceResponse>::read_version_inner(buffer, version)
+        } else {
+            CompactArray::<AlterConfigsResourceResponse>::read_version_inner(buffer, version)
+        }?
+        .ok_or(protocol::Error::IllegalArgument(
+            "non-nullable field responses was serialized as null",
+        ))?;
+        if version >= 2 {
+            let _tagged_fields = TaggedFields::read_result_inner(buffer)?;
+        }
+
+        Ok(Self {
+            version,
+            throttle_time_ms,
+            responses,
+        })
+    }
+}
+impl Writable for Response {
+    fn write<B: BufMut>(&self, buffer: &mut B) {
+        self.throttle_time_ms.write(buffer);
+        if self.version <= 1 {
+            Array::write_inner(buffer, Some(self.responses()));
+        } else {
+            CompactArray::write_inner(buffer, Some(self.responses()));
+            TaggedFields::write_empty(buffer);
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct AlterConfigsResourceResponse {
+    version: i16,
+    error_code: ErrorCode,
+    error_message: Option<Bytes>,
+    resource_type: i8,
+    resource_name: Bytes,
+}
+impl AlterConfigsResourceResponse {
+    fn new(
+        version: i16,
+        error_code: ErrorCode,
+        error_message: Option<&str>,
+        resource_type: i8,
+        resource_name: &str,
+    ) -> Self {
+        Self {
+            version,
+            error_code,
+            error_message: error_message.map(|m| Bytes::copy_from_slice(m.as_bytes())),
+            resource_type,
+            resource_name: Bytes::copy_from_slice(resource_name.as_bytes()),
+        }
+    }
+
+    pub fn v0(
+        error_code: ErrorCode,
+        error_message: Option<&str>,
+        resource_type: i8,
+        resource_name: &str,
+    ) -> Self {
+        Self::new(0, error_code, error_message, resource_type, resource_name)
+    }
+
+    pub fn v1(
+        error_code: ErrorCode,
+        error_message: Option<&str>,
+        resource_type: i8,
+        resource_name: &str,
+    ) -> Self {
+        Self::new(1, error_code, error_message, resource_type, resource_name)
+    }
+
+    pub fn v2(
+        error_code: ErrorCode,
+        error_message: Option<&str>,
+        resource_type: i8,
+        resource_name: &str,
+    ) -> Self {
+        Self::new(2, error_code, error_message, resource_type, resource_name)
+    }
+
+    pub fn error_code(&self) -> ErrorCode {
+        self.error_code
+    }
+
+    pub fn error_message(&self) -> Option<&[u8]> {
+        self.error_message.as_deref()
+    }
+
+    pub fn resource_type(&self) -> i8 {
+        self.resource_type
+    }
+
+    pub fn resource_name(&self) -> &[u8] {
+        &self.resource_name
+    }
+}
+impl ReadableVersion for AlterConfigsResourceResponse {
+    fn read_version<B: Buf>(buffer: &mut B, version: i16) -> Result<Self, protocol::Error> {
+        if !(0..=2).contains(&version) {
+            return Err(protocol::Error::UnsupportedVersion);
+        }
+
+        let error_code = ErrorCode::read(buffer);
+        let error_message = if version <= 1 {
+            KafkaString::read_inner(buffer)
+        } else {
+            CompactKafkaString::read_result_inner(buffer)?
+        };
+        let resource_type = i8::read(buffer);
+        let resource_name = if version <= 1 {
+            KafkaString::read_inner(buffer)
+        } else {
+            CompactKafkaString::read_result_inner(buffer)?
+        }
+        .ok_or(protocol::Error::IllegalArgument(
+            "non-nullable field resourceName was serialized as null",
+        ))?;
+        if version >= 2 {
+            let _tagged_fields = TaggedFields::read_result_inner(buffer)?;
+        }
+
+        Ok(Self {
+            version,
+            error_code,
+            error_message,
+            resource_type,
+            resource_name,
+        })
+    }
+}
+impl Writable for AlterConfigsResourceResponse {
+    fn write<B: BufMut>(&self, buffer: &mut B) {
+        self.error_code.write(buffer);
+        if self.version <= 1 {
+            KafkaString::write_inner(buffer, self.error_message());
+            self.resource_type.write(buffer);
+            KafkaString::write_inner(buffer, Some(self.resource_name()));
+        } else {
+            CompactKafkaString::write_inner(buffer, self.error_message());
+            self.resource_type.write(buffer);
+            CompactKafkaString::write_inner(buffer, Some(self.resource_name()));
+            TaggedFields::write_empty(buffer);
+        }
+    }
+}
+
+// Replaces the whole dynamic config of each resource: keys the request leaves
+// out are deleted.
+pub fn process_request(
+    request: Request,
+    metadata: &HashMap<Uuid, model::Topic>,
+    resolver: &ConfigResolver,
+    writer: &MetadataWriter,
+    node_id: i32,
+    auth: &Authorization,
+) -> Result<Response, protocol::Error> {
+    let version = request.version;
+    let mut responses = Vec::with_capacity(request.resources().len());
+    for resource in request.resources() {
+        let name = std::str::from_utf8(resource.resource_name())?;
+        let mut changes = Vec::with_capacity(resource.configs().len());
+        for config in resource.configs() {
+            let value = config.value().map(std::str::from_utf8).transpose()?;
+            changes.push((std::str::from_utf8(config.name())?, value));
+        }
+
+        let result = validate_resource(resource.resource_type(), name, metadata, node_id, auth)
+            .and_then(|config_resource| {
+                let mut records = Vec::with_capacity(changes.len());
+                for (key, value) in changes.iter() {
+                    let def = config_def(&config_resource, key)?;
+                    let Some(value) = value else {
+                        return Err((
+                            ErrorCode::InvalidRequest,
+                            format!("Null value not supported for: {}", key),
+                        ));
+                    };
+                    def.validate(value)
+                        .map_err(|message| (ErrorCode::InvalidConfig, message))?;
+                    records.push(config_record(&config_resource, key, Some(value)));
+                }
+                if let Some(current) = resolver.dynamic_configs(&config_resource) {
+                    let mut removed = current
+                        .keys()
+                        .filter(|key| !changes.iter().any(|(name, _)| name == key))
+                        .collect::<Vec<_>>();
+                    removed.sort();
+                    for key in removed {
+                        records.push(config_record(&config_resource, key, None));
+                    }
+                }
+                if !request.validate_only() {
+                    append(writer, records)?;
+                }
+                Ok(())
+            });
+
+        let (error_code, message) = match result {
+            Ok(()) => (ErrorCode::None, None),
+            Err((error_code, message)) => (error_code, Some(message)),
+        };
+        responses.push(AlterConfigsResourceResponse::new(
+            version,
+            error_code,
+            message.as_deref(),
+            resource.resource_type(),
+            name,
+        ));
+    }
+
+    Ok(Response::new(version, 0, responses))
+}
+
+// The key the resource's dynamic configs are stored under, if the resource
+// exists and the session may alter it. Broker configs can be set for this
+// broker or, with an empty name, for all brokers.
+pub(crate) fn validate_resource(
+    resource_type: i8,
+    name: &str,
+    metadata: &HashMap<Uuid, model::Topic>,
+    node_id: i32,
+    auth: &Authorization,
+) -> Result<ConfigResource, (ErrorCode, String)> {
+    match resource_type {
+        ConfigResource::TOPIC
+            if !auth.authorize(AclOperation::AlterConfigs, ResourceType::Topic, name) =>
+        {
+            Err((
+                ErrorCode::TopicAuthorizationFailed,
+                ErrorCode::TopicAuthorizationFailed.message().to_string(),
+            ))
+        }
+        ConfigResource::BROKER
+            if !auth.authorize(
+                AclOperation::AlterConfigs,
+                ResourceType::Cluster,
+                CLUSTER_NAME,
+            ) =>
+        {
+            Err((
+                ErrorCode::ClusterAuthorizationFailed,
+                ErrorCode::ClusterAuthorizationFailed.message().to_string(),
+            ))
+        }
+        ConfigResource::TOPIC if topic_by_name(metadata, name).is_some() => {
+            Ok(ConfigResource::new(resource_type, name))
+        }
+        ConfigResource::TOPIC => Err((
+            ErrorCode::UnknownTopicOrPartition,
+            format!("Topic {} does not exist.", name),
+        )),
+        ConfigResource::BROKER if name.is_empty() || name == node_id.to_string() => {
+            Ok(ConfigResource::new(resource_type, name))
+        }
+        ConfigResource::BROKER => Err((
+            ErrorCode::InvalidRequest,
+            format!(
+                "Unexpected broker id, expected {} or empty string, but received {}",
+                node_id, name
+            ),
+        )),
+        resource_type => Err((
+            ErrorCode::InvalidRequest,
+            format!("Unsupported resource type {}", resource_type),
+        )),
+    }
+}
+
+pub(crate) fn config_def(
+    resource: &ConfigResource,
+    name: &str,
+) -> Result<&'static ConfigDef, (ErrorCode, String)> {
+    if resource.resource_type() == ConfigResource::TOPIC {
+        return dynamic::topic_config(name).ok_or_else(|| {
+            (
+                ErrorCode::InvalidConfig,
+                format!("Unknown topic config name: {}", name),
+            )
+        });
+    }
+
+    match dynamic::broker_config(name) {
+        Some(def) if dynamic::is_dynamic_broker_config(name) => Ok(def),
+        Some(_) => Err((
+            ErrorCode::InvalidConfig,
+            format!("Cannot update these configs dynamically: {}", name),
+        )),
+        None => Err((
+            ErrorCode::InvalidConfig,
+            format!("Unknown broker config name: {}", name),
+        )),
+    }
+}
+
+pub(crate) fn config_record(resource: &ConfigResource, name: &str, value: Option<&str>) -> Bytes {
+    let record = ConfigRecord::new(resource.resource_type(), resource.name(), name, value);
+    metadata_value(config_record::API_KEY, 0, &record)
+}
+
+pub(crate) fn append(
+    writer: &MetadataWriter,
+    records: Vec<Bytes>,
+) -> Result<(), (ErrorCode, String)> {
+    if records.is_empty() {
+        return Ok(());
+    }
+    writer
+        .append(records)
+        .map(|_| ())
+        .map_err(|err| (ErrorCode::from(&err), err.to_string()))
+}
+
+pub(crate) fn error_response(version: i16, error_code: ErrorCode) -> Response {
+    let version = version.clamp(0, 2);
+    Response::new(
+        version,
+        0,
+        vec![AlterConfigsResourceResponse::new(
+            version, error_code, None, 0, "",
+        )],
+    )
+}
@@ -0,0 +1,459 @@
+use std::collections::HashMap;
+
+use bytes::{Buf, BufMut, Bytes};
+
+use crate::{
+    metadata::{
+        metadata_value,
+        records::{
+            remove_user_scram_credential_record, user_scram_credential_record,
+            RemoveUserScramCredentialRecord, UserScramCredentialRecord,
+        },
+        MetadataWriter,
+    },
+    model::ScramCredential,
+    protocol::{
+        self,
+        r#type::{CompactArray, CompactKafkaString, TaggedFields},
+        ErrorCode, Readable, ReadableResult, ReadableVersion, Writable,
+    },
+    security::{
+        acl::CLUSTER_NAME,
+        sasl::{SCRAM_MAX_ITERATIONS, SCRAM_MIN_ITERATIONS},
+        AclOperation, Authorization, ResourceType, ScramMechanism,
+    },
+};
+
+use super::alter_configs::append;
+
+pub(crate) const API_KEY: i16 = 51;
+
+#[derive(Debug, Clone)]
+pub struct Request {
+    deletions: Vec<ScramCredentialDeletion>,
+    upsertions: Vec<ScramCredentialUpsertion>,
+}
+impl Request {
+    pub fn v0(
+        deletions: Vec<ScramCredentialDeletion>,
+        upsertions: Vec<ScramCredentialUpsertion>,
+    ) -> Self {
+        Self {
+            deletions,
+            upsertions,
+        }
+    }
+
+    pub fn deletions(&self) -> &[ScramCredentialDeletion] {
+        &self.deletions
+    }
+
+    pub fn upsertions(&self) -> &[ScramCredentialUpsertion] {
+        &self.upsertions
+    }
+}
+impl ReadableVersion for Request {
+    fn read_version<B: Buf>(buffer: &mut B, version: i16) -> Result<Self, protocol::Error> {
+        if version != 0 {
+            return Err(protocol::Error::UnsupportedVersion);
+        }
+
+        let deletions = CompactArray::<ScramCredentialDeletion>::read_result_inner(buffer)?.ok_or(
+            protocol::Error::IllegalArgument("non-nullable field deletions was serialized as null"),
+        )?;
+        let upsertions = CompactArray::<ScramCredentialUpsertion>::read_result_inner(buffer)?
+            .ok_or(protocol::Error::IllegalArgument(
+                "non-nullable field upsertions was serialized as null",
+            ))?;
+        let _tagged_fields = TaggedFields::read_result_inner(buffer)?;
+
+        Ok(Self {
+            deletions,
+            upsertions,
+        })
+    }
+}
+impl Writable for Request {
+    fn write<B: BufMut>(&self, buffer: &mut B) {
+        CompactArray::write_inner(buffer, Some(self.deletions()));
+        CompactArray::write_inner(buffer, Some(self.upsertions()));
+        TaggedFields::write_empty(buffer);
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ScramCredentialDeletion {
+    name: Bytes,
+    mechanism: i8,
+}
+impl ScramCredentialDeletion {
+    pub fn v0(name: &str, mechanism: i8) -> Self {
+        Self {
+            name: Bytes::copy_from_slice(name.as_bytes()),
+            mechanism,
+        }
+    }
+
+    pub fn name(&self) -> &[u8] {
+        &self.name
+    }
+
+    pub fn mechanism(&self) -> i8 {
+        self.mechanism
+    }
+}
+impl ReadableResult for ScramCredentialDeletion {
+    fn read_result<B: Buf>(buffer: &mut B) -> Result<Self, protocol::Error> {
+        let name = CompactKafkaString::read_result_inner(buffer)?.ok_or(
+            protocol::Error::IllegalArgument("non-nullable field name was serialized as null"),
+        )?;
+        let mechanism = i8::read(buffer);
+        let _tagged_fields = TaggedFields::read_result_inner(buffer)?;
+
+        Ok(Self { name, mechanism })
+    }
+}
+impl Writable for ScramCredentialDeletion {
+    fn write<B: BufMut>(&self, buffer: &mut B) {
+        CompactKafkaString::write_inner(buffer, Some(self.name()));
+        self.mechanism.write(buffer);
+        TaggedFields::write_empty(buffer);
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ScramCredentialUpsertion {
+    name: Bytes,
+    mechanism: i8,
+    iterations: i32,
+    salt: Bytes,
+    salted_password: Bytes,
+}
+impl ScramCredentialUpsertion {
+    pub fn v0(
+        name: &str,
+        mechanism: i8,
+        iterations: i32,
+        salt: &[u8],
+        salted_password: &[u8],
+    ) -> Self {
+        Self {
+            name: Bytes::copy_from_slice(name.as_bytes()),
+            mechanism,
+            iterations,
+            salt: Bytes::copy_from_slice(salt),
+            salted_password: Bytes::copy_from_slice(salted_password),
+        }
+    }
+
+    pub fn name(&self) -> &[u8] {
+        &self.name
+    }
+
+    pub fn mechanism(&self) -> i8 {
+        self.mechanism
+    }
+
+    pub fn iterations(&self) -> i32 {
+        self.iterations
+    }
+
+    pub fn salt(&self) -> &[u8] {
+        &self.salt
+    }
+
+    pub fn salted_password(&self) -> &[u8] {
+        &self.salted_password
+    }
+}
+impl ReadableResult for ScramCredentialUpsertion {
+    fn read_result<B: Buf>(buffer: &mut B) -> Result<Self, protocol::Error> {
+        let name = CompactKafkaString::read_result_inner(buffer)?.ok_or(
+            protocol::Error::IllegalArgument("non-nullable field name was serialized as null"),
+        )?;
+        let mechanism = i8::read(buffer);
+        let iterations = i32::read(buffer);
+        let salt = CompactKafkaString::read_result_inner(buffer)?.ok_or(
+            protocol::Error::IllegalArgument("non-nullable field salt was serialized as null"),
+        )?;
+        let salted_password = CompactKafkaString::read_result_inner(buffer)?.ok_or(
+            protocol::Error::IllegalArgument(
+                "non-nullable field saltedPassword was serialized as null",
+            ),
+        )?;
+        let _tagged_fields = TaggedFields::read_result_inner(buffer)?;
+
+        Ok(Self {
+            name,
+            mechanism,
+            iterations,
+            salt,
+            salted_password,
+        })
+    }
+}
+impl Writable for ScramCredentialUpsertion {
+    fn write<B: BufMut>(&self, buffer: &mut B) {
+        CompactKafkaString::write_inner(buffer, Some(self.name()));
+        self.mechanism.write(buffer);
+        self.iterations.write(buffer);
+        CompactKafkaString::write_inner(buffer, Some(self.salt()));
+        CompactKafkaString::write_inner(buffer, Some(self.salted_password()));
+        TaggedFields::write_empty(buffer);
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Response {
+    throttle_time_ms: i32,
+    results: Vec<AlterUserScramCredentialsResult>,
+}
+impl Response {
+    pub fn v0(throttle_time_ms: i32, results: Vec<AlterUserScramCredentialsResult>) -> Self {
+        Self {
+            throttle_time_ms,
+            results,
+        }
+    }
+
+    pub fn throttle_time_ms(&self) -> i32 {
+        self.throttle_time_ms
+    }
+
+    pub fn set_throttle_time_ms(&mut self, throttle_time_ms: i32) {
+        self.throttle_time_ms = throttle_time_ms;
+    }
+
+    pub fn results(&self) -> &[AlterUserScramCredentialsResult] {
+        &self.results
+    }
+}
+impl ReadableVersion for Response {
+    fn read_version<B: Buf>(buffer: &mut B, version: i16) -> Result<Self, protocol::Error> {
+        if version != 0 {
+            return Err(protocol::Error::UnsupportedVersion);
+        }
+
+        let throttle_time_ms = i32::read(buffer);
+        let results = CompactArray::<AlterUserScramCredentialsResult>::read_result_inner(buffer)?
+            .ok_or(protocol::Error::IllegalArgument(
+            "non-nullable field results was serialized as null",
+        ))?;
+        let _tagged_fields = TaggedFields::read_result_inner(buffer)?;
+
+        Ok(Self {
+            throttle_time_ms,
+            results,
+        })
+    }
+}
+impl Writable for Response {
+    fn write<B: BufMut>(&self, buffer: &mut B) {
+        self.throttle_time_ms.write(buffer);
+        CompactArray::write_inner(buffer, Some(self.results()));
+        TaggedFields::write_empty(buffer);
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct AlterUserScramCredentialsResult {
+    user: Bytes,
+    error_code: ErrorCode,
+    error_message: Option<Bytes>,
+}
+impl AlterUserScramCredentialsResult {
+    pub fn v0(user: &str, error_code: ErrorCode, error_message: Option<&str>) -> Self {
+        Self {
+            user: Bytes::copy_from_slice(user.as_bytes()),
+            error_code,
+            error_message: error_message.map(|m| Bytes::copy_from_slice(m.as_bytes())),
+        }
+    }
+
+    pub fn user(&self) -> &[u8] {
+        &self.user
+    }
+
+    pub fn error_code(&self) -> ErrorCode {
+        self.error_code
+    }
+
+    pub fn error_message(&self) -> Option<&[u8]> {
+        self.error_message.as_deref()
+    }
+}
+impl ReadableResult for AlterUserScramCredentialsResult {
+    fn read_result<B: Buf>(buffer: &mut B) -> Result<Self, protocol::Error> {
+        let user = CompactKafkaString::read_result_inner(buffer)?.ok_or(
+            protocol::Error::IllegalArgument("non-nullable field user was serialized as null"),
+        )?;
+        let error_code = ErrorCode::read(buffer);
+        let error_message = CompactKafkaString::read_result_inner(buffer)?;
+        let _tagged_fields = TaggedFields::read_result_inner(buffer)?;
+
+        Ok(Self {
+            user,
+            error_code,
+            error_message,
+        })
+    }
+}
+impl Writable for AlterUserScramCredentialsResult {
+    fn write<B: BufMut>(&self, buffer: &mut B) {
+        CompactKafkaString::write_inner(buffer, Some(self.user()));
+        self.error_code.write(buffer);
+        CompactKafkaString::write_inner(buffer, self.error_message());
+        TaggedFields::write_empty(buffer);
+    }
+}
+
+// One result per user, in the order users first appear. Every change of a user
+// is rejected if any of them is invalid.
+pub fn process_request(
+    request: Request,
+    credentials: &HashMap<(String, i8), ScramCredential>,
+    writer: &MetadataWriter,
+    auth: &Authorization,
+) -> Result<Response, protocol::Error> {
+    let mut users = Vec::<(&str, Vec<Bytes>, Option<(ErrorCode, &str)>)>::new();
+    let mut mechanisms = HashMap::<(&str, i8), usize>::new();
+    let authorized = auth.authorize(AclOperation::Alter, ResourceType::Cluster, CLUSTER_NAME);
+
+    let deletions = request
+        .deletions()
+        .iter()
+        .map(|d| (d.name(), d.mechanism(), None));
+    let upsertions = request
+        .upsertions()
+        .iter()
+        .map(|u| (u.name(), u.mechanism(), Some(u)));
+    for (name, mechanism, upsertion) in deletions.chain(upsertions) {
+        let name = std::str::from_utf8(name)?;
+        let index = match users.iter().position(|(user, _, _)| *user == name) {
+            Some(index) => index,
+            None => {
+                users.push((name, vec![], None));
+                users.len() - 1
+            }
+        };
+        let seen = mechanisms.insert((name, mechanism), index).is_some();
+        let (_, records, error) = &mut users[index];
+        if error.is_some() {
+            continue;
+        }
+
+        let scram_mechanism = ScramMechanism::from_id(mechanism);
+        *error = if !authorized {
+            Some((
+                ErrorCode::ClusterAuthorizationFailed,
+                ErrorCode::ClusterAuthorizationFailed.message(),
+            ))
+        } else if name.is_empty() {
+            Some((
+                ErrorCode::UnacceptableCredential,
+                "Username must not be empty",
+            ))
+        } else if scram_mechanism.is_none() {
+            Some((
+                ErrorCode::UnsupportedSaslMechanism,
+                "Unknown SCRAM mechanism",
+            ))
+        } else if seen {
+            Some((
+                ErrorCode::DuplicateResource,
+                "A user credential cannot be altered twice in the same request",
+            ))
+        } else {
+            None
+        };
+        let Some(scram_mechanism) = scram_mechanism.filter(|_| error.is_none()) else {
+            continue;
+        };
+
+        match upsertion {
+            None if !credentials.contains_key(&(name.to_string(), mechanism)) => {
+                *error = Some((
+                    ErrorCode::ResourceNotFound,
+                    "Attempt to delete a user credential that does not exist",
+                ));
+            }
+            None => {
+                let record = RemoveUserScramCredentialRecord::new(name, mechanism);
+                records.push(metadata_value(
+                    remove_user_scram_credential_record::API_KEY,
+                    0,
+                    &record,
+                ));
+            }
+            Some(upsertion)
+                if !(SCRAM_MIN_ITERATIONS..=SCRAM_MAX_ITERATIONS)
+                    .contains(&upsertion.iterations()) =>
+            {
+                *error = Some((
+                    ErrorCode::UnacceptableCredential,
+                    "Iterations must be between 4096 and 16384",
+                ));
+            }
+            Some(upsertion)
+                if upsertion.salt().is_empty() || upsertion.salted_password().is_empty() =>
+            {
+                *error = Some((
+                    ErrorCode::UnacceptableCredential,
+                    "Salt and salted password must not be empty",
+                ));
+            }
+            Some(upsertion) => {
+                let credential = scram_mechanism.credential(
+                    Bytes::copy_from_slice(upsertion.salt()),
+                    upsertion.salted_password(),
+                    upsertion.iterations(),
+                );
+                let record = UserScramCredentialRecord::new(
+                    name,
+                    mechanism,
+                    credential.salt().clone(),
+                    credential.stored_key().clone(),
+                    credential.server_key().clone(),
+                    credential.iterations(),
+                );
+                records.push(metadata_value(
+                    user_scram_credential_record::API_KEY,
+                    0,
+                    &record,
+                ));
+            }
+        }
+    }
+
+    let records = users
+        .iter()
+        .filter(|(_, _, error)| error.is_none())
+        .flat_map(|(_, records, _)| records.iter().cloned())
+        .collect();
+    let outcome = append(writer, records);
+    let results = users
+        .iter()
+        .map(|(user, _, error)| match (error, &outcome) {
+            (Some((error_code, message)), _) => {
+                AlterUserScramCredentialsResult::v0(user, *error_code, Some(message))
+            }
+            (None, Ok(())) => AlterUserScramCredentialsResult::v0(user, ErrorCode::None, None),
+            (None, Err((error_code, message))) => {
+                AlterUserScramCredentialsResult::v0(user, *error_code, Some(message))
+            }
+        })
+        .collect();
+
+    Ok(Response::v0(0, results))
+}
+
+pub(crate) fn error_response(error_code: ErrorCode) -> Response {
+    Response::v0(
+        0,
+        vec![AlterUserScramCredentialsResult::v0(
+            "",
+            error_code,
+            Some(error_code.message()),
+        )],
+    )
+}
@@ -6,7 +6,7 @@ use bytes::{Buf, BufMut, Bytes, BytesMut};
 use crate::protocol::{
     self,
     r#type::{Array, CompactArray, CompactKafkaString, TaggedField, TaggedFields},
-    Readable, ReadableVersion, Writable,
+    ErrorCode, Readable, ReadableVersion, Writable,
 };
 
 pub(crate) const API_KEY: i16 = 18;
@@ -330,7 +330,7 @@ impl Writable for FinalizedFeature {
 #[derive(Debug, Clone)]
 pub struct Response {
     pub(crate) version: i16,
-    error_code: i16,
+    error_code: ErrorCode,
     api_keys: Vec<ApiKey>,
     throttle_time_ms: Option<i32>,
     supported_features: Option<Vec<SupportedFeature>>,
@@ -341,7 +341,7 @@ pub struct Response {
 impl Response {
     fn new(
         version: i16,
-        error_code: i16,
+        error_code: ErrorCode,
         api_keys: Vec<ApiKey>,
         throttle_time_ms: Option<i32>,
         supported_features: Option<Vec<SupportedFeature>>,
@@ -361,7 +361,7 @@ impl Response {
         }
     }
 
-    pub fn v0(error_code: i16, api_keys: &HashMap<i16, ApiKey>) -> Self {
+    pub fn v0(error_code: ErrorCode, api_keys: &HashMap<i16, ApiKey>) -> Self {
         Self::new(
             0,
             error_code,
@@ -374,7 +374,11 @@ impl Response {
         )
     }
 
-    pub fn v1(error_code: i16, api_keys: &HashMap<i16, ApiKey>, throttle_time_ms: i32) -> Self {
+    pub fn v1(
+        error_code: ErrorCode,
+        api_keys: &HashMap<i16, ApiKey>,
+        throttle_time_ms: i32,
+    ) -> Self {
         Self::new(
             1,
             error_code,
@@ -387,7 +391,11 @@ impl Response {
         )
     }
 
-    pub fn v2(error_code: i16, api_keys: &HashMap<i16, ApiKey>, throttle_time_ms: i32) -> Self {
+    pub fn v2(
+        error_code: ErrorCode,
+        api_keys: &HashMap<i16, ApiKey>,
+        throttle_time_ms: i32,
+    ) -> Self {
         Self::new(
             2,
             error_code,
@@ -401,7 +409,7 @@ impl Response {
     }
 
     pub fn v3(
-        error_code: i16,
+        error_code: ErrorCode,
         api_keys: &HashMap<i16, ApiKey>,
         throttle_time_ms: i32,
         supported_features: Option<&HashMap<String, SupportedFeature>>,
@@ -422,7 +430,7 @@ impl Response {
     }
 
     pub fn v4(
-        error_code: i16,
+        error_code: ErrorCode,
         api_keys: &HashMap<i16, ApiKey>,
         throttle_time_ms: i32,
         supported_features: Option<&HashMap<String, SupportedFeature>>,
@@ -442,7 +450,7 @@ impl Response {
         )
     }
 
-    pub fn error_code(&self) -> i16 {
+    pub fn error_code(&self) -> ErrorCode {
         self.error_code
     }
 
@@ -454,6 +462,10 @@ impl Response {
         self.throttle_time_ms.unwrap_or_default()
     }
 
+    pub fn set_throttle_time_ms(&mut self, throttle_time_ms: i32) {
+        self.throttle_time_ms = Some(throttle_time_ms);
+    }
+
     pub fn supported_features(&self) -> Option<&[SupportedFeature]> {
         self.supported_features.as_deref()
     }
@@ -476,7 +488,7 @@ impl ReadableVersion for Response {
             return Err(protocol::Error::UnsupportedVersion);
         }
 
-        let error_code = i16::read(buffer);
+        let error_code = ErrorCode::read(buffer);
         let api_keys = if version >= 3 {
             CompactArray::<ApiKey>::read_version_inner(buffer, version)
         } else {
@@ -512,7 +524,7 @@ impl ReadableVersion for Response {
                         supported_features.replace(value);
                     }
                     1 => {
-                        finalized_features_epoch.replace(i64::read(buffer));
+                        finalized_features_epoch.replace(i64::read(&mut inner_buffer));
                     }
                     2 => {
                         let value = CompactArray::<FinalizedFeature>::read_version_inner(
@@ -525,7 +537,7 @@ impl ReadableVersion for Response {
                         finalized_features.replace(value);
                     }
                     3 => {
-                        zk_migration_ready.replace(bool::read(buffer));
+                        zk_migration_ready.replace(bool::read(&mut inner_buffer));
                     }
                     _ => continue,
                 }
@@ -586,22 +598,59 @@ impl Writable for Response {
     }
 }
 
+// Finalized features come from FeatureLevelRecords; in KRaft a finalized
+// feature has a single level, reported as both its min and max.
 pub(crate) fn process_request(
     request: Request,
     api_keys: &HashMap<i16, ApiKey>,
-    _supported_features: &HashMap<String, SupportedFeature>,
-    _finalized_features: &HashMap<String, FinalizedFeature>,
+    supported_features: &HashMap<String, SupportedFeature>,
+    finalized_features_epoch: i64,
+    finalized_features: &HashMap<String, i16>,
 ) -> Response {
     if request.version < 0 || request.version > 4 {
-        return Response::v0(35, api_keys);
+        return error_response(request.version, ErrorCode::UnsupportedVersion, api_keys);
     }
 
+    let finalized_features = finalized_features
+        .iter()
+        .map(|(name, level)| (name.clone(), FinalizedFeature::v3(name, *level, *level)))
+        .collect::<HashMap<_, _>>();
     match request.version {
-        0 => Response::v0(0, api_keys),
-        1 => Response::v1(0, api_keys, 0),
-        2 => Response::v2(0, api_keys, 0),
-        3 => Response::v3(0, api_keys, 0, None, None, None, None),
-        4 => Response::v4(0, api_keys, 0, None, None, None, None),
+        0 => Response::v0(ErrorCode::None, api_keys),
+        1 => Response::v1(ErrorCode::None, api_keys, 0),
+        2 => Response::v2(ErrorCode::None, api_keys, 0),
+        3 => Response::v3(
+            ErrorCode::None,
+            api_keys,
+            0,
+            Some(supported_features),
+            Some(finalized_features_epoch),
+            Some(&finalized_features),
+            None,
+        ),
+        4 => Response::v4(
+            ErrorCode::None,
+            api_keys,
+            0,
+            Some(supported_features),
+            Some(finalized_features_epoch),
+            Some(&finalized_features),
+            None,
+        ),
         _ => unreachable!(),
     }
 }
+
+pub(crate) fn error_response(
+    version: i16,
+    error_code: ErrorCode,
+    api_keys: &HashMap<i16, ApiKey>,
+) -> Response {
+    match version {
+        1 => Response::v1(error_code, api_keys, 0),
+        2 => Response::v2(error_code, api_keys, 0),
+        3 => Response::v3(error_code, api_keys, 0, None, None, None, None),
+        4 => Response::v4(error_code, api_keys, 0, None, None, None, None),
+        _ => Response::v0(error_code, api_keys),
+    }
+}
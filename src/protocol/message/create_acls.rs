@@ -0,0 +1,622 @@
+#![allow(clippy::too_many_arguments)]
+use std::collections::HashMap;
+
+use bytes::{Buf, BufMut, Bytes};
+use uuid::Uuid;
+
+use crate::{
+    metadata::{
+        metadata_value,
+        records::{access_control_entry_record, AccessControlEntryRecord},
+        MetadataWriter,
+    },
+    model::Acl,
+    protocol::{
+        self,
+        r#type::{Array, CompactArray, CompactKafkaString, KafkaString, TaggedFields},
+        ErrorCode, Readable, ReadableVersion, Writable,
+    },
+    security::{
+        acl::CLUSTER_NAME, AclOperation, AclPermissionType, Authorization, PatternType,
+        ResourceType,
+    },
+};
+
+use super::alter_configs::append;
+
+pub(crate) const API_KEY: i16 = 30;
+
+#[derive(Debug, Clone)]
+pub struct Request {
+    version: i16,
+    creations: Vec<AclCreation>,
+}
+impl Request {
+    fn new(version: i16, creations: Vec<AclCreation>) -> Self {
+        Self { version, creations }
+    }
+
+    pub fn v0(creations: Vec<AclCreation>) -> Self {
+        Self::new(0, creations)
+    }
+
+    pub fn v1(creations: Vec<AclCreation>) -> Self {
+        Self::new(1, creations)
+    }
+
+    pub fn v2(creations: Vec<AclCreation>) -> Self {
+        Self::new(2, creations)
+    }
+
+    pub fn v3(creations: Vec<AclCreation>) -> Self {
+        Self::new(3, creations)
+    }
+
+    pub fn creations(&self) -> &[AclCreation] {
+        &self.creations
+    }
+}
+impl ReadableVersion for Request {
+    fn read_version<B: Buf>(buffer: &mut B, version: i16) -> Result<Self, protocol::Error> {
+        if !(0..=3).contains(&version) {
+            return Err(protocol::Error::UnsupportedVersion);
+        }
+
+        let creations = if version <= 1 {
+            Array::<AclCreation>::read_version_inner(buffer, version)
+        } else {
+            CompactArray::<AclCreation>::read_version_inner(buffer, version)
+        }?
+        .ok_or(protocol::Error::IllegalArgument(
+            "non-nullable field creations was serialized as null",
+        ))?;
+        if version >= 2 {
+            let _tagged_fields = TaggedFields::read_result_inner(buffer)?;
+        }
+
+        Ok(Self { version, creations })
+    }
+}
+impl Writable for Request {
+    fn write<B: BufMut>(&self, buffer: &mut B) {
+        if self.version <= 1 {
+            Array::write_inner(buffer, Some(self.creations()));
+        } else {
+            CompactArray::write_inner(buffer, Some(self.creations()));
+            TaggedFields::write_empty(buffer);
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct AclCreation {
+    version: i16,
+    resource_type: i8,
+    resource_name: Bytes,
+    resource_pattern_type: i8,
+    principal: Bytes,
+    host: Bytes,
+    operation: i8,
+    permission_type: i8,
+}
+impl AclCreation {
+    fn new(
+        version: i16,
+        resource_type: i8,
+        resource_name: &str,
+        resource_pattern_type: i8,
+        principal: &str,
+        host: &str,
+        operation: i8,
+        permission_type: i8,
+    ) -> Self {
+        Self {
+            version,
+            resource_type,
+            resource_name: Bytes::copy_from_slice(resource_name.as_bytes()),
+            resource_pattern_type,
+            principal: Bytes::copy_from_slice(principal.as_bytes()),
+            host: Bytes::copy_from_slice(host.as_bytes()),
+            operation,
+            permission_type,
+        }
+    }
+
+    pub fn v0(
+        resource_type: i8,
+        resource_name: &str,
+        principal: &str,
+        host: &str,
+        operation: i8,
+        permission_type: i8,
+    ) -> Self {
+        Self::new(
+            0,
+            resource_type,
+            resource_name,
+            PatternType::Literal.id(),
+            principal,
+            host,
+            operation,
+            permission_type,
+        )
+    }
+
+    pub fn v1(
+        resource_type: i8,
+        resource_name: &str,
+        resource_pattern_type: i8,
+        principal: &str,
+        host: &str,
+        operation: i8,
+        permission_type: i8,
+    ) -> Self {
+        Self::new(
+            1,
+            resource_type,
+            resource_name,
+            resource_pattern_type,
+            principal,
+            host,
+            operation,
+            permission_type,
+        )
+    }
+
+    pub fn v2(
+        resource_type: i8,
+        resource_name: &str,
+        resource_pattern_type: i8,
+        principal: &str,
+        host: &str,
+        operation: i8,
+        permission_type: i8,
+    ) -> Self {
+        Self::new(
+            2,
+            resource_type,
+            resource_name,
+            resource_pattern_type,
+            principal,
+            host,
+            operation,
+            permission_type,
+        )
+    }
+
+    pub fn v3(
+        resource_type: i8,
+        resource_name: &str,
+        resource_pattern_type: i8,
+        principal: &str,
+        host: &str,
+        operation: i8,
+        permission_type: i8,
+    ) -> Self {
+        Self::new(
+            3,
+            resource_type,
+            resource_name,
+            resource_pattern_type,
+            principal,
+            host,
+            operation,
+            permission_type,
+        )
+    }
+
+    pub fn resource_type(&self) -> i8 {
+        self.resource_type
+    }
+
+    pub fn resource_name(&self) -> &[u8] {
+        &self.resource_name
+    }
+
+    pub fn resource_pattern_type(&self) -> i8 {
+        self.resource_pattern_type
+    }
+
+    pub fn principal(&self) -> &[u8] {
+        &self.principal
+    }
+
+    pub fn host(&self) -> &[u8] {
+        &self.host
+    }
+
+    pub fn operation(&self) -> i8 {
+        self.operation
+    }
+
+    pub fn permission_type(&self) -> i8 {
+        self.permission_type
+    }
+}
+impl ReadableVersion for AclCreation {
+    fn read_version<B: Buf>(buffer: &mut B, version: i16) -> Result<Self, protocol::Error> {
+        if !(0..=3).contains(&version) {
+            return Err(protocol::Error::UnsupportedVersion);
+        }
+
+        let resource_type = i8::read(buffer);
+        let resource_name = if version <= 1 {
+            KafkaString::read_inner(buffer)
+        } else {
+            CompactKafkaString::read_result_inner(buffer)?
+        }
+        .ok_or(protocol::Error::IllegalArgument(
+            "non-nullable field resourceName was serialized as null",
+        ))?;
+        let resource_pattern_type = if version >= 1 {
+            i8::read(buffer)
+        } else {
+            PatternType::Literal.id()
+        };
+        let (principal, host) = if version <= 1 {
+            (
+                KafkaString::read_inner(buffer),
+                KafkaString::read_inner(buffer),
+            )
+        } else {
+            (
+                CompactKafkaString::read_result_inner(buffer)?,
+                CompactKafkaString::read_result_inner(buffer)?,
+            )
+        };
+        let principal = principal.ok_or(protocol::Error::IllegalArgument(
+            "non-nullable field principal was serialized as null",
+        ))?;
+        let host = host.ok_or(protocol::Error::IllegalArgument(
+            "non-nullable field host was serialized as null",
+        ))?;
+        let operation = i8::read(buffer);
+        let permission_type = i8::read(buffer);
+        if version >= 2 {
+            let _tagged_fields = TaggedFields::read_result_inner(buffer)?;
+        }
+
+        Ok(Self {
+            version,
+            resource_type,
+            resource_name,
+            resource_pattern_type,
+            principal,
+            host,
+            operation,
+            permission_type,
+        })
+    }
+}
+impl Writable for AclCreation {
+    fn write<B: BufMut>(&self, buffer: &mut B) {
+        self.resource_type.write(buffer);
+        if self.version <= 1 {
+            KafkaString::write_inner(buffer, Some(self.resource_name()));
+        } else {
+            CompactKafkaString::write_inner(buffer, Some(self.resource_name()));
+        }
+        if self.version >= 1 {
+            self.resource_pattern_type.write(buffer);
+        }
+        if self.version <= 1 {
+            KafkaString::write_inner(buffer, Some(self.principal()));
+            KafkaString::write_inner(buffer, Some(self.host()));
+        } else {
+            CompactKafkaString::write_inner(buffer, Some(self.principal()));
+            CompactKafkaString::write_inner(buffer, Some(self.host()));
+        }
+        self.operation.write(buffer);
+        self.permission_type.write(buffer);
+        if self.version >= 2 {
+            TaggedFields::write_empty(buffer);
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Response {
+    version: i16,
+    throttle_time_ms: i32,
+    results: Vec<AclCreationResult>,
+}
+impl Response {
+    fn new(version: i16, throttle_time_ms: i32, results: Vec<AclCreationResult>) -> Self {
+        Self {
+            version,
+            throttle_time_ms,
+            results,
+        }
+    }
+
+    pub fn v0(throttle_time_ms: i32, results: Vec<AclCreationResult>) -> Self {
+        Self::new(0, throttle_time_ms, results)
+    }
+
+    pub fn v1(throttle_time_ms: i32, results: Vec<AclCreationResult>) -> Self {
+        Self::new(1, throttle_time_ms, results)
+    }
+
+    pub fn v2(throttle_time_ms: i32, results: Vec<AclCreationResult>) -> Self {
+        Self::new(2, throttle_time_ms, results)
+    }
+
+    pub fn v3(throttle_time_ms: i32, results: Vec<AclCreationResult>) -> Self {
+        Self::new(3, throttle_time_ms, results)
+    }
+
+    pub fn throttle_time_ms(&self) -> i32 {
+        self.throttle_time_ms
+    }
+
+    pub fn set_throttle_time_ms(&mut self, throttle_time_ms: i32) {
+        self.throttle_time_ms = throttle_time_ms;
+    }
+
+    pub fn results(&self) -> &[AclCreationResult] {
+        &self.results
+    }
+}
+impl ReadableVersion for Response {
+    fn read_version<B: Buf>(buffer: &mut B, version: i16) -> Result<Self, protocol::Error> {
+        if !(0..=3).contains(&version) {
+            return Err(protocol::Error::UnsupportedVersion);
+        }
+
+        let throttle_time_ms = i32::read(buffer);
+        let results = if version <= 1 {
+            Array::<AclCreationResult>::read_version_inner(buffer, version)
+        } else {
+            CompactArray::<AclCreationResult>::read_version_inner(buffer, version)
+        }?
+        .ok_or(protocol::Error::IllegalArgument(
+            "non-nullable field results was serialized as null",
+        ))?;
+        if version >= 2 {
+            let _tagged_fields = TaggedFields::read_result_inner(buffer)?;
+        }
+
+        Ok(Self {
+            version,
+            throttle_time_ms,
+            results,
+        })
+    }
+}
+impl Writable for Response {
+    fn write<B: BufMut>(&self, buffer: &mut B) {
+        self.throttle_time_ms.write(buffer);
+        if self.version <= 1 {
+            Array::write_inner(buffer, Some(self.results()));
+        } else {
+            CompactArray::write_inner(buffer, Some(self.results()));
+            TaggedFields::write_empty(buffer);
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct AclCreationResult {
+    version: i16,
+    error_code: ErrorCode,
+    error_message: Option<Bytes>,
+}
+impl AclCreationResult {
+    fn new(version: i16, error_code: ErrorCode, error_message: Option<&str>) -> Self {
+        Self {
+            version,
+            error_code,
+            error_message: error_message.map(|m| Bytes::copy_from_slice(m.as_bytes())),
+        }
+    }
+
+    pub fn v0(error_code: ErrorCode, error_message: Option<&str>) -> Self {
+        Self::new(0, error_code, error_message)
+    }
+
+    pub fn v1(error_code: ErrorCode, error_message: Option<&str>) -> Self {
+        Self::new(1, error_code, error_message)
+    }
+
+    pub fn v2(error_code: ErrorCode, error_message: Option<&str>) -> Self {
+        Self::new(2, error_code, error_message)
+    }
+
+    pub fn v3(error_code: ErrorCode, error_message: Option<&str>) -> Self {
+        Self::new(3, error_code, error_message)
+    }
+
+    pub fn error_code(&self) -> ErrorCode {
+        self.error_code
+    }
+
+    pub fn error_message(&self) -> Option<&[u8]> {
+        self.error_message.as_deref()
+    }
+}
+impl ReadableVersion for AclCreationResult {
+    fn read_version<B: Buf>(buffer: &mut B, version: i16) -> Result<Self, protocol::Error> {
+        if !(0..=3).contains(&version) {
+            return Err(protocol::Error::UnsupportedVersion);
+        }
+
+        let error_code = ErrorCode::read(buffer);
+        let error_message = if version <= 1 {
+            KafkaString::read_inner(buffer)
+        } else {
+            CompactKafkaString::read_result_inner(buffer)?
+        };
+        if version >= 2 {
+            let _tagged_fields = TaggedFields::read_result_inner(buffer)?;
+        }
+
+        Ok(Self {
+            version,
+            error_code,
+            error_message,
+        })
+    }
+}
+impl Writable for AclCreationResult {
+    fn write<B: BufMut>(&self, buffer: &mut B) {
+        self.error_code.write(buffer);
+        if self.version <= 1 {
+            KafkaString::write_inner(buffer, self.error_message());
+        } else {
+            CompactKafkaString::write_inner(buffer, self.error_message());
+            TaggedFields::write_empty(buffer);
+        }
+    }
+}
+
+pub fn process_request(
+    request: Request,
+    acls: &HashMap<Uuid, Acl>,
+    writer: &MetadataWriter,
+    auth: &Authorization,
+) -> Result<Response, protocol::Error> {
+    let version = request.version;
+    let error = if !auth.is_enabled() {
+        Some(ErrorCode::SecurityDisabled)
+    } else if !auth.authorize(AclOperation::Alter, ResourceType::Cluster, CLUSTER_NAME) {
+        Some(ErrorCode::ClusterAuthorizationFailed)
+    } else {
+        None
+    };
+    if let Some(error_code) = error {
+        let results = request
+            .creations()
+            .iter()
+            .map(|_| AclCreationResult::new(version, error_code, Some(error_code.message())))
+            .collect();
+        return Ok(Response::new(version, 0, results));
+    }
+
+    // Valid creations are written together, so their results are only known
+    // once the append has gone through.
+    let mut results = Vec::with_capacity(request.creations().len());
+    let mut records = Vec::new();
+    let mut created = Vec::<Acl>::new();
+    for creation in request.creations() {
+        let acl = match validate(creation) {
+            Ok(acl) => acl,
+            Err(message) => {
+                results.push(Some(AclCreationResult::new(
+                    version,
+                    ErrorCode::InvalidRequest,
+                    Some(message),
+                )));
+                continue;
+            }
+        };
+        let exists = acls
+            .values()
+            .chain(created.iter())
+            .any(|existing| same_entry(existing, &acl));
+        if !exists {
+            let record = AccessControlEntryRecord::new(
+                acl.id(),
+                acl.resource_type(),
+                acl.resource_name(),
+                acl.pattern_type(),
+                acl.principal(),
+                acl.host(),
+                acl.operation(),
+                acl.permission_type(),
+            );
+            records.push(metadata_value(
+                access_control_entry_record::API_KEY,
+                0,
+                &record,
+            ));
+            created.push(acl);
+        }
+        results.push(None);
+    }
+
+    let outcome = append(writer, records);
+    let results = results
+        .into_iter()
+        .map(|result| {
+            result.unwrap_or_else(|| match &outcome {
+                Ok(()) => AclCreationResult::new(version, ErrorCode::None, None),
+                Err((error_code, message)) => {
+                    AclCreationResult::new(version, *error_code, Some(message))
+                }
+            })
+        })
+        .collect();
+
+    Ok(Response::new(version, 0, results))
+}
+
+fn validate(creation: &AclCreation) -> Result<Acl, &'static str> {
+    let resource_type = match ResourceType::from_id(creation.resource_type()) {
+        Some(ResourceType::Any) | None => return Err("Invalid resource type"),
+        Some(resource_type) => resource_type,
+    };
+    let pattern_type = match PatternType::from_id(creation.resource_pattern_type()) {
+        Some(pattern_type @ (PatternType::Literal | PatternType::Prefixed)) => pattern_type,
+        _ => return Err("Invalid pattern type"),
+    };
+    let operation = match AclOperation::from_id(creation.operation()) {
+        Some(AclOperation::Any) | None => return Err("Invalid operation"),
+        Some(operation) => operation,
+    };
+    let permission_type = match AclPermissionType::from_id(creation.permission_type()) {
+        Some(permission_type @ (AclPermissionType::Allow | AclPermissionType::Deny)) => {
+            permission_type
+        }
+        _ => return Err("Invalid permission type"),
+    };
+    let resource_name =
+        std::str::from_utf8(creation.resource_name()).map_err(|_| "Invalid resource name")?;
+    if resource_name.is_empty() {
+        return Err("Resource name must not be empty");
+    }
+    if resource_type == ResourceType::Cluster && resource_name != CLUSTER_NAME {
+        return Err("The only valid name for the CLUSTER resource is kafka-cluster");
+    }
+    let principal = std::str::from_utf8(creation.principal()).map_err(|_| "Invalid principal")?;
+    if !principal
+        .split_once(':')
+        .is_some_and(|(kind, name)| !kind.is_empty() && !name.is_empty())
+    {
+        return Err("Principal must be of the form Type:Name");
+    }
+    let host = std::str::from_utf8(creation.host()).map_err(|_| "Invalid host")?;
+
+    Ok(Acl::new(
+        Uuid::new_v4(),
+        resource_type.id(),
+        resource_name,
+        pattern_type.id(),
+        principal,
+        host,
+        operation.id(),
+        permission_type.id(),
+    ))
+}
+
+fn same_entry(a: &Acl, b: &Acl) -> bool {
+    a.resource_type() == b.resource_type()
+        && a.resource_name() == b.resource_name()
+        && a.pattern_type() == b.pattern_type()
+        && a.principal() == b.principal()
+        && a.host() == b.host()
+        && a.operation() == b.operation()
+        && a.permission_type() == b.permission_type()
+}
+
+pub(crate) fn error_response(version: i16, error_code: ErrorCode) -> Response {
+    let version = version.clamp(0, 3);
+    Response::new(
+        version,
+        0,
+        vec![AclCreationResult::new(
+            version,
+            error_code,
+            Some(error_code.message()),
+        )],
+    )
+}
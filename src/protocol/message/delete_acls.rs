@@ -0,0 +1,798 @@
+#![allow(clippy::too_many_arguments)]
+use std::collections::{HashMap, HashSet};
+
+use bytes::{Buf, BufMut, Bytes};
+use uuid::Uuid;
+
+use crate::{
+    metadata::{
+        metadata_value,
+        records::{remove_access_control_entry_record, RemoveAccessControlEntryRecord},
+        MetadataWriter,
+    },
+    model::Acl,
+    protocol::{
+        self,
+        r#type::{Array, CompactArray, CompactKafkaString, KafkaString, TaggedFields},
+        ErrorCode, Readable, ReadableVersion, Writable,
+    },
+    security::{
+        acl::CLUSTER_NAME, AclFilter, AclOperation, Authorization, PatternType, ResourceType,
+    },
+};
+
+use super::alter_configs::append;
+
+pub(crate) const API_KEY: i16 = 31;
+
+#[derive(Debug, Clone)]
+pub struct Request {
+    version: i16,
+    filters: Vec<DeleteAclsFilter>,
+}
+impl Request {
+    fn new(version: i16, filters: Vec<DeleteAclsFilter>) -> Self {
+        Self { version, filters }
+    }
+
+    pub fn v0(filters: Vec<DeleteAclsFilter>) -> Self {
+        Self::new(0, filters)
+    }
+
+    pub fn v1(filters: Vec<DeleteAclsFilter>) -> Self {
+        Self::new(1, filters)
+    }
+
+    pub fn v2(filters: Vec<DeleteAclsFilter>) -> Self {
+        Self::new(2, filters)
+    }
+
+    pub fn v3(filters: Vec<DeleteAclsFilter>) -> Self {
+        Self::new(3, filters)
+    }
+
+    pub fn filters(&self) -> &[DeleteAclsFilter] {
+        &self.filters
+    }
+}
+impl ReadableVersion for Request {
+    fn read_version<B: Buf>(buffer: &mut B, version: i16) -> Result<Self, protocol::Error> {
+        if !(0..=3).contains(&version) {
+            return Err(protocol::Error::UnsupportedVersion);
+        }
+
+        let filters = if version <= 1 {
+            Array::<DeleteAclsFilter>::read_version_inner(buffer, version)
+        } else {
+            CompactArray::<DeleteAclsFilter>::read_version_inner(buffer, version)
+        }?
+        .ok_or(protocol::Error::IllegalArgument(
+            "non-nullable field filters was serialized as null",
+        ))?;
+        if version >= 2 {
+            let _tagged_fields = TaggedFields::read_result_inner(buffer)?;
+        }
+
+        Ok(Self { version, filters })
+    }
+}
+impl Writable for Request {
+    fn write<B: BufMut>(&self, buffer: &mut B) {
+        if self.version <= 1 {
+            Array::write_inner(buffer, Some(self.filters()));
+        } else {
+            CompactArray::write_inner(buffer, Some(self.filters()));
+            TaggedFields::write_empty(buffer);
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct DeleteAclsFilter {
+    version: i16,
+    resource_type_filter: i8,
+    resource_name_filter: Option<Bytes>,
+    pattern_type_filter: i8,
+    principal_filter: Option<Bytes>,
+    host_filter: Option<Bytes>,
+    operation: i8,
+    permission_type: i8,
+}
+impl DeleteAclsFilter {
+    fn new(
+        version: i16,
+        resource_type_filter: i8,
+        resource_name_filter: Option<&str>,
+        pattern_type_filter: i8,
+        principal_filter: Option<&str>,
+        host_filter: Option<&str>,
+        operation: i8,
+        permission_type: i8,
+    ) -> Self {
+        Self {
+            version,
+            resource_type_filter,
+            resource_name_filter: resource_name_filter
+                .map(|n| Bytes::copy_from_slice(n.as_bytes())),
+            pattern_type_filter,
+            principal_filter: principal_filter.map(|p| Bytes::copy_from_slice(p.as_bytes())),
+            host_filter: host_filter.map(|h| Bytes::copy_from_slice(h.as_bytes())),
+            operation,
+            permission_type,
+        }
+    }
+
+    pub fn v0(
+        resource_type_filter: i8,
+        resource_name_filter: Option<&str>,
+        principal_filter: Option<&str>,
+        host_filter: Option<&str>,
+        operation: i8,
+        permission_type: i8,
+    ) -> Self {
+        Self::new(
+            0,
+            resource_type_filter,
+            resource_name_filter,
+            PatternType::Literal.id(),
+            principal_filter,
+            host_filter,
+            operation,
+            permission_type,
+        )
+    }
+
+    pub fn v1(
+        resource_type_filter: i8,
+        resource_name_filter: Option<&str>,
+        pattern_type_filter: i8,
+        principal_filter: Option<&str>,
+        host_filter: Option<&str>,
+        operation: i8,
+        permission_type: i8,
+    ) -> Self {
+        Self::new(
+            1,
+            resource_type_filter,
+            resource_name_filter,
+            pattern_type_filter,
+            principal_filter,
+            host_filter,
+            operation,
+            permission_type,
+        )
+    }
+
+    pub fn v2(
+        resource_type_filter: i8,
+        resource_name_filter: Option<&str>,
+        pattern_type_filter: i8,
+        principal_filter: Option<&str>,
+        host_filter: Option<&str>,
+        operation: i8,
+        permission_type: i8,
+    ) -> Self {
+        Self::new(
+            2,
+            resource_type_filter,
+            resource_name_filter,
+            pattern_type_filter,
+            principal_filter,
+            host_filter,
+            operation,
+            permission_type,
+        )
+    }
+
+    pub fn v3(
+        resource_type_filter: i8,
+        resource_name_filter: Option<&str>,
+        pattern_type_filter: i8,
+        principal_filter: Option<&str>,
+        host_filter: Option<&str>,
+        operation: i8,
+        permission_type: i8,
+    ) -> Self {
+        Self::new(
+            3,
+            resource_type_filter,
+            resource_name_filter,
+            pattern_type_filter,
+            principal_filter,
+            host_filter,
+            operation,
+            permission_type,
+        )
+    }
+
+    pub fn resource_type_filter(&self) -> i8 {
+        self.resource_type_filter
+    }
+
+    pub fn resource_name_filter(&self) -> Option<&[u8]> {
+        self.resource_name_filter.as_deref()
+    }
+
+    pub fn pattern_type_filter(&self) -> i8 {
+        self.pattern_type_filter
+    }
+
+    pub fn principal_filter(&self) -> Option<&[u8]> {
+        self.principal_filter.as_deref()
+    }
+
+    pub fn host_filter(&self) -> Option<&[u8]> {
+        self.host_filter.as_deref()
+    }
+
+    pub fn operation(&self) -> i8 {
+        self.operation
+    }
+
+    pub fn permission_type(&self) -> i8 {
+        self.permission_type
+    }
+}
+impl ReadableVersion for DeleteAclsFilter {
+    fn read_version<B: Buf>(buffer: &mut B, version: i16) -> Result<Self, protocol::Error> {
+        if !(0..=3).contains(&version) {
+            return Err(protocol::Error::UnsupportedVersion);
+        }
+
+        let resource_type_filter = i8::read(buffer);
+        let resource_name_filter = if version <= 1 {
+            KafkaString::read_inner(buffer)
+        } else {
+            CompactKafkaString::read_result_inner(buffer)?
+        };
+        let pattern_type_filter = if version >= 1 {
+            i8::read(buffer)
+        } else {
+            PatternType::Literal.id()
+        };
+        let (principal_filter, host_filter) = if version <= 1 {
+            (
+                KafkaString::read_inner(buffer),
+                KafkaString::read_inner(buffer),
+            )
+        } else {
+            (
+                CompactKafkaString::read_result_inner(buffer)?,
+                CompactKafkaString::read_result_inner(buffer)?,
+            )
+        };
+        let operation = i8::read(buffer);
+        let permission_type = i8::read(buffer);
+        if version >= 2 {
+            let _tagged_fields = TaggedFields::read_result_inner(buffer)?;
+        }
+
+        Ok(Self {
+            version,
+            resource_type_filter,
+            resource_name_filter,
+            pattern_type_filter,
+            principal_filter,
+            host_filter,
+            operation,
+            permission_type,
+        })
+    }
+}
+impl Writable for DeleteAclsFilter {
+    fn write<B: BufMut>(&self, buffer: &mut B) {
+        self.resource_type_filter.write(buffer);
+        if self.version <= 1 {
+            KafkaString::write_inner(buffer, self.resource_name_filter());
+        } else {
+            CompactKafkaString::write_inner(buffer, self.resource_name_filter());
+        }
+        if self.version >= 1 {
+            self.pattern_type_filter.write(buffer);
+        }
+        if self.version <= 1 {
+            KafkaString::write_inner(buffer, self.principal_filter());
+            KafkaString::write_inner(buffer, self.host_filter());
+        } else {
+            CompactKafkaString::write_inner(buffer, self.principal_filter());
+            CompactKafkaString::write_inner(buffer, self.host_filter());
+        }
+        self.operation.write(buffer);
+        self.permission_type.write(buffer);
+        if self.version >= 2 {
+            TaggedFields::write_empty(buffer);
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Response {
+    version: i16,
+    throttle_time_ms: i32,
+    filter_results: Vec<DeleteAclsFilterResult>,
+}
+impl Response {
+    fn new(
+        version: i16,
+        throttle_time_ms: i32,
+        filter_results: Vec<DeleteAclsFilterResult>,
+    ) -> Self {
+        Self {
+            version,
+            throttle_time_ms,
+            filter_results,
+        }
+    }
+
+    pub fn v0(throttle_time_ms: i32, filter_results: Vec<DeleteAclsFilterResult>) -> Self {
+        Self::new(0, throttle_time_ms, filter_results)
+    }
+
+    pub fn v1(throttle_time_ms: i32, filter_results: Vec<DeleteAclsFilterResult>) -> Self {
+        Self::new(1, throttle_time_ms, filter_results)
+    }
+
+    pub fn v2(throttle_time_ms: i32, filter_results: Vec<DeleteAclsFilterResult>) -> Self {
+        Self::new(2, throttle_time_ms, filter_results)
+    }
+
+    pub fn v3(throttle_time_ms: i32, filter_results: Vec<DeleteAclsFilterResult>) -> Self {
+        Self::new(3, throttle_time_ms, filter_results)
+    }
+
+    pub fn throttle_time_ms(&self) -> i32 {
+        self.throttle_time_ms
+    }
+
+    pub fn set_throttle_time_ms(&mut self, throttle_time_ms: i32) {
+        self.throttle_time_ms = throttle_time_ms;
+    }
+
+    pub fn filter_results(&self) -> &[DeleteAclsFilterResult] {
+        &self.filter_results
+    }
+}
+impl ReadableVersion for Response {
+    fn read_version<B: Buf>(buffer: &mut B, version: i16) -> Result<Self, protocol::Error> {
+        if !(0..=3).contains(&version) {
+            return Err(protocol::Error::UnsupportedVersion);
+        }
+
+        let throttle_time_ms = i32::read(buffer);
+        let filter_results = if version <= 1 {
+            Array::<DeleteAclsFilterResult>::read_version_inner(buffer, version)
+        } else {
+            CompactArray::<DeleteAclsFilterResult>::read_version_inner(buffer, version)
+        }?
+        .ok_or(protocol::Error::IllegalArgument(
+            "non-nullable field filterResults was serialized as null",
+        ))?;
+        if version >= 2 {
+            let _tagged_fields = TaggedFields::read_result_inner(buffer)?;
+        }
+
+        Ok(Self {
+            version,
+            throttle_time_ms,
+            filter_results,
+        })
+    }
+}
+impl Writable for Response {
+    fn write<B: BufMut>(&self, buffer: &mut B) {
+        self.throttle_time_ms.write(buffer);
+        if self.version <= 1 {
+            Array::write_inner(buffer, Some(self.filter_results()));
+        } else {
+            CompactArray::write_inner(buffer, Some(self.filter_results()));
+            TaggedFields::write_empty(buffer);
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct DeleteAclsFilterResult {
+    version: i16,
+    error_code: ErrorCode,
+    error_message: Option<Bytes>,
+    matching_acls: Vec<DeleteAclsMatchingAcl>,
+}
+impl DeleteAclsFilterResult {
+    fn new(
+        version: i16,
+        error_code: ErrorCode,
+        error_message: Option<&str>,
+        matching_acls: Vec<DeleteAclsMatchingAcl>,
+    ) -> Self {
+        Self {
+            version,
+            error_code,
+            error_message: error_message.map(|m| Bytes::copy_from_slice(m.as_bytes())),
+            matching_acls,
+        }
+    }
+
+    pub fn v0(
+        error_code: ErrorCode,
+        error_message: Option<&str>,
+        matching_acls: Vec<DeleteAclsMatchingAcl>,
+    ) -> Self {
+        Self::new(0, error_code, error_message, matching_acls)
+    }
+
+    pub fn v1(
+        error_code: ErrorCode,
+        error_message: Option<&str>,
+        matching_acls: Vec<DeleteAclsMatchingAcl>,
+    ) -> Self {
+        Self::new(1, error_code, error_message, matching_acls)
+    }
+
+    pub fn v2(
+        error_code: ErrorCode,
+        error_message: Option<&str>,
+        matching_acls: Vec<DeleteAclsMatchingAcl>,
+    ) -> Self {
+        Self::new(2, error_code, error_message, matching_acls)
+    }
+
+    pub fn v3(
+        error_code: ErrorCode,
+        error_message: Option<&str>,
+        matching_acls: Vec<DeleteAclsMatchingAcl>,
+    ) -> Self {
+        Self::new(3, error_code, error_message, matching_acls)
+    }
+
+    pub fn error_code(&self) -> ErrorCode {
+        self.error_code
+    }
+
+    pub fn error_message(&self) -> Option<&[u8]> {
+        self.error_message.as_deref()
+    }
+
+    pub fn matching_acls(&self) -> &[DeleteAclsMatchingAcl] {
+        &self.matching_acls
+    }
+}
+impl ReadableVersion for DeleteAclsFilterResult {
+    fn read_version<B: Buf>(buffer: &mut B, version: i16) -> Result<Self, protocol::Error> {
+        if !(0..=3).contains(&version) {
+            return Err(protocol::Error::UnsupportedVersion);
+        }
+
+        let error_code = ErrorCode::read(buffer);
+        let error_message = if version <= 1 {
+            KafkaString::read_inner(buffer)
+        } else {
+            CompactKafkaString::read_result_inner(buffer)?
+        };
+        let matching_acls = if version <= 1 {
+            Array::<DeleteAclsMatchingAcl>::read_version_inner(buffer, version)
+        } else {
+            CompactArray::<DeleteAclsMatchingAcl>::read_version_inner(buffer, version)
+        }?
+        .ok_or(protocol::Error::IllegalArgument(
+            "non-nullable field matchingAcls was serialized as null",
+        ))?;
+        if version >= 2 {
+            let _tagged_fields = TaggedFields::read_result_inner(buffer)?;
+        }
+
+        Ok(Self {
+            version,
+            error_code,
+            error_message,
+            matching_acls,
+        })
+    }
+}
+impl Writable for DeleteAclsFilterResult {
+    fn write<B: BufMut>(&self, buffer: &mut B) {
+        self.error_code.write(buffer);
+        if self.version <= 1 {
+            KafkaString::write_inner(buffer, self.error_message());
+            Array::write_inner(buffer, Some(self.matching_acls()));
+        } else {
+            CompactKafkaString::write_inner(buffer, self.error_message());
+            CompactArray::write_inner(buffer, Some(self.matching_acls()));
+            TaggedFields::write_empty(buffer);
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct DeleteAclsMatchingAcl {
+    version: i16,
+    error_code: ErrorCode,
+    error_message: Option<Bytes>,
+    resource_type: i8,
+    resource_name: Bytes,
+    pattern_type: i8,
+    principal: Bytes,
+    host: Bytes,
+    operation: i8,
+    permission_type: i8,
+}
+impl DeleteAclsMatchingAcl {
+    fn new(version: i16, error_code: ErrorCode, error_message: Option<&str>, acl: &Acl) -> Self {
+        Self {
+            version,
+            error_code,
+            error_message: error_message.map(|m| Bytes::copy_from_slice(m.as_bytes())),
+            resource_type: acl.resource_type(),
+            resource_name: Bytes::copy_from_slice(acl.resource_name().as_bytes()),
+            pattern_type: acl.pattern_type(),
+            principal: Bytes::copy_from_slice(acl.principal().as_bytes()),
+            host: Bytes::copy_from_slice(acl.host().as_bytes()),
+            operation: acl.operation(),
+            permission_type: acl.permission_type(),
+        }
+    }
+
+    pub fn v0(error_code: ErrorCode, error_message: Option<&str>, acl: &Acl) -> Self {
+        Self::new(0, error_code, error_message, acl)
+    }
+
+    pub fn v1(error_code: ErrorCode, error_message: Option<&str>, acl: &Acl) -> Self {
+        Self::new(1, error_code, error_message, acl)
+    }
+
+    pub fn v2(error_code: ErrorCode, error_message: Option<&str>, acl: &Acl) -> Self {
+        Self::new(2, error_code, error_message, acl)
+    }
+
+    pub fn v3(error_code: ErrorCode, error_message: Option<&str>, acl: &Acl) -> Self {
+        Self::new(3, error_code, error_message, acl)
+    }
+
+    pub fn error_code(&self) -> ErrorCode {
+        self.error_code
+    }
+
+    pub fn error_message(&self) -> Option<&[u8]> {
+        self.error_message.as_deref()
+    }
+
+    pub fn resource_type(&self) -> i8 {
+        self.resource_type
+    }
+
+    pub fn resource_name(&self) -> &[u8] {
+        &self.resource_name
+    }
+
+    pub fn pattern_type(&self) -> i8 {
+        self.pattern_type
+    }
+
+    pub fn principal(&self) -> &[u8] {
+        &self.principal
+    }
+
+    pub fn host(&self) -> &[u8] {
+        &self.host
+    }
+
+    pub fn operation(&self) -> i8 {
+        self.operation
+    }
+
+    pub fn permission_type(&self) -> i8 {
+        self.permission_type
+    }
+}
+impl ReadableVersion for DeleteAclsMatchingAcl {
+    fn read_version<B: Buf>(buffer: &mut B, version: i16) -> Result<Self, protocol::Error> {
+        if !(0..=3).contains(&version) {
+            return Err(protocol::Error::UnsupportedVersion);
+        }
+
+        let error_code = ErrorCode::read(buffer);
+        let error_message = if version <= 1 {
+            KafkaString::read_inner(buffer)
+        } else {
+            CompactKafkaString::read_result_inner(buffer)?
+        };
+        let resource_type = i8::read(buffer);
+        let resource_name = if version <= 1 {
+            KafkaString::read_inner(buffer)
+        } else {
+            CompactKafkaString::read_result_inner(buffer)?
+        }
+        .ok_or(protocol::Error::IllegalArgument(
+            "non-nullable field resourceName was serialized as null",
+        ))?;
+        let pattern_type = if version >= 1 {
+            i8::read(buffer)
+        } else {
+            PatternType::Literal.id()
+        };
+        let (principal, host) = if version <= 1 {
+            (
+                KafkaString::read_inner(buffer),
+                KafkaString::read_inner(buffer),
+            )
+        } else {
+            (
+                CompactKafkaString::read_result_inner(buffer)?,
+                CompactKafkaString::read_result_inner(buffer)?,
+            )
+        };
+        let principal = principal.ok_or(protocol::Error::IllegalArgument(
+            "non-nullable field principal was serialized as null",
+        ))?;
+        let host = host.ok_or(protocol::Error::IllegalArgument(
+            "non-nullable field host was serialized as null",
+        ))?;
+        let operation = i8::read(buffer);
+        let permission_type = i8::read(buffer);
+        if version >= 2 {
+            let _tagged_fields = TaggedFields::read_result_inner(buffer)?;
+        }
+
+        Ok(Self {
+            version,
+            error_code,
+            error_message,
+            resource_type,
+            resource_name,
+            pattern_type,
+            principal,
+            host,
+            operation,
+            permission_type,
+        })
+    }
+}
+impl Writable for DeleteAclsMatchingAcl {
+    fn write<B: BufMut>(&self, buffer: &mut B) {
+        self.error_code.write(buffer);
+        if self.version <= 1 {
+            KafkaString::write_inner(buffer, self.error_message());
+        } else {
+            CompactKafkaString::write_inner(buffer, self.error_message());
+        }
+        self.resource_type.write(buffer);
+        if self.version <= 1 {
+            KafkaString::write_inner(buffer, Some(self.resource_name()));
+        } else {
+            CompactKafkaString::write_inner(buffer, Some(self.resource_name()));
+        }
+        if self.version >= 1 {
+            self.pattern_type.write(buffer);
+        }
+        if self.version <= 1 {
+            KafkaString::write_inner(buffer, Some(self.principal()));
+            KafkaString::write_inner(buffer, Some(self.host()));
+        } else {
+            CompactKafkaString::write_inner(buffer, Some(self.principal()));
+            CompactKafkaString::write_inner(buffer, Some(self.host()));
+        }
+        self.operation.write(buffer);
+        self.permission_type.write(buffer);
+        if self.version >= 2 {
+            TaggedFields::write_empty(buffer);
+        }
+    }
+}
+
+pub fn process_request(
+    request: Request,
+    acls: &HashMap<Uuid, Acl>,
+    writer: &MetadataWriter,
+    auth: &Authorization,
+) -> Result<Response, protocol::Error> {
+    let version = request.version;
+    let error = if !auth.is_enabled() {
+        Some(ErrorCode::SecurityDisabled)
+    } else if !auth.authorize(AclOperation::Alter, ResourceType::Cluster, CLUSTER_NAME) {
+        Some(ErrorCode::ClusterAuthorizationFailed)
+    } else {
+        None
+    };
+    if let Some(error_code) = error {
+        let results = request
+            .filters()
+            .iter()
+            .map(|_| {
+                DeleteAclsFilterResult::new(version, error_code, Some(error_code.message()), vec![])
+            })
+            .collect();
+        return Ok(Response::new(version, 0, results));
+    }
+
+    // An ACL matched by several filters is only removed once, and reported
+    // under the first of them.
+    let mut removed = HashSet::new();
+    let mut records = Vec::new();
+    let mut matches = Vec::with_capacity(request.filters().len());
+    for filter in request.filters() {
+        let filter = AclFilter::new(
+            filter.resource_type_filter(),
+            filter
+                .resource_name_filter()
+                .map(std::str::from_utf8)
+                .transpose()?,
+            filter.pattern_type_filter(),
+            filter
+                .principal_filter()
+                .map(std::str::from_utf8)
+                .transpose()?,
+            filter.host_filter().map(std::str::from_utf8).transpose()?,
+            filter.operation(),
+            filter.permission_type(),
+        );
+        let filter = match filter {
+            Ok(filter) => filter,
+            Err(message) => {
+                matches.push(Err(message));
+                continue;
+            }
+        };
+        let mut matched = acls
+            .values()
+            .filter(|acl| filter.matches(acl) && removed.insert(acl.id()))
+            .collect::<Vec<_>>();
+        matched.sort_by_key(|acl| {
+            (
+                acl.resource_type(),
+                acl.resource_name(),
+                acl.pattern_type(),
+                acl.principal(),
+                acl.host(),
+                acl.operation(),
+            )
+        });
+        for acl in &matched {
+            let record = RemoveAccessControlEntryRecord::new(acl.id());
+            records.push(metadata_value(
+                remove_access_control_entry_record::API_KEY,
+                0,
+                &record,
+            ));
+        }
+        matches.push(Ok(matched));
+    }
+
+    let outcome = append(writer, records);
+    let filter_results = matches
+        .into_iter()
+        .map(|matched| match matched {
+            Err(message) => DeleteAclsFilterResult::new(
+                version,
+                ErrorCode::InvalidRequest,
+                Some(message),
+                vec![],
+            ),
+            Ok(matched) => {
+                let (error_code, message) = match &outcome {
+                    Ok(()) => (ErrorCode::None, None),
+                    Err((error_code, message)) => (*error_code, Some(message.as_str())),
+                };
+                let matching_acls = matched
+                    .into_iter()
+                    .map(|acl| DeleteAclsMatchingAcl::new(version, error_code, message, acl))
+                    .collect();
+                DeleteAclsFilterResult::new(version, ErrorCode::None, None, matching_acls)
+            }
+        })
+        .collect();
+
+    Ok(Response::new(version, 0, filter_results))
+}
+
+pub(crate) fn error_response(version: i16, error_code: ErrorCode) -> Response {
+    let version = version.clamp(0, 3);
+    Response::new(
+        version,
+        0,
+        vec![DeleteAclsFilterResult::new(
+            version,
+            error_code,
+            Some(error_code.message()),
+            vec![],
+        )],
+    )
+}
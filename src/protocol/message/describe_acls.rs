@@ -0,0 +1,696 @@
+#![allow(clippy::too_many_arguments)]
+use std::collections::{BTreeMap, HashMap};
+
+use bytes::{Buf, BufMut, Bytes};
+use uuid::Uuid;
+
+use crate::{
+    model::Acl,
+    protocol::{
+        self,
+        r#type::{Array, CompactArray, CompactKafkaString, KafkaString, TaggedFields},
+        ErrorCode, Readable, ReadableVersion, Writable,
+    },
+    security::{
+        acl::CLUSTER_NAME, AclFilter, AclOperation, Authorization, PatternType, ResourceType,
+    },
+};
+
+pub(crate) const API_KEY: i16 = 29;
+
+#[derive(Debug, Clone)]
+pub struct Request {
+    version: i16,
+    resource_type_filter: i8,
+    resource_name_filter: Option<Bytes>,
+    pattern_type_filter: i8,
+    principal_filter: Option<Bytes>,
+    host_filter: Option<Bytes>,
+    operation: i8,
+    permission_type: i8,
+}
+impl Request {
+    fn new(
+        version: i16,
+        resource_type_filter: i8,
+        resource_name_filter: Option<&str>,
+        pattern_type_filter: i8,
+        principal_filter: Option<&str>,
+        host_filter: Option<&str>,
+        operation: i8,
+        permission_type: i8,
+    ) -> Self {
+        Self {
+            version,
+            resource_type_filter,
+            resource_name_filter: resource_name_filter
+                .map(|n| Bytes::copy_from_slice(n.as_bytes())),
+            pattern_type_filter,
+            principal_filter: principal_filter.map(|p| Bytes::copy_from_slice(p.as_bytes())),
+            host_filter: host_filter.map(|h| Bytes::copy_from_slice(h.as_bytes())),
+            operation,
+            permission_type,
+        }
+    }
+
+    pub fn v0(
+        resource_type_filter: i8,
+        resource_name_filter: Option<&str>,
+        principal_filter: Option<&str>,
+        host_filter: Option<&str>,
+        operation: i8,
+        permission_type: i8,
+    ) -> Self {
+        Self::new(
+            0,
+            resource_type_filter,
+            resource_name_filter,
+            PatternType::Literal.id(),
+            principal_filter,
+            host_filter,
+            operation,
+            permission_type,
+        )
+    }
+
+    pub fn v1(
+        resource_type_filter: i8,
+        resource_name_filter: Option<&str>,
+        pattern_type_filter: i8,
+        principal_filter: Option<&str>,
+        host_filter: Option<&str>,
+        operation: i8,
+        permission_type: i8,
+    ) -> Self {
+        Self::new(
+            1,
+            resource_type_filter,
+            resource_name_filter,
+            pattern_type_filter,
+            principal_filter,
+            host_filter,
+            operation,
+            permission_type,
+        )
+    }
+
+    pub fn v2(
+        resource_type_filter: i8,
+        resource_name_filter: Option<&str>,
+        pattern_type_filter: i8,
+        principal_filter: Option<&str>,
+        host_filter: Option<&str>,
+        operation: i8,
+        permission_type: i8,
+    ) -> Self {
+        Self::new(
+            2,
+            resource_type_filter,
+            resource_name_filter,
+            pattern_type_filter,
+            principal_filter,
+            host_filter,
+            operation,
+            permission_type,
+        )
+    }
+
+    pub fn v3(
+        resource_type_filter: i8,
+        resource_name_filter: Option<&str>,
+        pattern_type_filter: i8,
+        principal_filter: Option<&str>,
+        host_filter: Option<&str>,
+        operation: i8,
+        permission_type: i8,
+    ) -> Self {
+        Self::new(
+            3,
+            resource_type_filter,
+            resource_name_filter,
+            pattern_type_filter,
+            principal_filter,
+            host_filter,
+            operation,
+            permission_type,
+        )
+    }
+
+    pub fn resource_type_filter(&self) -> i8 {
+        self.resource_type_filter
+    }
+
+    pub fn resource_name_filter(&self) -> Option<&[u8]> {
+        self.resource_name_filter.as_deref()
+    }
+
+    pub fn pattern_type_filter(&self) -> i8 {
+        self.pattern_type_filter
+    }
+
+    pub fn principal_filter(&self) -> Option<&[u8]> {
+        self.principal_filter.as_deref()
+    }
+
+    pub fn host_filter(&self) -> Option<&[u8]> {
+        self.host_filter.as_deref()
+    }
+
+    pub fn operation(&self) -> i8 {
+        self.operation
+    }
+
+    pub fn permission_type(&self) -> i8 {
+        self.permission_type
+    }
+}
+impl ReadableVersion for Request {
+    fn read_version<B: Buf>(buffer: &mut B, version: i16) -> Result<Self, protocol::Error> {
+        if !(0..=3).contains(&version) {
+            return Err(protocol::Error::UnsupportedVersion);
+        }
+
+        let resource_type_filter = i8::read(buffer);
+        let resource_name_filter = if version <= 1 {
+            KafkaString::read_inner(buffer)
+        } else {
+            CompactKafkaString::read_result_inner(buffer)?
+        };
+        let pattern_type_filter = if version >= 1 {
+            i8::read(buffer)
+        } else {
+            PatternType::Literal.id()
+        };
+        let (principal_filter, host_filter) = if version <= 1 {
+            (
+                KafkaString::read_inner(buffer),
+                KafkaString::read_inner(buffer),
+            )
+        } else {
+            (
+                CompactKafkaString::read_result_inner(buffer)?,
+                CompactKafkaString::read_result_inner(buffer)?,
+            )
+        };
+        let operation = i8::read(buffer);
+        let permission_type = i8::read(buffer);
+        if version >= 2 {
+            let _tagged_fields = TaggedFields::read_result_inner(buffer)?;
+        }
+
+        Ok(Self {
+            version,
+            resource_type_filter,
+            resource_name_filter,
+            pattern_type_filter,
+            principal_filter,
+            host_filter,
+            operation,
+            permission_type,
+        })
+    }
+}
+impl Writable for Request {
+    fn write<B: BufMut>(&self, buffer: &mut B) {
+        self.resource_type_filter.write(buffer);
+        if self.version <= 1 {
+            KafkaString::write_inner(buffer, self.resource_name_filter());
+        } else {
+            CompactKafkaString::write_inner(buffer, self.resource_name_filter());
+        }
+        if self.version >= 1 {
+            self.pattern_type_filter.write(buffer);
+        }
+        if self.version <= 1 {
+            KafkaString::write_inner(buffer, self.principal_filter());
+            KafkaString::write_inner(buffer, self.host_filter());
+        } else {
+            CompactKafkaString::write_inner(buffer, self.principal_filter());
+            CompactKafkaString::write_inner(buffer, self.host_filter());
+        }
+        self.operation.write(buffer);
+        self.permission_type.write(buffer);
+        if self.version >= 2 {
+            TaggedFields::write_empty(buffer);
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Response {
+    version: i16,
+    throttle_time_ms: i32,
+    error_code: ErrorCode,
+    error_message: Option<Bytes>,
+    resources: Vec<DescribeAclsResource>,
+}
+impl Response {
+    fn new(
+        version: i16,
+        throttle_time_ms: i32,
+        error_code: ErrorCode,
+        error_message: Option<&str>,
+        resources: Vec<DescribeAclsResource>,
+    ) -> Self {
+        Self {
+            version,
+            throttle_time_ms,
+            error_code,
+            error_message: error_message.map(|m| Bytes::copy_from_slice(m.as_bytes())),
+            resources,
+        }
+    }
+
+    pub fn v0(
+        throttle_time_ms: i32,
+        error_code: ErrorCode,
+        error_message: Option<&str>,
+        resources: Vec<DescribeAclsResource>,
+    ) -> Self {
+        Self::new(0, throttle_time_ms, error_code, error_message, resources)
+    }
+
+    pub fn v1(
+        throttle_time_ms: i32,
+        error_code: ErrorCode,
+        error_message: Option<&str>,
+        resources: Vec<DescribeAclsResource>,
+    ) -> Self {
+        Self::new(1, throttle_time_ms, error_code, error_message, resources)
+    }
+
+    pub fn v2(
+        throttle_time_ms: i32,
+        error_code: ErrorCode,
+        error_message: Option<&str>,
+        resources: Vec<DescribeAclsResource>,
+    ) -> Self {
+        Self::new(2, throttle_time_ms, error_code, error_message, resources)
+    }
+
+    pub fn v3(
+        throttle_time_ms: i32,
+        error_code: ErrorCode,
+        error_message: Option<&str>,
+        resources: Vec<DescribeAclsResource>,
+    ) -> Self {
+        Self::new(3, throttle_time_ms, error_code, error_message, resources)
+    }
+
+    pub fn throttle_time_ms(&self) -> i32 {
+        self.throttle_time_ms
+    }
+
+    pub fn set_throttle_time_ms(&mut self, throttle_time_ms: i32) {
+        self.throttle_time_ms = throttle_time_ms;
+    }
+
+    pub fn error_code(&self) -> ErrorCode {
+        self.error_code
+    }
+
+    pub fn error_message(&self) -> Option<&[u8]> {
+        self.error_message.as_deref()
+    }
+
+    pub fn resources(&self) -> &[DescribeAclsResource] {
+        &self.resources
+    }
+}
+impl ReadableVersion for Response {
+    fn read_version<B: Buf>(buffer: &mut B, version: i16) -> Result<Self, protocol::Error> {
+        if !(0..=3).contains(&version) {
+            return Err(protocol::Error::UnsupportedVersion);
+        }
+
+        let throttle_time_ms = i32::read(buffer);
+        let error_code = ErrorCode::read(buffer);
+        let error_message = if version <= 1 {
+            KafkaString::read_inner(buffer)
+        } else {
+            CompactKafkaString::read_result_inner(buffer)?
+        };
+        let resources = if version <= 1 {
+            Array::<DescribeAclsResource>::read_version_inner(buffer, version)
+        } else {
+            CompactArray::<DescribeAclsResource>::read_version_inner(buffer, version)
+        }?
+        .ok_or(protocol::Error::IllegalArgument(
+            "non-nullable field resources was serialized as null",
+        ))?;
+        if version >= 2 {
+            let _tagged_fields = TaggedFields::read_result_inner(buffer)?;
+        }
+
+        Ok(Self {
+            version,
+            throttle_time_ms,
+            error_code,
+            error_message,
+            resources,
+        })
+    }
+}
+impl Writable for Response {
+    fn write<B: BufMut>(&self, buffer: &mut B) {
+        self.throttle_time_ms.write(buffer);
+        self.error_code.write(buffer);
+        if self.version <= 1 {
+            KafkaString::write_inner(buffer, self.error_message());
+            Array::write_inner(buffer, Some(self.resources()));
+        } else {
+            CompactKafkaString::write_inner(buffer, self.error_message());
+            CompactArray::write_inner(buffer, Some(self.resources()));
+            TaggedFields::write_empty(buffer);
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct DescribeAclsResource {
+    version: i16,
+    resource_type: i8,
+    resource_name: Bytes,
+    pattern_type: i8,
+    acls: Vec<AclDescription>,
+}
+impl DescribeAclsResource {
+    fn new(
+        version: i16,
+        resource_type: i8,
+        resource_name: &str,
+        pattern_type: i8,
+        acls: Vec<AclDescription>,
+    ) -> Self {
+        Self {
+            version,
+            resource_type,
+            resource_name: Bytes::copy_from_slice(resource_name.as_bytes()),
+            pattern_type,
+            acls,
+        }
+    }
+
+    pub fn v0(resource_type: i8, resource_name: &str, acls: Vec<AclDescription>) -> Self {
+        Self::new(
+            0,
+            resource_type,
+            resource_name,
+            PatternType::Literal.id(),
+            acls,
+        )
+    }
+
+    pub fn v1(
+        resource_type: i8,
+        resource_name: &str,
+        pattern_type: i8,
+        acls: Vec<AclDescription>,
+    ) -> Self {
+        Self::new(1, resource_type, resource_name, pattern_type, acls)
+    }
+
+    pub fn v2(
+        resource_type: i8,
+        resource_name: &str,
+        pattern_type: i8,
+        acls: Vec<AclDescription>,
+    ) -> Self {
+        Self::new(2, resource_type, resource_name, pattern_type, acls)
+    }
+
+    pub fn v3(
+        resource_type: i8,
+        resource_name: &str,
+        pattern_type: i8,
+        acls: Vec<AclDescription>,
+    ) -> Self {
+        Self::new(3, resource_type, resource_name, pattern_type, acls)
+    }
+
+    pub fn resource_type(&self) -> i8 {
+        self.resource_type
+    }
+
+    pub fn resource_name(&self) -> &[u8] {
+        &self.resource_name
+    }
+
+    pub fn pattern_type(&self) -> i8 {
+        self.pattern_type
+    }
+
+    pub fn acls(&self) -> &[AclDescription] {
+        &self.acls
+    }
+}
+impl ReadableVersion for DescribeAclsResource {
+    fn read_version<B: Buf>(buffer: &mut B, version: i16) -> Result<Self, protocol::Error> {
+        if !(0..=3).contains(&version) {
+            return Err(protocol::Error::UnsupportedVersion);
+        }
+
+        let resource_type = i8::read(buffer);
+        let resource_name = if version <= 1 {
+            KafkaString::read_inner(buffer)
+        } else {
+            CompactKafkaString::read_result_inner(buffer)?
+        }
+        .ok_or(protocol::Error::IllegalArgument(
+            "non-nullable field resourceName was serialized as null",
+        ))?;
+        let pattern_type = if version >= 1 {
+            i8::read(buffer)
+        } else {
+            PatternType::Literal.id()
+        };
+        let acls = if version <= 1 {
+            Array::<AclDescription>::read_version_inner(buffer, version)
+        } else {
+            CompactArray::<AclDescription>::read_version_inner(buffer, version)
+        }?
+        .ok_or(protocol::Error::IllegalArgument(
+            "non-nullable field acls was serialized as null",
+        ))?;
+        if version >= 2 {
+            let _tagged_fields = TaggedFields::read_result_inner(buffer)?;
+        }
+
+        Ok(Self {
+            version,
+            resource_type,
+            resource_name,
+            pattern_type,
+            acls,
+        })
+    }
+}
+impl Writable for DescribeAclsResource {
+    fn write<B: BufMut>(&self, buffer: &mut B) {
+        self.resource_type.write(buffer);
+        if self.version <= 1 {
+            KafkaString::write_inner(buffer, Some(self.resource_name()));
+        } else {
+            CompactKafkaString::write_inner(buffer, Some(self.resource_name()));
+        }
+        if self.version >= 1 {
+            self.pattern_type.write(buffer);
+        }
+        if self.version <= 1 {
+            Array::write_inner(buffer, Some(self.acls()));
+        } else {
+            CompactArray::write_inner(buffer, Some(self.acls()));
+            TaggedFields::write_empty(buffer);
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct AclDescription {
+    version: i16,
+    principal: Bytes,
+    host: Bytes,
+    operation: i8,
+    permission_type: i8,
+}
+impl AclDescription {
+    fn new(version: i16, principal: &str, host: &str, operation: i8, permission_type: i8) -> Self {
+        Self {
+            version,
+            principal: Bytes::copy_from_slice(principal.as_bytes()),
+            host: Bytes::copy_from_slice(host.as_bytes()),
+            operation,
+            permission_type,
+        }
+    }
+
+    pub fn v0(principal: &str, host: &str, operation: i8, permission_type: i8) -> Self {
+        Self::new(0, principal, host, operation, permission_type)
+    }
+
+    pub fn v1(principal: &str, host: &str, operation: i8, permission_type: i8) -> Self {
+        Self::new(1, principal, host, operation, permission_type)
+    }
+
+    pub fn v2(principal: &str, host: &str, operation: i8, permission_type: i8) -> Self {
+        Self::new(2, principal, host, operation, permission_type)
+    }
+
+    pub fn v3(principal: &str, host: &str, operation: i8, permission_type: i8) -> Self {
+        Self::new(3, principal, host, operation, permission_type)
+    }
+
+    pub fn principal(&self) -> &[u8] {
+        &self.principal
+    }
+
+    pub fn host(&self) -> &[u8] {
+        &self.host
+    }
+
+    pub fn operation(&self) -> i8 {
+        self.operation
+    }
+
+    pub fn permission_type(&self) -> i8 {
+        self.permission_type
+    }
+}
+impl ReadableVersion for AclDescription {
+    fn read_version<B: Buf>(buffer: &mut B, version: i16) -> Result<Self, protocol::Error> {
+        if !(0..=3).contains(&version) {
+            return Err(protocol::Error::UnsupportedVersion);
+        }
+
+        let (principal, host) = if version <= 1 {
+            (
+                KafkaString::read_inner(buffer),
+                KafkaString::read_inner(buffer),
+            )
+        } else {
+            (
+                CompactKafkaString::read_result_inner(buffer)?,
+                CompactKafkaString::read_result_inner(buffer)?,
+            )
+        };
+        let principal = principal.ok_or(protocol::Error::IllegalArgument(
+            "non-nullable field principal was serialized as null",
+        ))?;
+        let host = host.ok_or(protocol::Error::IllegalArgument(
+            "non-nullable field host was serialized as null",
+        ))?;
+        let operation = i8::read(buffer);
+        let permission_type = i8::read(buffer);
+        if version >= 2 {
+            let _tagged_fields = TaggedFields::read_result_inner(buffer)?;
+        }
+
+        Ok(Self {
+            version,
+            principal,
+            host,
+            operation,
+            permission_type,
+        })
+    }
+}
+impl Writable for AclDescription {
+    fn write<B: BufMut>(&self, buffer: &mut B) {
+        if self.version <= 1 {
+            KafkaString::write_inner(buffer, Some(self.principal()));
+            KafkaString::write_inner(buffer, Some(self.host()));
+        } else {
+            CompactKafkaString::write_inner(buffer, Some(self.principal()));
+            CompactKafkaString::write_inner(buffer, Some(self.host()));
+        }
+        self.operation.write(buffer);
+        self.permission_type.write(buffer);
+        if self.version >= 2 {
+            TaggedFields::write_empty(buffer);
+        }
+    }
+}
+
+pub fn process_request(
+    request: Request,
+    acls: &HashMap<Uuid, Acl>,
+    auth: &Authorization,
+) -> Result<Response, protocol::Error> {
+    let version = request.version;
+    if !auth.is_enabled() {
+        return Ok(error_response(version, ErrorCode::SecurityDisabled));
+    }
+    if !auth.authorize(AclOperation::Describe, ResourceType::Cluster, CLUSTER_NAME) {
+        return Ok(error_response(
+            version,
+            ErrorCode::ClusterAuthorizationFailed,
+        ));
+    }
+
+    let filter = AclFilter::new(
+        request.resource_type_filter(),
+        request
+            .resource_name_filter()
+            .map(std::str::from_utf8)
+            .transpose()?,
+        request.pattern_type_filter(),
+        request
+            .principal_filter()
+            .map(std::str::from_utf8)
+            .transpose()?,
+        request.host_filter().map(std::str::from_utf8).transpose()?,
+        request.operation(),
+        request.permission_type(),
+    );
+    let filter = match filter {
+        Ok(filter) => filter,
+        Err(message) => {
+            return Ok(Response::new(
+                version,
+                0,
+                ErrorCode::InvalidRequest,
+                Some(message),
+                vec![],
+            ))
+        }
+    };
+
+    let mut resources = BTreeMap::<_, Vec<&Acl>>::new();
+    for acl in acls.values().filter(|acl| filter.matches(acl)) {
+        resources
+            .entry((acl.resource_type(), acl.resource_name(), acl.pattern_type()))
+            .or_default()
+            .push(acl);
+    }
+    let resources = resources
+        .into_iter()
+        .map(|((resource_type, resource_name, pattern_type), mut acls)| {
+            acls.sort_by_key(|acl| (acl.principal(), acl.host(), acl.operation()));
+            let acls = acls
+                .into_iter()
+                .map(|acl| {
+                    AclDescription::new(
+                        version,
+                        acl.principal(),
+                        acl.host(),
+                        acl.operation(),
+                        acl.permission_type(),
+                    )
+                })
+                .collect();
+            DescribeAclsResource::new(version, resource_type, resource_name, pattern_type, acls)
+        })
+        .collect();
+
+    Ok(Response::new(version, 0, ErrorCode::None, None, resources))
+}
+
+pub(crate) fn error_response(version: i16, error_code: ErrorCode) -> Response {
+    Response::new(
+        version.clamp(0, 3),
+        0,
+        error_code,
+        Some(error_code.message()),
+        vec![],
+    )
+}
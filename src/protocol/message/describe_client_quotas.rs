@@ -0,0 +1,588 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use bytes::{Buf, BufMut, Bytes};
+
+use crate::{
+    model::ClientQuotaEntity,
+    protocol::{
+        self,
+        r#type::{Array, CompactArray, CompactKafkaString, KafkaString, TaggedFields},
+        ErrorCode, Readable, ReadableVersion, Writable,
+    },
+    quota::ENTITY_TYPES,
+    security::{acl::CLUSTER_NAME, AclOperation, Authorization, ResourceType},
+};
+
+pub(crate) const API_KEY: i16 = 48;
+
+pub const MATCH_TYPE_EXACT: i8 = 0;
+pub const MATCH_TYPE_DEFAULT: i8 = 1;
+pub const MATCH_TYPE_SPECIFIED: i8 = 2;
+
+#[derive(Debug, Clone)]
+pub struct Request {
+    version: i16,
+    components: Vec<ComponentData>,
+    strict: bool,
+}
+impl Request {
+    fn new(version: i16, components: Vec<ComponentData>, strict: bool) -> Self {
+        Self {
+            version,
+            components,
+            strict,
+        }
+    }
+
+    pub fn v0(components: Vec<ComponentData>, strict: bool) -> Self {
+        Self::new(0, components, strict)
+    }
+
+    pub fn v1(components: Vec<ComponentData>, strict: bool) -> Self {
+        Self::new(1, components, strict)
+    }
+
+    pub fn components(&self) -> &[ComponentData] {
+        &self.components
+    }
+
+    pub fn strict(&self) -> bool {
+        self.strict
+    }
+}
+impl ReadableVersion for Request {
+    fn read_version<B: Buf>(buffer: &mut B, version: i16) -> Result<Self, protocol::Error> {
+        if !(0..=1).contains(&version) {
+            return Err(protocol::Error::UnsupportedVersion);
+        }
+
+        let components = if version == 0 {
+            Array::<ComponentData>::read_version_inner(buffer, version)
+        } else {
+            CompactArray::<ComponentData>::read_version_inner(buffer, version)
+        }?
+        .ok_or(protocol::Error::IllegalArgument(
+            "non-nullable field components was serialized as null",
+        ))?;
+        let strict = bool::read(buffer);
+        if version >= 1 {
+            let _tagged_fields = TaggedFields::read_result_inner(buffer)?;
+        }
+
+        Ok(Self {
+            version,
+            components,
+            strict,
+        })
+    }
+}
+impl Writable for Request {
+    fn write<B: BufMut>(&self, buffer: &mut B) {
+        if self.version == 0 {
+            Array::write_inner(buffer, Some(self.components()));
+        } else {
+            CompactArray::write_inner(buffer, Some(self.components()));
+        }
+        self.strict.write(buffer);
+        if self.version >= 1 {
+            TaggedFields::write_empty(buffer);
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ComponentData {
+    version: i16,
+    entity_type: Bytes,
+    match_type: i8,
+    r#match: Option<Bytes>,
+}
+impl ComponentData {
+    fn new(version: i16, entity_type: &str, match_type: i8, r#match: Option<&str>) -> Self {
+        Self {
+            version,
+            entity_type: Bytes::copy_from_slice(entity_type.as_bytes()),
+            match_type,
+            r#match: r#match.map(|m| Bytes::copy_from_slice(m.as_bytes())),
+        }
+    }
+
+    pub fn v0(entity_type: &str, match_type: i8, r#match: Option<&str>) -> Self {
+        Self::new(0, entity_type, match_type, r#match)
+    }
+
+    pub fn v1(entity_type: &str, match_type: i8, r#match: Option<&str>) -> Self {
+        Self::new(1, entity_type, match_type, r#match)
+    }
+
+    pub fn entity_type(&self) -> &[u8] {
+        &self.entity_type
+    }
+
+    pub fn match_type(&self) -> i8 {
+        self.match_type
+    }
+
+    pub fn r#match(&self) -> Option<&[u8]> {
+        self.r#match.as_deref()
+    }
+}
+impl ReadableVersion for ComponentData {
+    fn read_version<B: Buf>(buffer: &mut B, version: i16) -> Result<Self, protocol::Error> {
+        if !(0..=1).contains(&version) {
+            return Err(protocol::Error::UnsupportedVersion);
+        }
+
+        let entity_type = read_string(buffer, version)?.ok_or(protocol::Error::IllegalArgument(
+            "non-nullable field entityType was serialized as null",
+        ))?;
+        let match_type = i8::read(buffer);
+        let r#match = read_string(buffer, version)?;
+        if version >= 1 {
+            let _tagged_fields = TaggedFields::read_result_inner(buffer)?;
+        }
+
+        Ok(Self {
+            version,
+            entity_type,
+            match_type,
+            r#match,
+        })
+    }
+}
+impl Writable for ComponentData {
+    fn write<B: BufMut>(&self, buffer: &mut B) {
+        write_string(buffer, self.version, Some(self.entity_type()));
+        self.match_type.write(buffer);
+        write_string(buffer, self.version, self.r#match());
+        if self.version >= 1 {
+            TaggedFields::write_empty(buffer);
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Response {
+    version: i16,
+    throttle_time_ms: i32,
+    error_code: ErrorCode,
+    error_message: Option<Bytes>,
+    entries: Option<Vec<EntryData>>,
+}
+impl Response {
+    fn new(
+        version: i16,
+        throttle_time_ms: i32,
+        error_code: ErrorCode,
+        error_message: Option<&str>,
+        entries: Option<Vec<EntryData>>,
+    ) -> Self {
+        Self {
+            version,
+            throttle_time_ms,
+            error_code,
+            error_message: error_message.map(|m| Bytes::copy_from_slice(m.as_bytes())),
+            entries,
+        }
+    }
+
+    pub fn v0(
+        throttle_time_ms: i32,
+        error_code: ErrorCode,
+        error_message: Option<&str>,
+        entries: Option<Vec<EntryData>>,
+    ) -> Self {
+        Self::new(0, throttle_time_ms, error_code, error_message, entries)
+    }
+
+    pub fn v1(
+        throttle_time_ms: i32,
+        error_code: ErrorCode,
+        error_message: Option<&str>,
+        entries: Option<Vec<EntryData>>,
+    ) -> Self {
+        Self::new(1, throttle_time_ms, error_code, error_message, entries)
+    }
+
+    pub fn throttle_time_ms(&self) -> i32 {
+        self.throttle_time_ms
+    }
+
+    pub fn set_throttle_time_ms(&mut self, throttle_time_ms: i32) {
+        self.throttle_time_ms = throttle_time_ms;
+    }
+
+    pub fn error_code(&self) -> ErrorCode {
+        self.error_code
+    }
+
+    pub fn error_message(&self) -> Option<&[u8]> {
+        self.error_message.as_deref()
+    }
+
+    pub fn entries(&self) -> Option<&[EntryData]> {
+        self.entries.as_deref()
+    }
+}
+impl ReadableVersion for Response {
+    fn read_version<B: Buf>(buffer: &mut B, version: i16) -> Result<Self, protocol::Error> {
+        if !(0..=1).contains(&version) {
+            return Err(protocol::Error::UnsupportedVersion);
+        }
+
+        let throttle_time_ms = i32::read(buffer);
+        let error_code = ErrorCode::read(buffer);
+        let error_message = read_string(buffer, version)?;
+        let entries = if version == 0 {
+            Array::<EntryData>::read_version_inner(buffer, version)
+        } else {
+            CompactArray::<EntryData>::read_version_inner(buffer, version)
+        }?;
+        if version >= 1 {
+            let _tagged_fields = TaggedFields::read_result_inner(buffer)?;
+        }
+
+        Ok(Self {
+            version,
+            throttle_time_ms,
+            error_code,
+            error_message,
+            entries,
+        })
+    }
+}
+impl Writable for Response {
+    fn write<B: BufMut>(&self, buffer: &mut B) {
+        self.throttle_time_ms.write(buffer);
+        self.error_code.write(buffer);
+        write_string(buffer, self.version, self.error_message());
+        if self.version == 0 {
+            Array::write_inner(buffer, self.entries());
+        } else {
+            CompactArray::write_inner(buffer, self.entries());
+            TaggedFields::write_empty(buffer);
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct EntryData {
+    version: i16,
+    entity: Vec<EntityData>,
+    values: Vec<ValueData>,
+}
+impl EntryData {
+    fn new(version: i16, entity: Vec<EntityData>, values: Vec<ValueData>) -> Self {
+        Self {
+            version,
+            entity,
+            values,
+        }
+    }
+
+    pub fn v0(entity: Vec<EntityData>, values: Vec<ValueData>) -> Self {
+        Self::new(0, entity, values)
+    }
+
+    pub fn v1(entity: Vec<EntityData>, values: Vec<ValueData>) -> Self {
+        Self::new(1, entity, values)
+    }
+
+    pub fn entity(&self) -> &[EntityData] {
+        &self.entity
+    }
+
+    pub fn values(&self) -> &[ValueData] {
+        &self.values
+    }
+}
+impl ReadableVersion for EntryData {
+    fn read_version<B: Buf>(buffer: &mut B, version: i16) -> Result<Self, protocol::Error> {
+        if !(0..=1).contains(&version) {
+            return Err(protocol::Error::UnsupportedVersion);
+        }
+
+        let entity = if version == 0 {
+            Array::<EntityData>::read_version_inner(buffer, version)
+        } else {
+            CompactArray::<EntityData>::read_version_inner(buffer, version)
+        }?
+        .ok_or(protocol::Error::IllegalArgument(
+            "non-nullable field entity was serialized as null",
+        ))?;
+        let values = if version == 0 {
+            Array::<ValueData>::read_version_inner(buffer, version)
+        } else {
+            CompactArray::<ValueData>::read_version_inner(buffer, version)
+        }?
+        .ok_or(protocol::Error::IllegalArgument(
+            "non-nullable field values was serialized as null",
+        ))?;
+        if version >= 1 {
+            let _tagged_fields = TaggedFields::read_result_inner(buffer)?;
+        }
+
+        Ok(Self {
+            version,
+            entity,
+            values,
+        })
+    }
+}
+impl Writable for EntryData {
+    fn write<B: BufMut>(&self, buffer: &mut B) {
+        if self.version == 0 {
+            Array::write_inner(buffer, Some(self.entity()));
+            Array::write_inner(buffer, Some(self.values()));
+        } else {
+            CompactArray::write_inner(buffer, Some(self.entity()));
+            CompactArray::write_inner(buffer, Some(self.values()));
+            TaggedFields::write_empty(buffer);
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct EntityData {
+    version: i16,
+    entity_type: Bytes,
+    entity_name: Option<Bytes>,
+}
+impl EntityData {
+    pub(crate) fn new(version: i16, entity_type: &str, entity_name: Option<&str>) -> Self {
+        Self {
+            version,
+            entity_type: Bytes::copy_from_slice(entity_type.as_bytes()),
+            entity_name: entity_name.map(|name| Bytes::copy_from_slice(name.as_bytes())),
+        }
+    }
+
+    pub fn v0(entity_type: &str, entity_name: Option<&str>) -> Self {
+        Self::new(0, entity_type, entity_name)
+    }
+
+    pub fn v1(entity_type: &str, entity_name: Option<&str>) -> Self {
+        Self::new(1, entity_type, entity_name)
+    }
+
+    pub fn entity_type(&self) -> &[u8] {
+        &self.entity_type
+    }
+
+    // Null is the default entity of that type.
+    pub fn entity_name(&self) -> Option<&[u8]> {
+        self.entity_name.as_deref()
+    }
+}
+impl ReadableVersion for EntityData {
+    fn read_version<B: Buf>(buffer: &mut B, version: i16) -> Result<Self, protocol::Error> {
+        if !(0..=1).contains(&version) {
+            return Err(protocol::Error::UnsupportedVersion);
+        }
+
+        let entity_type = read_string(buffer, version)?.ok_or(protocol::Error::IllegalArgument(
+            "non-nullable field entityType was serialized as null",
+        ))?;
+        let entity_name = read_string(buffer, version)?;
+        if version >= 1 {
+            let _tagged_fields = TaggedFields::read_result_inner(buffer)?;
+        }
+
+        Ok(Self {
+            version,
+            entity_type,
+            entity_name,
+        })
+    }
+}
+impl Writable for EntityData {
+    fn write<B: BufMut>(&self, buffer: &mut B) {
+        write_string(buffer, self.version, Some(self.entity_type()));
+        write_string(buffer, self.version, self.entity_name());
+        if self.version >= 1 {
+            TaggedFields::write_empty(buffer);
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ValueData {
+    version: i16,
+    key: Bytes,
+    value: f64,
+}
+impl ValueData {
+    fn new(version: i16, key: &str, value: f64) -> Self {
+        Self {
+            version,
+            key: Bytes::copy_from_slice(key.as_bytes()),
+            value,
+        }
+    }
+
+    pub fn v0(key: &str, value: f64) -> Self {
+        Self::new(0, key, value)
+    }
+
+    pub fn v1(key: &str, value: f64) -> Self {
+        Self::new(1, key, value)
+    }
+
+    pub fn key(&self) -> &[u8] {
+        &self.key
+    }
+
+    pub fn value(&self) -> f64 {
+        self.value
+    }
+}
+impl ReadableVersion for ValueData {
+    fn read_version<B: Buf>(buffer: &mut B, version: i16) -> Result<Self, protocol::Error> {
+        if !(0..=1).contains(&version) {
+            return Err(protocol::Error::UnsupportedVersion);
+        }
+
+        let key = read_string(buffer, version)?.ok_or(protocol::Error::IllegalArgument(
+            "non-nullable field key was serialized as null",
+        ))?;
+        let value = f64::read(buffer);
+        if version >= 1 {
+            let _tagged_fields = TaggedFields::read_result_inner(buffer)?;
+        }
+
+        Ok(Self {
+            version,
+            key,
+            value,
+        })
+    }
+}
+impl Writable for ValueData {
+    fn write<B: BufMut>(&self, buffer: &mut B) {
+        write_string(buffer, self.version, Some(self.key()));
+        self.value.write(buffer);
+        if self.version >= 1 {
+            TaggedFields::write_empty(buffer);
+        }
+    }
+}
+
+// Strings in both messages are compact from version 1 on.
+pub(crate) fn read_string<B: Buf>(
+    buffer: &mut B,
+    version: i16,
+) -> Result<Option<Bytes>, protocol::Error> {
+    if version == 0 {
+        Ok(KafkaString::read_inner(buffer))
+    } else {
+        CompactKafkaString::read_result_inner(buffer)
+    }
+}
+
+pub(crate) fn write_string<B: BufMut>(buffer: &mut B, version: i16, value: Option<&[u8]>) {
+    if version == 0 {
+        KafkaString::write_inner(buffer, value);
+    } else {
+        CompactKafkaString::write_inner(buffer, value);
+    }
+}
+
+// Every component has to match an entity for it to be described; a strict
+// filter also rules out entities with entity types the filter does not name.
+pub fn process_request(
+    request: Request,
+    client_quotas: &HashMap<ClientQuotaEntity, HashMap<String, f64>>,
+    auth: &Authorization,
+) -> Result<Response, protocol::Error> {
+    let version = request.version;
+    if !auth.authorize(
+        AclOperation::DescribeConfigs,
+        ResourceType::Cluster,
+        CLUSTER_NAME,
+    ) {
+        return Ok(error_response(
+            version,
+            ErrorCode::ClusterAuthorizationFailed,
+        ));
+    }
+
+    let mut filter = BTreeMap::<&str, (i8, Option<&str>)>::new();
+    for component in request.components() {
+        let entity_type = std::str::from_utf8(component.entity_type())?;
+        let r#match = component.r#match().map(std::str::from_utf8).transpose()?;
+        let message = if !ENTITY_TYPES.contains(&entity_type) {
+            Some("Custom entity types are not supported")
+        } else if filter.contains_key(entity_type) {
+            Some("Duplicate entity type in filter")
+        } else {
+            match (component.match_type(), r#match) {
+                (MATCH_TYPE_EXACT, None) => Some("An exact match requires an entity name"),
+                (MATCH_TYPE_EXACT | MATCH_TYPE_DEFAULT | MATCH_TYPE_SPECIFIED, _) => None,
+                _ => Some("Unknown match type"),
+            }
+        };
+        if let Some(message) = message {
+            return Ok(Response::new(
+                version,
+                0,
+                ErrorCode::InvalidRequest,
+                Some(message),
+                None,
+            ));
+        }
+        filter.insert(entity_type, (component.match_type(), r#match));
+    }
+
+    let matches = |entity: &ClientQuotaEntity| {
+        let components_match = filter.iter().all(|(entity_type, (match_type, r#match))| {
+            match (entity.get(*entity_type), *match_type) {
+                (Some(name), MATCH_TYPE_EXACT) => name.as_deref() == *r#match,
+                (Some(name), MATCH_TYPE_DEFAULT) => name.is_none(),
+                (Some(name), _) => name.is_some(),
+                (None, _) => false,
+            }
+        });
+        let entity_types = entity.keys().map(String::as_str).collect::<HashSet<_>>();
+        components_match
+            && (!request.strict() || entity_types.iter().all(|t| filter.contains_key(t)))
+    };
+
+    let mut entities = client_quotas
+        .iter()
+        .filter(|(entity, _)| matches(entity))
+        .collect::<Vec<_>>();
+    entities.sort_by_key(|(entity, _)| *entity);
+    let entries = entities
+        .into_iter()
+        .map(|(entity, quotas)| {
+            let entity = entity
+                .iter()
+                .map(|(entity_type, name)| EntityData::new(version, entity_type, name.as_deref()))
+                .collect();
+            let mut values = quotas
+                .iter()
+                .map(|(key, value)| ValueData::new(version, key, *value))
+                .collect::<Vec<_>>();
+            values.sort_by(|a, b| a.key().cmp(b.key()));
+            EntryData::new(version, entity, values)
+        })
+        .collect();
+
+    Ok(Response::new(
+        version,
+        0,
+        ErrorCode::None,
+        None,
+        Some(entries),
+    ))
+}
+
+pub(crate) fn error_response(version: i16, error_code: ErrorCode) -> Response {
+    Response::new(
+        version.clamp(0, 1),
+        0,
+        error_code,
+        Some(error_code.message()),
+        None,
+    )
+}
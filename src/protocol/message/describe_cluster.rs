@@ -0,0 +1,412 @@
+#![allow(clippy::too_many_arguments)]
+use bytes::{Buf, BufMut, Bytes};
+
+use crate::{
+    model,
+    protocol::{
+        self,
+        r#type::{CompactArray, CompactKafkaString, TaggedFields},
+        ErrorCode, Readable, ReadableVersion, Writable,
+    },
+    security::{acl::CLUSTER_NAME, AclOperation, Authorization, ResourceType},
+};
+
+pub(crate) const API_KEY: i16 = 60;
+
+pub const ENDPOINT_TYPE_BROKERS: i8 = 1;
+pub const ENDPOINT_TYPE_CONTROLLERS: i8 = 2;
+
+const AUTHORIZED_OPERATIONS_OMITTED: i32 = i32::MIN;
+
+#[derive(Debug, Clone)]
+pub struct Request {
+    version: i16,
+    include_cluster_authorized_operations: bool,
+    endpoint_type: i8,
+}
+impl Request {
+    fn new(version: i16, include_cluster_authorized_operations: bool, endpoint_type: i8) -> Self {
+        Self {
+            version,
+            include_cluster_authorized_operations,
+            endpoint_type,
+        }
+    }
+
+    pub fn v0(include_cluster_authorized_operations: bool) -> Self {
+        Self::new(
+            0,
+            include_cluster_authorized_operations,
+            ENDPOINT_TYPE_BROKERS,
+        )
+    }
+
+    pub fn v1(include_cluster_authorized_operations: bool, endpoint_type: i8) -> Self {
+        Self::new(1, include_cluster_authorized_operations, endpoint_type)
+    }
+
+    pub fn include_cluster_authorized_operations(&self) -> bool {
+        self.include_cluster_authorized_operations
+    }
+
+    pub fn endpoint_type(&self) -> i8 {
+        self.endpoint_type
+    }
+}
+impl ReadableVersion for Request {
+    fn read_version<B: Buf>(buffer: &mut B, version: i16) -> Result<Self, protocol::Error> {
+        if !(0..=1).contains(&version) {
+            return Err(protocol::Error::UnsupportedVersion);
+        }
+
+        let include_cluster_authorized_operations = bool::read(buffer);
+        let endpoint_type = if version >= 1 {
+            i8::read(buffer)
+        } else {
+            ENDPOINT_TYPE_BROKERS
+        };
+        let _tagged_fields = TaggedFields::read_result_inner(buffer)?;
+
+        Ok(Self {
+            version,
+            include_cluster_authorized_operations,
+            endpoint_type,
+        })
+    }
+}
+impl Writable for Request {
+    fn write<B: BufMut>(&self, buffer: &mut B) {
+        self.include_cluster_authorized_operations.write(buffer);
+        if self.version >= 1 {
+            self.endpoint_type.write(buffer);
+        }
+        TaggedFields::write_empty(buffer);
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Response {
+    version: i16,
+    throttle_time_ms: i32,
+    error_code: ErrorCode,
+    error_message: Option<Bytes>,
+    endpoint_type: i8,
+    cluster_id: Bytes,
+    controller_id: i32,
+    brokers: Vec<DescribeClusterBroker>,
+    cluster_authorized_operations: i32,
+}
+impl Response {
+    fn new(
+        version: i16,
+        throttle_time_ms: i32,
+        error_code: ErrorCode,
+        error_message: Option<&str>,
+        endpoint_type: i8,
+        cluster_id: &str,
+        controller_id: i32,
+        brokers: Vec<DescribeClusterBroker>,
+        cluster_authorized_operations: i32,
+    ) -> Self {
+        Self {
+            version,
+            throttle_time_ms,
+            error_code,
+            error_message: error_message.map(|m| Bytes::copy_from_slice(m.as_bytes())),
+            endpoint_type,
+            cluster_id: Bytes::copy_from_slice(cluster_id.as_bytes()),
+            controller_id,
+            brokers,
+            cluster_authorized_operations,
+        }
+    }
+
+    pub fn v0(
+        throttle_time_ms: i32,
+        error_code: ErrorCode,
+        error_message: Option<&str>,
+        cluster_id: &str,
+        controller_id: i32,
+        brokers: Vec<DescribeClusterBroker>,
+        cluster_authorized_operations: i32,
+    ) -> Self {
+        Self::new(
+            0,
+            throttle_time_ms,
+            error_code,
+            error_message,
+            ENDPOINT_TYPE_BROKERS,
+            cluster_id,
+            controller_id,
+            brokers,
+            cluster_authorized_operations,
+        )
+    }
+
+    pub fn v1(
+        throttle_time_ms: i32,
+        error_code: ErrorCode,
+        error_message: Option<&str>,
+        endpoint_type: i8,
+        cluster_id: &str,
+        controller_id: i32,
+        brokers: Vec<DescribeClusterBroker>,
+        cluster_authorized_operations: i32,
+    ) -> Self {
+        Self::new(
+            1,
+            throttle_time_ms,
+            error_code,
+            error_message,
+            endpoint_type,
+            cluster_id,
+            controller_id,
+            brokers,
+            cluster_authorized_operations,
+        )
+    }
+
+    pub fn throttle_time_ms(&self) -> i32 {
+        self.throttle_time_ms
+    }
+
+    pub fn set_throttle_time_ms(&mut self, throttle_time_ms: i32) {
+        self.throttle_time_ms = throttle_time_ms;
+    }
+
+    pub fn error_code(&self) -> ErrorCode {
+        self.error_code
+    }
+
+    pub fn error_message(&self) -> Option<&[u8]> {
+        self.error_message.as_deref()
+    }
+
+    pub fn endpoint_type(&self) -> i8 {
+        self.endpoint_type
+    }
+
+    pub fn cluster_id(&self) -> &[u8] {
+        &self.cluster_id
+    }
+
+    pub fn controller_id(&self) -> i32 {
+        self.controller_id
+    }
+
+    pub fn brokers(&self) -> &[DescribeClusterBroker] {
+        &self.brokers
+    }
+
+    pub fn cluster_authorized_operations(&self) -> i32 {
+        self.cluster_authorized_operations
+    }
+}
+impl ReadableVersion for Response {
+    fn read_version<B: Buf>(buffer: &mut B, version: i16) -> Result<Self, protocol::Error> {
+        if !(0..=1).contains(&version) {
+            return Err(protocol::Error::UnsupportedVersion);
+        }
+
+        let throttle_time_ms = i32::read(buffer);
+        let error_code = ErrorCode::read(buffer);
+        let error_message = CompactKafkaString::read_result_inner(buffer)?;
+        let endpoint_type = if version >= 1 {
+            i8::read(buffer)
+        } else {
+            ENDPOINT_TYPE_BROKERS
+        };
+        let cluster_id = CompactKafkaString::read_result_inner(buffer)?.ok_or(
+            protocol::Error::IllegalArgument("non-nullable field clusterId was serialized as null"),
+        )?;
+        let controller_id = i32::read(buffer);
+        let brokers = CompactArray::<DescribeClusterBroker>::read_version_inner(buffer, version)?
+            .ok_or(protocol::Error::IllegalArgument(
+            "non-nullable field brokers was serialized as null",
+        ))?;
+        let cluster_authorized_operations = i32::read(buffer);
+        let _tagged_fields = TaggedFields::read_result_inner(buffer)?;
+
+        Ok(Self {
+            version,
+            throttle_time_ms,
+            error_code,
+            error_message,
+            endpoint_type,
+            cluster_id,
+            controller_id,
+            brokers,
+            cluster_authorized_operations,
+        })
+    }
+}
+impl Writable for Response {
+    fn write<B: BufMut>(&self, buffer: &mut B) {
+        self.throttle_time_ms.write(buffer);
+        self.error_code.write(buffer);
+        CompactKafkaString::write_inner(buffer, self.error_message());
+        if self.version >= 1 {
+            self.endpoint_type.write(buffer);
+        }
+        CompactKafkaString::write_inner(buffer, Some(self.cluster_id()));
+        self.controller_id.write(buffer);
+        CompactArray::write_inner(buffer, Some(self.brokers()));
+        self.cluster_authorized_operations.write(buffer);
+        TaggedFields::write_empty(buffer);
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct DescribeClusterBroker {
+    broker_id: i32,
+    host: Bytes,
+    port: i32,
+    rack: Option<Bytes>,
+}
+impl DescribeClusterBroker {
+    fn new(broker_id: i32, host: &str, port: i32, rack: Option<&str>) -> Self {
+        Self {
+            broker_id,
+            host: Bytes::copy_from_slice(host.as_bytes()),
+            port,
+            rack: rack.map(|r| Bytes::copy_from_slice(r.as_bytes())),
+        }
+    }
+
+    pub fn v0(broker_id: i32, host: &str, port: i32, rack: Option<&str>) -> Self {
+        Self::new(broker_id, host, port, rack)
+    }
+
+    pub fn v1(broker_id: i32, host: &str, port: i32, rack: Option<&str>) -> Self {
+        Self::new(broker_id, host, port, rack)
+    }
+
+    pub fn broker_id(&self) -> i32 {
+        self.broker_id
+    }
+
+    pub fn host(&self) -> &[u8] {
+        &self.host
+    }
+
+    pub fn port(&self) -> i32 {
+        self.port
+    }
+
+    pub fn rack(&self) -> Option<&[u8]> {
+        self.rack.as_deref()
+    }
+}
+impl ReadableVersion for DescribeClusterBroker {
+    fn read_version<B: Buf>(buffer: &mut B, version: i16) -> Result<Self, protocol::Error> {
+        if !(0..=1).contains(&version) {
+            return Err(protocol::Error::UnsupportedVersion);
+        }
+
+        let broker_id = i32::read(buffer);
+        let host = CompactKafkaString::read_result_inner(buffer)?.ok_or(
+            protocol::Error::IllegalArgument("non-nullable field host was serialized as null"),
+        )?;
+        let port = i32::read(buffer);
+        let rack = CompactKafkaString::read_result_inner(buffer)?;
+        let _tagged_fields = TaggedFields::read_result_inner(buffer)?;
+
+        Ok(Self {
+            broker_id,
+            host,
+            port,
+            rack,
+        })
+    }
+}
+impl Writable for DescribeClusterBroker {
+    fn write<B: BufMut>(&self, buffer: &mut B) {
+        self.broker_id.write(buffer);
+        CompactKafkaString::write_inner(buffer, Some(self.host()));
+        self.port.write(buffer);
+        CompactKafkaString::write_inner(buffer, self.rack());
+        TaggedFields::write_empty(buffer);
+    }
+}
+
+// Brokers are described with their endpoint on the listener the request
+// came in on. A broker does not know the controller quorum, so asking it for
+// controllers fails, and like Metadata it names a live broker as the
+// controller.
+pub fn process_request(
+    request: Request,
+    brokers: &[model::BrokerRegistration],
+    listener_name: &str,
+    cluster_id: Option<&str>,
+    auth: &Authorization,
+) -> Result<Response, protocol::Error> {
+    let version = request.version;
+    let cluster_id = cluster_id.unwrap_or_default();
+    if request.endpoint_type() != ENDPOINT_TYPE_BROKERS {
+        let message = if request.endpoint_type() == ENDPOINT_TYPE_CONTROLLERS {
+            "The request was sent to an endpoint of type BROKER, but we wanted an endpoint of type CONTROLLER".to_string()
+        } else {
+            format!("Unknown endpoint type {}", request.endpoint_type())
+        };
+        return Ok(Response::new(
+            version,
+            0,
+            ErrorCode::UnsupportedEndpointType,
+            Some(&message),
+            request.endpoint_type(),
+            cluster_id,
+            -1,
+            vec![],
+            AUTHORIZED_OPERATIONS_OMITTED,
+        ));
+    }
+
+    let brokers = brokers
+        .iter()
+        .filter_map(|broker| {
+            broker.listener(listener_name).map(|listener| {
+                DescribeClusterBroker::new(
+                    broker.id(),
+                    listener.host(),
+                    listener.port() as i32,
+                    broker.rack(),
+                )
+            })
+        })
+        .collect::<Vec<_>>();
+    let controller_id = brokers.iter().map(|b| b.broker_id()).min().unwrap_or(-1);
+    let cluster_authorized_operations = if !request.include_cluster_authorized_operations() {
+        AUTHORIZED_OPERATIONS_OMITTED
+    } else if auth.authorize(AclOperation::Describe, ResourceType::Cluster, CLUSTER_NAME) {
+        auth.authorized_operations(ResourceType::Cluster, CLUSTER_NAME)
+    } else {
+        0
+    };
+
+    Ok(Response::new(
+        version,
+        0,
+        ErrorCode::None,
+        None,
+        ENDPOINT_TYPE_BROKERS,
+        cluster_id,
+        controller_id,
+        brokers,
+        cluster_authorized_operations,
+    ))
+}
+
+pub(crate) fn error_response(version: i16, error_code: ErrorCode) -> Response {
+    let version = version.clamp(0, 1);
+    Response::new(
+        version,
+        0,
+        error_code,
+        Some(error_code.message()),
+        ENDPOINT_TYPE_BROKERS,
+        "",
+        -1,
+        vec![],
+        AUTHORIZED_OPERATIONS_OMITTED,
+    )
+}
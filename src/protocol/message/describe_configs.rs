@@ -0,0 +1,1179 @@
+#![allow(clippy::too_many_arguments)]
+use std::collections::HashMap;
+
+use bytes::{Buf, BufMut, Bytes};
+use uuid::Uuid;
+
+use crate::{
+    config::dynamic::{ConfigEntry, ConfigResolver, ConfigSource, ConfigType},
+    logging,
+    model::{self, ConfigResource},
+    protocol::{
+        self,
+        r#type::{Array, CompactArray, CompactKafkaString, KafkaString, TaggedFields},
+        ErrorCode, Readable, ReadableVersion, Writable,
+    },
+    security::{acl::CLUSTER_NAME, AclOperation, Authorization, ResourceType},
+};
+
+use super::topic_by_name;
+
+pub(crate) const API_KEY: i16 = 32;
+
+#[derive(Debug, Clone)]
+pub struct Request {
+    version: i16,
+    resources: Vec<DescribeConfigsResource>,
+    include_synonyms: bool,
+    include_documentation: bool,
+}
+impl Request {
+    fn new(
+        version: i16,
+        resources: Vec<DescribeConfigsResource>,
+        include_synonyms: bool,
+        include_documentation: bool,
+    ) -> Self {
+        Self {
+            version,
+            resources,
+            include_synonyms,
+            include_documentation,
+        }
+    }
+
+    pub fn v0(resources: Vec<DescribeConfigsResource>) -> Self {
+        Self::new(0, resources, false, false)
+    }
+
+    pub fn v1(resources: Vec<DescribeConfigsResource>, include_synonyms: bool) -> Self {
+        Self::new(1, resources, include_synonyms, false)
+    }
+
+    pub fn v2(resources: Vec<DescribeConfigsResource>, include_synonyms: bool) -> Self {
+        Self::new(2, resources, include_synonyms, false)
+    }
+
+    pub fn v3(
+        resources: Vec<DescribeConfigsResource>,
+        include_synonyms: bool,
+        include_documentation: bool,
+    ) -> Self {
+        Self::new(3, resources, include_synonyms, include_documentation)
+    }
+
+    pub fn v4(
+        resources: Vec<DescribeConfigsResource>,
+        include_synonyms: bool,
+        include_documentation: bool,
+    ) -> Self {
+        Self::new(4, resources, include_synonyms, include_documentation)
+    }
+
+    pub fn resources(&self) -> &[DescribeConfigsResource] {
+        &self.resources
+    }
+
+    pub fn include_synonyms(&self) -> bool {
+        self.include_synonyms
+    }
+
+    pub fn include_documentation(&self) -> bool {
+        self.include_documentation
+    }
+}
+impl ReadableVersion for Request {
+    fn read_version<B: Buf>(buffer: &mut B, version: i16) -> Result<Self, protocol::Error> {
+        if !(0..=4).contains(&version) {
+            return Err(protocol::Error::UnsupportedVersion);
+        }
+
+        let resources = if version <= 3 {
+            Array::<DescribeConfigsResource>::read_version_inner(buffer, version)
+        } else {
+            CompactArray::<DescribeConfigsResource>::read_version_inner(buffer, version)
+        }?
+        .ok_or(protocol::Error::IllegalArgument(
+            "non-nullable field resources was serialized as null",
+        ))?;
+        let include_synonyms = version >= 1 && bool::read(buffer);
+        let include_documentation = version >= 3 && bool::read(buffer);
+        if version >= 4 {
+            let _tagged_fields = TaggedFields::read_result_inner(buffer)?;
+        }
+
+        Ok(Self {
+            version,
+            resources,
+            include_synonyms,
+            include_documentation,
+        })
+    }
+}
+impl Writable for Request {
+    fn write<B: BufMut>(&self, buffer: &mut B) {
+        if self.version <= 3 {
+            Array::write_inner(buffer, Some(self.resources()));
+        } else {
+            CompactArray::write_inner(buffer, Some(self.resources()));
+        }
+        if self.version >= 1 {
+            self.include_synonyms.write(buffer);
+        }
+        if self.version >= 3 {
+            self.include_documentation.write(buffer);
+        }
+        if self.version >= 4 {
+            TaggedFields::write_empty(buffer);
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct DescribeConfigsResource {
+    version: i16,
+    resource_type: i8,
+    resource_name: Bytes,
+    configuration_keys: Option<Vec<Bytes>>,
+}
+impl DescribeConfigsResource {
+    fn new(
+        version: i16,
+        resource_type: i8,
+        resource_name: &str,
+        configuration_keys: Option<Vec<&str>>,
+    ) -> Self {
+        Self {
+            version,
+            resource_type,
+            resource_name: Bytes::copy_from_slice(resource_name.as_bytes()),
+            configuration_keys: configuration_keys.map(|keys| {
+                keys.into_iter()
+                    .map(|key| Bytes::copy_from_slice(key.as_bytes()))
+                    .collect()
+            }),
+        }
+    }
+
+    pub fn v0(
+        resource_type: i8,
+        resource_name: &str,
+        configuration_keys: Option<Vec<&str>>,
+    ) -> Self {
+        Self::new(0, resource_type, resource_name, configuration_keys)
+    }
+
+    pub fn v1(
+        resource_type: i8,
+        resource_name: &str,
+        configuration_keys: Option<Vec<&str>>,
+    ) -> Self {
+        Self::new(1, resource_type, resource_name, configuration_keys)
+    }
+
+    pub fn v2(
+        resource_type: i8,
+        resource_name: &str,
+        configuration_keys: Option<Vec<&str>>,
+    ) -> Self {
+        Self::new(2, resource_type, resource_name, configuration_keys)
+    }
+
+    pub fn v3(
+        resource_type: i8,
+        resource_name: &str,
+        configuration_keys: Option<Vec<&str>>,
+    ) -> Self {
+        Self::new(3, resource_type, resource_name, configuration_keys)
+    }
+
+    pub fn v4(
+        resource_type: i8,
+        resource_name: &str,
+        configuration_keys: Option<Vec<&str>>,
+    ) -> Self {
+        Self::new(4, resource_type, resource_name, configuration_keys)
+    }
+
+    pub fn resource_type(&self) -> i8 {
+        self.resource_type
+    }
+
+    pub fn resource_name(&self) -> &[u8] {
+        &self.resource_name
+    }
+
+    pub fn configuration_keys(&self) -> Option<&[Bytes]> {
+        self.configuration_keys.as_deref()
+    }
+}
+impl ReadableVersion for DescribeConfigsResource {
+    fn read_version<B: Buf>(buffer: &mut B, version: i16) -> Result<Self, protocol::Error> {
+        if !(0..=4).contains(&version) {
+            return Err(protocol::Error::UnsupportedVersion);
+        }
+
+        let resource_type = i8::read(buffer);
+        let resource_name = if version <= 3 {
+            KafkaString::read_inner(buffer)
+        } else {
+            CompactKafkaString::read_result_inner(buffer)?
+        }
+        .ok_or(protocol::Error::IllegalArgument(
+            "non-nullable field resourceName was serialized as null",
+        ))?;
+        let configuration_keys: Option<Vec<Option<Bytes>>> = if version <= 3 {
+            Array::<KafkaString>::read_inner(buffer).map(|keys| {
+                keys.iter()
+                    .map(|key| key.value().map(Bytes::copy_from_slice))
+                    .collect()
+            })
+        } else {
+            CompactArray::<CompactKafkaString>::read_result_inner(buffer)?.map(|keys| {
+                keys.iter()
+                    .map(|key| key.value().map(Bytes::copy_from_slice))
+                    .collect()
+            })
+        };
+        let configuration_keys = configuration_keys
+            .map(|keys| {
+                keys.into_iter().collect::<Option<Vec<_>>>().ok_or(
+                    protocol::Error::IllegalArgument(
+                        "non-nullable field configurationKeys was serialized with a null key",
+                    ),
+                )
+            })
+            .transpose()?;
+        if version >= 4 {
+            let _tagged_fields = TaggedFields::read_result_inner(buffer)?;
+        }
+
+        Ok(Self {
+            version,
+            resource_type,
+            resource_name,
+            configuration_keys,
+        })
+    }
+}
+impl Writable for DescribeConfigsResource {
+    fn write<B: BufMut>(&self, buffer: &mut B) {
+        self.resource_type.write(buffer);
+        if self.version <= 3 {
+            KafkaString::write_inner(buffer, Some(self.resource_name()));
+            let keys = self.configuration_keys().map(|keys| {
+                keys.iter()
+                    .map(|key| KafkaString::from(Some(key.clone())))
+                    .collect::<Vec<_>>()
+            });
+            Array::write_inner(buffer, keys.as_deref());
+        } else {
+            CompactKafkaString::write_inner(buffer, Some(self.resource_name()));
+            let keys = self.configuration_keys().map(|keys| {
+                keys.iter()
+                    .map(|key| CompactKafkaString::from(Some(key.clone())))
+                    .collect::<Vec<_>>()
+            });
+            CompactArray::write_inner(buffer, keys.as_deref());
+            TaggedFields::write_empty(buffer);
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Response {
+    version: i16,
+    throttle_time_ms: i32,
+    results: Vec<DescribeConfigsResult>,
+}
+impl Response {
+    fn new(version: i16, throttle_time_ms: i32, results: Vec<DescribeConfigsResult>) -> Self {
+        Self {
+            version,
+            throttle_time_ms,
+            results,
+        }
+    }
+
+    pub fn v0(throttle_time_ms: i32, results: Vec<DescribeConfigsResult>) -> Self {
+        Self::new(0, throttle_time_ms, results)
+    }
+
+    pub fn v1(throttle_time_ms: i32, results: Vec<DescribeConfigsResult>) -> Self {
+        Self::new(1, throttle_time_ms, results)
+    }
+
+    pub fn v2(throttle_time_ms: i32, results: Vec<DescribeConfigsResult>) -> Self {
+        Self::new(2, throttle_time_ms, results)
+    }
+
+    pub fn v3(throttle_time_ms: i32, results: Vec<DescribeConfigsResult>) -> Self {
+        Self::new(3, throttle_time_ms, results)
+    }
+
+    pub fn v4(throttle_time_ms: i32, results: Vec<DescribeConfigsResult>) -> Self {
+        Self::new(4, throttle_time_ms, results)
+    }
+
+    pub fn throttle_time_ms(&self) -> i32 {
+        self.throttle_time_ms
+    }
+
+    pub fn set_throttle_time_ms(&mut self, throttle_time_ms: i32) {
+        self.throttle_time_ms = throttle_time_ms;
+    }
+
+    pub fn results(&self) -> &[DescribeConfigsResult] {
+        &self.results
+    }
+}
+impl ReadableVersion for Response {
+    fn read_version<B: Buf>(buffer: &mut B, version: i16) -> Result<Self, protocol::Error> {
+        if !(0..=4).contains(&version) {
+            return Err(protocol::Error::UnsupportedVersion);
+        }
+
+        let throttle_time_ms = i32::read(buffer);
+        let results = if version <= 3 {
+            Array::<DescribeConfigsResult>::read_version_inner(buffer, version)
+        } else {
+            CompactArray::<DescribeConfigsResult>::read_version_inner(buffer, version)
+        }?
+        .ok_or(protocol::Error::IllegalArgument(
+            "non-nullable field results was serialized as null",
+        ))?;
+        if version >= 4 {
+            let _tagged_fields = TaggedFields::read_result_inner(buffer)?;
+        }
+
+        Ok(Self {
+            version,
+            throttle_time_ms,
+            results,
+        })
+    }
+}
+impl Writable for Response {
+    fn write<B: BufMut>(&self, buffer: &mut B) {
+        self.throttle_time_ms.write(buffer);
+        if self.version <= 3 {
+            Array::write_inner(buffer, Some(self.results()));
+        } else {
+            CompactArray::write_inner(buffer, Some(self.results()));
+            TaggedFields::write_empty(buffer);
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct DescribeConfigsResult {
+    version: i16,
+    error_code: ErrorCode,
+    error_message: Option<Bytes>,
+    resource_type: i8,
+    resource_name: Bytes,
+    configs: Vec<DescribeConfigsResourceResult>,
+}
+impl DescribeConfigsResult {
+    fn new(
+        version: i16,
+        error_code: ErrorCode,
+        error_message: Option<&str>,
+        resource_type: i8,
+        resource_name: &str,
+        configs: Vec<DescribeConfigsResourceResult>,
+    ) -> Self {
+        Self {
+            version,
+            error_code,
+            error_message: error_message.map(|m| Bytes::copy_from_slice(m.as_bytes())),
+            resource_type,
+            resource_name: Bytes::copy_from_slice(resource_name.as_bytes()),
+            configs,
+        }
+    }
+
+    pub fn v0(
+        error_code: ErrorCode,
+        error_message: Option<&str>,
+        resource_type: i8,
+        resource_name: &str,
+        configs: Vec<DescribeConfigsResourceResult>,
+    ) -> Self {
+        Self::new(
+            0,
+            error_code,
+            error_message,
+            resource_type,
+            resource_name,
+            configs,
+        )
+    }
+
+    pub fn v1(
+        error_code: ErrorCode,
+        error_message: Option<&str>,
+        resource_type: i8,
+        resource_name: &str,
+        configs: Vec<DescribeConfigsResourceResult>,
+    ) -> Self {
+        Self::new(
+            1,
+            error_code,
+            error_message,
+            resource_type,
+            resource_name,
+            configs,
+        )
+    }
+
+    pub fn v2(
+        error_code: ErrorCode,
+        error_message: Option<&str>,
+        resource_type: i8,
+        resource_name: &str,
+        configs: Vec<DescribeConfigsResourceResult>,
+    ) -> Self {
+        Self::new(
+            2,
+            error_code,
+            error_message,
+            resource_type,
+            resource_name,
+            configs,
+        )
+    }
+
+    pub fn v3(
+        error_code: ErrorCode,
+        error_message: Option<&str>,
+        resource_type: i8,
+        resource_name: &str,
+        configs: Vec<DescribeConfigsResourceResult>,
+    ) -> Self {
+        Self::new(
+            3,
+            error_code,
+            error_message,
+            resource_type,
+            resource_name,
+            configs,
+        )
+    }
+
+    pub fn v4(
+        error_code: ErrorCode,
+        error_message: Option<&str>,
+        resource_type: i8,
+        resource_name: &str,
+        configs: Vec<DescribeConfigsResourceResult>,
+    ) -> Self {
+        Self::new(
+            4,
+            error_code,
+            error_message,
+            resource_type,
+            resource_name,
+            configs,
+        )
+    }
+
+    pub fn error_code(&self) -> ErrorCode {
+        self.error_code
+    }
+
+    pub fn error_message(&self) -> Option<&[u8]> {
+        self.error_message.as_deref()
+    }
+
+    pub fn resource_type(&self) -> i8 {
+        self.resource_type
+    }
+
+    pub fn resource_name(&self) -> &[u8] {
+        &self.resource_name
+    }
+
+    pub fn configs(&self) -> &[DescribeConfigsResourceResult] {
+        &self.configs
+    }
+}
+impl ReadableVersion for DescribeConfigsResult {
+    fn read_version<B: Buf>(buffer: &mut B, version: i16) -> Result<Self, protocol::Error> {
+        if !(0..=4).contains(&version) {
+            return Err(protocol::Error::UnsupportedVersion);
+        }
+
+        let error_code = ErrorCode::read(buffer);
+        let error_message = if version <= 3 {
+            KafkaString::read_inner(buffer)
+        } else {
+            CompactKafkaString::read_result_inner(buffer)?
+        };
+        let resource_type = i8::read(buffer);
+        let resource_name = if version <= 3 {
+            KafkaString::read_inner(buffer)
+        } else {
+            CompactKafkaString::read_result_inner(buffer)?
+        }
+        .ok_or(protocol::Error::IllegalArgument(
+            "non-nullable field resourceName was serialized as null",
+        ))?;
+        let configs = if version <= 3 {
+            Array::<DescribeConfigsResourceResult>::read_version_inner(buffer, version)
+        } else {
+            CompactArray::<DescribeConfigsResourceResult>::read_version_inner(buffer, version)
+        }?
+        .ok_or(protocol::Error::IllegalArgument(
+            "non-nullable field configs was serialized as null",
+        ))?;
+        if version >= 4 {
+            let _tagged_fields = TaggedFields::read_result_inner(buffer)?;
+        }
+
+        Ok(Self {
+            version,
+            error_code,
+            error_message,
+            resource_type,
+            resource_name,
+            configs,
+        })
+    }
+}
+impl Writable for DescribeConfigsResult {
+    fn write<B: BufMut>(&self, buffer: &mut B) {
+        self.error_code.write(buffer);
+        if self.version <= 3 {
+            KafkaString::write_inner(buffer, self.error_message());
+            self.resource_type.write(buffer);
+            KafkaString::write_inner(buffer, Some(self.resource_name()));
+            Array::write_inner(buffer, Some(self.configs()));
+        } else {
+            CompactKafkaString::write_inner(buffer, self.error_message());
+            self.resource_type.write(buffer);
+            CompactKafkaString::write_inner(buffer, Some(self.resource_name()));
+            CompactArray::write_inner(buffer, Some(self.configs()));
+            TaggedFields::write_empty(buffer);
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct DescribeConfigsResourceResult {
+    version: i16,
+    name: Bytes,
+    value: Option<Bytes>,
+    read_only: bool,
+    is_default: bool,
+    config_source: i8,
+    is_sensitive: bool,
+    synonyms: Vec<DescribeConfigsSynonym>,
+    config_type: i8,
+    documentation: Option<Bytes>,
+}
+impl DescribeConfigsResourceResult {
+    fn new(
+        version: i16,
+        name: &str,
+        value: Option<&str>,
+        read_only: bool,
+        is_default: bool,
+        config_source: i8,
+        is_sensitive: bool,
+        synonyms: Vec<DescribeConfigsSynonym>,
+        config_type: i8,
+        documentation: Option<&str>,
+    ) -> Self {
+        Self {
+            version,
+            name: Bytes::copy_from_slice(name.as_bytes()),
+            value: value.map(|v| Bytes::copy_from_slice(v.as_bytes())),
+            read_only,
+            is_default,
+            config_source,
+            is_sensitive,
+            synonyms,
+            config_type,
+            documentation: documentation.map(|d| Bytes::copy_from_slice(d.as_bytes())),
+        }
+    }
+
+    pub fn v0(
+        name: &str,
+        value: Option<&str>,
+        read_only: bool,
+        is_default: bool,
+        is_sensitive: bool,
+    ) -> Self {
+        Self::new(
+            0,
+            name,
+            value,
+            read_only,
+            is_default,
+            -1,
+            is_sensitive,
+            vec![],
+            0,
+            None,
+        )
+    }
+
+    pub fn v1(
+        name: &str,
+        value: Option<&str>,
+        read_only: bool,
+        config_source: i8,
+        is_sensitive: bool,
+        synonyms: Vec<DescribeConfigsSynonym>,
+    ) -> Self {
+        Self::new(
+            1,
+            name,
+            value,
+            read_only,
+            false,
+            config_source,
+            is_sensitive,
+            synonyms,
+            0,
+            None,
+        )
+    }
+
+    pub fn v2(
+        name: &str,
+        value: Option<&str>,
+        read_only: bool,
+        config_source: i8,
+        is_sensitive: bool,
+        synonyms: Vec<DescribeConfigsSynonym>,
+    ) -> Self {
+        Self::new(
+            2,
+            name,
+            value,
+            read_only,
+            false,
+            config_source,
+            is_sensitive,
+            synonyms,
+            0,
+            None,
+        )
+    }
+
+    pub fn v3(
+        name: &str,
+        value: Option<&str>,
+        read_only: bool,
+        config_source: i8,
+        is_sensitive: bool,
+        synonyms: Vec<DescribeConfigsSynonym>,
+        config_type: i8,
+        documentation: Option<&str>,
+    ) -> Self {
+        Self::new(
+            3,
+            name,
+            value,
+            read_only,
+            false,
+            config_source,
+            is_sensitive,
+            synonyms,
+            config_type,
+            documentation,
+        )
+    }
+
+    pub fn v4(
+        name: &str,
+        value: Option<&str>,
+        read_only: bool,
+        config_source: i8,
+        is_sensitive: bool,
+        synonyms: Vec<DescribeConfigsSynonym>,
+        config_type: i8,
+        documentation: Option<&str>,
+    ) -> Self {
+        Self::new(
+            4,
+            name,
+            value,
+            read_only,
+            false,
+            config_source,
+            is_sensitive,
+            synonyms,
+            config_type,
+            documentation,
+        )
+    }
+
+    pub fn name(&self) -> &[u8] {
+        &self.name
+    }
+
+    pub fn value(&self) -> Option<&[u8]> {
+        self.value.as_deref()
+    }
+
+    pub fn read_only(&self) -> bool {
+        self.read_only
+    }
+
+    pub fn is_default(&self) -> bool {
+        self.is_default
+    }
+
+    pub fn config_source(&self) -> i8 {
+        self.config_source
+    }
+
+    pub fn is_sensitive(&self) -> bool {
+        self.is_sensitive
+    }
+
+    pub fn synonyms(&self) -> &[DescribeConfigsSynonym] {
+        &self.synonyms
+    }
+
+    pub fn config_type(&self) -> i8 {
+        self.config_type
+    }
+
+    pub fn documentation(&self) -> Option<&[u8]> {
+        self.documentation.as_deref()
+    }
+}
+impl ReadableVersion for DescribeConfigsResourceResult {
+    fn read_version<B: Buf>(buffer: &mut B, version: i16) -> Result<Self, protocol::Error> {
+        if !(0..=4).contains(&version) {
+            return Err(protocol::Error::UnsupportedVersion);
+        }
+
+        let (name, value) = if version <= 3 {
+            (
+                KafkaString::read_inner(buffer),
+                KafkaString::read_inner(buffer),
+            )
+        } else {
+            (
+                CompactKafkaString::read_result_inner(buffer)?,
+                CompactKafkaString::read_result_inner(buffer)?,
+            )
+        };
+        let name = name.ok_or(protocol::Error::IllegalArgument(
+            "non-nullable field name was serialized as null",
+        ))?;
+        let read_only = bool::read(buffer);
+        let is_default = version == 0 && bool::read(buffer);
+        let config_source = if version >= 1 { i8::read(buffer) } else { -1 };
+        let is_sensitive = bool::read(buffer);
+        let synonyms = match version {
+            0 => Some(vec![]),
+            1..=3 => Array::<DescribeConfigsSynonym>::read_version_inner(buffer, version)?,
+            _ => CompactArray::<DescribeConfigsSynonym>::read_version_inner(buffer, version)?,
+        }
+        .ok_or(protocol::Error::IllegalArgument(
+            "non-nullable field synonyms was serialized as null",
+        ))?;
+        let (config_type, documentation) = match version {
+            0..=2 => (0, None),
+            3 => (i8::read(buffer), KafkaString::read_inner(buffer)),
+            _ => (
+                i8::read(buffer),
+                CompactKafkaString::read_result_inner(buffer)?,
+            ),
+        };
+        if version >= 4 {
+            let _tagged_fields = TaggedFields::read_result_inner(buffer)?;
+        }
+
+        Ok(Self {
+            version,
+            name,
+            value,
+            read_only,
+            is_default,
+            config_source,
+            is_sensitive,
+            synonyms,
+            config_type,
+            documentation,
+        })
+    }
+}
+impl Writable for DescribeConfigsResourceResult {
+    fn write<B: BufMut>(&self, buffer: &mut B) {
+        if self.version <= 3 {
+            KafkaString::write_inner(buffer, Some(self.name()));
+            KafkaString::write_inner(buffer, self.value());
+        } else {
+            CompactKafkaString::write_inner(buffer, Some(self.name()));
+            CompactKafkaString::write_inner(buffer, self.value());
+        }
+        self.read_only.write(buffer);
+        if self.version == 0 {
+            self.is_default.write(buffer);
+        } else {
+            self.config_source.write(buffer);
+        }
+        self.is_sensitive.write(buffer);
+        match self.version {
+            0 => {}
+            1..=3 => Array::write_inner(buffer, Some(self.synonyms())),
+            _ => CompactArray::write_inner(buffer, Some(self.synonyms())),
+        }
+        match self.version {
+            0..=2 => {}
+            3 => {
+                self.config_type.write(buffer);
+                KafkaString::write_inner(buffer, self.documentation());
+            }
+            _ => {
+                self.config_type.write(buffer);
+                CompactKafkaString::write_inner(buffer, self.documentation());
+                TaggedFields::write_empty(buffer);
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct DescribeConfigsSynonym {
+    version: i16,
+    name: Bytes,
+    value: Option<Bytes>,
+    source: i8,
+}
+impl DescribeConfigsSynonym {
+    fn new(version: i16, name: &str, value: Option<&str>, source: i8) -> Self {
+        Self {
+            version,
+            name: Bytes::copy_from_slice(name.as_bytes()),
+            value: value.map(|v| Bytes::copy_from_slice(v.as_bytes())),
+            source,
+        }
+    }
+
+    pub fn v1(name: &str, value: Option<&str>, source: i8) -> Self {
+        Self::new(1, name, value, source)
+    }
+
+    pub fn v2(name: &str, value: Option<&str>, source: i8) -> Self {
+        Self::new(2, name, value, source)
+    }
+
+    pub fn v3(name: &str, value: Option<&str>, source: i8) -> Self {
+        Self::new(3, name, value, source)
+    }
+
+    pub fn v4(name: &str, value: Option<&str>, source: i8) -> Self {
+        Self::new(4, name, value, source)
+    }
+
+    pub fn name(&self) -> &[u8] {
+        &self.name
+    }
+
+    pub fn value(&self) -> Option<&[u8]> {
+        self.value.as_deref()
+    }
+
+    pub fn source(&self) -> i8 {
+        self.source
+    }
+}
+impl ReadableVersion for DescribeConfigsSynonym {
+    fn read_version<B: Buf>(buffer: &mut B, version: i16) -> Result<Self, protocol::Error> {
+        if !(1..=4).contains(&version) {
+            return Err(protocol::Error::UnsupportedVersion);
+        }
+
+        let (name, value) = if version <= 3 {
+            (
+                KafkaString::read_inner(buffer),
+                KafkaString::read_inner(buffer),
+            )
+        } else {
+            (
+                CompactKafkaString::read_result_inner(buffer)?,
+                CompactKafkaString::read_result_inner(buffer)?,
+            )
+        };
+        let name = name.ok_or(protocol::Error::IllegalArgument(
+            "non-nullable field name was serialized as null",
+        ))?;
+        let source = i8::read(buffer);
+        if version >= 4 {
+            let _tagged_fields = TaggedFields::read_result_inner(buffer)?;
+        }
+
+        Ok(Self {
+            version,
+            name,
+            value,
+            source,
+        })
+    }
+}
+impl Writable for DescribeConfigsSynonym {
+    fn write<B: BufMut>(&self, buffer: &mut B) {
+        if self.version <= 3 {
+            KafkaString::write_inner(buffer, Some(self.name()));
+            KafkaString::write_inner(buffer, self.value());
+            self.source.write(buffer);
+        } else {
+            CompactKafkaString::write_inner(buffer, Some(self.name()));
+            CompactKafkaString::write_inner(buffer, self.value());
+            self.source.write(buffer);
+            TaggedFields::write_empty(buffer);
+        }
+    }
+}
+
+pub fn process_request(
+    request: Request,
+    metadata: &HashMap<Uuid, model::Topic>,
+    resolver: &ConfigResolver,
+    node_id: i32,
+    auth: &Authorization,
+) -> Result<Response, protocol::Error> {
+    let version = request.version;
+    let mut results = Vec::with_capacity(request.resources().len());
+    for resource in request.resources() {
+        let name = std::str::from_utf8(resource.resource_name())?;
+        let keys = resource
+            .configuration_keys()
+            .map(|keys| {
+                keys.iter()
+                    .map(|key| std::str::from_utf8(key))
+                    .collect::<Result<Vec<_>, _>>()
+            })
+            .transpose()?;
+        if resource.resource_type() == ConfigResource::BROKER_LOGGER {
+            results.push(logger_result(version, name, keys.as_deref(), node_id, auth));
+            continue;
+        }
+
+        let entries = match resource.resource_type() {
+            ConfigResource::TOPIC
+                if !auth.authorize(AclOperation::DescribeConfigs, ResourceType::Topic, name) =>
+            {
+                Err((
+                    ErrorCode::TopicAuthorizationFailed,
+                    ErrorCode::TopicAuthorizationFailed.message().to_string(),
+                ))
+            }
+            ConfigResource::BROKER
+                if !auth.authorize(
+                    AclOperation::DescribeConfigs,
+                    ResourceType::Cluster,
+                    CLUSTER_NAME,
+                ) =>
+            {
+                Err((
+                    ErrorCode::ClusterAuthorizationFailed,
+                    ErrorCode::ClusterAuthorizationFailed.message().to_string(),
+                ))
+            }
+            ConfigResource::TOPIC => match topic_by_name(metadata, name) {
+                Some(_) => Ok(resolver.topic_configs(name)),
+                None => Err((
+                    ErrorCode::UnknownTopicOrPartition,
+                    format!("Topic {} does not exist.", name),
+                )),
+            },
+            // The empty name stands for the cluster-wide defaults, of which
+            // only the dynamic ones are described.
+            ConfigResource::BROKER if name.is_empty() => Ok(resolver
+                .broker_configs(name)
+                .into_iter()
+                .filter(|entry| entry.source() == ConfigSource::DynamicDefaultBrokerConfig)
+                .collect()),
+            ConfigResource::BROKER if name == node_id.to_string() => {
+                Ok(resolver.broker_configs(name))
+            }
+            ConfigResource::BROKER => Err((
+                ErrorCode::InvalidRequest,
+                format!(
+                    "Unexpected broker id, expected {} but received {}",
+                    node_id, name
+                ),
+            )),
+            resource_type => Err((
+                ErrorCode::InvalidRequest,
+                format!("Unsupported resource type {}", resource_type),
+            )),
+        };
+
+        let result = match entries {
+            Ok(entries) => {
+                let configs = entries
+                    .iter()
+                    .filter(|entry| {
+                        keys.as_ref()
+                            .map_or(true, |keys| keys.contains(&entry.name()))
+                    })
+                    .map(|entry| {
+                        resource_result(
+                            version,
+                            entry,
+                            request.include_synonyms(),
+                            request.include_documentation(),
+                        )
+                    })
+                    .collect();
+                DescribeConfigsResult::new(
+                    version,
+                    ErrorCode::None,
+                    None,
+                    resource.resource_type(),
+                    name,
+                    configs,
+                )
+            }
+            Err((error_code, message)) => DescribeConfigsResult::new(
+                version,
+                error_code,
+                Some(&message),
+                resource.resource_type(),
+                name,
+                vec![],
+            ),
+        };
+        results.push(result);
+    }
+
+    Ok(Response::new(version, 0, results))
+}
+
+// Loggers are described with their current level when they have one of
+// their own; the others follow the root logger.
+fn logger_result(
+    version: i16,
+    name: &str,
+    keys: Option<&[&str]>,
+    node_id: i32,
+    auth: &Authorization,
+) -> DescribeConfigsResult {
+    let error = if !auth.authorize(
+        AclOperation::DescribeConfigs,
+        ResourceType::Cluster,
+        CLUSTER_NAME,
+    ) {
+        Some((
+            ErrorCode::ClusterAuthorizationFailed,
+            ErrorCode::ClusterAuthorizationFailed.message().to_string(),
+        ))
+    } else if name != node_id.to_string() {
+        Some((
+            ErrorCode::InvalidRequest,
+            format!(
+                "Unexpected broker id, expected {} but received {}",
+                node_id, name
+            ),
+        ))
+    } else {
+        None
+    };
+    if let Some((error_code, message)) = error {
+        return DescribeConfigsResult::new(
+            version,
+            error_code,
+            Some(&message),
+            ConfigResource::BROKER_LOGGER,
+            name,
+            vec![],
+        );
+    }
+
+    let configs = logging::loggers()
+        .into_iter()
+        .filter(|(logger, _)| keys.map_or(true, |keys| keys.contains(&logger.as_str())))
+        .map(|(logger, level)| {
+            DescribeConfigsResourceResult::new(
+                version,
+                &logger,
+                Some(&level),
+                false,
+                false,
+                ConfigSource::DynamicBrokerLoggerConfig.id(),
+                false,
+                vec![],
+                ConfigType::String.id(),
+                None,
+            )
+        })
+        .collect();
+    DescribeConfigsResult::new(
+        version,
+        ErrorCode::None,
+        None,
+        ConfigResource::BROKER_LOGGER,
+        name,
+        configs,
+    )
+}
+
+// Sensitive values are never sent back, neither for the entry nor for its
+// synonyms.
+fn resource_result(
+    version: i16,
+    entry: &ConfigEntry,
+    include_synonyms: bool,
+    include_documentation: bool,
+) -> DescribeConfigsResourceResult {
+    let synonyms = if include_synonyms && version >= 1 {
+        entry
+            .synonyms()
+            .iter()
+            .map(|synonym| {
+                DescribeConfigsSynonym::new(
+                    version,
+                    synonym.name(),
+                    synonym.value().filter(|_| !entry.is_sensitive()),
+                    synonym.source().id(),
+                )
+            })
+            .collect()
+    } else {
+        vec![]
+    };
+    let documentation = if include_documentation && version >= 3 {
+        Some(entry.documentation())
+    } else {
+        None
+    };
+    DescribeConfigsResourceResult::new(
+        version,
+        entry.name(),
+        entry.value().filter(|_| !entry.is_sensitive()),
+        entry.read_only(),
+        entry.is_default(),
+        entry.source().id(),
+        entry.is_sensitive(),
+        synonyms,
+        entry.r#type().id(),
+        documentation,
+    )
+}
+
+pub(crate) fn error_response(version: i16, error_code: ErrorCode) -> Response {
+    let version = version.clamp(0, 4);
+    Response::new(
+        version,
+        0,
+        vec![DescribeConfigsResult::new(
+            version,
+            error_code,
+            None,
+            0,
+            "",
+            vec![],
+        )],
+    )
+}
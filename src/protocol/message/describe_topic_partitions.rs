@@ -9,8 +9,9 @@ use crate::{
     protocol::{
         self,
         r#type::{CompactArray, CompactKafkaString, NullableRecord, TaggedFields},
-        Readable, ReadableVersion, Writable,
+        ErrorCode, Readable, ReadableVersion, Writable,
     },
+    security::{AclOperation, Authorization, ResourceType},
 };
 
 use super::topic_by_name;
@@ -180,6 +181,10 @@ impl Response {
         self.throttle_time_ms
     }
 
+    pub fn set_throttle_time_ms(&mut self, throttle_time_ms: i32) {
+        self.throttle_time_ms = throttle_time_ms;
+    }
+
     pub fn topics(&self) -> &[DescribeTopicPartitionsResponseTopic] {
         self.topics.as_ref()
     }
@@ -217,7 +222,7 @@ impl Writable for Response {
 
 #[derive(Debug, Clone)]
 pub struct DescribeTopicPartitionsResponseTopic {
-    error_code: i16,
+    error_code: ErrorCode,
     name: Option<Bytes>,
     topic_id: Uuid,
     is_internal: bool,
@@ -226,7 +231,7 @@ pub struct DescribeTopicPartitionsResponseTopic {
 }
 impl DescribeTopicPartitionsResponseTopic {
     pub fn v0(
-        error_code: i16,
+        error_code: ErrorCode,
         name: Option<&str>,
         topic_id: Uuid,
         is_internal: bool,
@@ -243,7 +248,7 @@ impl DescribeTopicPartitionsResponseTopic {
         }
     }
 
-    pub fn error_code(&self) -> i16 {
+    pub fn error_code(&self) -> ErrorCode {
         self.error_code
     }
 
@@ -273,7 +278,7 @@ impl ReadableVersion for DescribeTopicPartitionsResponseTopic {
             return Err(protocol::Error::UnsupportedVersion);
         }
 
-        let error_code = i16::read(buffer);
+        let error_code = ErrorCode::read(buffer);
         let name = CompactKafkaString::read_result_inner(buffer)?;
         let topic_id = Uuid::read(buffer);
         let is_internal = bool::read(buffer);
@@ -308,7 +313,7 @@ impl Writable for DescribeTopicPartitionsResponseTopic {
 
 #[derive(Debug, Clone)]
 pub struct DescribeTopicPartitionsResponsePartition {
-    error_code: i16,
+    error_code: ErrorCode,
     partition_index: i32,
     leader_id: i32,
     leader_epoch: i32,
@@ -320,7 +325,7 @@ pub struct DescribeTopicPartitionsResponsePartition {
 }
 impl DescribeTopicPartitionsResponsePartition {
     pub fn v0(
-        error_code: i16,
+        error_code: ErrorCode,
         partition_index: i32,
         leader_id: i32,
         leader_epoch: i32,
@@ -343,7 +348,7 @@ impl DescribeTopicPartitionsResponsePartition {
         }
     }
 
-    pub fn error_code(&self) -> i16 {
+    pub fn error_code(&self) -> ErrorCode {
         self.error_code
     }
 
@@ -385,7 +390,7 @@ impl ReadableVersion for DescribeTopicPartitionsResponsePartition {
             return Err(protocol::Error::UnsupportedVersion);
         }
 
-        let error_code = i16::read(buffer);
+        let error_code = ErrorCode::read(buffer);
         let partition_index = i32::read(buffer);
         let leader_id = i32::read(buffer);
         let leader_epoch = i32::read(buffer);
@@ -433,48 +438,61 @@ impl Writable for DescribeTopicPartitionsResponsePartition {
 pub fn process_request(
     request: Request,
     metadata: &HashMap<Uuid, model::Topic>,
+    auth: &Authorization,
 ) -> Result<Response, protocol::Error> {
     let topics = {
         let mut result = Vec::new();
         for tr in request.topics() {
             let topic_name = std::str::from_utf8(tr.name())?;
-            let response = if let Some(topic) = topic_by_name(metadata, topic_name) {
-                DescribeTopicPartitionsResponseTopic {
-                    error_code: 0,
-                    name: Some(tr.name.clone()),
-                    topic_id: topic.id(),
-                    is_internal: false,
-                    partitions: {
-                        let mut result = vec![];
-                        for (index, partition) in topic.partitions().iter().enumerate() {
-                            result.push(DescribeTopicPartitionsResponsePartition {
-                                error_code: 0,
-                                partition_index: index as i32,
-                                leader_id: partition.leader(),
-                                leader_epoch: partition.leader_epoch(),
-                                replica_nodes: partition.replicas().to_vec(),
-                                isr_nodes: partition.isr().to_vec(),
-                                eligible_leader_replicas: partition
-                                    .eligible_leader_replicas()
-                                    .map(|v| v.to_vec()),
-                                last_known_elr: partition.last_known_elr().map(|v| v.to_vec()),
-                                offline_replicas: vec![],
-                            });
-                        }
-                        result
-                    },
-                    topic_authorized_operations: 0xdf8,
-                }
-            } else {
-                DescribeTopicPartitionsResponseTopic {
-                    error_code: 3,
-                    name: Some(tr.name.clone()),
-                    topic_id: Uuid::nil(),
-                    is_internal: false,
-                    partitions: vec![],
-                    topic_authorized_operations: 0xdf8,
-                }
-            };
+            let response =
+                if !auth.authorize(AclOperation::Describe, ResourceType::Topic, topic_name) {
+                    DescribeTopicPartitionsResponseTopic {
+                        error_code: ErrorCode::TopicAuthorizationFailed,
+                        name: Some(tr.name.clone()),
+                        topic_id: Uuid::nil(),
+                        is_internal: false,
+                        partitions: vec![],
+                        topic_authorized_operations: 0,
+                    }
+                } else if let Some(topic) = topic_by_name(metadata, topic_name) {
+                    DescribeTopicPartitionsResponseTopic {
+                        error_code: ErrorCode::None,
+                        name: Some(tr.name.clone()),
+                        topic_id: topic.id(),
+                        is_internal: false,
+                        partitions: {
+                            let mut result = vec![];
+                            for (index, partition) in topic.partitions().iter().enumerate() {
+                                result.push(DescribeTopicPartitionsResponsePartition {
+                                    error_code: ErrorCode::None,
+                                    partition_index: index as i32,
+                                    leader_id: partition.leader(),
+                                    leader_epoch: partition.leader_epoch(),
+                                    replica_nodes: partition.replicas().to_vec(),
+                                    isr_nodes: partition.isr().to_vec(),
+                                    eligible_leader_replicas: partition
+                                        .eligible_leader_replicas()
+                                        .map(|v| v.to_vec()),
+                                    last_known_elr: partition.last_known_elr().map(|v| v.to_vec()),
+                                    offline_replicas: vec![],
+                                });
+                            }
+                            result
+                        },
+                        topic_authorized_operations: auth
+                            .authorized_operations(ResourceType::Topic, topic_name),
+                    }
+                } else {
+                    DescribeTopicPartitionsResponseTopic {
+                        error_code: ErrorCode::UnknownTopicOrPartition,
+                        name: Some(tr.name.clone()),
+                        topic_id: Uuid::nil(),
+                        is_internal: false,
+                        partitions: vec![],
+                        topic_authorized_operations: auth
+                            .authorized_operations(ResourceType::Topic, topic_name),
+                    }
+                };
 
             result.push(response);
         }
@@ -486,3 +504,18 @@ pub fn process_request(
         next_cursor: None,
     })
 }
+
+pub(crate) fn error_response(error_code: ErrorCode) -> Response {
+    Response {
+        throttle_time_ms: 0,
+        topics: vec![DescribeTopicPartitionsResponseTopic {
+            error_code,
+            name: None,
+            topic_id: Uuid::nil(),
+            is_internal: false,
+            partitions: vec![],
+            topic_authorized_operations: 0,
+        }],
+        next_cursor: None,
+    }
+}
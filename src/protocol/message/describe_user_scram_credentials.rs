@@ -0,0 +1,345 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use bytes::{Buf, BufMut, Bytes};
+
+use crate::{
+    model::ScramCredential,
+    protocol::{
+        self,
+        r#type::{CompactArray, CompactKafkaString, TaggedFields},
+        ErrorCode, Readable, ReadableResult, ReadableVersion, Writable,
+    },
+    security::{acl::CLUSTER_NAME, AclOperation, Authorization, ResourceType},
+};
+
+pub(crate) const API_KEY: i16 = 50;
+
+#[derive(Debug, Clone)]
+pub struct Request {
+    users: Option<Vec<UserName>>,
+}
+impl Request {
+    pub fn v0(users: Option<Vec<UserName>>) -> Self {
+        Self { users }
+    }
+
+    pub fn users(&self) -> Option<&[UserName]> {
+        self.users.as_deref()
+    }
+}
+impl ReadableVersion for Request {
+    fn read_version<B: Buf>(buffer: &mut B, version: i16) -> Result<Self, protocol::Error> {
+        if version != 0 {
+            return Err(protocol::Error::UnsupportedVersion);
+        }
+
+        let users = CompactArray::<UserName>::read_result_inner(buffer)?;
+        let _tagged_fields = TaggedFields::read_result_inner(buffer)?;
+
+        Ok(Self { users })
+    }
+}
+impl Writable for Request {
+    fn write<B: BufMut>(&self, buffer: &mut B) {
+        CompactArray::write_inner(buffer, self.users());
+        TaggedFields::write_empty(buffer);
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct UserName {
+    name: Bytes,
+}
+impl UserName {
+    pub fn v0(name: &str) -> Self {
+        Self {
+            name: Bytes::copy_from_slice(name.as_bytes()),
+        }
+    }
+
+    pub fn name(&self) -> &[u8] {
+        &self.name
+    }
+}
+impl ReadableResult for UserName {
+    fn read_result<B: Buf>(buffer: &mut B) -> Result<Self, protocol::Error> {
+        let name = CompactKafkaString::read_result_inner(buffer)?.ok_or(
+            protocol::Error::IllegalArgument("non-nullable field name was serialized as null"),
+        )?;
+        let _tagged_fields = TaggedFields::read_result_inner(buffer)?;
+
+        Ok(Self { name })
+    }
+}
+impl Writable for UserName {
+    fn write<B: BufMut>(&self, buffer: &mut B) {
+        CompactKafkaString::write_inner(buffer, Some(self.name()));
+        TaggedFields::write_empty(buffer);
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Response {
+    throttle_time_ms: i32,
+    error_code: ErrorCode,
+    error_message: Option<Bytes>,
+    results: Vec<DescribeUserScramCredentialsResult>,
+}
+impl Response {
+    pub fn v0(
+        throttle_time_ms: i32,
+        error_code: ErrorCode,
+        error_message: Option<&str>,
+        results: Vec<DescribeUserScramCredentialsResult>,
+    ) -> Self {
+        Self {
+            throttle_time_ms,
+            error_code,
+            error_message: error_message.map(|m| Bytes::copy_from_slice(m.as_bytes())),
+            results,
+        }
+    }
+
+    pub fn throttle_time_ms(&self) -> i32 {
+        self.throttle_time_ms
+    }
+
+    pub fn set_throttle_time_ms(&mut self, throttle_time_ms: i32) {
+        self.throttle_time_ms = throttle_time_ms;
+    }
+
+    pub fn error_code(&self) -> ErrorCode {
+        self.error_code
+    }
+
+    pub fn error_message(&self) -> Option<&[u8]> {
+        self.error_message.as_deref()
+    }
+
+    pub fn results(&self) -> &[DescribeUserScramCredentialsResult] {
+        &self.results
+    }
+}
+impl ReadableVersion for Response {
+    fn read_version<B: Buf>(buffer: &mut B, version: i16) -> Result<Self, protocol::Error> {
+        if version != 0 {
+            return Err(protocol::Error::UnsupportedVersion);
+        }
+
+        let throttle_time_ms = i32::read(buffer);
+        let error_code = ErrorCode::read(buffer);
+        let error_message = CompactKafkaString::read_result_inner(buffer)?;
+        let results = CompactArray::<DescribeUserScramCredentialsResult>::read_result_inner(
+            buffer,
+        )?
+        .ok_or(protocol::Error::IllegalArgument(
+            "non-nullable field results was serialized as null",
+        ))?;
+        let _tagged_fields = TaggedFields::read_result_inner(buffer)?;
+
+        Ok(Self {
+            throttle_time_ms,
+            error_code,
+            error_message,
+            results,
+        })
+    }
+}
+impl Writable for Response {
+    fn write<B: BufMut>(&self, buffer: &mut B) {
+        self.throttle_time_ms.write(buffer);
+        self.error_code.write(buffer);
+        CompactKafkaString::write_inner(buffer, self.error_message());
+        CompactArray::write_inner(buffer, Some(self.results()));
+        TaggedFields::write_empty(buffer);
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct DescribeUserScramCredentialsResult {
+    user: Bytes,
+    error_code: ErrorCode,
+    error_message: Option<Bytes>,
+    credential_infos: Vec<CredentialInfo>,
+}
+impl DescribeUserScramCredentialsResult {
+    pub fn v0(
+        user: &str,
+        error_code: ErrorCode,
+        error_message: Option<&str>,
+        credential_infos: Vec<CredentialInfo>,
+    ) -> Self {
+        Self {
+            user: Bytes::copy_from_slice(user.as_bytes()),
+            error_code,
+            error_message: error_message.map(|m| Bytes::copy_from_slice(m.as_bytes())),
+            credential_infos,
+        }
+    }
+
+    pub fn user(&self) -> &[u8] {
+        &self.user
+    }
+
+    pub fn error_code(&self) -> ErrorCode {
+        self.error_code
+    }
+
+    pub fn error_message(&self) -> Option<&[u8]> {
+        self.error_message.as_deref()
+    }
+
+    pub fn credential_infos(&self) -> &[CredentialInfo] {
+        &self.credential_infos
+    }
+}
+impl ReadableResult for DescribeUserScramCredentialsResult {
+    fn read_result<B: Buf>(buffer: &mut B) -> Result<Self, protocol::Error> {
+        let user = CompactKafkaString::read_result_inner(buffer)?.ok_or(
+            protocol::Error::IllegalArgument("non-nullable field user was serialized as null"),
+        )?;
+        let error_code = ErrorCode::read(buffer);
+        let error_message = CompactKafkaString::read_result_inner(buffer)?;
+        let credential_infos = CompactArray::<CredentialInfo>::read_result_inner(buffer)?.ok_or(
+            protocol::Error::IllegalArgument(
+                "non-nullable field credentialInfos was serialized as null",
+            ),
+        )?;
+        let _tagged_fields = TaggedFields::read_result_inner(buffer)?;
+
+        Ok(Self {
+            user,
+            error_code,
+            error_message,
+            credential_infos,
+        })
+    }
+}
+impl Writable for DescribeUserScramCredentialsResult {
+    fn write<B: BufMut>(&self, buffer: &mut B) {
+        CompactKafkaString::write_inner(buffer, Some(self.user()));
+        self.error_code.write(buffer);
+        CompactKafkaString::write_inner(buffer, self.error_message());
+        CompactArray::write_inner(buffer, Some(self.credential_infos()));
+        TaggedFields::write_empty(buffer);
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct CredentialInfo {
+    mechanism: i8,
+    iterations: i32,
+}
+impl CredentialInfo {
+    pub fn v0(mechanism: i8, iterations: i32) -> Self {
+        Self {
+            mechanism,
+            iterations,
+        }
+    }
+
+    pub fn mechanism(&self) -> i8 {
+        self.mechanism
+    }
+
+    pub fn iterations(&self) -> i32 {
+        self.iterations
+    }
+}
+impl ReadableResult for CredentialInfo {
+    fn read_result<B: Buf>(buffer: &mut B) -> Result<Self, protocol::Error> {
+        let mechanism = i8::read(buffer);
+        let iterations = i32::read(buffer);
+        let _tagged_fields = TaggedFields::read_result_inner(buffer)?;
+
+        Ok(Self {
+            mechanism,
+            iterations,
+        })
+    }
+}
+impl Writable for CredentialInfo {
+    fn write<B: BufMut>(&self, buffer: &mut B) {
+        self.mechanism.write(buffer);
+        self.iterations.write(buffer);
+        TaggedFields::write_empty(buffer);
+    }
+}
+
+// A null or empty user list describes every user that has a credential.
+pub fn process_request(
+    request: Request,
+    credentials: &HashMap<(String, i8), ScramCredential>,
+    auth: &Authorization,
+) -> Result<Response, protocol::Error> {
+    if !auth.authorize(AclOperation::Describe, ResourceType::Cluster, CLUSTER_NAME) {
+        return Ok(error_response(ErrorCode::ClusterAuthorizationFailed));
+    }
+
+    let mut by_user = BTreeMap::<&str, Vec<CredentialInfo>>::new();
+    for ((user, mechanism), credential) in credentials {
+        by_user
+            .entry(user)
+            .or_default()
+            .push(CredentialInfo::v0(*mechanism, credential.iterations()));
+    }
+    for infos in by_user.values_mut() {
+        infos.sort_by_key(CredentialInfo::mechanism);
+    }
+
+    let results = match request.users().filter(|users| !users.is_empty()) {
+        None => by_user
+            .into_iter()
+            .map(|(user, infos)| {
+                DescribeUserScramCredentialsResult::v0(user, ErrorCode::None, None, infos)
+            })
+            .collect(),
+        Some(users) => {
+            let names = users
+                .iter()
+                .map(|user| std::str::from_utf8(user.name()))
+                .collect::<Result<Vec<_>, _>>()?;
+            let mut seen = HashSet::new();
+            let duplicates = names
+                .iter()
+                .filter(|name| !seen.insert(**name))
+                .copied()
+                .collect::<HashSet<_>>();
+            let mut reported = HashSet::new();
+            names
+                .into_iter()
+                .filter(|name| reported.insert(*name))
+                .map(|name| {
+                    if duplicates.contains(name) {
+                        DescribeUserScramCredentialsResult::v0(
+                            name,
+                            ErrorCode::DuplicateResource,
+                            Some("Cannot describe SCRAM credentials for the same user twice in a single request"),
+                            vec![],
+                        )
+                    } else if let Some(infos) = by_user.get(name) {
+                        DescribeUserScramCredentialsResult::v0(
+                            name,
+                            ErrorCode::None,
+                            None,
+                            infos.clone(),
+                        )
+                    } else {
+                        DescribeUserScramCredentialsResult::v0(
+                            name,
+                            ErrorCode::ResourceNotFound,
+                            Some("Attempt to describe a user credential that does not exist"),
+                            vec![],
+                        )
+                    }
+                })
+                .collect()
+        }
+    };
+
+    Ok(Response::v0(0, ErrorCode::None, None, results))
+}
+
+pub(crate) fn error_response(error_code: ErrorCode) -> Response {
+    Response::v0(0, error_code, Some(error_code.message()), vec![])
+}
@@ -1,16 +1,14 @@
-use std::{
-    collections::HashMap,
-    fs::{self, File},
-    io::Read,
-};
+use std::{collections::HashMap, fs, path::Path};
 
 use bytes::Bytes;
-use response::{FetchableTopicResponse, PartitionData};
+use response::{FetchableTopicResponse, NodeEndpoint, PartitionData};
 use uuid::Uuid;
 
 use crate::{
-    metadata, model,
-    protocol::{self, ReadableResult},
+    config::BrokerConfig,
+    model,
+    protocol::{self, ErrorCode},
+    security::{AclOperation, Authorization, ResourceType},
 };
 
 use super::topic_by_name;
@@ -29,9 +27,14 @@ enum TopicID {
 pub fn process_request(
     request: request::Request,
     metadata: &HashMap<Uuid, model::Topic>,
+    brokers: &[model::BrokerRegistration],
+    listener_name: &str,
+    config: &BrokerConfig,
+    auth: &Authorization,
 ) -> Result<response::Response, protocol::Error> {
     let version = request.version;
     let mut responses = Vec::with_capacity(request.topics.len());
+    let mut leaders = Vec::new();
 
     for topic_req in request.topics() {
         let topic_opt = match &topic_req.topic {
@@ -42,22 +45,68 @@ pub fn process_request(
             TopicID::Id(uuid) => metadata.get(uuid),
         };
 
+        let authorized = match (&topic_req.topic, topic_opt) {
+            (TopicID::Name(bytes), _) => auth.authorize(
+                AclOperation::Read,
+                ResourceType::Topic,
+                std::str::from_utf8(bytes.as_ref())?,
+            ),
+            (TopicID::Id(_), Some(topic)) => {
+                auth.authorize(AclOperation::Read, ResourceType::Topic, topic.name())
+            }
+            (TopicID::Id(_), None) => true,
+        };
+
         let fetch_topic_response = match topic_opt {
+            _ if !authorized => FetchableTopicResponse::new(
+                version,
+                topic_req.topic.clone(),
+                topic_req
+                    .partitions()
+                    .iter()
+                    .map(|partition| {
+                        PartitionData::new(
+                            version,
+                            partition.partition(),
+                            ErrorCode::TopicAuthorizationFailed,
+                            -1,
+                            None,
+                            None,
+                            None,
+                            -1,
+                            None,
+                            None,
+                            None,
+                            None,
+                        )
+                    })
+                    .collect(),
+            ),
             Some(topic_metadata) => {
                 let partitions = {
                     let mut values = Vec::new();
                     for partition in topic_req.partitions() {
                         let partition_index = partition.partition();
-                        let rel_log_path = format!(
-                            "{}-{}/00000000000000000000.log",
-                            topic_metadata.name(),
-                            partition_index
-                        );
+                        if let Some(leader) = topic_metadata
+                            .partitions()
+                            .iter()
+                            .find(|p| p.id() == partition_index)
+                            .map(|p| p.leader())
+                        {
+                            leaders.push(leader);
+                        }
+                        let partition_dir =
+                            format!("{}-{}", topic_metadata.name(), partition_index);
+                        let rel_log_path = format!("{}/00000000000000000000.log", partition_dir);
 
                         let records = {
-                            let record_batches =
-                                read_record_file("/tmp/kraft-combined-logs", &rel_log_path)?;
-                            println!("read {} bytes from {}", record_batches.len(), rel_log_path);
+                            let base_path = partition_log_dir(config, &partition_dir);
+                            let record_batches = read_record_file(base_path, &rel_log_path)?;
+                            tracing::debug!(
+                                "read {} bytes from {}",
+                                record_batches.len(),
+                                rel_log_path
+                            );
                             if record_batches.is_empty() {
                                 None
                             } else {
@@ -68,7 +117,7 @@ pub fn process_request(
                         values.push(PartitionData::new(
                             version,
                             partition_index,
-                            0,
+                            ErrorCode::None,
                             0,
                             None,
                             None,
@@ -89,7 +138,18 @@ pub fn process_request(
                 version,
                 topic_req.topic.clone(),
                 vec![PartitionData::new(
-                    version, 0, 100, 0, None, None, None, -1, None, None, None, None,
+                    version,
+                    0,
+                    ErrorCode::UnknownTopicId,
+                    0,
+                    None,
+                    None,
+                    None,
+                    -1,
+                    None,
+                    None,
+                    None,
+                    None,
                 )],
             ),
         };
@@ -100,70 +160,72 @@ pub fn process_request(
     Ok(response::Response {
         version,
         throttle_time_ms: 0,
-        error_code: 0,
+        error_code: ErrorCode::None,
         session_id: 0,
         responses,
-        node_endpoints: None,
+        node_endpoints: node_endpoints(version, &leaders, brokers, listener_name),
     })
 }
 
-pub(crate) fn read_record_file(
-    base_path: &str,
-    rel_log_path: &str,
-) -> Result<Vec<u8>, protocol::Error> {
-    fs::read(format!("{}/{}", base_path, rel_log_path))
-        .map_err(|err| protocol::Error::IOError(err.to_string()))
+pub(crate) fn error_response(version: i16, error_code: ErrorCode) -> response::Response {
+    response::Response {
+        version,
+        throttle_time_ms: 0,
+        error_code,
+        session_id: 0,
+        responses: vec![],
+        node_endpoints: None,
+    }
 }
 
-pub(crate) fn read_record_batches(
-    base_path: &str,
-    rel_log_path: &str,
-) -> Result<Vec<metadata::RecordBatch>, protocol::Error> {
-    let mut result = Vec::new();
-    let mut buffer = vec![0u8; 8192];
-    let mut file = File::open(format!("{}/{}", base_path, rel_log_path))
-        .map_err(|err| protocol::Error::IOError(err.to_string()))?;
-    let mut left_sz = 0usize;
-
-    loop {
-        let read_sz = file
-            .read(&mut buffer[left_sz..])
-            .map_err(|err| protocol::Error::IOError(err.to_string()))?;
-        if read_sz == 0 {
-            break;
-        }
-        buffer.truncate(left_sz + read_sz);
-
-        let mut read_buffer = &buffer[0..];
-        loop {
-            //         if rb.is_control_batch() {
-            //             continue;
-            //         }
-            //         result.append(
-            //             &mut rb
-            //                 .records()
-            //                 .iter()
-            //                 .filter_map(|r| match r {
-            //                     metadata::Record::Value(value_record) => {
-            //                         Some(value_record.value().clone())
-            //                     }
-            //                     metadata::Record::Control(_) => None,
-            //                 })
-            //                 .collect::<Vec<_>>(),
-            //         );
-            match metadata::RecordBatch::read_result(&mut read_buffer) {
-                Ok(rb) => result.push(rb),
-                Err(protocol::Error::BufferUnderflow) => break,
-                Err(err) => return Err(err),
-            }
-        }
-
-        left_sz = read_buffer.len();
-        buffer.copy_within(left_sz.., 0);
+// Version 16 tells clients where the leaders of the fetched partitions live, so
+// they can follow leadership changes without a Metadata round trip.
+fn node_endpoints(
+    version: i16,
+    leaders: &[i32],
+    brokers: &[model::BrokerRegistration],
+    listener_name: &str,
+) -> Option<Vec<NodeEndpoint>> {
+    if version < 16 {
+        return None;
     }
 
-    Ok(result)
+    let endpoints = brokers
+        .iter()
+        .filter(|broker| leaders.contains(&broker.id()))
+        .filter_map(|broker| {
+            broker.listener(listener_name).map(|listener| {
+                NodeEndpoint::new(
+                    broker.id(),
+                    Bytes::copy_from_slice(listener.host().as_bytes()),
+                    listener.port() as i32,
+                    broker
+                        .rack()
+                        .map(|rack| Bytes::copy_from_slice(rack.as_bytes())),
+                )
+            })
+        })
+        .collect::<Vec<_>>();
+    (!endpoints.is_empty()).then_some(endpoints)
+}
+
+// A partition lives in exactly one of the configured log.dirs; fall back to the
+// first one so a missing partition still reports a meaningful path.
+fn partition_log_dir<'a>(config: &'a BrokerConfig, partition_dir: &str) -> &'a Path {
+    config
+        .log_dirs()
+        .iter()
+        .find(|log_dir| log_dir.join(partition_dir).is_dir())
+        .unwrap_or(&config.log_dirs()[0])
+}
+
+pub(crate) fn read_record_file(
+    base_path: &Path,
+    rel_log_path: &str,
+) -> Result<Vec<u8>, protocol::Error> {
+    fs::read(base_path.join(rel_log_path)).map_err(|err| protocol::Error::IOError(err.to_string()))
 }
 
 #[cfg(test)]
+#[allow(clippy::assertions_on_constants)]
 mod tests;
@@ -5,7 +5,7 @@ use uuid::Uuid;
 use crate::protocol::{
     self,
     r#type::{Array, CompactArray, CompactKafkaString, KafkaString, TaggedField, TaggedFields},
-    Readable, ReadableVersion, Writable,
+    ErrorCode, Readable, ReadableVersion, Writable,
 };
 
 use super::TopicID;
@@ -14,7 +14,7 @@ use super::TopicID;
 pub struct Response {
     pub(super) version: i16,
     pub(super) throttle_time_ms: i32,
-    pub(super) error_code: i16,
+    pub(super) error_code: ErrorCode,
     pub(super) session_id: i32,
     pub(super) responses: Vec<FetchableTopicResponse>,
     pub(super) node_endpoints: Option<Vec<NodeEndpoint>>,
@@ -23,7 +23,7 @@ impl Response {
     fn new(
         version: i16,
         throttle_time_ms: Option<i32>,
-        error_code: Option<i16>,
+        error_code: Option<ErrorCode>,
         session_id: i32,
         responses: Vec<FetchableTopicResponse>,
         node_endpoints: Option<Vec<NodeEndpoint>>,
@@ -31,7 +31,7 @@ impl Response {
         Self {
             version,
             throttle_time_ms: throttle_time_ms.unwrap_or(-1),
-            error_code: error_code.unwrap_or(0),
+            error_code: error_code.unwrap_or(ErrorCode::None),
             session_id,
             responses,
             node_endpoints,
@@ -52,7 +52,7 @@ impl Response {
 
     pub fn v7(
         throttle_time_ms: Option<i32>,
-        error_code: Option<i16>,
+        error_code: Option<ErrorCode>,
         session_id: i32,
         responses: Vec<FetchableTopicResponse>,
     ) -> Self {
@@ -61,7 +61,7 @@ impl Response {
 
     pub fn v8(
         throttle_time_ms: Option<i32>,
-        error_code: Option<i16>,
+        error_code: Option<ErrorCode>,
         session_id: i32,
         responses: Vec<FetchableTopicResponse>,
     ) -> Self {
@@ -70,7 +70,7 @@ impl Response {
 
     pub fn v9(
         throttle_time_ms: Option<i32>,
-        error_code: Option<i16>,
+        error_code: Option<ErrorCode>,
         session_id: i32,
         responses: Vec<FetchableTopicResponse>,
     ) -> Self {
@@ -79,7 +79,7 @@ impl Response {
 
     pub fn v10(
         throttle_time_ms: Option<i32>,
-        error_code: Option<i16>,
+        error_code: Option<ErrorCode>,
         session_id: i32,
         responses: Vec<FetchableTopicResponse>,
     ) -> Self {
@@ -95,7 +95,7 @@ impl Response {
 
     pub fn v11(
         throttle_time_ms: Option<i32>,
-        error_code: Option<i16>,
+        error_code: Option<ErrorCode>,
         session_id: i32,
         responses: Vec<FetchableTopicResponse>,
     ) -> Self {
@@ -111,7 +111,7 @@ impl Response {
 
     pub fn v12(
         throttle_time_ms: Option<i32>,
-        error_code: Option<i16>,
+        error_code: Option<ErrorCode>,
         session_id: i32,
         responses: Vec<FetchableTopicResponse>,
     ) -> Self {
@@ -127,7 +127,7 @@ impl Response {
 
     pub fn v13(
         throttle_time_ms: Option<i32>,
-        error_code: Option<i16>,
+        error_code: Option<ErrorCode>,
         session_id: i32,
         responses: Vec<FetchableTopicResponse>,
     ) -> Self {
@@ -143,7 +143,7 @@ impl Response {
 
     pub fn v14(
         throttle_time_ms: Option<i32>,
-        error_code: Option<i16>,
+        error_code: Option<ErrorCode>,
         session_id: i32,
         responses: Vec<FetchableTopicResponse>,
     ) -> Self {
@@ -159,7 +159,7 @@ impl Response {
 
     pub fn v15(
         throttle_time_ms: Option<i32>,
-        error_code: Option<i16>,
+        error_code: Option<ErrorCode>,
         session_id: i32,
         responses: Vec<FetchableTopicResponse>,
     ) -> Self {
@@ -175,7 +175,7 @@ impl Response {
 
     pub fn v16(
         throttle_time_ms: Option<i32>,
-        error_code: Option<i16>,
+        error_code: Option<ErrorCode>,
         session_id: i32,
         responses: Vec<FetchableTopicResponse>,
         node_endpoints: Option<Vec<NodeEndpoint>>,
@@ -192,7 +192,7 @@ impl Response {
 
     pub fn v17(
         throttle_time_ms: Option<i32>,
-        error_code: Option<i16>,
+        error_code: Option<ErrorCode>,
         session_id: i32,
         responses: Vec<FetchableTopicResponse>,
         node_endpoints: Option<Vec<NodeEndpoint>>,
@@ -211,7 +211,11 @@ impl Response {
         self.throttle_time_ms
     }
 
-    pub fn error_code(&self) -> i16 {
+    pub fn set_throttle_time_ms(&mut self, throttle_time_ms: i32) {
+        self.throttle_time_ms = throttle_time_ms;
+    }
+
+    pub fn error_code(&self) -> ErrorCode {
         self.error_code
     }
 
@@ -234,7 +238,11 @@ impl ReadableVersion for Response {
         }
 
         let throttle_time_ms = i32::read(buffer);
-        let error_code = if version >= 7 { i16::read(buffer) } else { 0 };
+        let error_code = if version >= 7 {
+            ErrorCode::read(buffer)
+        } else {
+            ErrorCode::None
+        };
         let session_id = if version >= 7 { i32::read(buffer) } else { 0 };
         let responses = if version <= 11 {
             Array::<FetchableTopicResponse>::read_version_inner(buffer, version)
@@ -494,7 +502,7 @@ impl Writable for FetchableTopicResponse {
 pub struct PartitionData {
     version: i16,
     partition_index: i32,
-    error_code: i16,
+    error_code: ErrorCode,
     high_watermark: i64,
     last_stable_offset: i64,
     log_start_offset: i64,
@@ -509,7 +517,7 @@ impl PartitionData {
     pub(super) fn new(
         version: i16,
         partition_index: i32,
-        error_code: i16,
+        error_code: ErrorCode,
         high_watermark: i64,
         last_stable_offset: Option<i64>,
         log_start_offset: Option<i64>,
@@ -538,7 +546,7 @@ impl PartitionData {
 
     pub fn v4(
         partition_index: i32,
-        error_code: i16,
+        error_code: ErrorCode,
         high_watermark: i64,
         last_stable_offset: Option<i64>,
         aborted_transactions: Option<Vec<AbortedTransaction>>,
@@ -562,7 +570,7 @@ impl PartitionData {
 
     pub fn v5(
         partition_index: i32,
-        error_code: i16,
+        error_code: ErrorCode,
         high_watermark: i64,
         last_stable_offset: Option<i64>,
         log_start_offset: Option<i64>,
@@ -587,7 +595,7 @@ impl PartitionData {
 
     pub fn v6(
         partition_index: i32,
-        error_code: i16,
+        error_code: ErrorCode,
         high_watermark: i64,
         last_stable_offset: Option<i64>,
         log_start_offset: Option<i64>,
@@ -612,7 +620,7 @@ impl PartitionData {
 
     pub fn v7(
         partition_index: i32,
-        error_code: i16,
+        error_code: ErrorCode,
         high_watermark: i64,
         last_stable_offset: Option<i64>,
         log_start_offset: Option<i64>,
@@ -637,7 +645,7 @@ impl PartitionData {
 
     pub fn v8(
         partition_index: i32,
-        error_code: i16,
+        error_code: ErrorCode,
         high_watermark: i64,
         last_stable_offset: Option<i64>,
         log_start_offset: Option<i64>,
@@ -662,7 +670,7 @@ impl PartitionData {
 
     pub fn v9(
         partition_index: i32,
-        error_code: i16,
+        error_code: ErrorCode,
         high_watermark: i64,
         last_stable_offset: Option<i64>,
         log_start_offset: Option<i64>,
@@ -687,7 +695,7 @@ impl PartitionData {
 
     pub fn v10(
         partition_index: i32,
-        error_code: i16,
+        error_code: ErrorCode,
         high_watermark: i64,
         last_stable_offset: Option<i64>,
         log_start_offset: Option<i64>,
@@ -712,7 +720,7 @@ impl PartitionData {
 
     pub fn v11(
         partition_index: i32,
-        error_code: i16,
+        error_code: ErrorCode,
         high_watermark: i64,
         last_stable_offset: Option<i64>,
         log_start_offset: Option<i64>,
@@ -738,7 +746,7 @@ impl PartitionData {
 
     pub fn v12(
         partition_index: i32,
-        error_code: i16,
+        error_code: ErrorCode,
         high_watermark: i64,
         last_stable_offset: Option<i64>,
         log_start_offset: Option<i64>,
@@ -767,7 +775,7 @@ impl PartitionData {
 
     pub fn v13(
         partition_index: i32,
-        error_code: i16,
+        error_code: ErrorCode,
         high_watermark: i64,
         last_stable_offset: Option<i64>,
         log_start_offset: Option<i64>,
@@ -796,7 +804,7 @@ impl PartitionData {
 
     pub fn v14(
         partition_index: i32,
-        error_code: i16,
+        error_code: ErrorCode,
         high_watermark: i64,
         last_stable_offset: Option<i64>,
         log_start_offset: Option<i64>,
@@ -825,7 +833,7 @@ impl PartitionData {
 
     pub fn v15(
         partition_index: i32,
-        error_code: i16,
+        error_code: ErrorCode,
         high_watermark: i64,
         last_stable_offset: Option<i64>,
         log_start_offset: Option<i64>,
@@ -854,7 +862,7 @@ impl PartitionData {
 
     pub fn v16(
         partition_index: i32,
-        error_code: i16,
+        error_code: ErrorCode,
         high_watermark: i64,
         last_stable_offset: Option<i64>,
         log_start_offset: Option<i64>,
@@ -883,7 +891,7 @@ impl PartitionData {
 
     pub fn v17(
         partition_index: i32,
-        error_code: i16,
+        error_code: ErrorCode,
         high_watermark: i64,
         last_stable_offset: Option<i64>,
         log_start_offset: Option<i64>,
@@ -914,7 +922,7 @@ impl PartitionData {
         self.partition_index
     }
 
-    pub fn error_code(&self) -> i16 {
+    pub fn error_code(&self) -> ErrorCode {
         self.error_code
     }
 
@@ -961,7 +969,7 @@ impl ReadableVersion for PartitionData {
         }
 
         let partition_index = i32::read(buffer);
-        let error_code = i16::read(buffer);
+        let error_code = ErrorCode::read(buffer);
         let high_watermark = i64::read(buffer);
         let last_stable_offset = i64::read(buffer);
         let log_start_offset = if version >= 5 { i64::read(buffer) } else { -1 };
@@ -3,7 +3,7 @@ use protocol::message::RequestHeader;
 
 use crate::{
     metadata::{Record, RecordBatch},
-    protocol::{message::ResponseHeader, Readable, ReadableVersion},
+    protocol::{message::ResponseHeader, Readable, ReadableResult, ReadableVersion},
 };
 
 use super::{request::Request, response::Response, *};
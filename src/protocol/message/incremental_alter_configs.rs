@@ -0,0 +1,687 @@
+use std::collections::HashMap;
+
+use bytes::{Buf, BufMut, Bytes};
+use uuid::Uuid;
+
+use crate::{
+    config::dynamic::{ConfigResolver, ConfigType},
+    logging,
+    metadata::MetadataWriter,
+    model::{self, ConfigResource},
+    protocol::{
+        self,
+        r#type::{Array, CompactArray, CompactKafkaString, KafkaString, TaggedFields},
+        ErrorCode, Readable, ReadableVersion, Writable,
+    },
+    security::{acl::CLUSTER_NAME, AclOperation, Authorization, ResourceType},
+};
+
+use super::alter_configs::{append, config_def, config_record, validate_resource};
+
+pub(crate) const API_KEY: i16 = 44;
+
+const OP_SET: i8 = 0;
+const OP_DELETE: i8 = 1;
+const OP_APPEND: i8 = 2;
+const OP_SUBTRACT: i8 = 3;
+
+#[derive(Debug, Clone)]
+pub struct Request {
+    version: i16,
+    resources: Vec<AlterConfigsResource>,
+    validate_only: bool,
+}
+impl Request {
+    fn new(version: i16, resources: Vec<AlterConfigsResource>, validate_only: bool) -> Self {
+        Self {
+            version,
+            resources,
+            validate_only,
+        }
+    }
+
+    pub fn v0(resources: Vec<AlterConfigsResource>, validate_only: bool) -> Self {
+        Self::new(0, resources, validate_only)
+    }
+
+    pub fn v1(resources: Vec<AlterConfigsResource>, validate_only: bool) -> Self {
+        Self::new(1, resources, validate_only)
+    }
+
+    pub fn resources(&self) -> &[AlterConfigsResource] {
+        &self.resources
+    }
+
+    pub fn validate_only(&self) -> bool {
+        self.validate_only
+    }
+}
+impl ReadableVersion for Request {
+    fn read_version<B: Buf>(buffer: &mut B, version: i16) -> Result<Self, protocol::Error> {
+        if !(0..=1).contains(&version) {
+            return Err(protocol::Error::UnsupportedVersion);
+        }
+
+        let resources = if version == 0 {
+            Array::<AlterConfigsResource>::read_version_inner(buffer, version)
+        } else {
+            CompactArray::<AlterConfigsResource>::read_version_inner(buffer, version)
+        }?
+        .ok_or(protocol::Error::IllegalArgument(
+            "non-nullable field resources was serialized as null",
+        ))?;
+        let validate_only = bool::read(buffer);
+        if version >= 1 {
+            let _tagged_fields = TaggedFields::read_result_inner(buffer)?;
+        }
+
+        Ok(Self {
+            version,
+            resources,
+            validate_only,
+        })
+    }
+}
+impl Writable for Request {
+    fn write<B: BufMut>(&self, buffer: &mut B) {
+        if self.version == 0 {
+            Array::write_inner(buffer, Some(self.resources()));
+            self.validate_only.write(buffer);
+        } else {
+            CompactArray::write_inner(buffer, Some(self.resources()));
+            self.validate_only.write(buffer);
+            TaggedFields::write_empty(buffer);
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct AlterConfigsResource {
+    version: i16,
+    resource_type: i8,
+    resource_name: Bytes,
+    configs: Vec<AlterableConfig>,
+}
+impl AlterConfigsResource {
+    fn new(
+        version: i16,
+        resource_type: i8,
+        resource_name: &str,
+        configs: Vec<AlterableConfig>,
+    ) -> Self {
+        Self {
+            version,
+            resource_type,
+            resource_name: Bytes::copy_from_slice(resource_name.as_bytes()),
+            configs,
+        }
+    }
+
+    pub fn v0(resource_type: i8, resource_name: &str, configs: Vec<AlterableConfig>) -> Self {
+        Self::new(0, resource_type, resource_name, configs)
+    }
+
+    pub fn v1(resource_type: i8, resource_name: &str, configs: Vec<AlterableConfig>) -> Self {
+        Self::new(1, resource_type, resource_name, configs)
+    }
+
+    pub fn resource_type(&self) -> i8 {
+        self.resource_type
+    }
+
+    pub fn resource_name(&self) -> &[u8] {
+        &self.resource_name
+    }
+
+    pub fn configs(&self) -> &[AlterableConfig] {
+        &self.configs
+    }
+}
+impl ReadableVersion for AlterConfigsResource {
+    fn read_version<B: Buf>(buffer: &mut B, version: i16) -> Result<Self, protocol::Error> {
+        if !(0..=1).contains(&version) {
+            return Err(protocol::Error::UnsupportedVersion);
+        }
+
+        let resource_type = i8::read(buffer);
+        let resource_name = if version == 0 {
+            KafkaString::read_inner(buffer)
+        } else {
+            CompactKafkaString::read_result_inner(buffer)?
+        }
+        .ok_or(protocol::Error::IllegalArgument(
+            "non-nullable field resourceName was serialized as null",
+        ))?;
+        let configs = if version == 0 {
+            Array::<AlterableConfig>::read_version_inner(buffer, version)
+        } else {
+            CompactArray::<AlterableConfig>::read_version_inner(buffer, version)
+        }?
+        .ok_or(protocol::Error::IllegalArgument(
+            "non-nullable field configs was serialized as null",
+        ))?;
+        if version >= 1 {
+            let _tagged_fields = TaggedFields::read_result_inner(buffer)?;
+        }
+
+        Ok(Self {
+            version,
+            resource_type,
+            resource_name,
+            configs,
+        })
+    }
+}
+impl Writable for AlterConfigsResource {
+    fn write<B: BufMut>(&self, buffer: &mut B) {
+        self.resource_type.write(buffer);
+        if self.version == 0 {
+            KafkaString::write_inner(buffer, Some(self.resource_name()));
+            Array::write_inner(buffer, Some(self.configs()));
+        } else {
+            CompactKafkaString::write_inner(buffer, Some(self.resource_name()));
+            CompactArray::write_inner(buffer, Some(self.configs()));
+            TaggedFields::write_empty(buffer);
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct AlterableConfig {
+    version: i16,
+    name: Bytes,
+    config_operation: i8,
+    value: Option<Bytes>,
+}
+impl AlterableConfig {
+    fn new(version: i16, name: &str, config_operation: i8, value: Option<&str>) -> Self {
+        Self {
+            version,
+            name: Bytes::copy_from_slice(name.as_bytes()),
+            config_operation,
+            value: value.map(|v| Bytes::copy_from_slice(v.as_bytes())),
+        }
+    }
+
+    pub fn v0(name: &str, config_operation: i8, value: Option<&str>) -> Self {
+        Self::new(0, name, config_operation, value)
+    }
+
+    pub fn v1(name: &str, config_operation: i8, value: Option<&str>) -> Self {
+        Self::new(1, name, config_operation, value)
+    }
+
+    pub fn name(&self) -> &[u8] {
+        &self.name
+    }
+
+    pub fn config_operation(&self) -> i8 {
+        self.config_operation
+    }
+
+    pub fn value(&self) -> Option<&[u8]> {
+        self.value.as_deref()
+    }
+}
+impl ReadableVersion for AlterableConfig {
+    fn read_version<B: Buf>(buffer: &mut B, version: i16) -> Result<Self, protocol::Error> {
+        if !(0..=1).contains(&version) {
+            return Err(protocol::Error::UnsupportedVersion);
+        }
+
+        let name = if version == 0 {
+            KafkaString::read_inner(buffer)
+        } else {
+            CompactKafkaString::read_result_inner(buffer)?
+        }
+        .ok_or(protocol::Error::IllegalArgument(
+            "non-nullable field name was serialized as null",
+        ))?;
+        let config_operation = i8::read(buffer);
+        let value = if version == 0 {
+            KafkaString::read_inner(buffer)
+        } else {
+            CompactKafkaString::read_result_inner(buffer)?
+        };
+        if version >= 1 {
+            let _tagged_fields = TaggedFields::read_result_inner(buffer)?;
+        }
+
+        Ok(Self {
+            version,
+            name,
+            config_operation,
+            value,
+        })
+    }
+}
+impl Writable for AlterableConfig {
+    fn write<B: BufMut>(&self, buffer: &mut B) {
+        if self.version == 0 {
+            KafkaString::write_inner(buffer, Some(self.name()));
+            self.config_operation.write(buffer);
+            KafkaString::write_inner(buffer, self.value());
+        } else {
+            CompactKafkaString::write_inner(buffer, Some(self.name()));
+            self.config_operation.write(buffer);
+            CompactKafkaString::write_inner(buffer, self.value());
+            TaggedFields::write_empty(buffer);
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Response {
+    version: i16,
+    throttle_time_ms: i32,
+    responses: Vec<AlterConfigsResourceResponse>,
+}
+impl Response {
+    fn new(
+        version: i16,
+        throttle_time_ms: i32,
+        responses: Vec<AlterConfigsResourceResponse>,
+    ) -> Self {
+        Self {
+            version,
+            throttle_time_ms,
+            responses,
+        }
+    }
+
+    pub fn v0(throttle_time_ms: i32, responses: Vec<AlterConfigsResourceResponse>) -> Self {
+        Self::new(0, throttle_time_ms, responses)
+    }
+
+    pub fn v1(throttle_time_ms: i32, responses: Vec<AlterConfigsResourceResponse>) -> Self {
+        Self::new(1, throttle_time_ms, responses)
+    }
+
+    pub fn throttle_time_ms(&self) -> i32 {
+        self.throttle_time_ms
+    }
+
+    pub fn set_throttle_time_ms(&mut self, throttle_time_ms: i32) {
+        self.throttle_time_ms = throttle_time_ms;
+    }
+
+    pub fn responses(&self) -> &[AlterConfigsResourceResponse] {
+        &self.responses
+    }
+}
+impl ReadableVersion for Response {
+    fn read_version<B: Buf>(buffer: &mut B, version: i16) -> Result<Self, protocol::Error> {
+        if !(0..=1).contains(&version) {
+            return Err(protocol::Error::UnsupportedVersion);
+        }
+
+        let throttle_time_ms = i32::read(buffer);
+        let responses = if version == 0 {
+            Array::<AlterConfigsResourceResponse>::read_version_inner(buffer, version)
+        } else {
+            CompactArray::<AlterConfigsResourceResponse>::read_version_inner(buffer, version)
+        }?
+        .ok_or(protocol::Error::IllegalArgument(
+            "non-nullable field responses was serialized as null",
+        ))?;
+        if version >= 1 {
+            let _tagged_fields = TaggedFields::read_result_inner(buffer)?;
+        }
+
+        Ok(Self {
+            version,
+            throttle_time_ms,
+            responses,
+        })
+    }
+}
+impl Writable for Response {
+    fn write<B: BufMut>(&self, buffer: &mut B) {
+        self.throttle_time_ms.write(buffer);
+        if self.version == 0 {
+            Array::write_inner(buffer, Some(self.responses()));
+        } else {
+            CompactArray::write_inner(buffer, Some(self.responses()));
+            TaggedFields::write_empty(buffer);
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct AlterConfigsResourceResponse {
+    version: i16,
+    error_code: ErrorCode,
+    error_message: Option<Bytes>,
+    resource_type: i8,
+    resource_name: Bytes,
+}
+impl AlterConfigsResourceResponse {
+    fn new(
+        version: i16,
+        error_code: ErrorCode,
+        error_message: Option<&str>,
+        resource_type: i8,
+        resource_name: &str,
+    ) -> Self {
+        Self {
+            version,
+            error_code,
+            error_message: error_message.map(|m| Bytes::copy_from_slice(m.as_bytes())),
+            resource_type,
+            resource_name: Bytes::copy_from_slice(resource_name.as_bytes()),
+        }
+    }
+
+    pub fn v0(
+        error_code: ErrorCode,
+        error_message: Option<&str>,
+        resource_type: i8,
+        resource_name: &str,
+    ) -> Self {
+        Self::new(0, error_code, error_message, resource_type, resource_name)
+    }
+
+    pub fn v1(
+        error_code: ErrorCode,
+        error_message: Option<&str>,
+        resource_type: i8,
+        resource_name: &str,
+    ) -> Self {
+        Self::new(1, error_code, error_message, resource_type, resource_name)
+    }
+
+    pub fn error_code(&self) -> ErrorCode {
+        self.error_code
+    }
+
+    pub fn error_message(&self) -> Option<&[u8]> {
+        self.error_message.as_deref()
+    }
+
+    pub fn resource_type(&self) -> i8 {
+        self.resource_type
+    }
+
+    pub fn resource_name(&self) -> &[u8] {
+        &self.resource_name
+    }
+}
+impl ReadableVersion for AlterConfigsResourceResponse {
+    fn read_version<B: Buf>(buffer: &mut B, version: i16) -> Result<Self, protocol::Error> {
+        if !(0..=1).contains(&version) {
+            return Err(protocol::Error::UnsupportedVersion);
+        }
+
+        let error_code = ErrorCode::read(buffer);
+        let error_message = if version == 0 {
+            KafkaString::read_inner(buffer)
+        } else {
+            CompactKafkaString::read_result_inner(buffer)?
+        };
+        let resource_type = i8::read(buffer);
+        let resource_name = if version == 0 {
+            KafkaString::read_inner(buffer)
+        } else {
+            CompactKafkaString::read_result_inner(buffer)?
+        }
+        .ok_or(protocol::Error::IllegalArgument(
+            "non-nullable field resourceName was serialized as null",
+        ))?;
+        if version >= 1 {
+            let _tagged_fields = TaggedFields::read_result_inner(buffer)?;
+        }
+
+        Ok(Self {
+            version,
+            error_code,
+            error_message,
+            resource_type,
+            resource_name,
+        })
+    }
+}
+impl Writable for AlterConfigsResourceResponse {
+    fn write<B: BufMut>(&self, buffer: &mut B) {
+        self.error_code.write(buffer);
+        if self.version == 0 {
+            KafkaString::write_inner(buffer, self.error_message());
+            self.resource_type.write(buffer);
+            KafkaString::write_inner(buffer, Some(self.resource_name()));
+        } else {
+            CompactKafkaString::write_inner(buffer, self.error_message());
+            self.resource_type.write(buffer);
+            CompactKafkaString::write_inner(buffer, Some(self.resource_name()));
+            TaggedFields::write_empty(buffer);
+        }
+    }
+}
+
+pub fn process_request(
+    request: Request,
+    metadata: &HashMap<Uuid, model::Topic>,
+    resolver: &ConfigResolver,
+    writer: &MetadataWriter,
+    node_id: i32,
+    auth: &Authorization,
+) -> Result<Response, protocol::Error> {
+    let version = request.version;
+    let mut responses = Vec::with_capacity(request.resources().len());
+    for resource in request.resources() {
+        let name = std::str::from_utf8(resource.resource_name())?;
+        let mut operations = Vec::with_capacity(resource.configs().len());
+        for config in resource.configs() {
+            let value = config.value().map(std::str::from_utf8).transpose()?;
+            operations.push((
+                std::str::from_utf8(config.name())?,
+                config.config_operation(),
+                value,
+            ));
+        }
+
+        if resource.resource_type() == ConfigResource::BROKER_LOGGER {
+            let result = alter_loggers(name, &operations, request.validate_only(), node_id, auth);
+            let (error_code, message) = match result {
+                Ok(()) => (ErrorCode::None, None),
+                Err((error_code, message)) => (error_code, Some(message)),
+            };
+            responses.push(AlterConfigsResourceResponse::new(
+                version,
+                error_code,
+                message.as_deref(),
+                resource.resource_type(),
+                name,
+            ));
+            continue;
+        }
+
+        let result = validate_resource(resource.resource_type(), name, metadata, node_id, auth)
+            .and_then(|config_resource| {
+                let mut records = Vec::with_capacity(operations.len());
+                for (i, (key, operation, value)) in operations.iter().enumerate() {
+                    if operations[..i].iter().any(|(other, _, _)| other == key) {
+                        return Err((
+                            ErrorCode::InvalidRequest,
+                            format!("Error due to duplicate config keys: {}", key),
+                        ));
+                    }
+                    let value = alter(resolver, &config_resource, key, *operation, *value)?;
+                    records.push(config_record(&config_resource, key, value.as_deref()));
+                }
+                if !request.validate_only() {
+                    append(writer, records)?;
+                }
+                Ok(())
+            });
+
+        let (error_code, message) = match result {
+            Ok(()) => (ErrorCode::None, None),
+            Err((error_code, message)) => (error_code, Some(message)),
+        };
+        responses.push(AlterConfigsResourceResponse::new(
+            version,
+            error_code,
+            message.as_deref(),
+            resource.resource_type(),
+            name,
+        ));
+    }
+
+    Ok(Response::new(version, 0, responses))
+}
+
+// Log levels only live in this broker's memory, so they are neither written
+// to the metadata log nor kept across restarts. Every change is validated
+// before any is made.
+fn alter_loggers(
+    name: &str,
+    operations: &[(&str, i8, Option<&str>)],
+    validate_only: bool,
+    node_id: i32,
+    auth: &Authorization,
+) -> Result<(), (ErrorCode, String)> {
+    if !auth.authorize(
+        AclOperation::AlterConfigs,
+        ResourceType::Cluster,
+        CLUSTER_NAME,
+    ) {
+        return Err((
+            ErrorCode::ClusterAuthorizationFailed,
+            ErrorCode::ClusterAuthorizationFailed.message().to_string(),
+        ));
+    }
+    if name != node_id.to_string() {
+        return Err((
+            ErrorCode::InvalidRequest,
+            format!(
+                "Unexpected broker id, expected {} but received {}",
+                node_id, name
+            ),
+        ));
+    }
+
+    let mut levels = Vec::with_capacity(operations.len());
+    for (i, (logger, operation, value)) in operations.iter().enumerate() {
+        if operations[..i].iter().any(|(other, _, _)| other == logger) {
+            return Err((
+                ErrorCode::InvalidRequest,
+                format!("Error due to duplicate config keys: {}", logger),
+            ));
+        }
+        let level = match (*operation, value) {
+            (OP_SET, Some(level)) => Some(*level),
+            (OP_SET, None) => {
+                return Err((
+                    ErrorCode::InvalidRequest,
+                    format!("Null value not supported for: {}", logger),
+                ))
+            }
+            (OP_DELETE, _) => None,
+            (OP_APPEND | OP_SUBTRACT, _) => {
+                return Err((
+                    ErrorCode::InvalidRequest,
+                    format!(
+                        "{} operation is not allowed for the BROKER_LOGGER resource",
+                        if *operation == OP_APPEND {
+                            "APPEND"
+                        } else {
+                            "SUBTRACT"
+                        }
+                    ),
+                ))
+            }
+            (operation, _) => {
+                return Err((
+                    ErrorCode::InvalidRequest,
+                    format!("Unknown config operation {}", operation),
+                ))
+            }
+        };
+        logging::check(logger, level).map_err(|err| (ErrorCode::InvalidConfig, err.to_string()))?;
+        levels.push((*logger, level));
+    }
+    if !validate_only {
+        for (logger, level) in levels {
+            logging::set_level(logger, level)
+                .map_err(|err| (ErrorCode::InvalidConfig, err.to_string()))?;
+        }
+    }
+    Ok(())
+}
+
+// The value the config ends up with, or None when it is deleted. APPEND and
+// SUBTRACT work on the effective value, so appending to a topic that still
+// uses the broker's cleanup.policy starts from that policy.
+fn alter(
+    resolver: &ConfigResolver,
+    resource: &ConfigResource,
+    key: &str,
+    operation: i8,
+    value: Option<&str>,
+) -> Result<Option<String>, (ErrorCode, String)> {
+    let def = config_def(resource, key)?;
+    let value = match (operation, value) {
+        (OP_DELETE, _) => return Ok(None),
+        (OP_SET | OP_APPEND | OP_SUBTRACT, None) => {
+            return Err((
+                ErrorCode::InvalidRequest,
+                format!("Null value not supported for: {}", key),
+            ))
+        }
+        (OP_SET, Some(value)) => value.to_string(),
+        (OP_APPEND | OP_SUBTRACT, Some(value)) => {
+            if def.r#type() != ConfigType::List {
+                return Err((
+                    ErrorCode::InvalidConfig,
+                    format!("Config value append is not allowed for config key: {}", key),
+                ));
+            }
+            let current = match resource.resource_type() {
+                ConfigResource::TOPIC => resolver.topic_config(resource.name(), key),
+                _ => resolver.broker_config(resource.name(), key),
+            };
+            let mut items = current
+                .as_ref()
+                .and_then(|entry| entry.value())
+                .map(list)
+                .unwrap_or_default();
+            for item in list(value) {
+                if operation == OP_APPEND && !items.contains(&item) {
+                    items.push(item);
+                } else if operation == OP_SUBTRACT {
+                    items.retain(|existing| *existing != item);
+                }
+            }
+            items.join(",")
+        }
+        (operation, _) => {
+            return Err((
+                ErrorCode::InvalidRequest,
+                format!("Unknown config operation {}", operation),
+            ))
+        }
+    };
+
+    def.validate(&value)
+        .map_err(|message| (ErrorCode::InvalidConfig, message))?;
+    Ok(Some(value))
+}
+
+fn list(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+pub(crate) fn error_response(version: i16, error_code: ErrorCode) -> Response {
+    let version = version.clamp(0, 1);
+    Response::new(
+        version,
+        0,
+        vec![AlterConfigsResourceResponse::new(
+            version, error_code, None, 0, "",
+        )],
+    )
+}
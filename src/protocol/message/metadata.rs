@@ -0,0 +1,1789 @@
+#![allow(clippy::too_many_arguments)]
+use std::collections::HashMap;
+
+use bytes::{Buf, BufMut, Bytes};
+use uuid::Uuid;
+
+use crate::{
+    model,
+    protocol::{
+        self,
+        r#type::{Array, CompactArray, CompactKafkaString, KafkaString, TaggedFields},
+        ErrorCode, Readable, ReadableVersion, Writable,
+    },
+    security::{acl::CLUSTER_NAME, AclOperation, Authorization, ResourceType},
+};
+
+use super::topic_by_name;
+
+pub(crate) const API_KEY: i16 = 3;
+
+// Sent when the client did not ask for authorized operations.
+const AUTHORIZED_OPERATIONS_OMITTED: i32 = i32::MIN;
+
+const INTERNAL_TOPICS: [&str; 2] = ["__consumer_offsets", "__transaction_state"];
+
+#[derive(Debug, Clone)]
+pub struct Request {
+    version: i16,
+    topics: Option<Vec<MetadataRequestTopic>>,
+    allow_auto_topic_creation: bool,
+    include_cluster_authorized_operations: bool,
+    include_topic_authorized_operations: bool,
+}
+impl Request {
+    fn new(
+        version: i16,
+        topics: Option<Vec<MetadataRequestTopic>>,
+        allow_auto_topic_creation: bool,
+        include_cluster_authorized_operations: bool,
+        include_topic_authorized_operations: bool,
+    ) -> Self {
+        Self {
+            version,
+            topics,
+            allow_auto_topic_creation,
+            include_cluster_authorized_operations,
+            include_topic_authorized_operations,
+        }
+    }
+
+    pub fn v0(topics: Vec<MetadataRequestTopic>) -> Self {
+        Self::new(0, Some(topics), true, false, false)
+    }
+
+    pub fn v1(topics: Option<Vec<MetadataRequestTopic>>) -> Self {
+        Self::new(1, topics, true, false, false)
+    }
+
+    pub fn v2(topics: Option<Vec<MetadataRequestTopic>>) -> Self {
+        Self::new(2, topics, true, false, false)
+    }
+
+    pub fn v3(topics: Option<Vec<MetadataRequestTopic>>) -> Self {
+        Self::new(3, topics, true, false, false)
+    }
+
+    pub fn v4(topics: Option<Vec<MetadataRequestTopic>>, allow_auto_topic_creation: bool) -> Self {
+        Self::new(4, topics, allow_auto_topic_creation, false, false)
+    }
+
+    pub fn v5(topics: Option<Vec<MetadataRequestTopic>>, allow_auto_topic_creation: bool) -> Self {
+        Self::new(5, topics, allow_auto_topic_creation, false, false)
+    }
+
+    pub fn v6(topics: Option<Vec<MetadataRequestTopic>>, allow_auto_topic_creation: bool) -> Self {
+        Self::new(6, topics, allow_auto_topic_creation, false, false)
+    }
+
+    pub fn v7(topics: Option<Vec<MetadataRequestTopic>>, allow_auto_topic_creation: bool) -> Self {
+        Self::new(7, topics, allow_auto_topic_creation, false, false)
+    }
+
+    pub fn v8(
+        topics: Option<Vec<MetadataRequestTopic>>,
+        allow_auto_topic_creation: bool,
+        include_cluster_authorized_operations: bool,
+        include_topic_authorized_operations: bool,
+    ) -> Self {
+        Self::new(
+            8,
+            topics,
+            allow_auto_topic_creation,
+            include_cluster_authorized_operations,
+            include_topic_authorized_operations,
+        )
+    }
+
+    pub fn v9(
+        topics: Option<Vec<MetadataRequestTopic>>,
+        allow_auto_topic_creation: bool,
+        include_cluster_authorized_operations: bool,
+        include_topic_authorized_operations: bool,
+    ) -> Self {
+        Self::new(
+            9,
+            topics,
+            allow_auto_topic_creation,
+            include_cluster_authorized_operations,
+            include_topic_authorized_operations,
+        )
+    }
+
+    pub fn v10(
+        topics: Option<Vec<MetadataRequestTopic>>,
+        allow_auto_topic_creation: bool,
+        include_cluster_authorized_operations: bool,
+        include_topic_authorized_operations: bool,
+    ) -> Self {
+        Self::new(
+            10,
+            topics,
+            allow_auto_topic_creation,
+            include_cluster_authorized_operations,
+            include_topic_authorized_operations,
+        )
+    }
+
+    pub fn v11(
+        topics: Option<Vec<MetadataRequestTopic>>,
+        allow_auto_topic_creation: bool,
+        include_topic_authorized_operations: bool,
+    ) -> Self {
+        Self::new(
+            11,
+            topics,
+            allow_auto_topic_creation,
+            false,
+            include_topic_authorized_operations,
+        )
+    }
+
+    pub fn v12(
+        topics: Option<Vec<MetadataRequestTopic>>,
+        allow_auto_topic_creation: bool,
+        include_topic_authorized_operations: bool,
+    ) -> Self {
+        Self::new(
+            12,
+            topics,
+            allow_auto_topic_creation,
+            false,
+            include_topic_authorized_operations,
+        )
+    }
+
+    // `None` asks for every topic; so does an empty list in version 0.
+    pub fn topics(&self) -> Option<&[MetadataRequestTopic]> {
+        self.topics.as_deref()
+    }
+
+    pub fn allow_auto_topic_creation(&self) -> bool {
+        self.allow_auto_topic_creation
+    }
+
+    pub fn include_cluster_authorized_operations(&self) -> bool {
+        self.include_cluster_authorized_operations
+    }
+
+    pub fn include_topic_authorized_operations(&self) -> bool {
+        self.include_topic_authorized_operations
+    }
+}
+impl ReadableVersion for Request {
+    fn read_version<B: Buf>(buffer: &mut B, version: i16) -> Result<Self, protocol::Error> {
+        if !(0..=12).contains(&version) {
+            return Err(protocol::Error::UnsupportedVersion);
+        }
+
+        let topics = if version <= 8 {
+            Array::<MetadataRequestTopic>::read_version_inner(buffer, version)
+        } else {
+            CompactArray::<MetadataRequestTopic>::read_version_inner(buffer, version)
+        }?;
+        if version == 0 && topics.is_none() {
+            return Err(protocol::Error::IllegalArgument(
+                "non-nullable field topics was serialized as null",
+            ));
+        }
+        let allow_auto_topic_creation = version < 4 || bool::read(buffer);
+        let include_cluster_authorized_operations =
+            (8..=10).contains(&version) && bool::read(buffer);
+        let include_topic_authorized_operations = version >= 8 && bool::read(buffer);
+        if version >= 9 {
+            let _tagged_fields = TaggedFields::read_result_inner(buffer)?;
+        }
+
+        Ok(Self {
+            version,
+            topics,
+            allow_auto_topic_creation,
+            include_cluster_authorized_operations,
+            include_topic_authorized_operations,
+        })
+    }
+}
+impl Writable for Request {
+    fn write<B: BufMut>(&self, buffer: &mut B) {
+        if self.version <= 8 {
+            Array::write_inner(buffer, self.topics());
+        } else {
+            CompactArray::write_inner(buffer, self.topics());
+        }
+        if self.version >= 4 {
+            self.allow_auto_topic_creation.write(buffer);
+        }
+        if (8..=10).contains(&self.version) {
+            self.include_cluster_authorized_operations.write(buffer);
+        }
+        if self.version >= 8 {
+            self.include_topic_authorized_operations.write(buffer);
+        }
+        if self.version >= 9 {
+            TaggedFields::write_empty(buffer);
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct MetadataRequestTopic {
+    version: i16,
+    topic_id: Uuid,
+    name: Option<Bytes>,
+}
+impl MetadataRequestTopic {
+    fn new(version: i16, topic_id: Uuid, name: Option<&str>) -> Self {
+        Self {
+            version,
+            topic_id,
+            name: name.map(|n| Bytes::copy_from_slice(n.as_bytes())),
+        }
+    }
+
+    pub fn v0(name: &str) -> Self {
+        Self::new(0, Uuid::nil(), Some(name))
+    }
+
+    pub fn v1(name: &str) -> Self {
+        Self::new(1, Uuid::nil(), Some(name))
+    }
+
+    pub fn v2(name: &str) -> Self {
+        Self::new(2, Uuid::nil(), Some(name))
+    }
+
+    pub fn v3(name: &str) -> Self {
+        Self::new(3, Uuid::nil(), Some(name))
+    }
+
+    pub fn v4(name: &str) -> Self {
+        Self::new(4, Uuid::nil(), Some(name))
+    }
+
+    pub fn v5(name: &str) -> Self {
+        Self::new(5, Uuid::nil(), Some(name))
+    }
+
+    pub fn v6(name: &str) -> Self {
+        Self::new(6, Uuid::nil(), Some(name))
+    }
+
+    pub fn v7(name: &str) -> Self {
+        Self::new(7, Uuid::nil(), Some(name))
+    }
+
+    pub fn v8(name: &str) -> Self {
+        Self::new(8, Uuid::nil(), Some(name))
+    }
+
+    pub fn v9(name: &str) -> Self {
+        Self::new(9, Uuid::nil(), Some(name))
+    }
+
+    pub fn v10(topic_id: Uuid, name: Option<&str>) -> Self {
+        Self::new(10, topic_id, name)
+    }
+
+    pub fn v11(topic_id: Uuid, name: Option<&str>) -> Self {
+        Self::new(11, topic_id, name)
+    }
+
+    pub fn v12(topic_id: Uuid, name: Option<&str>) -> Self {
+        Self::new(12, topic_id, name)
+    }
+
+    pub fn topic_id(&self) -> Uuid {
+        self.topic_id
+    }
+
+    pub fn name(&self) -> Option<&[u8]> {
+        self.name.as_deref()
+    }
+}
+impl ReadableVersion for MetadataRequestTopic {
+    fn read_version<B: Buf>(buffer: &mut B, version: i16) -> Result<Self, protocol::Error> {
+        if !(0..=12).contains(&version) {
+            return Err(protocol::Error::UnsupportedVersion);
+        }
+
+        let topic_id = if version >= 10 {
+            Uuid::read(buffer)
+        } else {
+            Uuid::nil()
+        };
+        let name = if version <= 8 {
+            KafkaString::read_inner(buffer)
+        } else {
+            CompactKafkaString::read_result_inner(buffer)?
+        };
+        if version <= 9 && name.is_none() {
+            return Err(protocol::Error::IllegalArgument(
+                "non-nullable field name was serialized as null",
+            ));
+        }
+        if version >= 9 {
+            let _tagged_fields = TaggedFields::read_result_inner(buffer)?;
+        }
+
+        Ok(Self {
+            version,
+            topic_id,
+            name,
+        })
+    }
+}
+impl Writable for MetadataRequestTopic {
+    fn write<B: BufMut>(&self, buffer: &mut B) {
+        if self.version >= 10 {
+            self.topic_id.write(buffer);
+        }
+        if self.version <= 8 {
+            KafkaString::write_inner(buffer, self.name());
+        } else {
+            CompactKafkaString::write_inner(buffer, self.name());
+            TaggedFields::write_empty(buffer);
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Response {
+    version: i16,
+    throttle_time_ms: i32,
+    brokers: Vec<MetadataResponseBroker>,
+    cluster_id: Option<Bytes>,
+    controller_id: i32,
+    topics: Vec<MetadataResponseTopic>,
+    cluster_authorized_operations: i32,
+}
+impl Response {
+    fn new(
+        version: i16,
+        throttle_time_ms: i32,
+        brokers: Vec<MetadataResponseBroker>,
+        cluster_id: Option<&str>,
+        controller_id: i32,
+        topics: Vec<MetadataResponseTopic>,
+        cluster_authorized_operations: i32,
+    ) -> Self {
+        Self {
+            version,
+            throttle_time_ms,
+            brokers,
+            cluster_id: cluster_id.map(|id| Bytes::copy_from_slice(id.as_bytes())),
+            controller_id,
+            topics,
+            cluster_authorized_operations,
+        }
+    }
+
+    pub fn v0(brokers: Vec<MetadataResponseBroker>, topics: Vec<MetadataResponseTopic>) -> Self {
+        Self::new(
+            0,
+            0,
+            brokers,
+            None,
+            -1,
+            topics,
+            AUTHORIZED_OPERATIONS_OMITTED,
+        )
+    }
+
+    pub fn v1(
+        brokers: Vec<MetadataResponseBroker>,
+        controller_id: i32,
+        topics: Vec<MetadataResponseTopic>,
+    ) -> Self {
+        Self::new(
+            1,
+            0,
+            brokers,
+            None,
+            controller_id,
+            topics,
+            AUTHORIZED_OPERATIONS_OMITTED,
+        )
+    }
+
+    pub fn v2(
+        brokers: Vec<MetadataResponseBroker>,
+        cluster_id: Option<&str>,
+        controller_id: i32,
+        topics: Vec<MetadataResponseTopic>,
+    ) -> Self {
+        Self::new(
+            2,
+            0,
+            brokers,
+            cluster_id,
+            controller_id,
+            topics,
+            AUTHORIZED_OPERATIONS_OMITTED,
+        )
+    }
+
+    pub fn v3(
+        throttle_time_ms: i32,
+        brokers: Vec<MetadataResponseBroker>,
+        cluster_id: Option<&str>,
+        controller_id: i32,
+        topics: Vec<MetadataResponseTopic>,
+    ) -> Self {
+        Self::new(
+            3,
+            throttle_time_ms,
+            brokers,
+            cluster_id,
+            controller_id,
+            topics,
+            AUTHORIZED_OPERATIONS_OMITTED,
+        )
+    }
+
+    pub fn v4(
+        throttle_time_ms: i32,
+        brokers: Vec<MetadataResponseBroker>,
+        cluster_id: Option<&str>,
+        controller_id: i32,
+        topics: Vec<MetadataResponseTopic>,
+    ) -> Self {
+        Self::new(
+            4,
+            throttle_time_ms,
+            brokers,
+            cluster_id,
+            controller_id,
+            topics,
+            AUTHORIZED_OPERATIONS_OMITTED,
+        )
+    }
+
+    pub fn v5(
+        throttle_time_ms: i32,
+        brokers: Vec<MetadataResponseBroker>,
+        cluster_id: Option<&str>,
+        controller_id: i32,
+        topics: Vec<MetadataResponseTopic>,
+    ) -> Self {
+        Self::new(
+            5,
+            throttle_time_ms,
+            brokers,
+            cluster_id,
+            controller_id,
+            topics,
+            AUTHORIZED_OPERATIONS_OMITTED,
+        )
+    }
+
+    pub fn v6(
+        throttle_time_ms: i32,
+        brokers: Vec<MetadataResponseBroker>,
+        cluster_id: Option<&str>,
+        controller_id: i32,
+        topics: Vec<MetadataResponseTopic>,
+    ) -> Self {
+        Self::new(
+            6,
+            throttle_time_ms,
+            brokers,
+            cluster_id,
+            controller_id,
+            topics,
+            AUTHORIZED_OPERATIONS_OMITTED,
+        )
+    }
+
+    pub fn v7(
+        throttle_time_ms: i32,
+        brokers: Vec<MetadataResponseBroker>,
+        cluster_id: Option<&str>,
+        controller_id: i32,
+        topics: Vec<MetadataResponseTopic>,
+    ) -> Self {
+        Self::new(
+            7,
+            throttle_time_ms,
+            brokers,
+            cluster_id,
+            controller_id,
+            topics,
+            AUTHORIZED_OPERATIONS_OMITTED,
+        )
+    }
+
+    pub fn v8(
+        throttle_time_ms: i32,
+        brokers: Vec<MetadataResponseBroker>,
+        cluster_id: Option<&str>,
+        controller_id: i32,
+        topics: Vec<MetadataResponseTopic>,
+        cluster_authorized_operations: i32,
+    ) -> Self {
+        Self::new(
+            8,
+            throttle_time_ms,
+            brokers,
+            cluster_id,
+            controller_id,
+            topics,
+            cluster_authorized_operations,
+        )
+    }
+
+    pub fn v9(
+        throttle_time_ms: i32,
+        brokers: Vec<MetadataResponseBroker>,
+        cluster_id: Option<&str>,
+        controller_id: i32,
+        topics: Vec<MetadataResponseTopic>,
+        cluster_authorized_operations: i32,
+    ) -> Self {
+        Self::new(
+            9,
+            throttle_time_ms,
+            brokers,
+            cluster_id,
+            controller_id,
+            topics,
+            cluster_authorized_operations,
+        )
+    }
+
+    pub fn v10(
+        throttle_time_ms: i32,
+        brokers: Vec<MetadataResponseBroker>,
+        cluster_id: Option<&str>,
+        controller_id: i32,
+        topics: Vec<MetadataResponseTopic>,
+        cluster_authorized_operations: i32,
+    ) -> Self {
+        Self::new(
+            10,
+            throttle_time_ms,
+            brokers,
+            cluster_id,
+            controller_id,
+            topics,
+            cluster_authorized_operations,
+        )
+    }
+
+    pub fn v11(
+        throttle_time_ms: i32,
+        brokers: Vec<MetadataResponseBroker>,
+        cluster_id: Option<&str>,
+        controller_id: i32,
+        topics: Vec<MetadataResponseTopic>,
+    ) -> Self {
+        Self::new(
+            11,
+            throttle_time_ms,
+            brokers,
+            cluster_id,
+            controller_id,
+            topics,
+            AUTHORIZED_OPERATIONS_OMITTED,
+        )
+    }
+
+    pub fn v12(
+        throttle_time_ms: i32,
+        brokers: Vec<MetadataResponseBroker>,
+        cluster_id: Option<&str>,
+        controller_id: i32,
+        topics: Vec<MetadataResponseTopic>,
+    ) -> Self {
+        Self::new(
+            12,
+            throttle_time_ms,
+            brokers,
+            cluster_id,
+            controller_id,
+            topics,
+            AUTHORIZED_OPERATIONS_OMITTED,
+        )
+    }
+
+    pub fn throttle_time_ms(&self) -> i32 {
+        self.throttle_time_ms
+    }
+
+    pub fn set_throttle_time_ms(&mut self, throttle_time_ms: i32) {
+        self.throttle_time_ms = throttle_time_ms;
+    }
+
+    pub fn brokers(&self) -> &[MetadataResponseBroker] {
+        &self.brokers
+    }
+
+    pub fn cluster_id(&self) -> Option<&[u8]> {
+        self.cluster_id.as_deref()
+    }
+
+    pub fn controller_id(&self) -> i32 {
+        self.controller_id
+    }
+
+    pub fn topics(&self) -> &[MetadataResponseTopic] {
+        &self.topics
+    }
+
+    pub fn cluster_authorized_operations(&self) -> i32 {
+        self.cluster_authorized_operations
+    }
+}
+impl ReadableVersion for Response {
+    fn read_version<B: Buf>(buffer: &mut B, version: i16) -> Result<Self, protocol::Error> {
+        if !(0..=12).contains(&version) {
+            return Err(protocol::Error::UnsupportedVersion);
+        }
+
+        let throttle_time_ms = if version >= 3 { i32::read(buffer) } else { 0 };
+        let brokers = if version <= 8 {
+            Array::<MetadataResponseBroker>::read_version_inner(buffer, version)
+        } else {
+            CompactArray::<MetadataResponseBroker>::read_version_inner(buffer, version)
+        }?
+        .ok_or(protocol::Error::IllegalArgument(
+            "non-nullable field brokers was serialized as null",
+        ))?;
+        let cluster_id = match version {
+            0..=1 => None,
+            2..=8 => KafkaString::read_inner(buffer),
+            _ => CompactKafkaString::read_result_inner(buffer)?,
+        };
+        let controller_id = if version >= 1 { i32::read(buffer) } else { -1 };
+        let topics = if version <= 8 {
+            Array::<MetadataResponseTopic>::read_version_inner(buffer, version)
+        } else {
+            CompactArray::<MetadataResponseTopic>::read_version_inner(buffer, version)
+        }?
+        .ok_or(protocol::Error::IllegalArgument(
+            "non-nullable field topics was serialized as null",
+        ))?;
+        let cluster_authorized_operations = if (8..=10).contains(&version) {
+            i32::read(buffer)
+        } else {
+            AUTHORIZED_OPERATIONS_OMITTED
+        };
+        if version >= 9 {
+            let _tagged_fields = TaggedFields::read_result_inner(buffer)?;
+        }
+
+        Ok(Self {
+            version,
+            throttle_time_ms,
+            brokers,
+            cluster_id,
+            controller_id,
+            topics,
+            cluster_authorized_operations,
+        })
+    }
+}
+impl Writable for Response {
+    fn write<B: BufMut>(&self, buffer: &mut B) {
+        if self.version >= 3 {
+            self.throttle_time_ms.write(buffer);
+        }
+        if self.version <= 8 {
+            Array::write_inner(buffer, Some(self.brokers()));
+        } else {
+            CompactArray::write_inner(buffer, Some(self.brokers()));
+        }
+        match self.version {
+            0..=1 => {}
+            2..=8 => KafkaString::write_inner(buffer, self.cluster_id()),
+            _ => CompactKafkaString::write_inner(buffer, self.cluster_id()),
+        }
+        if self.version >= 1 {
+            self.controller_id.write(buffer);
+        }
+        if self.version <= 8 {
+            Array::write_inner(buffer, Some(self.topics()));
+        } else {
+            CompactArray::write_inner(buffer, Some(self.topics()));
+        }
+        if (8..=10).contains(&self.version) {
+            self.cluster_authorized_operations.write(buffer);
+        }
+        if self.version >= 9 {
+            TaggedFields::write_empty(buffer);
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct MetadataResponseBroker {
+    version: i16,
+    node_id: i32,
+    host: Bytes,
+    port: i32,
+    rack: Option<Bytes>,
+}
+impl MetadataResponseBroker {
+    fn new(version: i16, node_id: i32, host: &str, port: i32, rack: Option<&str>) -> Self {
+        Self {
+            version,
+            node_id,
+            host: Bytes::copy_from_slice(host.as_bytes()),
+            port,
+            rack: rack.map(|r| Bytes::copy_from_slice(r.as_bytes())),
+        }
+    }
+
+    pub fn v0(node_id: i32, host: &str, port: i32) -> Self {
+        Self::new(0, node_id, host, port, None)
+    }
+
+    pub fn v1(node_id: i32, host: &str, port: i32, rack: Option<&str>) -> Self {
+        Self::new(1, node_id, host, port, rack)
+    }
+
+    pub fn v2(node_id: i32, host: &str, port: i32, rack: Option<&str>) -> Self {
+        Self::new(2, node_id, host, port, rack)
+    }
+
+    pub fn v3(node_id: i32, host: &str, port: i32, rack: Option<&str>) -> Self {
+        Self::new(3, node_id, host, port, rack)
+    }
+
+    pub fn v4(node_id: i32, host: &str, port: i32, rack: Option<&str>) -> Self {
+        Self::new(4, node_id, host, port, rack)
+    }
+
+    pub fn v5(node_id: i32, host: &str, port: i32, rack: Option<&str>) -> Self {
+        Self::new(5, node_id, host, port, rack)
+    }
+
+    pub fn v6(node_id: i32, host: &str, port: i32, rack: Option<&str>) -> Self {
+        Self::new(6, node_id, host, port, rack)
+    }
+
+    pub fn v7(node_id: i32, host: &str, port: i32, rack: Option<&str>) -> Self {
+        Self::new(7, node_id, host, port, rack)
+    }
+
+    pub fn v8(node_id: i32, host: &str, port: i32, rack: Option<&str>) -> Self {
+        Self::new(8, node_id, host, port, rack)
+    }
+
+    pub fn v9(node_id: i32, host: &str, port: i32, rack: Option<&str>) -> Self {
+        Self::new(9, node_id, host, port, rack)
+    }
+
+    pub fn v10(node_id: i32, host: &str, port: i32, rack: Option<&str>) -> Self {
+        Self::new(10, node_id, host, port, rack)
+    }
+
+    pub fn v11(node_id: i32, host: &str, port: i32, rack: Option<&str>) -> Self {
+        Self::new(11, node_id, host, port, rack)
+    }
+
+    pub fn v12(node_id: i32, host: &str, port: i32, rack: Option<&str>) -> Self {
+        Self::new(12, node_id, host, port, rack)
+    }
+
+    pub fn node_id(&self) -> i32 {
+        self.node_id
+    }
+
+    pub fn host(&self) -> &[u8] {
+        &self.host
+    }
+
+    pub fn port(&self) -> i32 {
+        self.port
+    }
+
+    pub fn rack(&self) -> Option<&[u8]> {
+        self.rack.as_deref()
+    }
+}
+impl ReadableVersion for MetadataResponseBroker {
+    fn read_version<B: Buf>(buffer: &mut B, version: i16) -> Result<Self, protocol::Error> {
+        if !(0..=12).contains(&version) {
+            return Err(protocol::Error::UnsupportedVersion);
+        }
+
+        let node_id = i32::read(buffer);
+        let host = if version <= 8 {
+            KafkaString::read_inner(buffer)
+        } else {
+            CompactKafkaString::read_result_inner(buffer)?
+        }
+        .ok_or(protocol::Error::IllegalArgument(
+            "non-nullable field host was serialized as null",
+        ))?;
+        let port = i32::read(buffer);
+        let rack = match version {
+            0 => None,
+            1..=8 => KafkaString::read_inner(buffer),
+            _ => CompactKafkaString::read_result_inner(buffer)?,
+        };
+        if version >= 9 {
+            let _tagged_fields = TaggedFields::read_result_inner(buffer)?;
+        }
+
+        Ok(Self {
+            version,
+            node_id,
+            host,
+            port,
+            rack,
+        })
+    }
+}
+impl Writable for MetadataResponseBroker {
+    fn write<B: BufMut>(&self, buffer: &mut B) {
+        self.node_id.write(buffer);
+        if self.version <= 8 {
+            KafkaString::write_inner(buffer, Some(self.host()));
+        } else {
+            CompactKafkaString::write_inner(buffer, Some(self.host()));
+        }
+        self.port.write(buffer);
+        match self.version {
+            0 => {}
+            1..=8 => KafkaString::write_inner(buffer, self.rack()),
+            _ => CompactKafkaString::write_inner(buffer, self.rack()),
+        }
+        if self.version >= 9 {
+            TaggedFields::write_empty(buffer);
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct MetadataResponseTopic {
+    version: i16,
+    error_code: ErrorCode,
+    name: Option<Bytes>,
+    topic_id: Uuid,
+    is_internal: bool,
+    partitions: Vec<MetadataResponsePartition>,
+    topic_authorized_operations: i32,
+}
+impl MetadataResponseTopic {
+    fn new(
+        version: i16,
+        error_code: ErrorCode,
+        name: Option<&str>,
+        topic_id: Uuid,
+        is_internal: bool,
+        partitions: Vec<MetadataResponsePartition>,
+        topic_authorized_operations: i32,
+    ) -> Self {
+        Self {
+            version,
+            error_code,
+            name: name.map(|n| Bytes::copy_from_slice(n.as_bytes())),
+            topic_id,
+            is_internal,
+            partitions,
+            topic_authorized_operations,
+        }
+    }
+
+    pub fn v0(
+        error_code: ErrorCode,
+        name: &str,
+        partitions: Vec<MetadataResponsePartition>,
+    ) -> Self {
+        Self::new(
+            0,
+            error_code,
+            Some(name),
+            Uuid::nil(),
+            false,
+            partitions,
+            AUTHORIZED_OPERATIONS_OMITTED,
+        )
+    }
+
+    pub fn v1(
+        error_code: ErrorCode,
+        name: &str,
+        is_internal: bool,
+        partitions: Vec<MetadataResponsePartition>,
+    ) -> Self {
+        Self::new(
+            1,
+            error_code,
+            Some(name),
+            Uuid::nil(),
+            is_internal,
+            partitions,
+            AUTHORIZED_OPERATIONS_OMITTED,
+        )
+    }
+
+    pub fn v2(
+        error_code: ErrorCode,
+        name: &str,
+        is_internal: bool,
+        partitions: Vec<MetadataResponsePartition>,
+    ) -> Self {
+        Self::new(
+            2,
+            error_code,
+            Some(name),
+            Uuid::nil(),
+            is_internal,
+            partitions,
+            AUTHORIZED_OPERATIONS_OMITTED,
+        )
+    }
+
+    pub fn v3(
+        error_code: ErrorCode,
+        name: &str,
+        is_internal: bool,
+        partitions: Vec<MetadataResponsePartition>,
+    ) -> Self {
+        Self::new(
+            3,
+            error_code,
+            Some(name),
+            Uuid::nil(),
+            is_internal,
+            partitions,
+            AUTHORIZED_OPERATIONS_OMITTED,
+        )
+    }
+
+    pub fn v4(
+        error_code: ErrorCode,
+        name: &str,
+        is_internal: bool,
+        partitions: Vec<MetadataResponsePartition>,
+    ) -> Self {
+        Self::new(
+            4,
+            error_code,
+            Some(name),
+            Uuid::nil(),
+            is_internal,
+            partitions,
+            AUTHORIZED_OPERATIONS_OMITTED,
+        )
+    }
+
+    pub fn v5(
+        error_code: ErrorCode,
+        name: &str,
+        is_internal: bool,
+        partitions: Vec<MetadataResponsePartition>,
+    ) -> Self {
+        Self::new(
+            5,
+            error_code,
+            Some(name),
+            Uuid::nil(),
+            is_internal,
+            partitions,
+            AUTHORIZED_OPERATIONS_OMITTED,
+        )
+    }
+
+    pub fn v6(
+        error_code: ErrorCode,
+        name: &str,
+        is_internal: bool,
+        partitions: Vec<MetadataResponsePartition>,
+    ) -> Self {
+        Self::new(
+            6,
+            error_code,
+            Some(name),
+            Uuid::nil(),
+            is_internal,
+            partitions,
+            AUTHORIZED_OPERATIONS_OMITTED,
+        )
+    }
+
+    pub fn v7(
+        error_code: ErrorCode,
+        name: &str,
+        is_internal: bool,
+        partitions: Vec<MetadataResponsePartition>,
+    ) -> Self {
+        Self::new(
+            7,
+            error_code,
+            Some(name),
+            Uuid::nil(),
+            is_internal,
+            partitions,
+            AUTHORIZED_OPERATIONS_OMITTED,
+        )
+    }
+
+    pub fn v8(
+        error_code: ErrorCode,
+        name: &str,
+        is_internal: bool,
+        partitions: Vec<MetadataResponsePartition>,
+        topic_authorized_operations: i32,
+    ) -> Self {
+        Self::new(
+            8,
+            error_code,
+            Some(name),
+            Uuid::nil(),
+            is_internal,
+            partitions,
+            topic_authorized_operations,
+        )
+    }
+
+    pub fn v9(
+        error_code: ErrorCode,
+        name: &str,
+        is_internal: bool,
+        partitions: Vec<MetadataResponsePartition>,
+        topic_authorized_operations: i32,
+    ) -> Self {
+        Self::new(
+            9,
+            error_code,
+            Some(name),
+            Uuid::nil(),
+            is_internal,
+            partitions,
+            topic_authorized_operations,
+        )
+    }
+
+    pub fn v10(
+        error_code: ErrorCode,
+        name: &str,
+        topic_id: Uuid,
+        is_internal: bool,
+        partitions: Vec<MetadataResponsePartition>,
+        topic_authorized_operations: i32,
+    ) -> Self {
+        Self::new(
+            10,
+            error_code,
+            Some(name),
+            topic_id,
+            is_internal,
+            partitions,
+            topic_authorized_operations,
+        )
+    }
+
+    pub fn v11(
+        error_code: ErrorCode,
+        name: &str,
+        topic_id: Uuid,
+        is_internal: bool,
+        partitions: Vec<MetadataResponsePartition>,
+        topic_authorized_operations: i32,
+    ) -> Self {
+        Self::new(
+            11,
+            error_code,
+            Some(name),
+            topic_id,
+            is_internal,
+            partitions,
+            topic_authorized_operations,
+        )
+    }
+
+    pub fn v12(
+        error_code: ErrorCode,
+        name: Option<&str>,
+        topic_id: Uuid,
+        is_internal: bool,
+        partitions: Vec<MetadataResponsePartition>,
+        topic_authorized_operations: i32,
+    ) -> Self {
+        Self::new(
+            12,
+            error_code,
+            name,
+            topic_id,
+            is_internal,
+            partitions,
+            topic_authorized_operations,
+        )
+    }
+
+    pub fn error_code(&self) -> ErrorCode {
+        self.error_code
+    }
+
+    pub fn name(&self) -> Option<&[u8]> {
+        self.name.as_deref()
+    }
+
+    pub fn topic_id(&self) -> Uuid {
+        self.topic_id
+    }
+
+    pub fn is_internal(&self) -> bool {
+        self.is_internal
+    }
+
+    pub fn partitions(&self) -> &[MetadataResponsePartition] {
+        &self.partitions
+    }
+
+    pub fn topic_authorized_operations(&self) -> i32 {
+        self.topic_authorized_operations
+    }
+}
+impl ReadableVersion for MetadataResponseTopic {
+    fn read_version<B: Buf>(buffer: &mut B, version: i16) -> Result<Self, protocol::Error> {
+        if !(0..=12).contains(&version) {
+            return Err(protocol::Error::UnsupportedVersion);
+        }
+
+        let error_code = ErrorCode::read(buffer);
+        let name = if version <= 8 {
+            KafkaString::read_inner(buffer)
+        } else {
+            CompactKafkaString::read_result_inner(buffer)?
+        };
+        if version <= 11 && name.is_none() {
+            return Err(protocol::Error::IllegalArgument(
+                "non-nullable field name was serialized as null",
+            ));
+        }
+        let topic_id = if version >= 10 {
+            Uuid::read(buffer)
+        } else {
+            Uuid::nil()
+        };
+        let is_internal = version >= 1 && bool::read(buffer);
+        let partitions = if version <= 8 {
+            Array::<MetadataResponsePartition>::read_version_inner(buffer, version)
+        } else {
+            CompactArray::<MetadataResponsePartition>::read_version_inner(buffer, version)
+        }?
+        .ok_or(protocol::Error::IllegalArgument(
+            "non-nullable field partitions was serialized as null",
+        ))?;
+        let topic_authorized_operations = if version >= 8 {
+            i32::read(buffer)
+        } else {
+            AUTHORIZED_OPERATIONS_OMITTED
+        };
+        if version >= 9 {
+            let _tagged_fields = TaggedFields::read_result_inner(buffer)?;
+        }
+
+        Ok(Self {
+            version,
+            error_code,
+            name,
+            topic_id,
+            is_internal,
+            partitions,
+            topic_authorized_operations,
+        })
+    }
+}
+impl Writable for MetadataResponseTopic {
+    fn write<B: BufMut>(&self, buffer: &mut B) {
+        self.error_code.write(buffer);
+        // Only version 12 can say "no name"; older ones get an empty one.
+        let name = match self.name() {
+            None if self.version <= 11 => Some(&[][..]),
+            name => name,
+        };
+        if self.version <= 8 {
+            KafkaString::write_inner(buffer, name);
+        } else {
+            CompactKafkaString::write_inner(buffer, name);
+        }
+        if self.version >= 10 {
+            self.topic_id.write(buffer);
+        }
+        if self.version >= 1 {
+            self.is_internal.write(buffer);
+        }
+        if self.version <= 8 {
+            Array::write_inner(buffer, Some(self.partitions()));
+        } else {
+            CompactArray::write_inner(buffer, Some(self.partitions()));
+        }
+        if self.version >= 8 {
+            self.topic_authorized_operations.write(buffer);
+        }
+        if self.version >= 9 {
+            TaggedFields::write_empty(buffer);
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct MetadataResponsePartition {
+    version: i16,
+    error_code: ErrorCode,
+    partition_index: i32,
+    leader_id: i32,
+    leader_epoch: i32,
+    replica_nodes: Vec<i32>,
+    isr_nodes: Vec<i32>,
+    offline_replicas: Vec<i32>,
+}
+impl MetadataResponsePartition {
+    fn new(
+        version: i16,
+        error_code: ErrorCode,
+        partition_index: i32,
+        leader_id: i32,
+        leader_epoch: i32,
+        replica_nodes: Vec<i32>,
+        isr_nodes: Vec<i32>,
+        offline_replicas: Vec<i32>,
+    ) -> Self {
+        Self {
+            version,
+            error_code,
+            partition_index,
+            leader_id,
+            leader_epoch,
+            replica_nodes,
+            isr_nodes,
+            offline_replicas,
+        }
+    }
+
+    pub fn v0(
+        error_code: ErrorCode,
+        partition_index: i32,
+        leader_id: i32,
+        replica_nodes: Vec<i32>,
+        isr_nodes: Vec<i32>,
+    ) -> Self {
+        Self::new(
+            0,
+            error_code,
+            partition_index,
+            leader_id,
+            -1,
+            replica_nodes,
+            isr_nodes,
+            vec![],
+        )
+    }
+
+    pub fn v1(
+        error_code: ErrorCode,
+        partition_index: i32,
+        leader_id: i32,
+        replica_nodes: Vec<i32>,
+        isr_nodes: Vec<i32>,
+    ) -> Self {
+        Self::new(
+            1,
+            error_code,
+            partition_index,
+            leader_id,
+            -1,
+            replica_nodes,
+            isr_nodes,
+            vec![],
+        )
+    }
+
+    pub fn v2(
+        error_code: ErrorCode,
+        partition_index: i32,
+        leader_id: i32,
+        replica_nodes: Vec<i32>,
+        isr_nodes: Vec<i32>,
+    ) -> Self {
+        Self::new(
+            2,
+            error_code,
+            partition_index,
+            leader_id,
+            -1,
+            replica_nodes,
+            isr_nodes,
+            vec![],
+        )
+    }
+
+    pub fn v3(
+        error_code: ErrorCode,
+        partition_index: i32,
+        leader_id: i32,
+        replica_nodes: Vec<i32>,
+        isr_nodes: Vec<i32>,
+    ) -> Self {
+        Self::new(
+            3,
+            error_code,
+            partition_index,
+            leader_id,
+            -1,
+            replica_nodes,
+            isr_nodes,
+            vec![],
+        )
+    }
+
+    pub fn v4(
+        error_code: ErrorCode,
+        partition_index: i32,
+        leader_id: i32,
+        replica_nodes: Vec<i32>,
+        isr_nodes: Vec<i32>,
+    ) -> Self {
+        Self::new(
+            4,
+            error_code,
+            partition_index,
+            leader_id,
+            -1,
+            replica_nodes,
+            isr_nodes,
+            vec![],
+        )
+    }
+
+    pub fn v5(
+        error_code: ErrorCode,
+        partition_index: i32,
+        leader_id: i32,
+        replica_nodes: Vec<i32>,
+        isr_nodes: Vec<i32>,
+        offline_replicas: Vec<i32>,
+    ) -> Self {
+        Self::new(
+            5,
+            error_code,
+            partition_index,
+            leader_id,
+            -1,
+            replica_nodes,
+            isr_nodes,
+            offline_replicas,
+        )
+    }
+
+    pub fn v6(
+        error_code: ErrorCode,
+        partition_index: i32,
+        leader_id: i32,
+        replica_nodes: Vec<i32>,
+        isr_nodes: Vec<i32>,
+        offline_replicas: Vec<i32>,
+    ) -> Self {
+        Self::new(
+            6,
+            error_code,
+            partition_index,
+            leader_id,
+            -1,
+            replica_nodes,
+            isr_nodes,
+            offline_replicas,
+        )
+    }
+
+    pub fn v7(
+        error_code: ErrorCode,
+        partition_index: i32,
+        leader_id: i32,
+        leader_epoch: i32,
+        replica_nodes: Vec<i32>,
+        isr_nodes: Vec<i32>,
+        offline_replicas: Vec<i32>,
+    ) -> Self {
+        Self::new(
+            7,
+            error_code,
+            partition_index,
+            leader_id,
+            leader_epoch,
+            replica_nodes,
+            isr_nodes,
+            offline_replicas,
+        )
+    }
+
+    pub fn v8(
+        error_code: ErrorCode,
+        partition_index: i32,
+        leader_id: i32,
+        leader_epoch: i32,
+        replica_nodes: Vec<i32>,
+        isr_nodes: Vec<i32>,
+        offline_replicas: Vec<i32>,
+    ) -> Self {
+        Self::new(
+            8,
+            error_code,
+            partition_index,
+            leader_id,
+            leader_epoch,
+            replica_nodes,
+            isr_nodes,
+            offline_replicas,
+        )
+    }
+
+    pub fn v9(
+        error_code: ErrorCode,
+        partition_index: i32,
+        leader_id: i32,
+        leader_epoch: i32,
+        replica_nodes: Vec<i32>,
+        isr_nodes: Vec<i32>,
+        offline_replicas: Vec<i32>,
+    ) -> Self {
+        Self::new(
+            9,
+            error_code,
+            partition_index,
+            leader_id,
+            leader_epoch,
+            replica_nodes,
+            isr_nodes,
+            offline_replicas,
+        )
+    }
+
+    pub fn v10(
+        error_code: ErrorCode,
+        partition_index: i32,
+        leader_id: i32,
+        leader_epoch: i32,
+        replica_nodes: Vec<i32>,
+        isr_nodes: Vec<i32>,
+        offline_replicas: Vec<i32>,
+    ) -> Self {
+        Self::new(
+            10,
+            error_code,
+            partition_index,
+            leader_id,
+            leader_epoch,
+            replica_nodes,
+            isr_nodes,
+            offline_replicas,
+        )
+    }
+
+    pub fn v11(
+        error_code: ErrorCode,
+        partition_index: i32,
+        leader_id: i32,
+        leader_epoch: i32,
+        replica_nodes: Vec<i32>,
+        isr_nodes: Vec<i32>,
+        offline_replicas: Vec<i32>,
+    ) -> Self {
+        Self::new(
+            11,
+            error_code,
+            partition_index,
+            leader_id,
+            leader_epoch,
+            replica_nodes,
+            isr_nodes,
+            offline_replicas,
+        )
+    }
+
+    pub fn v12(
+        error_code: ErrorCode,
+        partition_index: i32,
+        leader_id: i32,
+        leader_epoch: i32,
+        replica_nodes: Vec<i32>,
+        isr_nodes: Vec<i32>,
+        offline_replicas: Vec<i32>,
+    ) -> Self {
+        Self::new(
+            12,
+            error_code,
+            partition_index,
+            leader_id,
+            leader_epoch,
+            replica_nodes,
+            isr_nodes,
+            offline_replicas,
+        )
+    }
+
+    pub fn error_code(&self) -> ErrorCode {
+        self.error_code
+    }
+
+    pub fn partition_index(&self) -> i32 {
+        self.partition_index
+    }
+
+    pub fn leader_id(&self) -> i32 {
+        self.leader_id
+    }
+
+    pub fn leader_epoch(&self) -> i32 {
+        self.leader_epoch
+    }
+
+    pub fn replica_nodes(&self) -> &[i32] {
+        &self.replica_nodes
+    }
+
+    pub fn isr_nodes(&self) -> &[i32] {
+        &self.isr_nodes
+    }
+
+    pub fn offline_replicas(&self) -> &[i32] {
+        &self.offline_replicas
+    }
+}
+impl ReadableVersion for MetadataResponsePartition {
+    fn read_version<B: Buf>(buffer: &mut B, version: i16) -> Result<Self, protocol::Error> {
+        if !(0..=12).contains(&version) {
+            return Err(protocol::Error::UnsupportedVersion);
+        }
+
+        let read_nodes = |buffer: &mut B, null_error| {
+            if version <= 8 {
+                Ok(Array::<i32>::read_inner(buffer))
+            } else {
+                CompactArray::<i32>::read_inner(buffer)
+            }
+            .and_then(|nodes| nodes.ok_or(protocol::Error::IllegalArgument(null_error)))
+        };
+
+        let error_code = ErrorCode::read(buffer);
+        let partition_index = i32::read(buffer);
+        let leader_id = i32::read(buffer);
+        let leader_epoch = if version >= 7 { i32::read(buffer) } else { -1 };
+        let replica_nodes = read_nodes(
+            buffer,
+            "non-nullable field replicaNodes was serialized as null",
+        )?;
+        let isr_nodes = read_nodes(buffer, "non-nullable field isrNodes was serialized as null")?;
+        let offline_replicas = if version >= 5 {
+            read_nodes(
+                buffer,
+                "non-nullable field offlineReplicas was serialized as null",
+            )?
+        } else {
+            vec![]
+        };
+        if version >= 9 {
+            let _tagged_fields = TaggedFields::read_result_inner(buffer)?;
+        }
+
+        Ok(Self {
+            version,
+            error_code,
+            partition_index,
+            leader_id,
+            leader_epoch,
+            replica_nodes,
+            isr_nodes,
+            offline_replicas,
+        })
+    }
+}
+impl Writable for MetadataResponsePartition {
+    fn write<B: BufMut>(&self, buffer: &mut B) {
+        let mut nodes = vec![self.replica_nodes(), self.isr_nodes()];
+        if self.version >= 5 {
+            nodes.push(self.offline_replicas());
+        }
+
+        self.error_code.write(buffer);
+        self.partition_index.write(buffer);
+        self.leader_id.write(buffer);
+        if self.version >= 7 {
+            self.leader_epoch.write(buffer);
+        }
+        for nodes in nodes {
+            if self.version <= 8 {
+                Array::write_inner(buffer, Some(nodes));
+            } else {
+                CompactArray::write_inner(buffer, Some(nodes));
+            }
+        }
+        if self.version >= 9 {
+            TaggedFields::write_empty(buffer);
+        }
+    }
+}
+
+// Brokers without an endpoint on the client's listener are left out, and so a
+// partition led by one of them reports LEADER_NOT_AVAILABLE. Topics are never
+// auto-created.
+pub fn process_request(
+    request: Request,
+    metadata: &HashMap<Uuid, model::Topic>,
+    brokers: &[model::BrokerRegistration],
+    listener_name: &str,
+    cluster_id: Option<&str>,
+    auth: &Authorization,
+) -> Result<Response, protocol::Error> {
+    let version = request.version;
+    let brokers = brokers
+        .iter()
+        .filter_map(|broker| {
+            broker.listener(listener_name).map(|listener| {
+                MetadataResponseBroker::new(
+                    version,
+                    broker.id(),
+                    listener.host(),
+                    listener.port() as i32,
+                    broker.rack(),
+                )
+            })
+        })
+        .collect::<Vec<_>>();
+    let requested = match request.topics() {
+        Some(topics) if version > 0 || !topics.is_empty() => {
+            let mut result = Vec::with_capacity(topics.len());
+            for topic in topics {
+                // Unauthorized topics are reported before unknown ones so that
+                // clients cannot probe for names they may not see.
+                let found = match topic.name() {
+                    Some(name) => {
+                        let name = std::str::from_utf8(name)?;
+                        if auth.authorize(AclOperation::Describe, ResourceType::Topic, name) {
+                            topic_by_name(metadata, name).ok_or((
+                                ErrorCode::UnknownTopicOrPartition,
+                                Some(name),
+                                topic.topic_id(),
+                            ))
+                        } else {
+                            Err((ErrorCode::TopicAuthorizationFailed, Some(name), Uuid::nil()))
+                        }
+                    }
+                    None => match metadata.get(&topic.topic_id()) {
+                        Some(found)
+                            if !auth.authorize(
+                                AclOperation::Describe,
+                                ResourceType::Topic,
+                                found.name(),
+                            ) =>
+                        {
+                            Err((ErrorCode::TopicAuthorizationFailed, None, topic.topic_id()))
+                        }
+                        Some(found) => Ok(found),
+                        None => Err((ErrorCode::UnknownTopicId, None, topic.topic_id())),
+                    },
+                };
+                result.push(found);
+            }
+            result
+        }
+        _ => {
+            let mut topics = metadata
+                .values()
+                .filter(|topic| {
+                    auth.authorize(AclOperation::Describe, ResourceType::Topic, topic.name())
+                })
+                .collect::<Vec<_>>();
+            topics.sort_by(|a, b| a.name().cmp(b.name()));
+            topics.into_iter().map(Ok).collect()
+        }
+    };
+
+    let topics = requested
+        .into_iter()
+        .map(|found| match found {
+            Ok(topic) => {
+                let mut partitions = topic
+                    .partitions()
+                    .iter()
+                    .map(|partition| {
+                        let leader = partition.leader();
+                        let error_code = if brokers.iter().any(|b| b.node_id() == leader) {
+                            ErrorCode::None
+                        } else {
+                            ErrorCode::LeaderNotAvailable
+                        };
+                        MetadataResponsePartition::new(
+                            version,
+                            error_code,
+                            partition.id(),
+                            leader,
+                            partition.leader_epoch(),
+                            partition.replicas().to_vec(),
+                            partition.isr().to_vec(),
+                            vec![],
+                        )
+                    })
+                    .collect::<Vec<_>>();
+                partitions.sort_by_key(|partition| partition.partition_index());
+                let topic_authorized_operations = if request.include_topic_authorized_operations() {
+                    auth.authorized_operations(ResourceType::Topic, topic.name())
+                } else {
+                    AUTHORIZED_OPERATIONS_OMITTED
+                };
+                MetadataResponseTopic::new(
+                    version,
+                    ErrorCode::None,
+                    Some(topic.name()),
+                    topic.id(),
+                    INTERNAL_TOPICS.contains(&topic.name()),
+                    partitions,
+                    topic_authorized_operations,
+                )
+            }
+            Err((error_code, name, topic_id)) => MetadataResponseTopic::new(
+                version,
+                error_code,
+                name,
+                topic_id,
+                false,
+                vec![],
+                AUTHORIZED_OPERATIONS_OMITTED,
+            ),
+        })
+        .collect();
+
+    // KRaft brokers do not expose the controller quorum; like Kafka, report a
+    // live broker instead.
+    let controller_id = brokers.iter().map(|b| b.node_id()).min().unwrap_or(-1);
+    let cluster_authorized_operations = if request.include_cluster_authorized_operations() {
+        auth.authorized_operations(ResourceType::Cluster, CLUSTER_NAME)
+    } else {
+        AUTHORIZED_OPERATIONS_OMITTED
+    };
+    Ok(Response::new(
+        version,
+        0,
+        brokers,
+        cluster_id,
+        controller_id,
+        topics,
+        cluster_authorized_operations,
+    ))
+}
+
+pub(crate) fn error_response(version: i16, error_code: ErrorCode) -> Response {
+    let version = version.clamp(0, 12);
+    Response::new(
+        version,
+        0,
+        vec![],
+        None,
+        -1,
+        vec![MetadataResponseTopic::new(
+            version,
+            error_code,
+            None,
+            Uuid::nil(),
+            false,
+            vec![],
+            AUTHORIZED_OPERATIONS_OMITTED,
+        )],
+        AUTHORIZED_OPERATIONS_OMITTED,
+    )
+}
@@ -1,33 +1,95 @@
+pub(crate) mod alter_client_quotas;
+pub(crate) mod alter_configs;
+pub(crate) mod alter_user_scram_credentials;
 pub(crate) mod api_versions;
+pub(crate) mod create_acls;
+pub(crate) mod delete_acls;
+pub(crate) mod describe_acls;
+pub(crate) mod describe_client_quotas;
+pub(crate) mod describe_cluster;
+pub(crate) mod describe_configs;
 pub(crate) mod describe_topic_partitions;
+pub(crate) mod describe_user_scram_credentials;
 pub(crate) mod fetch;
+pub(crate) mod incremental_alter_configs;
+pub(crate) mod metadata;
 pub(crate) mod request_header;
 pub(crate) mod response_header;
+pub(crate) mod sasl_authenticate;
+pub(crate) mod sasl_handshake;
 
 use std::{collections::HashMap, sync::LazyLock};
 
-use bytes::{Buf, BufMut};
+use bytes::{Buf, BufMut, Bytes};
 use uuid::Uuid;
 
 use crate::{
+    broker::Broker,
+    config::dynamic::ConfigResolver,
     model,
-    protocol::{ReadableResult, Writable},
-    FINALIZED_FEATURES, METADATA_CACHE, SUPPORTED_APIS, SUPPORTED_FEATURES,
+    protocol::{ErrorCode, ReadableResult, Writable},
+    security::{Authorization, Session},
+    SUPPORTED_APIS, SUPPORTED_FEATURES,
 };
 
-use super::{Readable, ReadableVersion};
+use super::ReadableVersion;
 
+use alter_client_quotas::process_request as process_alter_client_quotas_request;
+use alter_configs::process_request as process_alter_configs_request;
+use alter_user_scram_credentials::process_request as process_alter_user_scram_credentials_request;
 use api_versions::process_request as process_api_versions_request;
+use create_acls::process_request as process_create_acls_request;
+use delete_acls::process_request as process_delete_acls_request;
+use describe_acls::process_request as process_describe_acls_request;
+use describe_client_quotas::process_request as process_describe_client_quotas_request;
+use describe_cluster::process_request as process_describe_cluster_request;
+use describe_configs::process_request as process_describe_configs_request;
 use describe_topic_partitions::process_request as process_describe_topic_partitions_request;
+use describe_user_scram_credentials::process_request as process_describe_user_scram_credentials_request;
 use fetch::process_request as process_fetch_request;
+use incremental_alter_configs::process_request as process_incremental_alter_configs_request;
+use metadata::process_request as process_metadata_request;
+use sasl_authenticate::process_request as process_sasl_authenticate_request;
+use sasl_handshake::process_request as process_sasl_handshake_request;
 
+pub use alter_client_quotas::{
+    Request as AlterClientQuotasRequest, Response as AlterClientQuotasResponse,
+};
+pub use alter_configs::{Request as AlterConfigsRequest, Response as AlterConfigsResponse};
+pub use alter_user_scram_credentials::{
+    Request as AlterUserScramCredentialsRequest, Response as AlterUserScramCredentialsResponse,
+};
 pub use api_versions::{Request as ApiVersionsRequest, Response as ApiVersionsResponse};
+pub use create_acls::{Request as CreateAclsRequest, Response as CreateAclsResponse};
+pub use delete_acls::{Request as DeleteAclsRequest, Response as DeleteAclsResponse};
+pub use describe_acls::{Request as DescribeAclsRequest, Response as DescribeAclsResponse};
+pub use describe_client_quotas::{
+    Request as DescribeClientQuotasRequest, Response as DescribeClientQuotasResponse,
+};
+pub use describe_cluster::{
+    Request as DescribeClusterRequest, Response as DescribeClusterResponse,
+};
+pub use describe_configs::{
+    Request as DescribeConfigsRequest, Response as DescribeConfigsResponse,
+};
 pub use describe_topic_partitions::{
     Request as DescribeTopicPartitionsRequest, Response as DescribeTopicPartitionsResponse,
 };
+pub use describe_user_scram_credentials::{
+    Request as DescribeUserScramCredentialsRequest,
+    Response as DescribeUserScramCredentialsResponse,
+};
 pub use fetch::{request::Request as FetchRequest, response::Response as FetchResponse};
+pub use incremental_alter_configs::{
+    Request as IncrementalAlterConfigsRequest, Response as IncrementalAlterConfigsResponse,
+};
+pub use metadata::{Request as MetadataRequest, Response as MetadataResponse};
 pub use request_header::RequestHeader;
 pub use response_header::ResponseHeader;
+pub use sasl_authenticate::{
+    Request as SaslAuthenticateRequest, Response as SaslAuthenticateResponse,
+};
+pub use sasl_handshake::{Request as SaslHandshakeRequest, Response as SaslHandshakeResponse};
 
 static REQUEST_HEADER_VERSIONS: LazyLock<HashMap<(i16, i16), u8>> = LazyLock::new(|| {
     HashMap::from([
@@ -45,11 +107,59 @@ static REQUEST_HEADER_VERSIONS: LazyLock<HashMap<(i16, i16), u8>> = LazyLock::ne
         ((fetch::API_KEY, 15), 2),
         ((fetch::API_KEY, 16), 2),
         ((fetch::API_KEY, 17), 2),
+        ((metadata::API_KEY, 0), 1),
+        ((metadata::API_KEY, 1), 1),
+        ((metadata::API_KEY, 2), 1),
+        ((metadata::API_KEY, 3), 1),
+        ((metadata::API_KEY, 4), 1),
+        ((metadata::API_KEY, 5), 1),
+        ((metadata::API_KEY, 6), 1),
+        ((metadata::API_KEY, 7), 1),
+        ((metadata::API_KEY, 8), 1),
+        ((metadata::API_KEY, 9), 2),
+        ((metadata::API_KEY, 10), 2),
+        ((metadata::API_KEY, 11), 2),
+        ((metadata::API_KEY, 12), 2),
+        ((sasl_handshake::API_KEY, 0), 1),
+        ((sasl_handshake::API_KEY, 1), 1),
         ((api_versions::API_KEY, 0), 1),
         ((api_versions::API_KEY, 1), 1),
         ((api_versions::API_KEY, 2), 1),
         ((api_versions::API_KEY, 3), 2),
         ((api_versions::API_KEY, 4), 2),
+        ((sasl_authenticate::API_KEY, 0), 1),
+        ((sasl_authenticate::API_KEY, 1), 1),
+        ((sasl_authenticate::API_KEY, 2), 2),
+        ((describe_acls::API_KEY, 0), 1),
+        ((describe_acls::API_KEY, 1), 1),
+        ((describe_acls::API_KEY, 2), 2),
+        ((describe_acls::API_KEY, 3), 2),
+        ((create_acls::API_KEY, 0), 1),
+        ((create_acls::API_KEY, 1), 1),
+        ((create_acls::API_KEY, 2), 2),
+        ((create_acls::API_KEY, 3), 2),
+        ((delete_acls::API_KEY, 0), 1),
+        ((delete_acls::API_KEY, 1), 1),
+        ((delete_acls::API_KEY, 2), 2),
+        ((delete_acls::API_KEY, 3), 2),
+        ((describe_configs::API_KEY, 0), 1),
+        ((describe_configs::API_KEY, 1), 1),
+        ((describe_configs::API_KEY, 2), 1),
+        ((describe_configs::API_KEY, 3), 1),
+        ((describe_configs::API_KEY, 4), 2),
+        ((alter_configs::API_KEY, 0), 1),
+        ((alter_configs::API_KEY, 1), 1),
+        ((alter_configs::API_KEY, 2), 2),
+        ((incremental_alter_configs::API_KEY, 0), 1),
+        ((incremental_alter_configs::API_KEY, 1), 2),
+        ((describe_client_quotas::API_KEY, 0), 1),
+        ((describe_client_quotas::API_KEY, 1), 2),
+        ((alter_client_quotas::API_KEY, 0), 1),
+        ((alter_client_quotas::API_KEY, 1), 2),
+        ((describe_user_scram_credentials::API_KEY, 0), 2),
+        ((alter_user_scram_credentials::API_KEY, 0), 2),
+        ((describe_cluster::API_KEY, 0), 2),
+        ((describe_cluster::API_KEY, 1), 2),
         ((describe_topic_partitions::API_KEY, 0), 2),
     ])
 });
@@ -70,124 +180,698 @@ static RESPONSE_HEADER_VERSIONS: LazyLock<HashMap<(i16, i16), u8>> = LazyLock::n
         ((fetch::API_KEY, 15), 1),
         ((fetch::API_KEY, 16), 1),
         ((fetch::API_KEY, 17), 1),
+        ((metadata::API_KEY, 0), 0),
+        ((metadata::API_KEY, 1), 0),
+        ((metadata::API_KEY, 2), 0),
+        ((metadata::API_KEY, 3), 0),
+        ((metadata::API_KEY, 4), 0),
+        ((metadata::API_KEY, 5), 0),
+        ((metadata::API_KEY, 6), 0),
+        ((metadata::API_KEY, 7), 0),
+        ((metadata::API_KEY, 8), 0),
+        ((metadata::API_KEY, 9), 1),
+        ((metadata::API_KEY, 10), 1),
+        ((metadata::API_KEY, 11), 1),
+        ((metadata::API_KEY, 12), 1),
+        ((sasl_handshake::API_KEY, 0), 0),
+        ((sasl_handshake::API_KEY, 1), 0),
         ((api_versions::API_KEY, 0), 0),
         ((api_versions::API_KEY, 1), 0),
         ((api_versions::API_KEY, 2), 0),
         ((api_versions::API_KEY, 3), 0),
         ((api_versions::API_KEY, 4), 0),
+        ((sasl_authenticate::API_KEY, 0), 0),
+        ((sasl_authenticate::API_KEY, 1), 0),
+        ((sasl_authenticate::API_KEY, 2), 1),
+        ((describe_acls::API_KEY, 0), 0),
+        ((describe_acls::API_KEY, 1), 0),
+        ((describe_acls::API_KEY, 2), 1),
+        ((describe_acls::API_KEY, 3), 1),
+        ((create_acls::API_KEY, 0), 0),
+        ((create_acls::API_KEY, 1), 0),
+        ((create_acls::API_KEY, 2), 1),
+        ((create_acls::API_KEY, 3), 1),
+        ((delete_acls::API_KEY, 0), 0),
+        ((delete_acls::API_KEY, 1), 0),
+        ((delete_acls::API_KEY, 2), 1),
+        ((delete_acls::API_KEY, 3), 1),
+        ((describe_configs::API_KEY, 0), 0),
+        ((describe_configs::API_KEY, 1), 0),
+        ((describe_configs::API_KEY, 2), 0),
+        ((describe_configs::API_KEY, 3), 0),
+        ((describe_configs::API_KEY, 4), 1),
+        ((alter_configs::API_KEY, 0), 0),
+        ((alter_configs::API_KEY, 1), 0),
+        ((alter_configs::API_KEY, 2), 1),
+        ((incremental_alter_configs::API_KEY, 0), 0),
+        ((incremental_alter_configs::API_KEY, 1), 1),
+        ((describe_client_quotas::API_KEY, 0), 0),
+        ((describe_client_quotas::API_KEY, 1), 1),
+        ((alter_client_quotas::API_KEY, 0), 0),
+        ((alter_client_quotas::API_KEY, 1), 1),
+        ((describe_user_scram_credentials::API_KEY, 0), 1),
+        ((alter_user_scram_credentials::API_KEY, 0), 1),
+        ((describe_cluster::API_KEY, 0), 1),
+        ((describe_cluster::API_KEY, 1), 1),
         ((describe_topic_partitions::API_KEY, 0), 1),
     ])
 });
 
 pub enum KafkaRequest {
     Fetch(FetchRequest),
+    Metadata(MetadataRequest),
+    SaslHandshake(SaslHandshakeRequest),
     ApiVersions(ApiVersionsRequest),
+    DescribeAcls(DescribeAclsRequest),
+    CreateAcls(CreateAclsRequest),
+    DeleteAcls(DeleteAclsRequest),
+    SaslAuthenticate(SaslAuthenticateRequest),
+    DescribeConfigs(DescribeConfigsRequest),
+    AlterConfigs(AlterConfigsRequest),
+    IncrementalAlterConfigs(IncrementalAlterConfigsRequest),
+    DescribeClientQuotas(DescribeClientQuotasRequest),
+    AlterClientQuotas(AlterClientQuotasRequest),
+    DescribeUserScramCredentials(DescribeUserScramCredentialsRequest),
+    AlterUserScramCredentials(AlterUserScramCredentialsRequest),
+    DescribeCluster(DescribeClusterRequest),
     DescribeTopicPartitions(DescribeTopicPartitionsRequest),
 }
 
 pub enum KafkaResponse {
     Fetch(FetchResponse),
+    Metadata(MetadataResponse),
+    SaslHandshake(SaslHandshakeResponse),
     ApiVersions(ApiVersionsResponse),
+    DescribeAcls(DescribeAclsResponse),
+    CreateAcls(CreateAclsResponse),
+    DeleteAcls(DeleteAclsResponse),
+    SaslAuthenticate(SaslAuthenticateResponse),
+    DescribeConfigs(DescribeConfigsResponse),
+    AlterConfigs(AlterConfigsResponse),
+    IncrementalAlterConfigs(IncrementalAlterConfigsResponse),
+    DescribeClientQuotas(DescribeClientQuotasResponse),
+    AlterClientQuotas(AlterClientQuotasResponse),
+    DescribeUserScramCredentials(DescribeUserScramCredentialsResponse),
+    AlterUserScramCredentials(AlterUserScramCredentialsResponse),
+    DescribeCluster(DescribeClusterResponse),
     DescribeTopicPartitions(DescribeTopicPartitionsResponse),
+    Error(ErrorCode),
+}
+
+impl KafkaResponse {
+    // Responses without a throttle time are left as they are; the channel is
+    // muted either way.
+    pub fn set_throttle_time_ms(&mut self, throttle_time_ms: i32) {
+        match self {
+            KafkaResponse::Fetch(resp) => resp.set_throttle_time_ms(throttle_time_ms),
+            KafkaResponse::Metadata(resp) => resp.set_throttle_time_ms(throttle_time_ms),
+            KafkaResponse::ApiVersions(resp) => resp.set_throttle_time_ms(throttle_time_ms),
+            KafkaResponse::DescribeAcls(resp) => resp.set_throttle_time_ms(throttle_time_ms),
+            KafkaResponse::CreateAcls(resp) => resp.set_throttle_time_ms(throttle_time_ms),
+            KafkaResponse::DeleteAcls(resp) => resp.set_throttle_time_ms(throttle_time_ms),
+            KafkaResponse::DescribeUserScramCredentials(resp) => {
+                resp.set_throttle_time_ms(throttle_time_ms)
+            }
+            KafkaResponse::AlterUserScramCredentials(resp) => {
+                resp.set_throttle_time_ms(throttle_time_ms)
+            }
+            KafkaResponse::DescribeConfigs(resp) => resp.set_throttle_time_ms(throttle_time_ms),
+            KafkaResponse::AlterConfigs(resp) => resp.set_throttle_time_ms(throttle_time_ms),
+            KafkaResponse::IncrementalAlterConfigs(resp) => {
+                resp.set_throttle_time_ms(throttle_time_ms)
+            }
+            KafkaResponse::DescribeClientQuotas(resp) => {
+                resp.set_throttle_time_ms(throttle_time_ms)
+            }
+            KafkaResponse::AlterClientQuotas(resp) => resp.set_throttle_time_ms(throttle_time_ms),
+            KafkaResponse::DescribeCluster(resp) => resp.set_throttle_time_ms(throttle_time_ms),
+            KafkaResponse::DescribeTopicPartitions(resp) => {
+                resp.set_throttle_time_ms(throttle_time_ms)
+            }
+            KafkaResponse::SaslHandshake(_)
+            | KafkaResponse::SaslAuthenticate(_)
+            | KafkaResponse::Error(_) => {}
+        }
+    }
+
+    // Every error code of the response, top level and per resource alike;
+    // successes are left out.
+    pub fn error_codes(&self) -> Vec<ErrorCode> {
+        let codes: Vec<ErrorCode> = match self {
+            KafkaResponse::Fetch(resp) => std::iter::once(resp.error_code())
+                .chain(resp.responses().iter().flat_map(|topic| {
+                    topic
+                        .partitions()
+                        .iter()
+                        .map(|partition| partition.error_code())
+                }))
+                .collect(),
+            KafkaResponse::Metadata(resp) => resp
+                .topics()
+                .iter()
+                .flat_map(|topic| {
+                    std::iter::once(topic.error_code()).chain(
+                        topic
+                            .partitions()
+                            .iter()
+                            .map(|partition| partition.error_code()),
+                    )
+                })
+                .collect(),
+            KafkaResponse::SaslHandshake(resp) => vec![resp.error_code()],
+            KafkaResponse::ApiVersions(resp) => vec![resp.error_code()],
+            KafkaResponse::DescribeAcls(resp) => vec![resp.error_code()],
+            KafkaResponse::CreateAcls(resp) => resp
+                .results()
+                .iter()
+                .map(|result| result.error_code())
+                .collect(),
+            KafkaResponse::DeleteAcls(resp) => resp
+                .filter_results()
+                .iter()
+                .flat_map(|result| {
+                    std::iter::once(result.error_code())
+                        .chain(result.matching_acls().iter().map(|acl| acl.error_code()))
+                })
+                .collect(),
+            KafkaResponse::SaslAuthenticate(resp) => vec![resp.error_code()],
+            KafkaResponse::DescribeConfigs(resp) => resp
+                .results()
+                .iter()
+                .map(|result| result.error_code())
+                .collect(),
+            KafkaResponse::AlterConfigs(resp) => resp
+                .responses()
+                .iter()
+                .map(|result| result.error_code())
+                .collect(),
+            KafkaResponse::IncrementalAlterConfigs(resp) => resp
+                .responses()
+                .iter()
+                .map(|result| result.error_code())
+                .collect(),
+            KafkaResponse::DescribeClientQuotas(resp) => vec![resp.error_code()],
+            KafkaResponse::AlterClientQuotas(resp) => resp
+                .entries()
+                .iter()
+                .map(|entry| entry.error_code())
+                .collect(),
+            KafkaResponse::DescribeUserScramCredentials(resp) => std::iter::once(resp.error_code())
+                .chain(resp.results().iter().map(|result| result.error_code()))
+                .collect(),
+            KafkaResponse::AlterUserScramCredentials(resp) => resp
+                .results()
+                .iter()
+                .map(|result| result.error_code())
+                .collect(),
+            KafkaResponse::DescribeCluster(resp) => vec![resp.error_code()],
+            KafkaResponse::DescribeTopicPartitions(resp) => resp
+                .topics()
+                .iter()
+                .flat_map(|topic| {
+                    std::iter::once(topic.error_code()).chain(
+                        topic
+                            .partitions()
+                            .iter()
+                            .map(|partition| partition.error_code()),
+                    )
+                })
+                .collect(),
+            KafkaResponse::Error(error_code) => vec![*error_code],
+        };
+        codes.into_iter().filter(|code| code.is_error()).collect()
+    }
 }
 
-pub fn read_request<B: Buf>(buffer: &mut B) -> Result<(RequestHeader, KafkaRequest), super::Error> {
-    if buffer.remaining() < 4 {
+// Nothing is consumed until the whole frame is there, so a partial frame stays
+// in the buffer. The size prefix is read from a copy of the cursor, as it may
+// be split across the chunks of the buffer.
+pub fn read_request<B: Buf + Clone>(
+    buffer: &mut B,
+) -> Result<(RequestHeader, KafkaRequest), super::Error> {
+    let mut frame = buffer.clone();
+    if frame.remaining() < 4 {
         return Err(super::Error::BufferUnderflow);
     }
+    let sz = frame.get_i32();
+    if sz < 0 {
+        return Err(super::Error::IllegalArgument("negative request size"));
+    }
 
-    let sz = i32::read(buffer) as usize;
-    if buffer.remaining() < sz {
+    let sz = sz as usize;
+    if frame.remaining() < sz {
         return Err(super::Error::BufferUnderflow);
     }
 
-    let mut inner_buffer = buffer.copy_to_bytes(sz);
+    *buffer = frame;
+    parse_request(buffer.copy_to_bytes(sz))
+}
+
+pub fn parse_request(
+    mut inner_buffer: Bytes,
+) -> Result<(RequestHeader, KafkaRequest), super::Error> {
     let header = RequestHeader::read_result(&mut inner_buffer)?;
+    let request = read_request_body(&header, &mut inner_buffer)?;
+    Ok((header, request))
+}
 
+pub fn read_request_body<B: Buf>(
+    header: &RequestHeader,
+    buffer: &mut B,
+) -> Result<KafkaRequest, super::Error> {
     match header.request_api_key() {
         fetch::API_KEY => {
-            let request =
-                FetchRequest::read_version(&mut inner_buffer, header.request_api_version())?;
-            Ok((header, KafkaRequest::Fetch(request)))
+            let request = FetchRequest::read_version(buffer, header.request_api_version())?;
+            Ok(KafkaRequest::Fetch(request))
+        }
+        metadata::API_KEY => {
+            let request = MetadataRequest::read_version(buffer, header.request_api_version())?;
+            Ok(KafkaRequest::Metadata(request))
+        }
+        sasl_handshake::API_KEY => {
+            let request = SaslHandshakeRequest::read_version(buffer, header.request_api_version())?;
+            Ok(KafkaRequest::SaslHandshake(request))
         }
         api_versions::API_KEY => {
+            let request = ApiVersionsRequest::read_version(buffer, header.request_api_version())?;
+            Ok(KafkaRequest::ApiVersions(request))
+        }
+        describe_acls::API_KEY => {
+            let request = DescribeAclsRequest::read_version(buffer, header.request_api_version())?;
+            Ok(KafkaRequest::DescribeAcls(request))
+        }
+        create_acls::API_KEY => {
+            let request = CreateAclsRequest::read_version(buffer, header.request_api_version())?;
+            Ok(KafkaRequest::CreateAcls(request))
+        }
+        delete_acls::API_KEY => {
+            let request = DeleteAclsRequest::read_version(buffer, header.request_api_version())?;
+            Ok(KafkaRequest::DeleteAcls(request))
+        }
+        sasl_authenticate::API_KEY => {
             let request =
-                ApiVersionsRequest::read_version(&mut inner_buffer, header.request_api_version())?;
-            Ok((header, KafkaRequest::ApiVersions(request)))
+                SaslAuthenticateRequest::read_version(buffer, header.request_api_version())?;
+            Ok(KafkaRequest::SaslAuthenticate(request))
         }
-        describe_topic_partitions::API_KEY => {
-            let request = DescribeTopicPartitionsRequest::read_version(
-                &mut inner_buffer,
+        describe_configs::API_KEY => {
+            let request =
+                DescribeConfigsRequest::read_version(buffer, header.request_api_version())?;
+            Ok(KafkaRequest::DescribeConfigs(request))
+        }
+        alter_configs::API_KEY => {
+            let request = AlterConfigsRequest::read_version(buffer, header.request_api_version())?;
+            Ok(KafkaRequest::AlterConfigs(request))
+        }
+        incremental_alter_configs::API_KEY => {
+            let request =
+                IncrementalAlterConfigsRequest::read_version(buffer, header.request_api_version())?;
+            Ok(KafkaRequest::IncrementalAlterConfigs(request))
+        }
+        describe_client_quotas::API_KEY => {
+            let request =
+                DescribeClientQuotasRequest::read_version(buffer, header.request_api_version())?;
+            Ok(KafkaRequest::DescribeClientQuotas(request))
+        }
+        alter_client_quotas::API_KEY => {
+            let request =
+                AlterClientQuotasRequest::read_version(buffer, header.request_api_version())?;
+            Ok(KafkaRequest::AlterClientQuotas(request))
+        }
+        describe_user_scram_credentials::API_KEY => {
+            let request = DescribeUserScramCredentialsRequest::read_version(
+                buffer,
                 header.request_api_version(),
             )?;
-            Ok((header, KafkaRequest::DescribeTopicPartitions(request)))
+            Ok(KafkaRequest::DescribeUserScramCredentials(request))
+        }
+        alter_user_scram_credentials::API_KEY => {
+            let request = AlterUserScramCredentialsRequest::read_version(
+                buffer,
+                header.request_api_version(),
+            )?;
+            Ok(KafkaRequest::AlterUserScramCredentials(request))
+        }
+        describe_cluster::API_KEY => {
+            let request =
+                DescribeClusterRequest::read_version(buffer, header.request_api_version())?;
+            Ok(KafkaRequest::DescribeCluster(request))
+        }
+        describe_topic_partitions::API_KEY => {
+            let request =
+                DescribeTopicPartitionsRequest::read_version(buffer, header.request_api_version())?;
+            Ok(KafkaRequest::DescribeTopicPartitions(request))
         }
         key => Err(super::Error::UnknownRequest(key)),
     }
 }
 
-pub fn process_request(request: KafkaRequest) -> Result<KafkaResponse, super::Error> {
+// Endpoints handed back to the client are the ones of the listener the
+// request arrived on.
+pub fn process_request(
+    request: KafkaRequest,
+    broker: &Broker,
+    listener_name: &str,
+    session: &mut Session,
+) -> Result<KafkaResponse, super::Error> {
     match request {
         KafkaRequest::Fetch(request) => {
-            let response = match METADATA_CACHE.as_ref() {
-                Ok(metadata_cache) => process_fetch_request(request, metadata_cache)?,
-                Err(err) => return Err(err.clone()),
-            };
+            let metadata = broker.metadata().image();
+            let auth = Authorization::new(broker.authorizer(), metadata.acls(), session);
+            let response = process_fetch_request(
+                request,
+                metadata.topics(),
+                &broker.live_brokers(&metadata),
+                listener_name,
+                broker.config(),
+                &auth,
+            )?;
             Ok(KafkaResponse::Fetch(response))
         }
+        KafkaRequest::Metadata(request) => {
+            let metadata = broker.metadata().image();
+            let auth = Authorization::new(broker.authorizer(), metadata.acls(), session);
+            let response = process_metadata_request(
+                request,
+                metadata.topics(),
+                &broker.live_brokers(&metadata),
+                listener_name,
+                broker.cluster_id(),
+                &auth,
+            )?;
+            Ok(KafkaResponse::Metadata(response))
+        }
+        KafkaRequest::SaslHandshake(request) => {
+            let response = process_sasl_handshake_request(request, session, broker.config())?;
+            Ok(KafkaResponse::SaslHandshake(response))
+        }
         KafkaRequest::ApiVersions(request) => {
+            if let (Some(name), Some(version)) = (
+                request.client_software_name(),
+                request.client_software_version(),
+            ) {
+                session.set_client_software(
+                    &String::from_utf8_lossy(name),
+                    &String::from_utf8_lossy(version),
+                );
+            }
+            let metadata = broker.metadata().image();
             let response = process_api_versions_request(
                 request,
                 &SUPPORTED_APIS,
                 &SUPPORTED_FEATURES,
-                &FINALIZED_FEATURES,
+                metadata.last_offset().unwrap_or(-1),
+                metadata.features(),
             );
             Ok(KafkaResponse::ApiVersions(response))
         }
+        KafkaRequest::DescribeAcls(request) => {
+            let metadata = broker.metadata().image();
+            let auth = Authorization::new(broker.authorizer(), metadata.acls(), session);
+            let response = process_describe_acls_request(request, metadata.acls(), &auth)?;
+            Ok(KafkaResponse::DescribeAcls(response))
+        }
+        KafkaRequest::CreateAcls(request) => {
+            let metadata = broker.metadata().image();
+            let auth = Authorization::new(broker.authorizer(), metadata.acls(), session);
+            let response = process_create_acls_request(
+                request,
+                metadata.acls(),
+                broker.metadata_writer(),
+                &auth,
+            )?;
+            Ok(KafkaResponse::CreateAcls(response))
+        }
+        KafkaRequest::DeleteAcls(request) => {
+            let metadata = broker.metadata().image();
+            let auth = Authorization::new(broker.authorizer(), metadata.acls(), session);
+            let response = process_delete_acls_request(
+                request,
+                metadata.acls(),
+                broker.metadata_writer(),
+                &auth,
+            )?;
+            Ok(KafkaResponse::DeleteAcls(response))
+        }
+        KafkaRequest::SaslAuthenticate(request) => {
+            let metadata = broker.metadata().image();
+            let response = process_sasl_authenticate_request(
+                request,
+                session,
+                broker.config(),
+                metadata.scram_credentials(),
+            )?;
+            Ok(KafkaResponse::SaslAuthenticate(response))
+        }
+        KafkaRequest::DescribeUserScramCredentials(request) => {
+            let metadata = broker.metadata().image();
+            let auth = Authorization::new(broker.authorizer(), metadata.acls(), session);
+            let response = process_describe_user_scram_credentials_request(
+                request,
+                metadata.scram_credentials(),
+                &auth,
+            )?;
+            Ok(KafkaResponse::DescribeUserScramCredentials(response))
+        }
+        KafkaRequest::AlterUserScramCredentials(request) => {
+            let metadata = broker.metadata().image();
+            let auth = Authorization::new(broker.authorizer(), metadata.acls(), session);
+            let response = process_alter_user_scram_credentials_request(
+                request,
+                metadata.scram_credentials(),
+                broker.metadata_writer(),
+                &auth,
+            )?;
+            Ok(KafkaResponse::AlterUserScramCredentials(response))
+        }
+        KafkaRequest::DescribeConfigs(request) => {
+            let metadata = broker.metadata().image();
+            let auth = Authorization::new(broker.authorizer(), metadata.acls(), session);
+            let resolver = ConfigResolver::new(broker.config(), metadata.configs());
+            let response = process_describe_configs_request(
+                request,
+                metadata.topics(),
+                &resolver,
+                broker.config().node_id(),
+                &auth,
+            )?;
+            Ok(KafkaResponse::DescribeConfigs(response))
+        }
+        KafkaRequest::AlterConfigs(request) => {
+            let metadata = broker.metadata().image();
+            let auth = Authorization::new(broker.authorizer(), metadata.acls(), session);
+            let resolver = ConfigResolver::new(broker.config(), metadata.configs());
+            let response = process_alter_configs_request(
+                request,
+                metadata.topics(),
+                &resolver,
+                broker.metadata_writer(),
+                broker.config().node_id(),
+                &auth,
+            )?;
+            Ok(KafkaResponse::AlterConfigs(response))
+        }
+        KafkaRequest::IncrementalAlterConfigs(request) => {
+            let metadata = broker.metadata().image();
+            let auth = Authorization::new(broker.authorizer(), metadata.acls(), session);
+            let resolver = ConfigResolver::new(broker.config(), metadata.configs());
+            let response = process_incremental_alter_configs_request(
+                request,
+                metadata.topics(),
+                &resolver,
+                broker.metadata_writer(),
+                broker.config().node_id(),
+                &auth,
+            )?;
+            Ok(KafkaResponse::IncrementalAlterConfigs(response))
+        }
+        KafkaRequest::DescribeClientQuotas(request) => {
+            let metadata = broker.metadata().image();
+            let auth = Authorization::new(broker.authorizer(), metadata.acls(), session);
+            let response =
+                process_describe_client_quotas_request(request, metadata.client_quotas(), &auth)?;
+            Ok(KafkaResponse::DescribeClientQuotas(response))
+        }
+        KafkaRequest::AlterClientQuotas(request) => {
+            let metadata = broker.metadata().image();
+            let auth = Authorization::new(broker.authorizer(), metadata.acls(), session);
+            let response = process_alter_client_quotas_request(
+                request,
+                metadata.client_quotas(),
+                broker.metadata_writer(),
+                &auth,
+            )?;
+            Ok(KafkaResponse::AlterClientQuotas(response))
+        }
+        KafkaRequest::DescribeCluster(request) => {
+            let metadata = broker.metadata().image();
+            let auth = Authorization::new(broker.authorizer(), metadata.acls(), session);
+            let response = process_describe_cluster_request(
+                request,
+                &broker.live_brokers(&metadata),
+                listener_name,
+                broker.cluster_id(),
+                &auth,
+            )?;
+            Ok(KafkaResponse::DescribeCluster(response))
+        }
         KafkaRequest::DescribeTopicPartitions(request) => {
-            let response = match METADATA_CACHE.as_ref() {
-                Ok(metadata_cache) => {
-                    process_describe_topic_partitions_request(request, metadata_cache)?
-                }
-                Err(err) => return Err(err.clone()),
-            };
+            let metadata = broker.metadata().image();
+            let auth = Authorization::new(broker.authorizer(), metadata.acls(), session);
+            let response =
+                process_describe_topic_partitions_request(request, metadata.topics(), &auth)?;
             Ok(KafkaResponse::DescribeTopicPartitions(response))
         }
     }
 }
 
+// Only versions whose layout is known get a body; for any other version the
+// client cannot parse one, so the error code follows the header on its own.
+pub fn error_response(request_header: &RequestHeader, error_code: ErrorCode) -> KafkaResponse {
+    let api_key = request_header.request_api_key();
+    let api_version = request_header.request_api_version();
+    let supported = SUPPORTED_APIS
+        .get(&api_key)
+        .is_some_and(|api| (api.min_version()..=api.max_version()).contains(&api_version));
+    if !supported && api_key != api_versions::API_KEY {
+        return KafkaResponse::Error(error_code);
+    }
+    match api_key {
+        fetch::API_KEY => KafkaResponse::Fetch(fetch::error_response(api_version, error_code)),
+        metadata::API_KEY => {
+            KafkaResponse::Metadata(metadata::error_response(api_version, error_code))
+        }
+        sasl_handshake::API_KEY => {
+            KafkaResponse::SaslHandshake(sasl_handshake::error_response(error_code))
+        }
+        api_versions::API_KEY => KafkaResponse::ApiVersions(api_versions::error_response(
+            api_version,
+            error_code,
+            &SUPPORTED_APIS,
+        )),
+        describe_acls::API_KEY => {
+            KafkaResponse::DescribeAcls(describe_acls::error_response(api_version, error_code))
+        }
+        create_acls::API_KEY => {
+            KafkaResponse::CreateAcls(create_acls::error_response(api_version, error_code))
+        }
+        delete_acls::API_KEY => {
+            KafkaResponse::DeleteAcls(delete_acls::error_response(api_version, error_code))
+        }
+        sasl_authenticate::API_KEY => KafkaResponse::SaslAuthenticate(
+            sasl_authenticate::error_response(api_version, error_code),
+        ),
+        describe_configs::API_KEY => KafkaResponse::DescribeConfigs(
+            describe_configs::error_response(api_version, error_code),
+        ),
+        alter_configs::API_KEY => {
+            KafkaResponse::AlterConfigs(alter_configs::error_response(api_version, error_code))
+        }
+        incremental_alter_configs::API_KEY => KafkaResponse::IncrementalAlterConfigs(
+            incremental_alter_configs::error_response(api_version, error_code),
+        ),
+        describe_client_quotas::API_KEY => KafkaResponse::DescribeClientQuotas(
+            describe_client_quotas::error_response(api_version, error_code),
+        ),
+        alter_client_quotas::API_KEY => KafkaResponse::AlterClientQuotas(
+            alter_client_quotas::error_response(api_version, error_code),
+        ),
+        describe_user_scram_credentials::API_KEY => KafkaResponse::DescribeUserScramCredentials(
+            describe_user_scram_credentials::error_response(error_code),
+        ),
+        alter_user_scram_credentials::API_KEY => KafkaResponse::AlterUserScramCredentials(
+            alter_user_scram_credentials::error_response(error_code),
+        ),
+        describe_cluster::API_KEY => KafkaResponse::DescribeCluster(
+            describe_cluster::error_response(api_version, error_code),
+        ),
+        describe_topic_partitions::API_KEY => KafkaResponse::DescribeTopicPartitions(
+            describe_topic_partitions::error_response(error_code),
+        ),
+        _ => KafkaResponse::Error(error_code),
+    }
+}
+
+// The name of the API as Kafka spells it, for logs and metrics.
+pub fn api_name(api_key: i16) -> &'static str {
+    match api_key {
+        fetch::API_KEY => "Fetch",
+        metadata::API_KEY => "Metadata",
+        sasl_handshake::API_KEY => "SaslHandshake",
+        api_versions::API_KEY => "ApiVersions",
+        describe_acls::API_KEY => "DescribeAcls",
+        create_acls::API_KEY => "CreateAcls",
+        delete_acls::API_KEY => "DeleteAcls",
+        describe_configs::API_KEY => "DescribeConfigs",
+        alter_configs::API_KEY => "AlterConfigs",
+        sasl_authenticate::API_KEY => "SaslAuthenticate",
+        incremental_alter_configs::API_KEY => "IncrementalAlterConfigs",
+        describe_client_quotas::API_KEY => "DescribeClientQuotas",
+        alter_client_quotas::API_KEY => "AlterClientQuotas",
+        describe_user_scram_credentials::API_KEY => "DescribeUserScramCredentials",
+        alter_user_scram_credentials::API_KEY => "AlterUserScramCredentials",
+        describe_cluster::API_KEY => "DescribeCluster",
+        describe_topic_partitions::API_KEY => "DescribeTopicPartitions",
+        _ => "Unknown",
+    }
+}
+
 pub fn write_response<B: BufMut>(
     buffer: &mut B,
-    request_header: RequestHeader,
-    response: KafkaResponse,
+    request_header: &RequestHeader,
+    response: &KafkaResponse,
 ) -> Result<(), super::Error> {
     let api_key = request_header.request_api_key();
     let api_version = request_header.request_api_version();
-    let response_header = match RESPONSE_HEADER_VERSIONS.get(&(api_key, api_version)) {
-        Some(header_version) => match header_version {
-            0 => Ok(ResponseHeader::v0(request_header.correlation_id())),
-            1 => Ok(ResponseHeader::v1(request_header.correlation_id())),
-            _ => Err(super::Error::UnsupportedVersion),
-        },
-        None => {
-            if api_key == api_versions::API_KEY {
-                Ok(ResponseHeader::v0(request_header.correlation_id()))
-            } else {
-                Err(super::Error::UnsupportedVersion)
-            }
-        }
+    let response_header = match response_header_version(api_key, api_version) {
+        0 => Ok(ResponseHeader::v0(request_header.correlation_id())),
+        1 => Ok(ResponseHeader::v1(request_header.correlation_id())),
+        _ => Err(super::Error::UnsupportedVersion),
     }?;
     response_header.write(buffer);
 
     match response {
         KafkaResponse::Fetch(resp) => resp.write(buffer),
+        KafkaResponse::Metadata(resp) => resp.write(buffer),
+        KafkaResponse::SaslHandshake(resp) => resp.write(buffer),
         KafkaResponse::ApiVersions(resp) => resp.write(buffer),
+        KafkaResponse::DescribeAcls(resp) => resp.write(buffer),
+        KafkaResponse::CreateAcls(resp) => resp.write(buffer),
+        KafkaResponse::DeleteAcls(resp) => resp.write(buffer),
+        KafkaResponse::SaslAuthenticate(resp) => resp.write(buffer),
+        KafkaResponse::DescribeUserScramCredentials(resp) => resp.write(buffer),
+        KafkaResponse::AlterUserScramCredentials(resp) => resp.write(buffer),
+        KafkaResponse::DescribeConfigs(resp) => resp.write(buffer),
+        KafkaResponse::AlterConfigs(resp) => resp.write(buffer),
+        KafkaResponse::IncrementalAlterConfigs(resp) => resp.write(buffer),
+        KafkaResponse::DescribeClientQuotas(resp) => resp.write(buffer),
+        KafkaResponse::AlterClientQuotas(resp) => resp.write(buffer),
+        KafkaResponse::DescribeCluster(resp) => resp.write(buffer),
         KafkaResponse::DescribeTopicPartitions(resp) => resp.write(buffer),
+        KafkaResponse::Error(error_code) => error_code.write(buffer),
     };
 
     Ok(())
 }
 
+// ApiVersions always answers with header v0 so that clients can negotiate. For
+// versions outside the known range, newer versions keep the header of the
+// latest known one (flexible stays flexible) and older ones fall back to v0.
+fn response_header_version(api_key: i16, api_version: i16) -> u8 {
+    if let Some(header_version) = RESPONSE_HEADER_VERSIONS.get(&(api_key, api_version)) {
+        return *header_version;
+    }
+    if api_key == api_versions::API_KEY {
+        return 0;
+    }
+
+    RESPONSE_HEADER_VERSIONS
+        .iter()
+        .filter(|((key, version), _)| *key == api_key && *version < api_version)
+        .max_by_key(|((_, version), _)| *version)
+        .map(|(_, header_version)| *header_version)
+        .unwrap_or(0)
+}
+
 pub(crate) fn topic_by_name<'m>(
     metadata: &'m HashMap<Uuid, model::Topic>,
     name: &str,
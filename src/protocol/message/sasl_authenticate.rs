@@ -0,0 +1,236 @@
+use std::collections::HashMap;
+
+use bytes::{Buf, BufMut, Bytes};
+
+use crate::{
+    config::BrokerConfig,
+    model::ScramCredential,
+    protocol::{
+        self,
+        r#type::{CompactKafkaString, KafkaBytes, KafkaString, TaggedFields},
+        ErrorCode, Readable, ReadableVersion, Writable,
+    },
+    security::Session,
+};
+
+pub(crate) const API_KEY: i16 = 36;
+
+#[derive(Debug, Clone)]
+pub struct Request {
+    version: i16,
+    auth_bytes: Bytes,
+}
+impl Request {
+    fn new(version: i16, auth_bytes: &[u8]) -> Self {
+        Self {
+            version,
+            auth_bytes: Bytes::copy_from_slice(auth_bytes),
+        }
+    }
+
+    pub fn v0(auth_bytes: &[u8]) -> Self {
+        Self::new(0, auth_bytes)
+    }
+
+    pub fn v1(auth_bytes: &[u8]) -> Self {
+        Self::new(1, auth_bytes)
+    }
+
+    pub fn v2(auth_bytes: &[u8]) -> Self {
+        Self::new(2, auth_bytes)
+    }
+
+    pub fn auth_bytes(&self) -> &[u8] {
+        &self.auth_bytes
+    }
+}
+impl ReadableVersion for Request {
+    fn read_version<B: Buf>(buffer: &mut B, version: i16) -> Result<Self, protocol::Error> {
+        if !(0..=2).contains(&version) {
+            return Err(protocol::Error::UnsupportedVersion);
+        }
+
+        let auth_bytes = if version <= 1 {
+            KafkaBytes::read_inner(buffer)
+        } else {
+            CompactKafkaString::read_result_inner(buffer)?
+        }
+        .ok_or(protocol::Error::IllegalArgument(
+            "non-nullable field authBytes was serialized as null",
+        ))?;
+        if version >= 2 {
+            let _tagged_fields = TaggedFields::read_result_inner(buffer)?;
+        }
+
+        Ok(Self {
+            version,
+            auth_bytes,
+        })
+    }
+}
+impl Writable for Request {
+    fn write<B: BufMut>(&self, buffer: &mut B) {
+        if self.version <= 1 {
+            KafkaBytes::write_inner(buffer, Some(self.auth_bytes()));
+        } else {
+            CompactKafkaString::write_inner(buffer, Some(self.auth_bytes()));
+            TaggedFields::write_empty(buffer);
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Response {
+    version: i16,
+    error_code: ErrorCode,
+    error_message: Option<Bytes>,
+    auth_bytes: Bytes,
+    session_lifetime_ms: i64,
+}
+impl Response {
+    fn new(
+        version: i16,
+        error_code: ErrorCode,
+        error_message: Option<&str>,
+        auth_bytes: &[u8],
+        session_lifetime_ms: i64,
+    ) -> Self {
+        Self {
+            version,
+            error_code,
+            error_message: error_message.map(|m| Bytes::copy_from_slice(m.as_bytes())),
+            auth_bytes: Bytes::copy_from_slice(auth_bytes),
+            session_lifetime_ms,
+        }
+    }
+
+    pub fn v0(error_code: ErrorCode, error_message: Option<&str>, auth_bytes: &[u8]) -> Self {
+        Self::new(0, error_code, error_message, auth_bytes, 0)
+    }
+
+    pub fn v1(
+        error_code: ErrorCode,
+        error_message: Option<&str>,
+        auth_bytes: &[u8],
+        session_lifetime_ms: i64,
+    ) -> Self {
+        Self::new(
+            1,
+            error_code,
+            error_message,
+            auth_bytes,
+            session_lifetime_ms,
+        )
+    }
+
+    pub fn v2(
+        error_code: ErrorCode,
+        error_message: Option<&str>,
+        auth_bytes: &[u8],
+        session_lifetime_ms: i64,
+    ) -> Self {
+        Self::new(
+            2,
+            error_code,
+            error_message,
+            auth_bytes,
+            session_lifetime_ms,
+        )
+    }
+
+    pub fn error_code(&self) -> ErrorCode {
+        self.error_code
+    }
+
+    pub fn error_message(&self) -> Option<&[u8]> {
+        self.error_message.as_deref()
+    }
+
+    pub fn auth_bytes(&self) -> &[u8] {
+        &self.auth_bytes
+    }
+
+    pub fn session_lifetime_ms(&self) -> i64 {
+        self.session_lifetime_ms
+    }
+}
+impl ReadableVersion for Response {
+    fn read_version<B: Buf>(buffer: &mut B, version: i16) -> Result<Self, protocol::Error> {
+        if !(0..=2).contains(&version) {
+            return Err(protocol::Error::UnsupportedVersion);
+        }
+
+        let error_code = ErrorCode::read(buffer);
+        let (error_message, auth_bytes) = if version <= 1 {
+            (
+                KafkaString::read_inner(buffer),
+                KafkaBytes::read_inner(buffer),
+            )
+        } else {
+            (
+                CompactKafkaString::read_result_inner(buffer)?,
+                CompactKafkaString::read_result_inner(buffer)?,
+            )
+        };
+        let auth_bytes = auth_bytes.ok_or(protocol::Error::IllegalArgument(
+            "non-nullable field authBytes was serialized as null",
+        ))?;
+        let session_lifetime_ms = if version >= 1 { i64::read(buffer) } else { 0 };
+        if version >= 2 {
+            let _tagged_fields = TaggedFields::read_result_inner(buffer)?;
+        }
+
+        Ok(Self {
+            version,
+            error_code,
+            error_message,
+            auth_bytes,
+            session_lifetime_ms,
+        })
+    }
+}
+impl Writable for Response {
+    fn write<B: BufMut>(&self, buffer: &mut B) {
+        self.error_code.write(buffer);
+        if self.version <= 1 {
+            KafkaString::write_inner(buffer, self.error_message());
+            KafkaBytes::write_inner(buffer, Some(self.auth_bytes()));
+        } else {
+            CompactKafkaString::write_inner(buffer, self.error_message());
+            CompactKafkaString::write_inner(buffer, Some(self.auth_bytes()));
+        }
+        if self.version >= 1 {
+            self.session_lifetime_ms.write(buffer);
+        }
+        if self.version >= 2 {
+            TaggedFields::write_empty(buffer);
+        }
+    }
+}
+
+// Sessions never expire, so the lifetime is always 0 and clients do not
+// re-authenticate.
+pub fn process_request(
+    request: Request,
+    session: &mut Session,
+    config: &BrokerConfig,
+    credentials: &HashMap<(String, i8), ScramCredential>,
+) -> Result<Response, protocol::Error> {
+    let version = request.version;
+    let response = match session.authenticate(request.auth_bytes(), config, credentials) {
+        Ok(reply) => Response::new(version, ErrorCode::None, None, &reply, 0),
+        Err((error_code, message)) => Response::new(version, error_code, Some(&message), &[], 0),
+    };
+
+    Ok(response)
+}
+
+pub(crate) fn error_response(version: i16, error_code: ErrorCode) -> Response {
+    Response::new(
+        version.clamp(0, 2),
+        error_code,
+        Some(error_code.message()),
+        &[],
+        0,
+    )
+}
@@ -0,0 +1,145 @@
+use bytes::{Buf, BufMut, Bytes};
+
+use crate::{
+    config::BrokerConfig,
+    protocol::{
+        self,
+        r#type::{Array, KafkaString},
+        ErrorCode, Readable, ReadableVersion, Writable,
+    },
+    security::Session,
+};
+
+pub(crate) const API_KEY: i16 = 17;
+
+// Versions 0 and 1 share the same layout; v1 only means the tokens that
+// follow are wrapped in SaslAuthenticate requests.
+#[derive(Debug, Clone)]
+pub struct Request {
+    mechanism: Bytes,
+}
+impl Request {
+    fn new(mechanism: &str) -> Self {
+        Self {
+            mechanism: Bytes::copy_from_slice(mechanism.as_bytes()),
+        }
+    }
+
+    pub fn v0(mechanism: &str) -> Self {
+        Self::new(mechanism)
+    }
+
+    pub fn v1(mechanism: &str) -> Self {
+        Self::new(mechanism)
+    }
+
+    pub fn mechanism(&self) -> &[u8] {
+        &self.mechanism
+    }
+}
+impl ReadableVersion for Request {
+    fn read_version<B: Buf>(buffer: &mut B, version: i16) -> Result<Self, protocol::Error> {
+        if !(0..=1).contains(&version) {
+            return Err(protocol::Error::UnsupportedVersion);
+        }
+
+        let mechanism = KafkaString::read_inner(buffer).ok_or(protocol::Error::IllegalArgument(
+            "non-nullable field mechanism was serialized as null",
+        ))?;
+
+        Ok(Self { mechanism })
+    }
+}
+impl Writable for Request {
+    fn write<B: BufMut>(&self, buffer: &mut B) {
+        KafkaString::write_inner(buffer, Some(self.mechanism()));
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Response {
+    error_code: ErrorCode,
+    mechanisms: Vec<Bytes>,
+}
+impl Response {
+    fn new(error_code: ErrorCode, mechanisms: Vec<&str>) -> Self {
+        Self {
+            error_code,
+            mechanisms: mechanisms
+                .into_iter()
+                .map(|m| Bytes::copy_from_slice(m.as_bytes()))
+                .collect(),
+        }
+    }
+
+    pub fn v0(error_code: ErrorCode, mechanisms: Vec<&str>) -> Self {
+        Self::new(error_code, mechanisms)
+    }
+
+    pub fn v1(error_code: ErrorCode, mechanisms: Vec<&str>) -> Self {
+        Self::new(error_code, mechanisms)
+    }
+
+    pub fn error_code(&self) -> ErrorCode {
+        self.error_code
+    }
+
+    pub fn mechanisms(&self) -> &[Bytes] {
+        &self.mechanisms
+    }
+}
+impl ReadableVersion for Response {
+    fn read_version<B: Buf>(buffer: &mut B, version: i16) -> Result<Self, protocol::Error> {
+        if !(0..=1).contains(&version) {
+            return Err(protocol::Error::UnsupportedVersion);
+        }
+
+        let error_code = ErrorCode::read(buffer);
+        let mechanisms = Array::<KafkaString>::read_inner(buffer)
+            .ok_or(protocol::Error::IllegalArgument(
+                "non-nullable field mechanisms was serialized as null",
+            ))?
+            .iter()
+            .map(|mechanism| mechanism.value().map(Bytes::copy_from_slice))
+            .collect::<Option<Vec<_>>>()
+            .ok_or(protocol::Error::IllegalArgument(
+                "non-nullable field mechanisms was serialized with a null mechanism",
+            ))?;
+
+        Ok(Self {
+            error_code,
+            mechanisms,
+        })
+    }
+}
+impl Writable for Response {
+    fn write<B: BufMut>(&self, buffer: &mut B) {
+        self.error_code.write(buffer);
+        let mechanisms = self
+            .mechanisms()
+            .iter()
+            .map(|mechanism| KafkaString::from(Some(mechanism.clone())))
+            .collect::<Vec<_>>();
+        Array::write_inner(buffer, Some(&mechanisms));
+    }
+}
+
+pub fn process_request(
+    request: Request,
+    session: &mut Session,
+    config: &BrokerConfig,
+) -> Result<Response, protocol::Error> {
+    let mechanism = std::str::from_utf8(request.mechanism())?;
+    let error_code = session.handshake(mechanism, config);
+    let mechanisms = config
+        .sasl_enabled_mechanisms()
+        .iter()
+        .map(String::as_str)
+        .collect();
+
+    Ok(Response::new(error_code, mechanisms))
+}
+
+pub(crate) fn error_response(error_code: ErrorCode) -> Response {
+    Response::new(error_code, vec![])
+}
@@ -1,10 +1,12 @@
 use bytes::{Buf, BufMut};
 
 mod error;
+mod error_code;
 pub mod message;
 pub mod r#type;
 
 pub use error::Error;
+pub use error_code::ErrorCode;
 
 pub trait Readable: Sized {
     fn read<B: Buf>(buffer: &mut B) -> Self;
@@ -21,3 +23,6 @@ pub trait ReadableVersion: Sized {
 pub trait Writable {
     fn write<B: BufMut>(&self, buffer: &mut B);
 }
+
+#[cfg(test)]
+mod tests;
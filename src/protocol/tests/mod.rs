@@ -0,0 +1,50 @@
+use std::collections::HashSet;
+
+use super::{ErrorCode, Readable, Writable};
+
+#[test]
+fn test_error_codes_round_trip() {
+    let mut codes = HashSet::new();
+    for error_code in ErrorCode::ALL {
+        assert_eq!(Some(*error_code), ErrorCode::from_code(error_code.code()));
+        assert!(
+            codes.insert(error_code.code()),
+            "{} is listed twice",
+            error_code
+        );
+
+        let mut buffer = Vec::new();
+        error_code.write(&mut buffer);
+        assert_eq!(*error_code, ErrorCode::read(&mut buffer.as_slice()));
+    }
+    assert_eq!((-1..=127).collect::<HashSet<i16>>(), codes);
+}
+
+#[test]
+fn test_error_codes_match_kafka() {
+    assert_eq!(
+        "NOT_LEADER_OR_FOLLOWER",
+        ErrorCode::NotLeaderOrFollower.name()
+    );
+    assert!(ErrorCode::NotLeaderOrFollower.is_retriable());
+    assert_eq!(29, ErrorCode::TopicAuthorizationFailed.code());
+    assert!(!ErrorCode::TopicAuthorizationFailed.is_retriable());
+    assert!(ErrorCode::UnknownTopicOrPartition.is_retriable());
+    assert_eq!("UNSUPPORTED_VERSION", ErrorCode::UnsupportedVersion.name());
+    assert_eq!(35, ErrorCode::UnsupportedVersion.code());
+    assert!(!ErrorCode::None.is_error());
+    assert_eq!(
+        "TOPIC_AUTHORIZATION_FAILED (29)",
+        ErrorCode::TopicAuthorizationFailed.to_string()
+    );
+}
+
+#[test]
+fn test_unknown_error_code() {
+    assert_eq!(None, ErrorCode::from_code(128));
+    assert_eq!(None, ErrorCode::from_code(-2));
+    assert_eq!(
+        ErrorCode::UnknownServerError,
+        ErrorCode::read(&mut 1000i16.to_be_bytes().as_slice())
+    );
+}
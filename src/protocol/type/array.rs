@@ -129,7 +129,7 @@ where
     pub(crate) fn read_inner<B: Buf>(buffer: &mut B) -> Result<Option<Vec<T>>, protocol::Error> {
         let sz = read_unsigned_varint(buffer)?;
         if sz == 0 {
-            return Ok(Some(vec![]));
+            return Ok(None);
         }
 
         let mut data = Vec::with_capacity(sz as usize - 1);
@@ -155,7 +155,7 @@ where
         }
 
         let sz = sz as usize - 1;
-        let mut data = Vec::with_capacity(sz);
+        let mut data = Vec::with_capacity(sz.min(buffer.remaining()));
         for _ in 0..sz {
             data.push(T::read_result(buffer)?);
         }
@@ -179,7 +179,7 @@ where
         }
 
         let sz = sz as usize - 1;
-        let mut data = Vec::with_capacity(sz);
+        let mut data = Vec::with_capacity(sz.min(buffer.remaining()));
         for _ in 0..sz {
             data.push(T::read_version(buffer, version)?);
         }
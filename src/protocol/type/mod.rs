@@ -7,7 +7,7 @@ pub use array::{Array, CompactArray};
 use bytes::{Buf, BufMut};
 pub(crate) use numeric::{read_unsigned_varint, write_unsigned_varint};
 pub use numeric::{VarInt, VarLong};
-pub use string::{CompactKafkaString, KafkaString};
+pub use string::{CompactKafkaString, KafkaBytes, KafkaString};
 pub use tagged_fields::{TaggedField, TaggedFields};
 use uuid::Uuid;
 
@@ -18,6 +18,14 @@ impl Readable for Uuid {
         Uuid::from_u128(buffer.get_u128())
     }
 }
+impl ReadableResult for Uuid {
+    fn read_result<B: Buf>(buffer: &mut B) -> Result<Uuid, super::Error> {
+        if buffer.remaining() < 16 {
+            return Err(super::Error::BufferUnderflow);
+        }
+        Ok(Uuid::read(buffer))
+    }
+}
 impl Writable for Uuid {
     fn write<B: BufMut>(&self, buffer: &mut B) {
         buffer.put_u128(self.as_u128());
@@ -90,6 +90,24 @@ impl Writable for i64 {
     }
 }
 
+// Checked counterparts of the reads above, for data that may be truncated
+// rather than framed by a length that was already validated.
+macro_rules! read_result_checked {
+    ($($type:ty),*) => {
+        $(
+            impl ReadableResult for $type {
+                fn read_result<B: Buf>(buffer: &mut B) -> Result<$type, protocol::Error> {
+                    if buffer.remaining() < std::mem::size_of::<$type>() {
+                        return Err(protocol::Error::BufferUnderflow);
+                    }
+                    Ok(<$type>::read(buffer))
+                }
+            }
+        )*
+    };
+}
+read_result_checked!(bool, i8, u8, i16, u16, i32, u32, i64, f64);
+
 pub(crate) fn write_unsigned_varint<B: BufMut>(buffer: &mut B, value: u32) {
     if (value & (0xFFFFFFFF << 7)) == 0 {
         (value as u8).write(buffer);
@@ -115,27 +133,27 @@ pub(crate) fn write_unsigned_varint<B: BufMut>(buffer: &mut B, value: u32) {
 }
 
 pub(crate) fn read_unsigned_varint<B: Buf>(buffer: &mut B) -> Result<u32, protocol::Error> {
-    let tmp = i8::read(buffer);
+    let tmp = i8::read_result(buffer)?;
     if tmp >= 0 {
         Ok((tmp as u8) as u32)
     } else {
         let mut result: u32 = (tmp as u8 & 0x7F) as u32;
-        let tmp = i8::read(buffer);
+        let tmp = i8::read_result(buffer)?;
         if tmp >= 0 {
             result |= ((tmp as u8) as u32) << 7;
         } else {
             result |= ((tmp as u8 & 0x7F) as u32) << 7;
-            let tmp = i8::read(buffer);
+            let tmp = i8::read_result(buffer)?;
             if tmp >= 0 {
                 result |= ((tmp as u8) as u32) << 14;
             } else {
                 result |= ((tmp as u8 & 0x7F) as u32) << 14;
-                let tmp = i8::read(buffer);
+                let tmp = i8::read_result(buffer)?;
                 if tmp >= 0 {
                     result |= ((tmp as u8) as u32) << 21;
                 } else {
                     result |= ((tmp as u8 & 0x7f) as u32) << 21;
-                    let tmp = i8::read(buffer);
+                    let tmp = i8::read_result(buffer)?;
                     if tmp < 0 {
                         return Err(protocol::Error::IllegalArgument(
                             "Invalid VarInt, msb of 5th byte is set",
@@ -162,7 +180,7 @@ pub(crate) fn read_unsigned_varlong<B: Buf>(buffer: &mut B) -> Result<u64, proto
     let mut value: u64 = 0;
     let mut i = 0;
     loop {
-        let b = i8::read(buffer);
+        let b = i8::read_result(buffer)?;
         if b >= 0 {
             value |= ((b as u8) as u64) << i;
             break Ok(value);
@@ -56,6 +56,54 @@ impl Writable for KafkaString {
     }
 }
 
+// BYTES: like a string, but with an INT32 length.
+#[derive(Debug, Clone)]
+pub struct KafkaBytes(Option<Bytes>);
+impl KafkaBytes {
+    pub fn value(&self) -> Option<&[u8]> {
+        self.0.as_deref()
+    }
+
+    pub(crate) fn write_none<B: BufMut>(buffer: &mut B) {
+        (-1i32).write(buffer);
+    }
+
+    pub(crate) fn read_inner<B: Buf>(buffer: &mut B) -> Option<Bytes> {
+        let sz = i32::read(buffer);
+        if sz < 0 {
+            return None;
+        }
+
+        let value = buffer.copy_to_bytes(sz as usize);
+        Some(value)
+    }
+
+    pub(crate) fn write_inner<B: BufMut>(buffer: &mut B, data: Option<&[u8]>) {
+        if let Some(data) = data {
+            let sz = data.len() as i32;
+            sz.write(buffer);
+            buffer.put_slice(data);
+        } else {
+            Self::write_none(buffer);
+        }
+    }
+}
+impl From<Option<Bytes>> for KafkaBytes {
+    fn from(value: Option<Bytes>) -> Self {
+        Self(value)
+    }
+}
+impl Readable for KafkaBytes {
+    fn read<B: Buf>(buffer: &mut B) -> Self {
+        Self::read_inner(buffer).into()
+    }
+}
+impl Writable for KafkaBytes {
+    fn write<B: BufMut>(&self, buffer: &mut B) {
+        Self::write_inner(buffer, self.value());
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct CompactKafkaString(Option<Bytes>);
 impl CompactKafkaString {
@@ -79,6 +127,9 @@ impl CompactKafkaString {
         }
 
         let sz = sz as usize - 1;
+        if buffer.remaining() < sz {
+            return Err(protocol::Error::BufferUnderflow);
+        }
         let value = buffer.copy_to_bytes(sz);
         Ok(Some(value))
     }
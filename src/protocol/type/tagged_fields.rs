@@ -25,13 +25,18 @@ impl TaggedField {
 impl ReadableResult for TaggedField {
     fn read_result<B: Buf>(buffer: &mut B) -> Result<Self, protocol::Error> {
         let key = read_unsigned_varint(buffer)?;
-        let data = buffer.copy_to_bytes(key as usize);
+        let size = read_unsigned_varint(buffer)? as usize;
+        if buffer.remaining() < size {
+            return Err(protocol::Error::BufferUnderflow);
+        }
+        let data = buffer.copy_to_bytes(size);
         Ok(Self { key, data })
     }
 }
 impl Writable for TaggedField {
     fn write<B: BufMut>(&self, buffer: &mut B) {
         write_unsigned_varint(buffer, self.key);
+        write_unsigned_varint(buffer, self.data.len() as u32);
         buffer.put_slice(self.data.as_ref());
     }
 }
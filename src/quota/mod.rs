@@ -0,0 +1,169 @@
+mod rate;
+
+use std::{
+    collections::HashMap,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use crate::{config::BrokerConfig, model::ClientQuotaEntity};
+
+pub use rate::Rate;
+
+pub const USER: &str = "user";
+pub const CLIENT_ID: &str = "client-id";
+pub const IP: &str = "ip";
+pub const ENTITY_TYPES: [&str; 3] = [USER, CLIENT_ID, IP];
+
+pub const CONNECTION_CREATION_RATE: &str = "connection_creation_rate";
+
+// Rates of clients that have been idle this long are dropped.
+const INACTIVE_RATE_EXPIRATION: Duration = Duration::from_secs(60 * 60);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum QuotaType {
+    Produce,
+    Fetch,
+    Request,
+}
+impl QuotaType {
+    // The key of the quota in ClientQuotaRecords.
+    pub fn key(&self) -> &'static str {
+        match self {
+            QuotaType::Produce => "producer_byte_rate",
+            QuotaType::Fetch => "consumer_byte_rate",
+            QuotaType::Request => "request_percentage",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<Self> {
+        match key {
+            "producer_byte_rate" => Some(QuotaType::Produce),
+            "consumer_byte_rate" => Some(QuotaType::Fetch),
+            "request_percentage" => Some(QuotaType::Request),
+            _ => None,
+        }
+    }
+}
+
+// Tracks how fast each quota-bound client uses the broker and works out how
+// long it has to back off to get under its quota again.
+#[derive(Debug)]
+pub struct QuotaManager {
+    num_samples: usize,
+    window: Duration,
+    state: Mutex<State>,
+}
+#[derive(Debug)]
+struct State {
+    rates: HashMap<(QuotaType, ClientQuotaEntity), Rate>,
+    last_purge: Instant,
+}
+impl QuotaManager {
+    pub fn new(config: &BrokerConfig) -> Self {
+        Self {
+            num_samples: config.quota_window_num(),
+            window: config.quota_window_size(),
+            state: Mutex::new(State {
+                rates: HashMap::new(),
+                last_purge: Instant::now(),
+            }),
+        }
+    }
+
+    // Records `value` against the quota that applies to the client and
+    // returns the throttle time in milliseconds. Clients without a quota are
+    // not tracked at all.
+    pub fn record(
+        &self,
+        quota_type: QuotaType,
+        client_quotas: &HashMap<ClientQuotaEntity, HashMap<String, f64>>,
+        user: &str,
+        client_id: &str,
+        value: f64,
+        now: Instant,
+    ) -> i32 {
+        let Some((entity, bound)) = resolve(client_quotas, quota_type, user, client_id) else {
+            return 0;
+        };
+
+        let mut state = self.state.lock().unwrap();
+        if now.duration_since(state.last_purge) >= INACTIVE_RATE_EXPIRATION {
+            state.rates.retain(|_, rate| {
+                now.duration_since(rate.last_recorded()) < INACTIVE_RATE_EXPIRATION
+            });
+            state.last_purge = now;
+        }
+        let rate = state
+            .rates
+            .entry((quota_type, entity))
+            .or_insert_with(|| Rate::new(self.num_samples, self.window, now));
+        rate.record(value, now);
+        let observed = rate.measure(now);
+        if observed <= bound {
+            return 0;
+        }
+
+        // Long enough for the observed rate to drop to the bound, but never
+        // more than all sample windows together.
+        let throttle_ms = (observed - bound) / bound * rate.elapsed(now).as_millis() as f64;
+        let max_throttle_ms = (self.window * self.num_samples as u32).as_millis() as f64;
+        throttle_ms.min(max_throttle_ms) as i32
+    }
+}
+
+// The most specific quota wins, in the same order as Kafka:
+// /users/<user>/clients/<client-id>, /users/<user>/clients/<default>,
+// /users/<user>, /users/<default>/clients/<client-id>,
+// /users/<default>/clients/<default>, /users/<default>,
+// /clients/<client-id>, /clients/<default>.
+//
+// Returns the entity the usage is tracked under along with the quota. A
+// default quota still applies to every user or client on its own, so the
+// defaults in the entity are replaced with the actual names.
+pub fn resolve(
+    client_quotas: &HashMap<ClientQuotaEntity, HashMap<String, f64>>,
+    quota_type: QuotaType,
+    user: &str,
+    client_id: &str,
+) -> Option<(ClientQuotaEntity, f64)> {
+    if client_quotas.is_empty() {
+        return None;
+    }
+    let candidates = [
+        (Some(Some(user)), Some(Some(client_id))),
+        (Some(Some(user)), Some(None)),
+        (Some(Some(user)), None),
+        (Some(None), Some(Some(client_id))),
+        (Some(None), Some(None)),
+        (Some(None), None),
+        (None, Some(Some(client_id))),
+        (None, Some(None)),
+    ];
+    for (user_name, client_id_name) in candidates {
+        let mut entity = ClientQuotaEntity::new();
+        if let Some(name) = user_name {
+            entity.insert(USER.to_string(), name.map(str::to_string));
+        }
+        if let Some(name) = client_id_name {
+            entity.insert(CLIENT_ID.to_string(), name.map(str::to_string));
+        }
+        let Some(bound) = client_quotas
+            .get(&entity)
+            .and_then(|quotas| quotas.get(quota_type.key()))
+        else {
+            continue;
+        };
+        if let Some(name) = entity.get_mut(USER) {
+            *name = Some(user.to_string());
+        }
+        if let Some(name) = entity.get_mut(CLIENT_ID) {
+            *name = Some(client_id.to_string());
+        }
+        return Some((entity, *bound));
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests;
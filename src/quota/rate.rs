@@ -0,0 +1,82 @@
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone, Copy)]
+struct Sample {
+    start: Instant,
+    value: f64,
+}
+
+// A rate over a fixed number of consecutive sample windows. Samples older
+// than all windows together are forgotten, so the rate follows recent
+// traffic only.
+#[derive(Debug, Clone)]
+pub struct Rate {
+    samples: Vec<Sample>,
+    current: usize,
+    window: Duration,
+    last_recorded: Instant,
+}
+impl Rate {
+    pub fn new(num_samples: usize, window: Duration, now: Instant) -> Self {
+        Self {
+            samples: vec![
+                Sample {
+                    start: now,
+                    value: 0.0
+                };
+                num_samples.max(2)
+            ],
+            current: 0,
+            window,
+            last_recorded: now,
+        }
+    }
+
+    pub fn record(&mut self, value: f64, now: Instant) {
+        if now.duration_since(self.samples[self.current].start) >= self.window {
+            self.current = (self.current + 1) % self.samples.len();
+            self.samples[self.current] = Sample {
+                start: now,
+                value: 0.0,
+            };
+        }
+        self.samples[self.current].value += value;
+        self.last_recorded = now;
+    }
+
+    // Per second, averaged over the time the samples cover.
+    pub fn measure(&mut self, now: Instant) -> f64 {
+        self.purge_obsolete(now);
+        let total = self.samples.iter().map(|sample| sample.value).sum::<f64>();
+        total / self.elapsed(now).as_secs_f64()
+    }
+
+    // The time the samples cover, but at least all windows but the current
+    // one, so a burst on a fresh rate is not measured over a tiny interval.
+    pub fn elapsed(&self, now: Instant) -> Duration {
+        let oldest = self
+            .samples
+            .iter()
+            .map(|sample| sample.start)
+            .min()
+            .unwrap_or(now);
+        let minimum = self.window * (self.samples.len() as u32 - 1);
+        now.duration_since(oldest).max(minimum)
+    }
+
+    pub fn last_recorded(&self) -> Instant {
+        self.last_recorded
+    }
+
+    fn purge_obsolete(&mut self, now: Instant) {
+        let expiry = self.window * self.samples.len() as u32;
+        for sample in self.samples.iter_mut() {
+            if now.duration_since(sample.start) >= expiry {
+                *sample = Sample {
+                    start: now,
+                    value: 0.0,
+                };
+            }
+        }
+    }
+}
@@ -0,0 +1,90 @@
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+use crate::{config::BrokerConfig, model::ClientQuotaEntity};
+
+use super::{resolve, QuotaManager, QuotaType, Rate, CLIENT_ID, USER};
+
+fn entity(entries: &[(&str, Option<&str>)]) -> ClientQuotaEntity {
+    entries
+        .iter()
+        .map(|(entity_type, name)| (entity_type.to_string(), name.map(str::to_string)))
+        .collect()
+}
+
+#[test]
+fn test_resolve_prefers_most_specific_quota() {
+    let mut client_quotas = HashMap::new();
+    for (quota_entity, value) in [
+        (entity(&[(CLIENT_ID, None)]), 1.0),
+        (entity(&[(USER, None)]), 2.0),
+        (entity(&[(USER, Some("alice"))]), 3.0),
+        (
+            entity(&[(USER, Some("alice")), (CLIENT_ID, Some("app"))]),
+            4.0,
+        ),
+    ] {
+        client_quotas.insert(
+            quota_entity,
+            HashMap::from([(QuotaType::Fetch.key().to_string(), value)]),
+        );
+    }
+
+    let resolved = |user, client_id| {
+        resolve(&client_quotas, QuotaType::Fetch, user, client_id).map(|(_, value)| value)
+    };
+    assert_eq!(Some(4.0), resolved("alice", "app"));
+    assert_eq!(Some(3.0), resolved("alice", "other"));
+    assert_eq!(Some(2.0), resolved("bob", "app"));
+    assert_eq!(
+        None,
+        resolve(&client_quotas, QuotaType::Request, "alice", "app")
+    );
+
+    // A default quota is tracked per actual user.
+    let (tracked, _) = resolve(&client_quotas, QuotaType::Fetch, "bob", "app").unwrap();
+    assert_eq!(entity(&[(USER, Some("bob"))]), tracked);
+}
+
+#[test]
+fn test_rate_covers_at_least_all_but_one_window() {
+    let start = Instant::now();
+    let mut rate = Rate::new(11, Duration::from_secs(1), start);
+    rate.record(1000.0, start);
+    assert_eq!(100.0, rate.measure(start));
+
+    // Everything recorded is forgotten once all windows have passed.
+    assert_eq!(0.0, rate.measure(start + Duration::from_secs(12)));
+}
+
+#[test]
+fn test_record_throttles_clients_over_their_quota() {
+    let manager = QuotaManager::new(&BrokerConfig::default());
+    let client_quotas = HashMap::from([(
+        entity(&[(USER, Some("alice"))]),
+        HashMap::from([(QuotaType::Fetch.key().to_string(), 100.0)]),
+    )]);
+    let now = Instant::now();
+
+    assert_eq!(
+        0,
+        manager.record(QuotaType::Fetch, &client_quotas, "bob", "", 1e9, now)
+    );
+    assert_eq!(
+        0,
+        manager.record(QuotaType::Fetch, &client_quotas, "alice", "", 500.0, now)
+    );
+    // 2000 bytes over 10 seconds is twice the quota, which takes another 10
+    // seconds to even out.
+    assert_eq!(
+        10_000,
+        manager.record(QuotaType::Fetch, &client_quotas, "alice", "", 1500.0, now)
+    );
+    // Never more than all windows together.
+    assert_eq!(
+        11_000,
+        manager.record(QuotaType::Fetch, &client_quotas, "alice", "", 1e9, now)
+    );
+}
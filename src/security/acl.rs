@@ -0,0 +1,275 @@
+use crate::model::Acl;
+
+// The name every CLUSTER resource goes by.
+pub const CLUSTER_NAME: &str = "kafka-cluster";
+
+pub const WILDCARD_PRINCIPAL: &str = "User:*";
+pub const WILDCARD_HOST: &str = "*";
+pub const WILDCARD_RESOURCE: &str = "*";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ResourceType {
+    Any,
+    Topic,
+    Group,
+    Cluster,
+    TransactionalId,
+    DelegationToken,
+    User,
+}
+impl ResourceType {
+    pub fn id(&self) -> i8 {
+        match self {
+            ResourceType::Any => 1,
+            ResourceType::Topic => 2,
+            ResourceType::Group => 3,
+            ResourceType::Cluster => 4,
+            ResourceType::TransactionalId => 5,
+            ResourceType::DelegationToken => 6,
+            ResourceType::User => 7,
+        }
+    }
+
+    pub fn from_id(id: i8) -> Option<Self> {
+        match id {
+            1 => Some(ResourceType::Any),
+            2 => Some(ResourceType::Topic),
+            3 => Some(ResourceType::Group),
+            4 => Some(ResourceType::Cluster),
+            5 => Some(ResourceType::TransactionalId),
+            6 => Some(ResourceType::DelegationToken),
+            7 => Some(ResourceType::User),
+            _ => None,
+        }
+    }
+
+    // The operations reported in authorized-operations bitfields.
+    pub fn operations(&self) -> &'static [AclOperation] {
+        use AclOperation::*;
+        match self {
+            ResourceType::Topic => &[
+                Read,
+                Write,
+                Create,
+                Delete,
+                Alter,
+                Describe,
+                DescribeConfigs,
+                AlterConfigs,
+            ],
+            ResourceType::Group => &[Read, Describe, Delete],
+            ResourceType::Cluster => &[
+                Create,
+                ClusterAction,
+                DescribeConfigs,
+                AlterConfigs,
+                IdempotentWrite,
+                Alter,
+                Describe,
+            ],
+            ResourceType::TransactionalId => &[Describe, Write],
+            ResourceType::DelegationToken => &[Describe],
+            ResourceType::User => &[CreateTokens, DescribeTokens],
+            ResourceType::Any => &[],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum PatternType {
+    Any,
+    Match,
+    Literal,
+    Prefixed,
+}
+impl PatternType {
+    pub fn id(&self) -> i8 {
+        match self {
+            PatternType::Any => 1,
+            PatternType::Match => 2,
+            PatternType::Literal => 3,
+            PatternType::Prefixed => 4,
+        }
+    }
+
+    pub fn from_id(id: i8) -> Option<Self> {
+        match id {
+            1 => Some(PatternType::Any),
+            2 => Some(PatternType::Match),
+            3 => Some(PatternType::Literal),
+            4 => Some(PatternType::Prefixed),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AclOperation {
+    Any,
+    All,
+    Read,
+    Write,
+    Create,
+    Delete,
+    Alter,
+    Describe,
+    ClusterAction,
+    DescribeConfigs,
+    AlterConfigs,
+    IdempotentWrite,
+    CreateTokens,
+    DescribeTokens,
+}
+impl AclOperation {
+    pub fn id(&self) -> i8 {
+        match self {
+            AclOperation::Any => 1,
+            AclOperation::All => 2,
+            AclOperation::Read => 3,
+            AclOperation::Write => 4,
+            AclOperation::Create => 5,
+            AclOperation::Delete => 6,
+            AclOperation::Alter => 7,
+            AclOperation::Describe => 8,
+            AclOperation::ClusterAction => 9,
+            AclOperation::DescribeConfigs => 10,
+            AclOperation::AlterConfigs => 11,
+            AclOperation::IdempotentWrite => 12,
+            AclOperation::CreateTokens => 13,
+            AclOperation::DescribeTokens => 14,
+        }
+    }
+
+    pub fn from_id(id: i8) -> Option<Self> {
+        match id {
+            1 => Some(AclOperation::Any),
+            2 => Some(AclOperation::All),
+            3 => Some(AclOperation::Read),
+            4 => Some(AclOperation::Write),
+            5 => Some(AclOperation::Create),
+            6 => Some(AclOperation::Delete),
+            7 => Some(AclOperation::Alter),
+            8 => Some(AclOperation::Describe),
+            9 => Some(AclOperation::ClusterAction),
+            10 => Some(AclOperation::DescribeConfigs),
+            11 => Some(AclOperation::AlterConfigs),
+            12 => Some(AclOperation::IdempotentWrite),
+            13 => Some(AclOperation::CreateTokens),
+            14 => Some(AclOperation::DescribeTokens),
+            _ => None,
+        }
+    }
+
+    // Allowing any of these operations also allows `self`, e.g. a principal
+    // that may read a topic may describe it as well.
+    pub fn implied_by(&self) -> &'static [AclOperation] {
+        use AclOperation::*;
+        match self {
+            Describe => &[Read, Write, Delete, Alter],
+            DescribeConfigs => &[AlterConfigs],
+            _ => &[],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AclPermissionType {
+    Any,
+    Deny,
+    Allow,
+}
+impl AclPermissionType {
+    pub fn id(&self) -> i8 {
+        match self {
+            AclPermissionType::Any => 1,
+            AclPermissionType::Deny => 2,
+            AclPermissionType::Allow => 3,
+        }
+    }
+
+    pub fn from_id(id: i8) -> Option<Self> {
+        match id {
+            1 => Some(AclPermissionType::Any),
+            2 => Some(AclPermissionType::Deny),
+            3 => Some(AclPermissionType::Allow),
+            _ => None,
+        }
+    }
+}
+
+// Whether the ACL's resource pattern covers the named resource.
+pub fn pattern_matches(acl: &Acl, resource_type: ResourceType, name: &str) -> bool {
+    if acl.resource_type() != resource_type.id() {
+        return false;
+    }
+    match PatternType::from_id(acl.pattern_type()) {
+        Some(PatternType::Literal) => {
+            acl.resource_name() == name || acl.resource_name() == WILDCARD_RESOURCE
+        }
+        Some(PatternType::Prefixed) => name.starts_with(acl.resource_name()),
+        _ => false,
+    }
+}
+
+// Selects ACLs for DescribeAcls and DeleteAcls; `None` and the ANY types match
+// everything.
+#[derive(Debug, Clone)]
+pub struct AclFilter {
+    resource_type: ResourceType,
+    resource_name: Option<String>,
+    pattern_type: PatternType,
+    principal: Option<String>,
+    host: Option<String>,
+    operation: AclOperation,
+    permission_type: AclPermissionType,
+}
+impl AclFilter {
+    pub fn new(
+        resource_type: i8,
+        resource_name: Option<&str>,
+        pattern_type: i8,
+        principal: Option<&str>,
+        host: Option<&str>,
+        operation: i8,
+        permission_type: i8,
+    ) -> Result<Self, &'static str> {
+        Ok(Self {
+            resource_type: ResourceType::from_id(resource_type).ok_or("Invalid resource type")?,
+            resource_name: resource_name.map(str::to_string),
+            pattern_type: PatternType::from_id(pattern_type).ok_or("Invalid pattern type")?,
+            principal: principal.map(str::to_string),
+            host: host.map(str::to_string),
+            operation: AclOperation::from_id(operation).ok_or("Invalid operation")?,
+            permission_type: AclPermissionType::from_id(permission_type)
+                .ok_or("Invalid permission type")?,
+        })
+    }
+
+    pub fn matches(&self, acl: &Acl) -> bool {
+        if self.resource_type != ResourceType::Any && acl.resource_type() != self.resource_type.id()
+        {
+            return false;
+        }
+        let resource_matches = match (self.pattern_type, self.resource_name.as_deref()) {
+            (PatternType::Match, Some(name)) => ResourceType::from_id(acl.resource_type())
+                .is_some_and(|resource_type| pattern_matches(acl, resource_type, name)),
+            (PatternType::Any | PatternType::Match, name) => {
+                name.map_or(true, |name| acl.resource_name() == name)
+            }
+            (pattern_type, name) => {
+                acl.pattern_type() == pattern_type.id()
+                    && name.map_or(true, |name| acl.resource_name() == name)
+            }
+        };
+
+        resource_matches
+            && self
+                .principal
+                .as_ref()
+                .map_or(true, |principal| acl.principal() == principal)
+            && self.host.as_ref().map_or(true, |host| acl.host() == host)
+            && (self.operation == AclOperation::Any || acl.operation() == self.operation.id())
+            && (self.permission_type == AclPermissionType::Any
+                || acl.permission_type() == self.permission_type.id())
+    }
+}
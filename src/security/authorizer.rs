@@ -0,0 +1,130 @@
+use std::collections::HashMap;
+
+use uuid::Uuid;
+
+use crate::{config::BrokerConfig, model::Acl};
+
+use super::{
+    acl::{pattern_matches, WILDCARD_HOST, WILDCARD_PRINCIPAL},
+    AclOperation, AclPermissionType, ResourceType, Session,
+};
+
+// Decides requests against the ACLs of the metadata image. Like Kafka, ACLs
+// are only enforced once `authorizer.class.name` is set; until then every
+// request is allowed and the ACL APIs answer SECURITY_DISABLED.
+#[derive(Debug, Clone)]
+pub struct Authorizer {
+    enabled: bool,
+    super_users: Vec<String>,
+    allow_everyone_if_no_acl_found: bool,
+}
+impl Authorizer {
+    pub fn new(config: &BrokerConfig) -> Self {
+        Self {
+            enabled: config.authorizer_class_name().is_some(),
+            super_users: config.super_users().to_vec(),
+            allow_everyone_if_no_acl_found: config.allow_everyone_if_no_acl_found(),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    // A matching DENY wins over any ALLOW. A resource without any ACL is only
+    // open when allow.everyone.if.no.acl.found is set.
+    pub fn authorize(
+        &self,
+        acls: &HashMap<Uuid, Acl>,
+        session: &Session,
+        operation: AclOperation,
+        resource_type: ResourceType,
+        name: &str,
+    ) -> bool {
+        if !self.enabled
+            || self
+                .super_users
+                .iter()
+                .any(|user| user == session.principal())
+        {
+            return true;
+        }
+
+        let mut resource_acls = acls
+            .values()
+            .filter(|acl| pattern_matches(acl, resource_type, name))
+            .peekable();
+        if resource_acls.peek().is_none() {
+            return self.allow_everyone_if_no_acl_found;
+        }
+
+        let mut allowed = false;
+        for acl in resource_acls.filter(|acl| {
+            (acl.principal() == session.principal() || acl.principal() == WILDCARD_PRINCIPAL)
+                && (acl.host() == session.host() || acl.host() == WILDCARD_HOST)
+        }) {
+            let acl_operation = AclOperation::from_id(acl.operation());
+            match AclPermissionType::from_id(acl.permission_type()) {
+                Some(AclPermissionType::Deny)
+                    if acl_operation == Some(AclOperation::All)
+                        || acl_operation == Some(operation) =>
+                {
+                    return false;
+                }
+                Some(AclPermissionType::Allow) => {
+                    allowed |= acl_operation.is_some_and(|acl_operation| {
+                        acl_operation == AclOperation::All
+                            || acl_operation == operation
+                            || operation.implied_by().contains(&acl_operation)
+                    });
+                }
+                _ => {}
+            }
+        }
+        allowed
+    }
+}
+
+// The authorizer bound to the ACLs and session of one request.
+pub struct Authorization<'a> {
+    authorizer: &'a Authorizer,
+    acls: &'a HashMap<Uuid, Acl>,
+    session: &'a Session,
+}
+impl<'a> Authorization<'a> {
+    pub fn new(
+        authorizer: &'a Authorizer,
+        acls: &'a HashMap<Uuid, Acl>,
+        session: &'a Session,
+    ) -> Self {
+        Self {
+            authorizer,
+            acls,
+            session,
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.authorizer.is_enabled()
+    }
+
+    pub fn authorize(
+        &self,
+        operation: AclOperation,
+        resource_type: ResourceType,
+        name: &str,
+    ) -> bool {
+        self.authorizer
+            .authorize(self.acls, self.session, operation, resource_type, name)
+    }
+
+    // One bit per operation id, as in the authorized-operations fields of
+    // Metadata and DescribeTopicPartitions.
+    pub fn authorized_operations(&self, resource_type: ResourceType, name: &str) -> i32 {
+        resource_type
+            .operations()
+            .iter()
+            .filter(|operation| self.authorize(**operation, resource_type, name))
+            .fold(0, |bits, operation| bits | 1 << operation.id())
+    }
+}
@@ -0,0 +1,160 @@
+pub mod acl;
+mod authorizer;
+pub mod sasl;
+pub mod tls;
+
+use std::collections::HashMap;
+
+use bytes::Bytes;
+
+use crate::{
+    config::BrokerConfig,
+    model::ScramCredential,
+    protocol::{
+        message::{api_versions, sasl_authenticate, sasl_handshake},
+        ErrorCode,
+    },
+};
+
+pub use acl::{AclFilter, AclOperation, AclPermissionType, PatternType, ResourceType};
+pub use authorizer::{Authorization, Authorizer};
+pub use sasl::{SaslMechanism, ScramMechanism};
+
+use sasl::SaslState;
+
+pub const ANONYMOUS_PRINCIPAL: &str = "User:ANONYMOUS";
+
+// Who is on the other end of a connection. On SASL listeners the principal is
+// only known once the client has completed SaslAuthenticate. Clients name
+// their software in ApiVersions v3 and later.
+#[derive(Debug, Clone)]
+pub struct Session {
+    principal: String,
+    host: String,
+    sasl: SaslState,
+    client_software: Option<(String, String)>,
+}
+impl Session {
+    pub fn new(principal: &str, host: &str) -> Self {
+        Self {
+            principal: principal.to_string(),
+            host: host.to_string(),
+            sasl: SaslState::Complete,
+            client_software: None,
+        }
+    }
+
+    pub fn anonymous(host: &str) -> Self {
+        Self::new(ANONYMOUS_PRINCIPAL, host)
+    }
+
+    // A session that has to go through SaslHandshake and SaslAuthenticate
+    // before anything but ApiVersions is served.
+    pub fn unauthenticated(host: &str) -> Self {
+        Self {
+            sasl: SaslState::Handshake,
+            ..Self::anonymous(host)
+        }
+    }
+
+    pub fn principal(&self) -> &str {
+        &self.principal
+    }
+
+    pub fn host(&self) -> &str {
+        &self.host
+    }
+
+    pub fn client_software_name(&self) -> Option<&str> {
+        self.client_software.as_ref().map(|(name, _)| name.as_str())
+    }
+
+    pub fn client_software_version(&self) -> Option<&str> {
+        self.client_software
+            .as_ref()
+            .map(|(_, version)| version.as_str())
+    }
+
+    pub fn set_client_software(&mut self, name: &str, version: &str) {
+        self.client_software = Some((name.to_string(), version.to_string()));
+    }
+
+    pub fn is_authenticated(&self) -> bool {
+        matches!(self.sasl, SaslState::Complete)
+    }
+
+    // After a failed SaslAuthenticate nothing more is served; the connection
+    // is closed once the error has been sent.
+    pub fn authentication_failed(&self) -> bool {
+        matches!(self.sasl, SaslState::Failed)
+    }
+
+    // Whether a request with this API key may be processed in the current
+    // SASL state.
+    pub fn permits(&self, api_key: i16) -> bool {
+        match self.sasl {
+            SaslState::Complete => true,
+            SaslState::Handshake => {
+                api_key == api_versions::API_KEY || api_key == sasl_handshake::API_KEY
+            }
+            SaslState::Authenticate(_) | SaslState::ScramClientFinal(_) => {
+                api_key == sasl_authenticate::API_KEY
+            }
+            SaslState::Failed => false,
+        }
+    }
+
+    pub fn handshake(&mut self, mechanism: &str, config: &BrokerConfig) -> ErrorCode {
+        if !matches!(self.sasl, SaslState::Handshake) {
+            return ErrorCode::IllegalSaslState;
+        }
+        let enabled = config
+            .sasl_enabled_mechanisms()
+            .iter()
+            .any(|enabled| enabled == mechanism);
+        match SaslMechanism::from_name(mechanism) {
+            Some(mechanism) if enabled => {
+                self.sasl = SaslState::Authenticate(mechanism);
+                ErrorCode::None
+            }
+            _ => ErrorCode::UnsupportedSaslMechanism,
+        }
+    }
+
+    // Feeds one SaslAuthenticate token through the chosen mechanism and
+    // returns the bytes to send back. A failed attempt leaves the session
+    // failed, as Kafka closes the connection instead of allowing a retry.
+    pub fn authenticate(
+        &mut self,
+        token: &[u8],
+        config: &BrokerConfig,
+        credentials: &HashMap<(String, i8), ScramCredential>,
+    ) -> Result<Bytes, (ErrorCode, String)> {
+        let state = std::mem::replace(&mut self.sasl, SaslState::Failed);
+        let (state, reply) = match state {
+            SaslState::Authenticate(SaslMechanism::Plain) => {
+                let user = sasl::authenticate_plain(token, config.sasl_plain_credentials_file())?;
+                self.principal = format!("User:{}", user);
+                (SaslState::Complete, Bytes::new())
+            }
+            SaslState::Authenticate(SaslMechanism::Scram(mechanism)) => {
+                let (exchange, reply) = sasl::scram_client_first(mechanism, token, credentials)?;
+                (SaslState::ScramClientFinal(Box::new(exchange)), reply)
+            }
+            SaslState::ScramClientFinal(exchange) => {
+                let (user, reply) = sasl::scram_client_final(&exchange, token)?;
+                self.principal = format!("User:{}", user);
+                (SaslState::Complete, reply)
+            }
+            state => {
+                self.sasl = state;
+                return Err((
+                    ErrorCode::IllegalSaslState,
+                    ErrorCode::IllegalSaslState.message().to_string(),
+                ));
+            }
+        };
+        self.sasl = state;
+        Ok(reply)
+    }
+}
@@ -0,0 +1,331 @@
+use std::{collections::HashMap, path::Path};
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use bytes::Bytes;
+use hmac::{Hmac, Mac};
+use sha2::{Digest, Sha256, Sha512};
+
+use crate::{config::parse_properties, model::ScramCredential, protocol::ErrorCode};
+
+pub const SCRAM_MIN_ITERATIONS: i32 = 4096;
+pub const SCRAM_MAX_ITERATIONS: i32 = 16384;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SaslMechanism {
+    Plain,
+    Scram(ScramMechanism),
+}
+impl SaslMechanism {
+    pub fn name(&self) -> &'static str {
+        match self {
+            SaslMechanism::Plain => "PLAIN",
+            SaslMechanism::Scram(mechanism) => mechanism.name(),
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "PLAIN" => Some(SaslMechanism::Plain),
+            name => ScramMechanism::from_name(name).map(SaslMechanism::Scram),
+        }
+    }
+}
+
+// The mechanism ids of UserScramCredentialRecord and the SCRAM credential APIs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ScramMechanism {
+    Sha256,
+    Sha512,
+}
+impl ScramMechanism {
+    pub fn id(&self) -> i8 {
+        match self {
+            ScramMechanism::Sha256 => 1,
+            ScramMechanism::Sha512 => 2,
+        }
+    }
+
+    pub fn from_id(id: i8) -> Option<Self> {
+        match id {
+            1 => Some(ScramMechanism::Sha256),
+            2 => Some(ScramMechanism::Sha512),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ScramMechanism::Sha256 => "SCRAM-SHA-256",
+            ScramMechanism::Sha512 => "SCRAM-SHA-512",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "SCRAM-SHA-256" => Some(ScramMechanism::Sha256),
+            "SCRAM-SHA-512" => Some(ScramMechanism::Sha512),
+            _ => None,
+        }
+    }
+
+    // The stored and server keys for a password the client has already salted,
+    // as AlterUserScramCredentials sends it.
+    pub fn credential(
+        &self,
+        salt: Bytes,
+        salted_password: &[u8],
+        iterations: i32,
+    ) -> ScramCredential {
+        let client_key = self.hmac(salted_password, b"Client Key");
+        let stored_key = self.hash(&client_key);
+        let server_key = self.hmac(salted_password, b"Server Key");
+        ScramCredential::new(salt, stored_key.into(), server_key.into(), iterations)
+    }
+
+    // Hi() of RFC 5802, i.e. PBKDF2 with this mechanism's HMAC and a single
+    // block of output.
+    pub fn salted_password(&self, password: &[u8], salt: &[u8], iterations: i32) -> Vec<u8> {
+        let mut block = salt.to_vec();
+        block.extend_from_slice(&1u32.to_be_bytes());
+        let mut u = self.hmac(password, &block);
+        let mut salted_password = u.clone();
+        for _ in 1..iterations {
+            u = self.hmac(password, &u);
+            for (byte, next) in salted_password.iter_mut().zip(&u) {
+                *byte ^= next;
+            }
+        }
+        salted_password
+    }
+
+    fn hash(&self, data: &[u8]) -> Vec<u8> {
+        match self {
+            ScramMechanism::Sha256 => Sha256::digest(data).to_vec(),
+            ScramMechanism::Sha512 => Sha512::digest(data).to_vec(),
+        }
+    }
+
+    fn hmac(&self, key: &[u8], data: &[u8]) -> Vec<u8> {
+        match self {
+            ScramMechanism::Sha256 => {
+                let mut mac =
+                    Hmac::<Sha256>::new_from_slice(key).expect("HMAC takes keys of any size");
+                mac.update(data);
+                mac.finalize().into_bytes().to_vec()
+            }
+            ScramMechanism::Sha512 => {
+                let mut mac =
+                    Hmac::<Sha512>::new_from_slice(key).expect("HMAC takes keys of any size");
+                mac.update(data);
+                mac.finalize().into_bytes().to_vec()
+            }
+        }
+    }
+}
+
+// Where a connection is in the SaslHandshake/SaslAuthenticate exchange.
+#[derive(Debug, Clone)]
+pub(crate) enum SaslState {
+    Handshake,
+    Authenticate(SaslMechanism),
+    ScramClientFinal(Box<ScramExchange>),
+    Complete,
+    Failed,
+}
+
+// What the server remembers between the two SCRAM round trips.
+#[derive(Debug, Clone)]
+pub(crate) struct ScramExchange {
+    mechanism: ScramMechanism,
+    user: String,
+    gs2_header: String,
+    client_first_bare: String,
+    server_first: String,
+    nonce: String,
+    credential: ScramCredential,
+}
+
+type SaslError = (ErrorCode, String);
+
+fn failed(mechanism: SaslMechanism) -> SaslError {
+    (
+        ErrorCode::SaslAuthenticationFailed,
+        format!(
+            "Authentication failed: Invalid username or password with SASL mechanism {}",
+            mechanism.name()
+        ),
+    )
+}
+
+fn invalid(message: &str) -> SaslError {
+    (
+        ErrorCode::SaslAuthenticationFailed,
+        format!("Authentication failed: {}", message),
+    )
+}
+
+// Compares without bailing out at the first difference.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+// PLAIN sends `authzid NUL authcid NUL password` in one go; the credentials
+// file is read on every attempt so users can be added without a restart.
+pub(crate) fn authenticate_plain(
+    token: &[u8],
+    credentials_file: Option<&Path>,
+) -> Result<String, SaslError> {
+    let token = std::str::from_utf8(token).map_err(|_| invalid("Invalid SASL/PLAIN request"))?;
+    let mut parts = token.split('\0');
+    let (Some(authzid), Some(user), Some(password), None) =
+        (parts.next(), parts.next(), parts.next(), parts.next())
+    else {
+        return Err(invalid("Invalid SASL/PLAIN request: expected 3 tokens"));
+    };
+    if user.is_empty() {
+        return Err(invalid(
+            "Invalid SASL/PLAIN request: username not specified",
+        ));
+    }
+    if !authzid.is_empty() && authzid != user {
+        return Err(invalid(
+            "Invalid SASL/PLAIN request: authorization id must match the username",
+        ));
+    }
+
+    let credentials = credentials_file
+        .and_then(|path| std::fs::read_to_string(path).ok())
+        .map(|content| parse_properties(&content))
+        .unwrap_or_default();
+    match credentials.get(user) {
+        Some(expected) if constant_time_eq(expected.as_bytes(), password.as_bytes()) => {
+            Ok(user.to_string())
+        }
+        _ => Err(failed(SaslMechanism::Plain)),
+    }
+}
+
+// Handles `gs2-header client-first-message-bare` and answers with the
+// server-first-message.
+pub(crate) fn scram_client_first(
+    mechanism: ScramMechanism,
+    token: &[u8],
+    credentials: &HashMap<(String, i8), ScramCredential>,
+) -> Result<(ScramExchange, Bytes), SaslError> {
+    let message =
+        std::str::from_utf8(token).map_err(|_| invalid("Invalid SCRAM client first message"))?;
+    let mut gs2 = message.splitn(3, ',');
+    let (Some(cbind_flag), Some(authzid), Some(client_first_bare)) =
+        (gs2.next(), gs2.next(), gs2.next())
+    else {
+        return Err(invalid("Invalid SCRAM client first message"));
+    };
+    if cbind_flag != "n" && cbind_flag != "y" {
+        return Err(invalid("SCRAM channel binding is not supported"));
+    }
+
+    let mut user = None;
+    let mut client_nonce = None;
+    for attribute in client_first_bare.split(',') {
+        match attribute.split_once('=') {
+            Some(("n", value)) => user = Some(value.replace("=2C", ",").replace("=3D", "=")),
+            Some(("r", value)) => client_nonce = Some(value),
+            // Extensions such as tokenauth are not supported and ignored.
+            _ => {}
+        }
+    }
+    let (Some(user), Some(client_nonce)) = (user, client_nonce) else {
+        return Err(invalid("Invalid SCRAM client first message"));
+    };
+    if let Some(authzid) = authzid.strip_prefix("a=") {
+        if authzid != user {
+            return Err(invalid(
+                "Authorization id must match the username for SCRAM",
+            ));
+        }
+    }
+    let credential = credentials
+        .get(&(user.clone(), mechanism.id()))
+        .ok_or_else(|| failed(SaslMechanism::Scram(mechanism)))?
+        .clone();
+
+    let nonce = format!("{}{}", client_nonce, uuid::Uuid::new_v4().simple());
+    let server_first = format!(
+        "r={},s={},i={}",
+        nonce,
+        STANDARD.encode(credential.salt()),
+        credential.iterations()
+    );
+    let reply = Bytes::copy_from_slice(server_first.as_bytes());
+    let exchange = ScramExchange {
+        mechanism,
+        user,
+        gs2_header: format!("{},{},", cbind_flag, authzid),
+        client_first_bare: client_first_bare.to_string(),
+        server_first,
+        nonce,
+        credential,
+    };
+    Ok((exchange, reply))
+}
+
+// Checks the client proof and answers with the server signature; returns the
+// authenticated user.
+pub(crate) fn scram_client_final(
+    exchange: &ScramExchange,
+    token: &[u8],
+) -> Result<(String, Bytes), SaslError> {
+    let message =
+        std::str::from_utf8(token).map_err(|_| invalid("Invalid SCRAM client final message"))?;
+    let (without_proof, proof) = message
+        .rsplit_once(",p=")
+        .ok_or_else(|| invalid("Invalid SCRAM client final message"))?;
+    let mut channel_binding = None;
+    let mut nonce = None;
+    for attribute in without_proof.split(',') {
+        match attribute.split_once('=') {
+            Some(("c", value)) => channel_binding = Some(value),
+            Some(("r", value)) => nonce = Some(value),
+            _ => {}
+        }
+    }
+    if channel_binding != Some(STANDARD.encode(&exchange.gs2_header).as_str()) {
+        return Err(invalid("Invalid SCRAM channel binding"));
+    }
+    if nonce != Some(exchange.nonce.as_str()) {
+        return Err(invalid("Invalid SCRAM nonce"));
+    }
+
+    let mechanism = exchange.mechanism;
+    let proof = STANDARD
+        .decode(proof)
+        .map_err(|_| invalid("Invalid SCRAM client proof"))?;
+    let auth_message = format!(
+        "{},{},{}",
+        exchange.client_first_bare, exchange.server_first, without_proof
+    );
+    let client_signature =
+        mechanism.hmac(exchange.credential.stored_key(), auth_message.as_bytes());
+    if proof.len() != client_signature.len() {
+        return Err(failed(SaslMechanism::Scram(mechanism)));
+    }
+    let client_key = proof
+        .iter()
+        .zip(&client_signature)
+        .map(|(p, s)| p ^ s)
+        .collect::<Vec<_>>();
+    if !constant_time_eq(
+        &mechanism.hash(&client_key),
+        exchange.credential.stored_key(),
+    ) {
+        return Err(failed(SaslMechanism::Scram(mechanism)));
+    }
+
+    let server_signature =
+        mechanism.hmac(exchange.credential.server_key(), auth_message.as_bytes());
+    let server_final = format!("v={}", STANDARD.encode(server_signature));
+    Ok((
+        exchange.user.clone(),
+        Bytes::copy_from_slice(server_final.as_bytes()),
+    ))
+}
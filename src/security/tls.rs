@@ -0,0 +1,162 @@
+use std::{fs::File, io::BufReader, path::Path, sync::Arc};
+
+use tokio_rustls::{
+    rustls::{
+        crypto::ring,
+        pki_types::{CertificateDer, PrivateKeyDer},
+        server::{danger::ClientCertVerifier, WebPkiClientVerifier},
+        RootCertStore, ServerConfig,
+    },
+    TlsAcceptor,
+};
+use x509_parser::{
+    objects::{oid2abbrev, oid_registry},
+    x509::X509Name,
+};
+
+use crate::config::{BrokerConfig, SslClientAuth};
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("{0} is required for SSL listeners")]
+    MissingConfig(&'static str),
+
+    #[error("unable to read {path}: {source}")]
+    IOError {
+        path: String,
+        source: std::io::Error,
+    },
+
+    #[error("no certificate found in {0}")]
+    NoCertificate(String),
+
+    #[error("no private key found in {0}")]
+    NoPrivateKey(String),
+
+    #[error("tls error: {0}")]
+    TlsError(#[from] tokio_rustls::rustls::Error),
+
+    #[error("invalid client certificate verifier: {0}")]
+    VerifierError(#[from] tokio_rustls::rustls::server::VerifierBuilderError),
+}
+
+// Builds the acceptor for SSL and SASL_SSL listeners from the PEM files in the
+// broker config. Clients are asked for a certificate only when
+// ssl.client.auth is requested or required, and those are checked against
+// ssl.truststore.location.
+pub fn acceptor(config: &BrokerConfig) -> Result<TlsAcceptor, Error> {
+    let certificates = certificates(
+        config
+            .ssl_certificate_location()
+            .ok_or(Error::MissingConfig("ssl.certificate.location"))?,
+    )?;
+    let key = private_key(
+        config
+            .ssl_key_location()
+            .ok_or(Error::MissingConfig("ssl.key.location"))?,
+    )?;
+
+    let provider = Arc::new(ring::default_provider());
+    let builder = ServerConfig::builder_with_provider(provider.clone())
+        .with_safe_default_protocol_versions()?;
+    let builder = match config.ssl_client_auth() {
+        SslClientAuth::None => builder.with_no_client_auth(),
+        client_auth => {
+            let mut roots = RootCertStore::empty();
+            let truststore = config
+                .ssl_truststore_location()
+                .ok_or(Error::MissingConfig("ssl.truststore.location"))?;
+            for certificate in certificates_from(truststore)? {
+                roots.add(certificate)?;
+            }
+            let verifier = WebPkiClientVerifier::builder_with_provider(Arc::new(roots), provider);
+            let verifier: Arc<dyn ClientCertVerifier> = match client_auth {
+                SslClientAuth::Requested => verifier.allow_unauthenticated().build()?,
+                _ => verifier.build()?,
+            };
+            builder.with_client_cert_verifier(verifier)
+        }
+    };
+    let server_config = builder.with_single_cert(certificates, key)?;
+    Ok(TlsAcceptor::from(Arc::new(server_config)))
+}
+
+// The principal for a verified client certificate, e.g.
+// `User:CN=alice,O=example`. Connections without one stay anonymous.
+pub fn principal(certificates: Option<&[CertificateDer<'_>]>) -> Option<String> {
+    let certificate = certificates?.first()?;
+    let (_, certificate) = x509_parser::parse_x509_certificate(certificate).ok()?;
+    Some(format!(
+        "User:{}",
+        distinguished_name(certificate.subject())
+    ))
+}
+
+// RFC 2253 order, most specific attribute first, which is what Kafka uses for
+// SSL principals.
+fn distinguished_name(name: &X509Name) -> String {
+    let mut rdns = name
+        .iter()
+        .map(|rdn| {
+            rdn.iter()
+                .map(|attribute| {
+                    let key = oid2abbrev(attribute.attr_type(), oid_registry())
+                        .map(str::to_string)
+                        .unwrap_or_else(|_| attribute.attr_type().to_id_string());
+                    let value = attribute.as_str().unwrap_or_default();
+                    format!("{}={}", key, escape(value))
+                })
+                .collect::<Vec<_>>()
+                .join("+")
+        })
+        .collect::<Vec<_>>();
+    rdns.reverse();
+    rdns.join(",")
+}
+
+fn escape(value: &str) -> String {
+    let last = value.chars().count().saturating_sub(1);
+    let mut escaped = String::with_capacity(value.len());
+    for (i, c) in value.chars().enumerate() {
+        let leading = i == 0 && (c == ' ' || c == '#');
+        let trailing = i == last && c == ' ';
+        if leading || trailing || matches!(c, ',' | '+' | '"' | '\\' | '<' | '>' | ';') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+fn certificates(path: &Path) -> Result<Vec<CertificateDer<'static>>, Error> {
+    let certificates = certificates_from(path)?;
+    if certificates.is_empty() {
+        return Err(Error::NoCertificate(path.display().to_string()));
+    }
+    Ok(certificates)
+}
+
+fn certificates_from(path: &Path) -> Result<Vec<CertificateDer<'static>>, Error> {
+    rustls_pemfile::certs(&mut reader(path)?)
+        .collect::<Result<_, _>>()
+        .map_err(|source| io_error(path, source))
+}
+
+fn private_key(path: &Path) -> Result<PrivateKeyDer<'static>, Error> {
+    rustls_pemfile::private_key(&mut reader(path)?)
+        .map_err(|source| io_error(path, source))?
+        .ok_or_else(|| Error::NoPrivateKey(path.display().to_string()))
+}
+
+fn reader(path: &Path) -> Result<BufReader<File>, Error> {
+    File::open(path)
+        .map(BufReader::new)
+        .map_err(|source| io_error(path, source))
+}
+
+fn io_error(path: &Path, source: std::io::Error) -> Error {
+    Error::IOError {
+        path: path.display().to_string(),
+        source,
+    }
+}
@@ -0,0 +1,186 @@
+use std::{
+    collections::HashMap,
+    fs::{self, File},
+    io::{self, Write},
+    path::Path,
+};
+
+use base64::{
+    alphabet,
+    engine::{
+        general_purpose::URL_SAFE_NO_PAD, DecodePaddingMode, GeneralPurpose, GeneralPurposeConfig,
+    },
+    Engine,
+};
+use uuid::Uuid;
+
+use crate::config::{parse_properties, BrokerConfig};
+
+use super::{io_error, log_dirs, sync_dir, Error};
+
+pub const META_PROPERTIES_FILE: &str = "meta.properties";
+// The only version KRaft brokers write; version 0 belongs to ZooKeeper mode.
+const VERSION: &str = "1";
+// Java's decoder ignores the unused bits of the last character, which ids
+// like the well-known `MkU3OEVBNTcwNTJENDM2Qk` depend on.
+const UUID_DECODER: GeneralPurpose = GeneralPurpose::new(
+    &alphabet::URL_SAFE,
+    GeneralPurposeConfig::new()
+        .with_decode_allow_trailing_bits(true)
+        .with_decode_padding_mode(DecodePaddingMode::Indifferent),
+);
+
+// What `meta.properties` records about the cluster and node a log directory
+// was formatted for.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MetaProperties {
+    cluster_id: String,
+    node_id: i32,
+    directory_id: Option<Uuid>,
+}
+impl MetaProperties {
+    pub fn new(cluster_id: &str, node_id: i32, directory_id: Option<Uuid>) -> Self {
+        Self {
+            cluster_id: cluster_id.to_string(),
+            node_id,
+            directory_id,
+        }
+    }
+
+    pub fn cluster_id(&self) -> &str {
+        &self.cluster_id
+    }
+
+    pub fn node_id(&self) -> i32 {
+        self.node_id
+    }
+
+    pub fn directory_id(&self) -> Option<Uuid> {
+        self.directory_id
+    }
+
+    // None if the directory was never formatted.
+    pub fn read(log_dir: &Path) -> Result<Option<Self>, Error> {
+        let path = log_dir.join(META_PROPERTIES_FILE);
+        let content = match fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(source) => return Err(Error::IOError { path, source }),
+        };
+        let properties = parse_properties(&content);
+        let invalid = |reason: String| {
+            Error::InvalidMetaProperties(format!("{} in {}", reason, path.display()))
+        };
+
+        match properties.get("version").map(String::as_str) {
+            Some(VERSION) => {}
+            version => {
+                return Err(invalid(format!(
+                    "Unsupported version {}, expected {}",
+                    version.unwrap_or("0"),
+                    VERSION
+                )))
+            }
+        }
+        let cluster_id = properties
+            .get("cluster.id")
+            .filter(|cluster_id| !cluster_id.is_empty())
+            .ok_or_else(|| invalid("No cluster.id".to_string()))?;
+        let node_id = properties
+            .get("node.id")
+            .ok_or_else(|| invalid("No node.id".to_string()))?
+            .parse()
+            .map_err(|_| invalid("Invalid node.id".to_string()))?;
+        let directory_id = properties
+            .get("directory.id")
+            .map(|id| {
+                decode_uuid(id).ok_or_else(|| invalid(format!("Invalid directory.id {}", id)))
+            })
+            .transpose()?;
+        Ok(Some(Self::new(cluster_id, node_id, directory_id)))
+    }
+
+    // Creates the directory if needed. Like checkpoints the file goes through
+    // a temporary one, so that a crash never leaves half of it behind.
+    pub fn write(&self, log_dir: &Path) -> Result<(), Error> {
+        fs::create_dir_all(log_dir).map_err(io_error(log_dir))?;
+        let mut content = format!(
+            "version={}\ncluster.id={}\nnode.id={}\n",
+            VERSION, self.cluster_id, self.node_id
+        );
+        if let Some(directory_id) = self.directory_id {
+            content.push_str(&format!("directory.id={}\n", encode_uuid(directory_id)));
+        }
+        let path = log_dir.join(META_PROPERTIES_FILE);
+        let tmp = path.with_extension("properties.tmp");
+        File::create(&tmp)
+            .and_then(|mut file| {
+                file.write_all(content.as_bytes())?;
+                file.sync_all()
+            })
+            .map_err(io_error(&tmp))?;
+        fs::rename(&tmp, &path).map_err(io_error(&path))?;
+        sync_dir(log_dir)
+    }
+}
+
+// Reads `meta.properties` from every log directory and checks that they
+// belong to one cluster and to this node, and that no two directories share
+// an id. Directories that were never formatted are skipped. Returns the
+// properties of the first formatted directory.
+pub fn load(config: &BrokerConfig) -> Result<Option<MetaProperties>, Error> {
+    let mut loaded: Option<MetaProperties> = None;
+    let mut directory_ids = HashMap::new();
+    for log_dir in log_dirs(config) {
+        let Some(properties) = MetaProperties::read(&log_dir)? else {
+            continue;
+        };
+        let path = log_dir.join(META_PROPERTIES_FILE);
+        if let Some(first) = &loaded {
+            if first.cluster_id() != properties.cluster_id() {
+                return Err(Error::InvalidMetaProperties(format!(
+                    "Invalid cluster.id in {}. Expected {}, but read {}",
+                    path.display(),
+                    first.cluster_id(),
+                    properties.cluster_id()
+                )));
+            }
+        }
+        if properties.node_id() != config.node_id() {
+            return Err(Error::InvalidMetaProperties(format!(
+                "Stored node id {} doesn't match the configured node id {} in {}. If you moved \
+                 your data, make sure your configured node id matches. If you intend to create a \
+                 new node, you should remove all data in your data directories.",
+                properties.node_id(),
+                config.node_id(),
+                path.display()
+            )));
+        }
+        if let Some(directory_id) = properties.directory_id() {
+            if let Some(other) = directory_ids.insert(directory_id, log_dir.clone()) {
+                return Err(Error::InvalidMetaProperties(format!(
+                    "Duplicate directory ID {} found. It was the ID of {}, but also of {}",
+                    directory_id,
+                    other.display(),
+                    log_dir.display()
+                )));
+            }
+        }
+        loaded.get_or_insert(properties);
+    }
+    Ok(loaded)
+}
+
+// Kafka writes ids, the cluster id included, as the URL-safe base64 of the
+// UUID's 16 bytes. The hyphenated form is accepted too.
+pub fn encode_uuid(uuid: Uuid) -> String {
+    URL_SAFE_NO_PAD.encode(uuid.as_bytes())
+}
+
+pub fn decode_uuid(id: &str) -> Option<Uuid> {
+    UUID_DECODER
+        .decode(id)
+        .ok()
+        .and_then(|bytes| Uuid::from_slice(&bytes).ok())
+        .or_else(|| Uuid::parse_str(id).ok())
+}
@@ -0,0 +1,274 @@
+pub mod meta_properties;
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs::{self, File, OpenOptions},
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    config::BrokerConfig,
+    metadata::{loader::list_segments, writer::next_offset},
+    protocol,
+};
+
+pub use meta_properties::MetaProperties;
+
+pub const RECOVERY_POINT_CHECKPOINT: &str = "recovery-point-offset-checkpoint";
+pub const LOG_START_OFFSET_CHECKPOINT: &str = "log-start-offset-checkpoint";
+// Written last on a clean shutdown and removed on startup, so that a log
+// directory without it was not closed cleanly.
+pub const CLEAN_SHUTDOWN_FILE: &str = ".kafka_cleanshutdown";
+
+const CHECKPOINT_VERSION: i32 = 0;
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("io error on {path}: {source}")]
+    IOError { path: PathBuf, source: io::Error },
+
+    #[error("corrupt segment {path}: {source}")]
+    CorruptSegment {
+        path: PathBuf,
+        source: protocol::Error,
+    },
+
+    #[error("malformed checkpoint file {0}")]
+    MalformedCheckpoint(PathBuf),
+
+    #[error("{0}")]
+    InvalidMetaProperties(String),
+}
+
+type TopicPartition = (String, i32);
+
+// Every directory the broker keeps logs in, the metadata log's included.
+pub fn log_dirs(config: &BrokerConfig) -> Vec<PathBuf> {
+    let mut log_dirs = config.log_dirs().to_vec();
+    if !log_dirs.iter().any(|dir| dir == config.metadata_log_dir()) {
+        log_dirs.push(config.metadata_log_dir().to_path_buf());
+    }
+    log_dirs
+}
+
+// Makes the logs of a directory safe to read and append to. After a clean
+// shutdown the marker is all that needs to go; otherwise every segment past
+// the recovery point is cut back to its last complete batch, and any segment
+// after a cut one is dropped. Returns the number of partitions that were
+// recovered.
+pub fn recover(log_dir: &Path) -> Result<usize, Error> {
+    if !log_dir.is_dir() {
+        return Ok(0);
+    }
+    let marker = log_dir.join(CLEAN_SHUTDOWN_FILE);
+    if marker.exists() {
+        fs::remove_file(&marker).map_err(io_error(&marker))?;
+        return Ok(0);
+    }
+
+    let recovery_points = read_checkpoint(&log_dir.join(RECOVERY_POINT_CHECKPOINT))?;
+    let mut recovered = 0;
+    for (topic_partition, partition_dir) in partitions(log_dir)? {
+        let recovery_point = recovery_points.get(&topic_partition).copied().unwrap_or(0);
+        if recover_partition(&partition_dir, recovery_point)? {
+            recovered += 1;
+        }
+    }
+    Ok(recovered)
+}
+
+// The last step of a clean shutdown: the active segments are synced, both
+// checkpoints are written and then the marker.
+pub fn close(log_dir: &Path) -> Result<(), Error> {
+    if !log_dir.is_dir() {
+        return Ok(());
+    }
+    let mut recovery_points = BTreeMap::new();
+    let mut log_start_offsets = BTreeMap::new();
+    for (topic_partition, partition_dir) in partitions(log_dir)? {
+        let segments = list_segments(&partition_dir).map_err(io_error(&partition_dir))?;
+        let (log_start_offset, log_end_offset) = match (segments.first(), segments.last()) {
+            (Some(first), Some(last)) => {
+                let path = segment_path(&partition_dir, *last);
+                File::open(&path)
+                    .and_then(|file| file.sync_all())
+                    .map_err(io_error(&path))?;
+                let (log_end_offset, _) =
+                    next_offset(&path, *last as i64).map_err(|source| Error::CorruptSegment {
+                        path: path.clone(),
+                        source,
+                    })?;
+                (*first as i64, log_end_offset)
+            }
+            _ => (0, 0),
+        };
+        recovery_points.insert(topic_partition.clone(), log_end_offset);
+        log_start_offsets.insert(topic_partition, log_start_offset);
+    }
+
+    write_checkpoint(&log_dir.join(RECOVERY_POINT_CHECKPOINT), &recovery_points)?;
+    write_checkpoint(
+        &log_dir.join(LOG_START_OFFSET_CHECKPOINT),
+        &log_start_offsets,
+    )?;
+    let marker = log_dir.join(CLEAN_SHUTDOWN_FILE);
+    File::create(&marker)
+        .and_then(|file| file.sync_all())
+        .map_err(io_error(&marker))?;
+    sync_dir(log_dir)
+}
+
+fn recover_partition(partition_dir: &Path, recovery_point: i64) -> Result<bool, Error> {
+    let segments = list_segments(partition_dir).map_err(io_error(partition_dir))?;
+    let mut truncated = false;
+    for (i, base_offset) in segments.iter().enumerate() {
+        let path = segment_path(partition_dir, *base_offset);
+        if truncated {
+            tracing::warn!("deleting segment {} after a truncated one", path.display());
+            fs::remove_file(&path).map_err(io_error(&path))?;
+            continue;
+        }
+        // Segments that end before the recovery point were synced already.
+        if segments
+            .get(i + 1)
+            .is_some_and(|next| *next as i64 <= recovery_point)
+        {
+            continue;
+        }
+        let data = fs::read(&path).map_err(io_error(&path))?;
+        let valid = valid_bytes(&data);
+        if valid < data.len() {
+            tracing::warn!(
+                "truncating {} from {} to {} bytes",
+                path.display(),
+                data.len(),
+                valid
+            );
+            let file = OpenOptions::new()
+                .write(true)
+                .open(&path)
+                .map_err(io_error(&path))?;
+            file.set_len(valid as u64)
+                .and_then(|_| file.sync_all())
+                .map_err(io_error(&path))?;
+            truncated = true;
+        }
+    }
+    Ok(truncated)
+}
+
+// The length of the complete, intact batches at the start of a segment. Only
+// the framing and the CRC are checked, so compressed batches are kept like
+// any other.
+fn valid_bytes(data: &[u8]) -> usize {
+    let mut position = 0;
+    while let Some(header) = data.get(position..position + 12) {
+        let batch_length = u32::from_be_bytes(header[8..12].try_into().unwrap()) as usize;
+        // partitionLeaderEpoch and magic come before the CRC, which covers
+        // the rest of the batch.
+        let Some(batch) = data.get(position + 12..position + 12 + batch_length) else {
+            break;
+        };
+        if batch_length < 9 {
+            break;
+        }
+        let crc = u32::from_be_bytes(batch[5..9].try_into().unwrap());
+        if crc != crc32c::crc32c(&batch[9..]) {
+            break;
+        }
+        position += 12 + batch_length;
+    }
+    position
+}
+
+// The `<topic>-<partition>` directories of a log directory.
+fn partitions(log_dir: &Path) -> Result<Vec<(TopicPartition, PathBuf)>, Error> {
+    let mut partitions = fs::read_dir(log_dir)
+        .map_err(io_error(log_dir))?
+        .filter_map(|entry| {
+            let entry = entry.ok()?;
+            let name = entry.file_name().into_string().ok()?;
+            let (topic, partition) = name.rsplit_once('-')?;
+            let partition = partition.parse().ok()?;
+            entry
+                .file_type()
+                .ok()?
+                .is_dir()
+                .then(|| ((topic.to_string(), partition), entry.path()))
+        })
+        .collect::<Vec<_>>();
+    partitions.sort();
+    Ok(partitions)
+}
+
+// Kafka's checkpoint format: the version, the number of entries and then one
+// `<topic> <partition> <offset>` line per partition.
+pub fn read_checkpoint(path: &Path) -> Result<HashMap<TopicPartition, i64>, Error> {
+    let content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(HashMap::new()),
+        Err(err) => return Err(io_error(path)(err)),
+    };
+    let malformed = || Error::MalformedCheckpoint(path.to_path_buf());
+    let mut lines = content.lines();
+    if lines.next().and_then(|line| line.trim().parse().ok()) != Some(CHECKPOINT_VERSION) {
+        return Err(malformed());
+    }
+    let count = lines
+        .next()
+        .and_then(|line| line.trim().parse::<usize>().ok())
+        .ok_or_else(malformed)?;
+    let entries = lines
+        .take(count)
+        .map(|line| {
+            let mut fields = line.split_whitespace();
+            let topic = fields.next()?.to_string();
+            let partition = fields.next()?.parse().ok()?;
+            let offset = fields.next()?.parse().ok()?;
+            Some(((topic, partition), offset))
+        })
+        .collect::<Option<HashMap<_, _>>>()
+        .ok_or_else(malformed)?;
+    if entries.len() != count {
+        return Err(malformed());
+    }
+    Ok(entries)
+}
+
+// Written to a temporary file first, so that a crash leaves either the old
+// or the new checkpoint.
+fn write_checkpoint(path: &Path, entries: &BTreeMap<TopicPartition, i64>) -> Result<(), Error> {
+    let mut content = format!("{}\n{}\n", CHECKPOINT_VERSION, entries.len());
+    for ((topic, partition), offset) in entries {
+        content.push_str(&format!("{} {} {}\n", topic, partition, offset));
+    }
+    let tmp = path.with_extension("tmp");
+    File::create(&tmp)
+        .and_then(|mut file| {
+            file.write_all(content.as_bytes())?;
+            file.sync_all()
+        })
+        .map_err(io_error(&tmp))?;
+    fs::rename(&tmp, path).map_err(io_error(path))
+}
+
+fn sync_dir(dir: &Path) -> Result<(), Error> {
+    File::open(dir)
+        .and_then(|dir| dir.sync_all())
+        .map_err(io_error(dir))
+}
+
+fn segment_path(partition_dir: &Path, base_offset: u64) -> PathBuf {
+    partition_dir.join(format!("{:020}.log", base_offset))
+}
+
+fn io_error(path: &Path) -> impl Fn(io::Error) -> Error + '_ {
+    move |source| Error::IOError {
+        path: path.to_path_buf(),
+        source,
+    }
+}
+
+#[cfg(test)]
+mod tests;
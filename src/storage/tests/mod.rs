@@ -0,0 +1,193 @@
+use std::{collections::HashMap, path::PathBuf};
+
+use bytes::{Bytes, BytesMut};
+
+use crate::{
+    config::BrokerConfig,
+    metadata::{Record, RecordBatch, ValueRecord},
+    protocol::Writable,
+};
+
+use super::{
+    close, meta_properties, read_checkpoint, recover, Error, MetaProperties, CLEAN_SHUTDOWN_FILE,
+    LOG_START_OFFSET_CHECKPOINT, RECOVERY_POINT_CHECKPOINT,
+};
+
+fn record_batch(base_offset: i64, attributes: u16, count: usize) -> BytesMut {
+    let records = (0..count)
+        .map(|offset_delta| {
+            Record::Value(ValueRecord::new(
+                0,
+                0,
+                offset_delta as i32,
+                None,
+                Bytes::from_static(b"value"),
+                vec![],
+            ))
+        })
+        .collect();
+    let mut data = BytesMut::new();
+    RecordBatch::new(
+        base_offset,
+        0,
+        2,
+        attributes,
+        count as i32 - 1,
+        0,
+        0,
+        -1,
+        -1,
+        -1,
+        records,
+    )
+    .write(&mut data);
+    data
+}
+
+fn log_dir() -> PathBuf {
+    let log_dir = std::env::temp_dir().join(format!("storage-{}", uuid::Uuid::new_v4()));
+    std::fs::create_dir_all(&log_dir).unwrap();
+    log_dir
+}
+
+#[test]
+fn test_recover_truncates_torn_batches() {
+    let log_dir = log_dir();
+    let partition_dir = log_dir.join("foo-0");
+    std::fs::create_dir_all(&partition_dir).unwrap();
+    let mut segment = record_batch(0, 0, 2);
+    let valid = segment.len();
+    segment.extend_from_slice(&record_batch(2, 0, 3)[..20]);
+    std::fs::write(partition_dir.join("00000000000000000000.log"), &segment).unwrap();
+    std::fs::write(
+        partition_dir.join("00000000000000000005.log"),
+        record_batch(5, 0, 1),
+    )
+    .unwrap();
+
+    assert_eq!(1, recover(&log_dir).unwrap());
+    let segment = std::fs::read(partition_dir.join("00000000000000000000.log")).unwrap();
+    assert_eq!(valid, segment.len());
+    assert!(
+        !partition_dir.join("00000000000000000005.log").exists(),
+        "segments after a truncated one are dropped"
+    );
+}
+
+#[test]
+fn test_recover_keeps_compressed_batches() {
+    let log_dir = log_dir();
+    let partition_dir = log_dir.join("foo-0");
+    std::fs::create_dir_all(&partition_dir).unwrap();
+    // Only the header of the second batch says gzip, which is all recovery
+    // and close look at.
+    let mut segment = record_batch(0, 0, 2);
+    segment.extend_from_slice(&record_batch(2, 1, 3));
+    std::fs::write(partition_dir.join("00000000000000000000.log"), &segment).unwrap();
+    std::fs::write(
+        partition_dir.join("00000000000000000005.log"),
+        record_batch(5, 0, 1),
+    )
+    .unwrap();
+
+    assert_eq!(0, recover(&log_dir).unwrap());
+    assert_eq!(
+        segment.len(),
+        std::fs::read(partition_dir.join("00000000000000000000.log"))
+            .unwrap()
+            .len()
+    );
+    assert!(partition_dir.join("00000000000000000005.log").exists());
+
+    close(&log_dir).unwrap();
+    assert_eq!(
+        HashMap::from([(("foo".to_string(), 0), 6)]),
+        read_checkpoint(&log_dir.join(RECOVERY_POINT_CHECKPOINT)).unwrap()
+    );
+}
+
+#[test]
+fn test_clean_shutdown_skips_recovery() {
+    let log_dir = log_dir();
+    let partition_dir = log_dir.join("foo-bar-1");
+    std::fs::create_dir_all(&partition_dir).unwrap();
+    let mut segment = record_batch(3, 0, 2);
+    segment.extend_from_slice(&record_batch(5, 0, 1));
+    std::fs::write(partition_dir.join("00000000000000000003.log"), &segment).unwrap();
+
+    close(&log_dir).unwrap();
+    assert!(log_dir.join(CLEAN_SHUTDOWN_FILE).exists());
+    assert_eq!(
+        HashMap::from([(("foo-bar".to_string(), 1), 6)]),
+        read_checkpoint(&log_dir.join(RECOVERY_POINT_CHECKPOINT)).unwrap()
+    );
+    assert_eq!(
+        HashMap::from([(("foo-bar".to_string(), 1), 3)]),
+        read_checkpoint(&log_dir.join(LOG_START_OFFSET_CHECKPOINT)).unwrap()
+    );
+
+    // A torn write after the clean shutdown is not looked for.
+    segment.extend_from_slice(&[0, 0, 0]);
+    std::fs::write(partition_dir.join("00000000000000000003.log"), &segment).unwrap();
+    assert_eq!(0, recover(&log_dir).unwrap());
+    assert!(!log_dir.join(CLEAN_SHUTDOWN_FILE).exists());
+    assert_eq!(1, recover(&log_dir).unwrap());
+}
+
+#[test]
+fn test_load_meta_properties() {
+    let (first, second) = (log_dir(), log_dir());
+    let config = |node_id: i32| {
+        BrokerConfig::from_properties(HashMap::from([
+            ("node.id".to_string(), node_id.to_string()),
+            (
+                "log.dirs".to_string(),
+                format!("{},{}", first.display(), second.display()),
+            ),
+        ]))
+        .unwrap()
+    };
+    assert_eq!(None, meta_properties::load(&config(1)).unwrap());
+
+    let directory_id = uuid::Uuid::new_v4();
+    let write = |dir: &PathBuf, cluster_id: &str, directory_id: uuid::Uuid| {
+        std::fs::write(
+            dir.join(meta_properties::META_PROPERTIES_FILE),
+            format!(
+                "version=1\ncluster.id={}\nnode.id=1\ndirectory.id={}\n",
+                cluster_id, directory_id
+            ),
+        )
+        .unwrap();
+    };
+    write(&first, "MkU3OEVBNTcwNTJENDM2Qk", directory_id);
+    assert_eq!(
+        Some(MetaProperties::new(
+            "MkU3OEVBNTcwNTJENDM2Qk",
+            1,
+            Some(directory_id)
+        )),
+        meta_properties::load(&config(1)).unwrap()
+    );
+    assert!(matches!(
+        meta_properties::load(&config(2)),
+        Err(Error::InvalidMetaProperties(_))
+    ));
+
+    write(&second, "MkU3OEVBNTcwNTJENDM2Qk", directory_id);
+    assert!(
+        matches!(
+            meta_properties::load(&config(1)),
+            Err(Error::InvalidMetaProperties(_))
+        ),
+        "duplicate directory id"
+    );
+    write(&second, "another-cluster", uuid::Uuid::new_v4());
+    assert!(
+        matches!(
+            meta_properties::load(&config(1)),
+            Err(Error::InvalidMetaProperties(_))
+        ),
+        "cluster id mismatch"
+    );
+}
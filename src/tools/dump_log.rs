@@ -0,0 +1,481 @@
+use std::{
+    fs,
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde_json::{json, Value};
+
+use crate::{
+    metadata::{
+        read_record_batches, records::MetadataRecord, MetadataValue, Record, StoredBatch,
+        ValueRecord,
+    },
+    protocol::ReadableResult,
+    storage::meta_properties::encode_uuid,
+};
+
+use super::{value, Error};
+
+const COMPRESSION_CODECS: [&str; 5] = ["none", "gzip", "snappy", "lz4", "zstd"];
+
+// The `dump-log` subcommand, our `kafka-dump-log`: prints the batches of
+// log segments and snapshots, optionally with their records.
+#[derive(Debug)]
+pub struct DumpLog {
+    files: Vec<PathBuf>,
+    print_data: bool,
+    cluster_metadata_decoder: bool,
+    json: bool,
+}
+impl DumpLog {
+    // Takes `--files a.log,b.log`, `--print-data-log`,
+    // `--cluster-metadata-decoder`, which implies printing records, and
+    // `--json` for one JSON object per batch instead of text.
+    pub fn from_args<I: IntoIterator<Item = String>>(args: I) -> Result<Self, Error> {
+        let mut files = Vec::new();
+        let mut print_data = false;
+        let mut cluster_metadata_decoder = false;
+        let mut json = false;
+
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--files" => files.extend(
+                    value(&mut args, &arg)?
+                        .split(',')
+                        .filter(|file| !file.is_empty())
+                        .map(PathBuf::from),
+                ),
+                "--print-data-log" => print_data = true,
+                "--cluster-metadata-decoder" => cluster_metadata_decoder = true,
+                "--json" => json = true,
+                _ => return Err(Error::InvalidArgument(format!("unknown argument {}", arg))),
+            }
+        }
+        if files.is_empty() {
+            return Err(Error::InvalidArgument("--files is required".to_string()));
+        }
+
+        Ok(Self {
+            files,
+            print_data: print_data || cluster_metadata_decoder,
+            cluster_metadata_decoder,
+            json,
+        })
+    }
+
+    pub fn run<W: Write>(&self, out: &mut W) -> Result<(), Error> {
+        for path in &self.files {
+            self.dump(path, out)?;
+        }
+        Ok(())
+    }
+
+    fn dump<W: Write>(&self, path: &Path, out: &mut W) -> Result<(), Error> {
+        let data = fs::read(path).map_err(|source| Error::IOError {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        let batches = read_record_batches(&data);
+        if !self.json {
+            writeln!(out, "Dumping {}", path.display())?;
+            if let Some(base_offset) = path
+                .file_stem()
+                .and_then(|stem| stem.to_str()?.split('-').next()?.parse::<i64>().ok())
+            {
+                writeln!(out, "Log starting offset: {}", base_offset)?;
+            }
+        }
+
+        for stored in &batches {
+            let mut batch = self.batch_json(stored);
+            if self.json {
+                batch["file"] = json!(path.display().to_string());
+                writeln!(out, "{}", batch)?;
+                continue;
+            }
+            writeln!(out, "{}", batch_text(&batch))?;
+            if let Some(reason) = batch["recordsNotShown"].as_str() {
+                writeln!(out, "| records not shown: {}", reason)?;
+            }
+            for record in batch["records"].as_array().into_iter().flatten() {
+                writeln!(out, "{}", record_text(record))?;
+            }
+        }
+
+        let valid = batches
+            .last()
+            .map_or(0, |stored| stored.position() + stored.size());
+        if valid < data.len() {
+            if self.json {
+                let invalid = json!({
+                    "file": path.display().to_string(),
+                    "position": valid,
+                    "invalidBytes": data.len() - valid,
+                });
+                writeln!(out, "{}", invalid)?;
+            } else {
+                writeln!(
+                    out,
+                    "Found {} invalid bytes at the end of {} starting at position {}",
+                    data.len() - valid,
+                    path.display(),
+                    valid
+                )?;
+            }
+        }
+        Ok(())
+    }
+
+    fn batch_json(&self, stored: &StoredBatch) -> Value {
+        let batch = stored.batch();
+        let last_sequence = match batch.base_sequence() {
+            -1 => -1,
+            base_sequence => base_sequence + batch.last_offset_delta(),
+        };
+        let codec = COMPRESSION_CODECS
+            .get(batch.compression() as usize)
+            .copied()
+            .unwrap_or("unknown");
+        let mut value = json!({
+            "baseOffset": batch.base_offset(),
+            "lastOffset": batch.base_offset() + batch.last_offset_delta() as i64,
+            "count": batch.last_offset_delta() + 1,
+            "baseSequence": batch.base_sequence(),
+            "lastSequence": last_sequence,
+            "producerId": batch.producer_id(),
+            "producerEpoch": batch.producer_epoch(),
+            "partitionLeaderEpoch": batch.partition_leader_epoch(),
+            "isControl": batch.is_control_batch(),
+            "position": stored.position(),
+            "createTime": batch.max_timestamp(),
+            "size": stored.size(),
+            "magic": batch.magic_byte(),
+            "compressCodec": codec,
+            "crc": stored.crc(),
+            "isValid": stored.crc_valid(),
+        });
+        // Compressed records are not decoded, so there are none to show.
+        if self.print_data && batch.compression() != 0 && stored.crc_valid() {
+            value["recordsNotShown"] = json!(format!("compressed with {}", codec));
+        } else if self.print_data {
+            value["records"] = batch
+                .records()
+                .iter()
+                .map(|record| match record {
+                    Record::Value(record) => self.record_json(
+                        batch.base_offset(),
+                        batch.base_timestamp(),
+                        batch.base_sequence(),
+                        record,
+                    ),
+                    Record::Control(record) => json!({
+                        "offset": batch.base_offset(),
+                        "createTime": batch.base_timestamp(),
+                        "controlType": record.r#type(),
+                    }),
+                })
+                .collect();
+        }
+        value
+    }
+
+    fn record_json(
+        &self,
+        base_offset: i64,
+        base_timestamp: i64,
+        base_sequence: i32,
+        record: &ValueRecord,
+    ) -> Value {
+        let mut value = json!({
+            "offset": base_offset + record.offset_delta() as i64,
+            "createTime": base_timestamp + record.timestamp_delta(),
+            "keySize": record.key().map_or(-1, |key| key.len() as i64),
+            "valueSize": record.value().len(),
+            "sequence": match base_sequence {
+                -1 => -1,
+                base_sequence => base_sequence + record.offset_delta(),
+            },
+            "headers": record
+                .headers()
+                .iter()
+                .map(|header| json!({
+                    "key": String::from_utf8_lossy(header.key()),
+                    "value": String::from_utf8_lossy(header.value()),
+                }))
+                .collect::<Vec<_>>(),
+        });
+        if self.cluster_metadata_decoder {
+            value["payload"] = metadata_value_json(record.value());
+        } else {
+            value["key"] = json!(record.key().map(String::from_utf8_lossy));
+            value["payload"] = json!(String::from_utf8_lossy(record.value()));
+        }
+        value
+    }
+}
+
+fn batch_text(batch: &Value) -> String {
+    format!(
+        "baseOffset: {} lastOffset: {} count: {} baseSequence: {} lastSequence: {} \
+         producerId: {} producerEpoch: {} partitionLeaderEpoch: {} isControl: {} \
+         position: {} CreateTime: {} size: {} magic: {} compresscodec: {} crc: {} isvalid: {}",
+        batch["baseOffset"],
+        batch["lastOffset"],
+        batch["count"],
+        batch["baseSequence"],
+        batch["lastSequence"],
+        batch["producerId"],
+        batch["producerEpoch"],
+        batch["partitionLeaderEpoch"],
+        batch["isControl"],
+        batch["position"],
+        batch["createTime"],
+        batch["size"],
+        batch["magic"],
+        batch["compressCodec"].as_str().unwrap_or_default(),
+        batch["crc"],
+        batch["isValid"],
+    )
+}
+
+fn record_text(record: &Value) -> String {
+    if let Some(control_type) = record.get("controlType") {
+        return format!(
+            "| offset: {} CreateTime: {} controlType: {}",
+            record["offset"], record["createTime"], control_type
+        );
+    }
+    let header_keys = record["headers"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .map(|header| header["key"].as_str().unwrap_or_default())
+        .collect::<Vec<_>>();
+    let mut text = format!(
+        "| offset: {} CreateTime: {} keySize: {} valueSize: {} sequence: {} headerKeys: [{}]",
+        record["offset"],
+        record["createTime"],
+        record["keySize"],
+        record["valueSize"],
+        record["sequence"],
+        header_keys.join(",")
+    );
+    if let Some(key) = record.get("key") {
+        text.push_str(&format!(" key: {}", key.as_str().unwrap_or("null")));
+    }
+    match &record["payload"] {
+        Value::String(payload) => text.push_str(&format!(" payload: {}", payload)),
+        payload => text.push_str(&format!(" payload: {}", payload)),
+    }
+    text
+}
+
+// A `__cluster_metadata` value as `{"type": ..., "version": ..., "data": ...}`,
+// or `{"error": ...}` if it can't be decoded.
+fn metadata_value_json(value: &[u8]) -> Value {
+    let Ok(value) = MetadataValue::read_result(&mut &value[..]) else {
+        return json!({ "error": "value too short for a metadata record" });
+    };
+    match MetadataRecord::read(&value) {
+        Ok(record) => metadata_record_json(&record, value.version()),
+        Err(err) => json!({
+            "type": value.r#type(),
+            "version": value.version(),
+            "error": err.to_string(),
+        }),
+    }
+}
+
+pub(crate) fn metadata_record_json(record: &MetadataRecord, version: u8) -> Value {
+    let (r#type, data) = match record {
+        MetadataRecord::RegisterBroker(record) => (
+            "REGISTER_BROKER_RECORD",
+            json!({
+                "brokerId": record.broker_id(),
+                "isMigratingZkBroker": record.is_migrating_zk_broker(),
+                "incarnationId": encode_uuid(record.incarnation_id()),
+                "brokerEpoch": record.broker_epoch(),
+                "endPoints": record
+                    .end_points()
+                    .iter()
+                    .map(|endpoint| json!({
+                        "name": endpoint.name(),
+                        "host": endpoint.host(),
+                        "port": endpoint.port(),
+                        "securityProtocol": endpoint.security_protocol(),
+                    }))
+                    .collect::<Vec<_>>(),
+                "features": record
+                    .features()
+                    .iter()
+                    .map(|feature| json!({
+                        "name": feature.name(),
+                        "minSupportedVersion": feature.min_supported_version(),
+                        "maxSupportedVersion": feature.max_supported_version(),
+                    }))
+                    .collect::<Vec<_>>(),
+                "rack": record.rack(),
+                "fenced": record.fenced(),
+                "inControlledShutdown": record.in_controlled_shutdown(),
+                "logDirs": uuids(record.log_dirs()),
+            }),
+        ),
+        MetadataRecord::UnregisterBroker(record) => (
+            "UNREGISTER_BROKER_RECORD",
+            json!({
+                "brokerId": record.broker_id(),
+                "brokerEpoch": record.broker_epoch(),
+            }),
+        ),
+        MetadataRecord::Topic(record) => (
+            "TOPIC_RECORD",
+            json!({
+                "name": String::from_utf8_lossy(record.name()),
+                "topicId": encode_uuid(record.topic_id()),
+            }),
+        ),
+        MetadataRecord::Partition(record) => (
+            "PARTITION_RECORD",
+            json!({
+                "partitionId": record.partition_id(),
+                "topicId": encode_uuid(record.topic_id()),
+                "replicas": record.replicas(),
+                "isr": record.isr(),
+                "removingReplicas": record.removing_replicas(),
+                "addingReplicas": record.adding_replicas(),
+                "leader": record.leader(),
+                "leaderRecoveryState": record.leader_recovery_state(),
+                "leaderEpoch": record.leader_epoch(),
+                "partitionEpoch": record.partition_epoch(),
+                "directories": uuids(record.directories()),
+                "eligibleLeaderReplicas": record.eligible_leader_replicas(),
+                "lastKnownElr": record.last_known_elr(),
+            }),
+        ),
+        MetadataRecord::Config(record) => (
+            "CONFIG_RECORD",
+            json!({
+                "resourceType": record.resource_type(),
+                "resourceName": record.resource_name(),
+                "name": record.name(),
+                "value": record.value(),
+            }),
+        ),
+        MetadataRecord::PartitionChange(record) => (
+            "PARTITION_CHANGE_RECORD",
+            json!({
+                "partitionId": record.partition_id(),
+                "topicId": encode_uuid(record.topic_id()),
+                "isr": record.isr(),
+                "leader": record.leader(),
+                "replicas": record.replicas(),
+                "removingReplicas": record.removing_replicas(),
+                "addingReplicas": record.adding_replicas(),
+                "leaderRecoveryState": record.leader_recovery_state(),
+                "eligibleLeaderReplicas": record.eligible_leader_replicas(),
+                "lastKnownElr": record.last_known_elr(),
+                "directories": record.directories().map(uuids),
+            }),
+        ),
+        MetadataRecord::AccessControlEntry(record) => (
+            "ACCESS_CONTROL_ENTRY_RECORD",
+            json!({
+                "id": encode_uuid(record.id()),
+                "resourceType": record.resource_type(),
+                "resourceName": record.resource_name(),
+                "patternType": record.pattern_type(),
+                "principal": record.principal(),
+                "host": record.host(),
+                "operation": record.operation(),
+                "permissionType": record.permission_type(),
+            }),
+        ),
+        MetadataRecord::RemoveAccessControlEntry(record) => (
+            "REMOVE_ACCESS_CONTROL_ENTRY_RECORD",
+            json!({ "id": encode_uuid(record.id()) }),
+        ),
+        MetadataRecord::FenceBroker(record) => (
+            "FENCE_BROKER_RECORD",
+            json!({ "id": record.id(), "epoch": record.epoch() }),
+        ),
+        MetadataRecord::UnfenceBroker(record) => (
+            "UNFENCE_BROKER_RECORD",
+            json!({ "id": record.id(), "epoch": record.epoch() }),
+        ),
+        MetadataRecord::RemoveTopic(record) => (
+            "REMOVE_TOPIC_RECORD",
+            json!({ "topicId": encode_uuid(record.topic_id()) }),
+        ),
+        MetadataRecord::UserScramCredential(record) => (
+            "USER_SCRAM_CREDENTIAL_RECORD",
+            json!({
+                "name": record.name(),
+                "mechanism": record.mechanism(),
+                "salt": STANDARD.encode(record.salt()),
+                "storedKey": STANDARD.encode(record.stored_key()),
+                "serverKey": STANDARD.encode(record.server_key()),
+                "iterations": record.iterations(),
+            }),
+        ),
+        MetadataRecord::FeatureLevel(record) => (
+            "FEATURE_LEVEL_RECORD",
+            json!({
+                "name": record.name(),
+                "featureLevel": record.feature_level(),
+            }),
+        ),
+        MetadataRecord::ClientQuota(record) => (
+            "CLIENT_QUOTA_RECORD",
+            json!({
+                "entity": record
+                    .entity()
+                    .iter()
+                    .map(|entity| json!({
+                        "entityType": entity.entity_type(),
+                        "entityName": entity.entity_name(),
+                    }))
+                    .collect::<Vec<_>>(),
+                "key": record.key(),
+                "value": record.value(),
+                "remove": record.remove(),
+            }),
+        ),
+        MetadataRecord::ProducerIds(record) => (
+            "PRODUCER_IDS_RECORD",
+            json!({
+                "brokerId": record.broker_id(),
+                "brokerEpoch": record.broker_epoch(),
+                "nextProducerId": record.next_producer_id(),
+            }),
+        ),
+        MetadataRecord::BrokerRegistrationChange(record) => (
+            "BROKER_REGISTRATION_CHANGE_RECORD",
+            json!({
+                "brokerId": record.broker_id(),
+                "brokerEpoch": record.broker_epoch(),
+                "fenced": record.fenced(),
+                "inControlledShutdown": record.in_controlled_shutdown(),
+                "logDirs": record.log_dirs().map(uuids),
+            }),
+        ),
+        MetadataRecord::NoOp => ("NO_OP_RECORD", json!({})),
+        MetadataRecord::RemoveUserScramCredential(record) => (
+            "REMOVE_USER_SCRAM_CREDENTIAL_RECORD",
+            json!({
+                "name": record.name(),
+                "mechanism": record.mechanism(),
+            }),
+        ),
+        MetadataRecord::Unknown { r#type, version } => {
+            return json!({ "type": r#type, "version": version });
+        }
+    };
+    json!({ "type": r#type, "version": version, "data": data })
+}
+
+fn uuids(ids: &[uuid::Uuid]) -> Vec<String> {
+    ids.iter().copied().map(encode_uuid).collect()
+}
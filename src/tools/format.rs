@@ -0,0 +1,327 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs,
+    path::PathBuf,
+};
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use bytes::Bytes;
+use uuid::Uuid;
+
+use crate::{
+    config::BrokerConfig,
+    metadata::{
+        loader::METADATA_PARTITION_DIR,
+        metadata_value,
+        records::{
+            feature_level_record, user_scram_credential_record, FeatureLevelRecord,
+            UserScramCredentialRecord,
+        },
+        MetadataWriter,
+    },
+    security::{
+        sasl::{SCRAM_MAX_ITERATIONS, SCRAM_MIN_ITERATIONS},
+        ScramMechanism,
+    },
+    storage::{
+        self,
+        meta_properties::{decode_uuid, META_PROPERTIES_FILE},
+        MetaProperties,
+    },
+    SUPPORTED_FEATURES,
+};
+
+use super::{value, Error};
+
+pub const METADATA_VERSION: &str = "metadata.version";
+// 3.5-IV2, the first metadata.version that keeps SCRAM credentials in the
+// metadata log.
+const SCRAM_METADATA_VERSION: i16 = 11;
+const DEFAULT_ITERATIONS: i32 = 4096;
+
+// The `format` subcommand, our `kafka-storage format`: every log directory
+// gets a meta.properties for the cluster and this node, and the metadata log
+// starts with the finalized features and any SCRAM credentials to bootstrap
+// with.
+#[derive(Debug)]
+pub struct Format {
+    config: BrokerConfig,
+    cluster_id: String,
+    features: BTreeMap<String, i16>,
+    scram_credentials: Vec<UserScramCredentialRecord>,
+    ignore_formatted: bool,
+}
+impl Format {
+    // Takes `--cluster-id`, `--config` and `--override` for the broker
+    // config, `--feature name=level`, `--add-scram` and `--ignore-formatted`.
+    pub fn from_args<I: IntoIterator<Item = String>>(args: I) -> Result<Self, Error> {
+        let mut cluster_id = None;
+        let mut config_args = Vec::new();
+        let mut features = BTreeMap::new();
+        let mut scram_args = Vec::new();
+        let mut ignore_formatted = false;
+
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "-t" | "--cluster-id" => cluster_id = Some(value(&mut args, &arg)?),
+                "-c" | "--config" => config_args.push(value(&mut args, &arg)?),
+                "--override" => {
+                    config_args.push(arg.clone());
+                    config_args.push(value(&mut args, &arg)?);
+                }
+                "-f" | "--feature" => {
+                    let (name, level) = feature(&value(&mut args, &arg)?)?;
+                    features.insert(name, level);
+                }
+                "-S" | "--add-scram" => scram_args.push(value(&mut args, &arg)?),
+                "-g" | "--ignore-formatted" => ignore_formatted = true,
+                _ => return Err(Error::InvalidArgument(format!("unknown argument {}", arg))),
+            }
+        }
+
+        let cluster_id = cluster_id
+            .ok_or_else(|| Error::InvalidArgument("--cluster-id is required".to_string()))?;
+        if decode_uuid(&cluster_id).is_none() {
+            return Err(Error::InvalidArgument(format!(
+                "Cluster ID string {} does not appear to be a valid UUID",
+                cluster_id
+            )));
+        }
+        let config = BrokerConfig::from_args(config_args)?;
+        if config.node_id() < 0 {
+            return Err(Error::InvalidArgument(
+                "node.id must be set to format the log directories".to_string(),
+            ));
+        }
+
+        for (name, level) in &features {
+            let supported = SUPPORTED_FEATURES
+                .get(name)
+                .ok_or_else(|| Error::InvalidArgument(format!("Unsupported feature: {}", name)))?;
+            if !(supported.min_version()..=supported.max_version()).contains(level) {
+                return Err(Error::InvalidArgument(format!(
+                    "Unsupported level {} for {}, expected {} to {}",
+                    level,
+                    name,
+                    supported.min_version(),
+                    supported.max_version()
+                )));
+            }
+        }
+        let metadata_version = *features
+            .entry(METADATA_VERSION.to_string())
+            .or_insert_with(|| SUPPORTED_FEATURES[METADATA_VERSION].max_version());
+        if !scram_args.is_empty() && metadata_version < SCRAM_METADATA_VERSION {
+            return Err(Error::InvalidArgument(format!(
+                "SCRAM is only supported in {} {} or later",
+                METADATA_VERSION, SCRAM_METADATA_VERSION
+            )));
+        }
+        let scram_credentials = scram_args
+            .iter()
+            .map(|arg| scram_credential(arg))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Self {
+            config,
+            cluster_id,
+            features,
+            scram_credentials,
+            ignore_formatted,
+        })
+    }
+
+    pub fn metadata_version(&self) -> i16 {
+        self.features[METADATA_VERSION]
+    }
+
+    // Nothing is written unless every directory can be formatted. Returns
+    // the directories that were.
+    pub fn run(&self) -> Result<Vec<PathBuf>, Error> {
+        let mut unformatted = Vec::new();
+        for log_dir in storage::log_dirs(&self.config) {
+            if MetaProperties::read(&log_dir)?.is_none() {
+                unformatted.push(log_dir);
+            } else if !self.ignore_formatted {
+                return Err(Error::InvalidArgument(format!(
+                    "Log directory {} is already formatted. Use --ignore-formatted to ignore \
+                     this directory and format the others.",
+                    log_dir.display()
+                )));
+            }
+        }
+        let metadata_log_dir = self.config.metadata_log_dir();
+        let bootstrap = unformatted.iter().any(|dir| dir == metadata_log_dir);
+        if bootstrap && metadata_log_dir.join(METADATA_PARTITION_DIR).exists() {
+            return Err(Error::InvalidArgument(format!(
+                "{} already holds a metadata log",
+                metadata_log_dir.display()
+            )));
+        }
+
+        // meta.properties is what marks a directory as formatted, so it comes
+        // last, and what was written is removed again if it fails. Either way
+        // a format that did not finish can simply be run again.
+        let partition_dir = metadata_log_dir.join(METADATA_PARTITION_DIR);
+        if bootstrap {
+            if let Err(err) = MetadataWriter::new(metadata_log_dir).append(self.bootstrap_records())
+            {
+                let _ = fs::remove_dir_all(&partition_dir);
+                return Err(Error::Metadata(err));
+            }
+        }
+        for (formatted, log_dir) in unformatted.iter().enumerate() {
+            let result = MetaProperties::new(
+                &self.cluster_id,
+                self.config.node_id(),
+                Some(Uuid::new_v4()),
+            )
+            .write(log_dir);
+            if let Err(err) = result {
+                for log_dir in &unformatted[..formatted] {
+                    let _ = fs::remove_file(log_dir.join(META_PROPERTIES_FILE));
+                }
+                if bootstrap {
+                    let _ = fs::remove_dir_all(&partition_dir);
+                }
+                return Err(err.into());
+            }
+        }
+        Ok(unformatted)
+    }
+
+    // metadata.version comes first, as every later record depends on it.
+    fn bootstrap_records(&self) -> Vec<Bytes> {
+        let mut values = vec![metadata_value(
+            feature_level_record::API_KEY,
+            0,
+            &FeatureLevelRecord::new(METADATA_VERSION, self.metadata_version()),
+        )];
+        for (name, level) in &self.features {
+            if name != METADATA_VERSION && *level > 0 {
+                values.push(metadata_value(
+                    feature_level_record::API_KEY,
+                    0,
+                    &FeatureLevelRecord::new(name, *level),
+                ));
+            }
+        }
+        for record in &self.scram_credentials {
+            values.push(metadata_value(
+                user_scram_credential_record::API_KEY,
+                0,
+                record,
+            ));
+        }
+        values
+    }
+}
+
+fn feature(arg: &str) -> Result<(String, i16), Error> {
+    arg.split_once('=')
+        .and_then(|(name, level)| Some((name.trim().to_string(), level.trim().parse().ok()?)))
+        .ok_or_else(|| Error::InvalidArgument(format!("--feature {}, expected name=level", arg)))
+}
+
+// `SCRAM-SHA-256=[name=alice,password=alice-secret]`, where the password can
+// also be given already salted as `salt=<base64>,saltedpassword=<base64>`,
+// and `iterations` defaults to 4096. Values in double quotes may contain `,`
+// and `=`.
+fn scram_credential(arg: &str) -> Result<UserScramCredentialRecord, Error> {
+    let invalid = |reason: &str| Error::InvalidArgument(format!("--add-scram {}: {}", arg, reason));
+    let (mechanism, fields) = arg
+        .split_once('=')
+        .ok_or_else(|| invalid("expected MECHANISM=[key=value,...]"))?;
+    let mechanism = ScramMechanism::from_name(mechanism.trim())
+        .ok_or_else(|| invalid("unknown SCRAM mechanism"))?;
+    let mut fields = fields
+        .trim()
+        .strip_prefix('[')
+        .and_then(|fields| fields.strip_suffix(']'))
+        .ok_or_else(|| invalid("expected MECHANISM=[key=value,...]"))
+        .and_then(|fields| split_unquoted(fields).ok_or_else(|| invalid("unbalanced quotes")))?
+        .into_iter()
+        .map(|field| {
+            let (key, value) = field.split_once('=')?;
+            let value = value.trim();
+            let value = value
+                .strip_prefix('"')
+                .and_then(|value| value.strip_suffix('"'))
+                .unwrap_or(value);
+            Some((key.trim(), value))
+        })
+        .collect::<Option<HashMap<_, _>>>()
+        .ok_or_else(|| invalid("expected key=value"))?;
+
+    let name = fields.remove("name").ok_or_else(|| invalid("no name"))?;
+    let iterations = match fields.remove("iterations") {
+        Some(iterations) => iterations
+            .parse()
+            .map_err(|_| invalid("invalid iterations"))?,
+        None => DEFAULT_ITERATIONS,
+    };
+    if !(SCRAM_MIN_ITERATIONS..=SCRAM_MAX_ITERATIONS).contains(&iterations) {
+        return Err(invalid("iterations must be between 4096 and 16384"));
+    }
+    let salt = fields
+        .remove("salt")
+        .map(|salt| STANDARD.decode(salt).map_err(|_| invalid("invalid salt")))
+        .transpose()?;
+    let (salt, salted_password) = match (
+        fields.remove("password"),
+        fields.remove("saltedpassword"),
+        salt,
+    ) {
+        (Some(password), None, salt) => {
+            let salt = salt.unwrap_or_else(|| {
+                [Uuid::new_v4().into_bytes(), Uuid::new_v4().into_bytes()].concat()
+            });
+            let salted_password = mechanism.salted_password(password.as_bytes(), &salt, iterations);
+            (salt, salted_password)
+        }
+        (None, Some(salted_password), Some(salt)) => {
+            let salted_password = STANDARD
+                .decode(salted_password)
+                .map_err(|_| invalid("invalid saltedpassword"))?;
+            (salt, salted_password)
+        }
+        _ => {
+            return Err(invalid(
+                "expected either password or both salt and saltedpassword",
+            ))
+        }
+    };
+    if let Some(key) = fields.keys().next() {
+        return Err(invalid(&format!("unknown key {}", key)));
+    }
+
+    let credential = mechanism.credential(Bytes::from(salt), &salted_password, iterations);
+    Ok(UserScramCredentialRecord::new(
+        name,
+        mechanism.id(),
+        credential.salt().clone(),
+        credential.stored_key().clone(),
+        credential.server_key().clone(),
+        credential.iterations(),
+    ))
+}
+
+// Splits at every `,` outside double quotes.
+fn split_unquoted(fields: &str) -> Option<Vec<&str>> {
+    let mut parts = Vec::new();
+    let mut quoted = false;
+    let mut start = 0;
+    for (i, c) in fields.char_indices() {
+        match c {
+            '"' => quoted = !quoted,
+            ',' if !quoted => {
+                parts.push(&fields[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    parts.push(&fields[start..]);
+    (!quoted).then_some(parts)
+}
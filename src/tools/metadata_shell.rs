@@ -0,0 +1,476 @@
+use std::{
+    collections::BTreeMap,
+    fmt::Debug,
+    io::{BufRead, Write},
+    path::PathBuf,
+};
+
+use serde_json::json;
+
+use crate::{
+    metadata::{loader::METADATA_PARTITION_DIR, MetadataCache, MetadataImage, MetadataLoader},
+    model::ConfigResource,
+    security::{AclOperation, AclPermissionType, PatternType, ResourceType},
+    storage::meta_properties::encode_uuid,
+};
+
+use super::{value, Error};
+
+const HELP: &str = "\
+cat <path>...       Show the contents of files
+cd [path]           Change the working directory, / by default
+exit                Leave the shell
+find [path]...      List everything under a directory
+help                Show this help
+history [count]     Show the commands entered so far
+ls [path]...        List a directory
+pwd                 Show the working directory
+tree [path]...      Show a directory with the contents of its files
+Paths may be relative and may contain * and ? wildcards.";
+
+// The `metadata-shell` subcommand, our `kafka-metadata-shell`: loads the
+// metadata log and its snapshots into an image and lets it be browsed like
+// a file system, either interactively or with a single command.
+#[derive(Debug)]
+pub struct MetadataShell {
+    directory: PathBuf,
+    command: Vec<String>,
+}
+impl MetadataShell {
+    // Takes `--directory`, the metadata log dir or the `__cluster_metadata-0`
+    // directory in it; anything after it is run as the only command.
+    pub fn from_args<I: IntoIterator<Item = String>>(args: I) -> Result<Self, Error> {
+        let mut directory = None;
+        let mut command = Vec::new();
+
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "-d" | "--directory" if command.is_empty() => {
+                    directory = Some(PathBuf::from(value(&mut args, &arg)?))
+                }
+                _ => command.push(arg),
+            }
+        }
+
+        let mut directory = directory
+            .ok_or_else(|| Error::InvalidArgument("--directory is required".to_string()))?;
+        if directory.ends_with(METADATA_PARTITION_DIR) {
+            directory.pop();
+        }
+        if !directory.join(METADATA_PARTITION_DIR).is_dir() {
+            return Err(Error::InvalidArgument(format!(
+                "{} has no {} directory",
+                directory.display(),
+                METADATA_PARTITION_DIR
+            )));
+        }
+        Ok(Self { directory, command })
+    }
+
+    // Commands are read from `input` unless one was given on the command
+    // line. `prompt` is for terminals.
+    pub fn run<R: BufRead, W: Write>(
+        &self,
+        input: R,
+        out: &mut W,
+        prompt: bool,
+    ) -> Result<(), Error> {
+        let metadata = MetadataCache::new();
+        MetadataLoader::new(&self.directory, u64::MAX)
+            .poll(&metadata)
+            .map_err(Error::Metadata)?;
+        let mut navigator = Navigator::new(&metadata.image());
+
+        if !self.command.is_empty() {
+            navigator.execute(&self.command.join(" "), out)?;
+            return Ok(());
+        }
+        let mut lines = input.lines();
+        loop {
+            if prompt {
+                write!(out, "{} >> ", navigator.pwd())?;
+                out.flush()?;
+            }
+            let Some(line) = lines.next().transpose()? else {
+                break;
+            };
+            if !navigator.execute(&line, out)? {
+                break;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+enum Node {
+    Directory(BTreeMap<String, Node>),
+    File(String),
+}
+impl Node {
+    fn directory() -> Self {
+        Node::Directory(BTreeMap::new())
+    }
+
+    // The directory at `path` below this one, created as needed.
+    fn mkdirs(&mut self, path: &[&str]) -> &mut BTreeMap<String, Node> {
+        let Node::Directory(children) = self else {
+            unreachable!("files have no children");
+        };
+        match path.split_first() {
+            Some((name, rest)) => children
+                .entry(name.to_string())
+                .or_insert_with(Node::directory)
+                .mkdirs(rest),
+            None => children,
+        }
+    }
+
+    fn get(&self, path: &[String]) -> Option<&Node> {
+        match path.split_first() {
+            Some((name, rest)) => match self {
+                Node::Directory(children) => children.get(name)?.get(rest),
+                Node::File(_) => None,
+            },
+            None => Some(self),
+        }
+    }
+}
+
+// The image laid out as a tree of directories and files, and the state of
+// the shell browsing it.
+struct Navigator {
+    root: Node,
+    cwd: Vec<String>,
+    history: Vec<String>,
+}
+impl Navigator {
+    fn new(image: &MetadataImage) -> Self {
+        let mut root = Node::directory();
+        root.mkdirs(&["brokers"]);
+        root.mkdirs(&["topics"]);
+        root.mkdirs(&["configs"]);
+        root.mkdirs(&["features"]);
+        root.mkdirs(&["acls"]);
+
+        for (name, level) in image.features() {
+            root.mkdirs(&["features"])
+                .insert(name.clone(), Node::File(level.to_string()));
+        }
+
+        for broker in image.brokers().values() {
+            let id = broker.id().to_string();
+            let registration = json!({
+                "id": broker.id(),
+                "epoch": broker.epoch(),
+                "incarnationId": encode_uuid(broker.incarnation_id()),
+                "listeners": broker
+                    .listeners()
+                    .iter()
+                    .map(|listener| json!({
+                        "name": listener.name(),
+                        "host": listener.host(),
+                        "port": listener.port(),
+                        "securityProtocol": listener.security_protocol(),
+                    }))
+                    .collect::<Vec<_>>(),
+                "supportedFeatures": broker
+                    .supported_features()
+                    .iter()
+                    .map(|(name, (min, max))| (name.clone(), json!({ "min": min, "max": max })))
+                    .collect::<serde_json::Map<_, _>>(),
+                "rack": broker.rack(),
+            });
+            let dir = root.mkdirs(&["brokers", &id]);
+            dir.insert(
+                "registration".to_string(),
+                Node::File(pretty(&registration)),
+            );
+            dir.insert(
+                "isFenced".to_string(),
+                Node::File(broker.fenced().to_string()),
+            );
+            dir.insert(
+                "inControlledShutdown".to_string(),
+                Node::File(broker.in_controlled_shutdown().to_string()),
+            );
+        }
+
+        for topic in image.topics().values() {
+            let dir = root.mkdirs(&["topics", topic.name()]);
+            dir.insert("id".to_string(), Node::File(encode_uuid(topic.id())));
+            for partition in topic.partitions() {
+                let data = json!({
+                    "leader": partition.leader(),
+                    "leaderEpoch": partition.leader_epoch(),
+                    "partitionEpoch": partition.partition_epoch(),
+                    "replicas": partition.replicas(),
+                    "isr": partition.isr(),
+                    "eligibleLeaderReplicas": partition.eligible_leader_replicas(),
+                    "lastKnownElr": partition.last_known_elr(),
+                });
+                root.mkdirs(&["topics", topic.name(), &partition.id().to_string()])
+                    .insert("data".to_string(), Node::File(pretty(&data)));
+            }
+        }
+
+        for (resource, entries) in image.configs() {
+            let resource_type = match resource.resource_type() {
+                ConfigResource::TOPIC => "topic".to_string(),
+                ConfigResource::BROKER => "broker".to_string(),
+                ConfigResource::BROKER_LOGGER => "broker_logger".to_string(),
+                resource_type => resource_type.to_string(),
+            };
+            // The cluster-wide broker defaults have an empty name.
+            let name = match resource.name() {
+                "" => "<default>",
+                name => name,
+            };
+            let dir = root.mkdirs(&["configs", &resource_type, name]);
+            for (key, value) in entries {
+                dir.insert(key.clone(), Node::File(value.clone()));
+            }
+        }
+
+        for acl in image.acls().values() {
+            let data = json!({
+                "resourceType": named(
+                    acl.resource_type(),
+                    ResourceType::from_id(acl.resource_type()),
+                ),
+                "resourceName": acl.resource_name(),
+                "patternType": named(acl.pattern_type(), PatternType::from_id(acl.pattern_type())),
+                "principal": acl.principal(),
+                "host": acl.host(),
+                "operation": named(acl.operation(), AclOperation::from_id(acl.operation())),
+                "permissionType": named(
+                    acl.permission_type(),
+                    AclPermissionType::from_id(acl.permission_type()),
+                ),
+            });
+            root.mkdirs(&["acls"])
+                .insert(encode_uuid(acl.id()), Node::File(pretty(&data)));
+        }
+
+        Self {
+            root,
+            cwd: vec![],
+            history: vec![],
+        }
+    }
+
+    fn pwd(&self) -> String {
+        format!("/{}", self.cwd.join("/"))
+    }
+
+    // Runs one command line and returns whether the shell should go on.
+    // Mistakes are reported to `out` like a shell would.
+    fn execute<W: Write>(&mut self, line: &str, out: &mut W) -> Result<bool, Error> {
+        let words = line.split_whitespace().collect::<Vec<_>>();
+        let Some((command, args)) = words.split_first() else {
+            return Ok(true);
+        };
+        self.history.push(line.trim().to_string());
+
+        match *command {
+            "cat" => {
+                for (arg, path) in self.expand_all(args, out)? {
+                    match self.root.get(&path) {
+                        Some(Node::File(contents)) => writeln!(out, "{}", contents)?,
+                        _ => writeln!(out, "cat: {}: Is a directory", arg)?,
+                    }
+                }
+            }
+            "cd" => {
+                let arg = args.first().copied().unwrap_or("/");
+                match self.expand(arg).as_slice() {
+                    [path] if matches!(self.root.get(path), Some(Node::Directory(_))) => {
+                        self.cwd = path.clone()
+                    }
+                    [path] if self.root.get(path).is_some() => {
+                        writeln!(out, "cd: {}: Not a directory", arg)?
+                    }
+                    _ => writeln!(out, "cd: {}: No such file or directory", arg)?,
+                }
+            }
+            "exit" => return Ok(false),
+            "find" => {
+                let args = if args.is_empty() { &["."][..] } else { args };
+                for (_, path) in self.expand_all(args, out)? {
+                    if let Some(node) = self.root.get(&path) {
+                        find(&format!("/{}", path.join("/")), node, out)?;
+                    }
+                }
+            }
+            "help" => writeln!(out, "{}", HELP)?,
+            "history" => {
+                let count = match args.first().map(|count| count.parse::<usize>()) {
+                    Some(Ok(count)) => count,
+                    Some(Err(_)) => {
+                        writeln!(out, "history: {}: numeric argument required", args[0])?;
+                        return Ok(true);
+                    }
+                    None => self.history.len(),
+                };
+                let start = self.history.len().saturating_sub(count);
+                for (i, line) in self.history.iter().enumerate().skip(start) {
+                    writeln!(out, "{:>5}  {}", i + 1, line)?;
+                }
+            }
+            "ls" => {
+                let args = if args.is_empty() { &["."][..] } else { args };
+                let paths = self.expand_all(args, out)?;
+                let headers = paths.len() > 1;
+                for (i, (_, path)) in paths.iter().enumerate() {
+                    match self.root.get(path) {
+                        Some(Node::Directory(children)) => {
+                            if headers {
+                                if i > 0 {
+                                    writeln!(out)?;
+                                }
+                                writeln!(out, "/{}:", path.join("/"))?;
+                            }
+                            for name in children.keys() {
+                                writeln!(out, "{}", name)?;
+                            }
+                        }
+                        _ => writeln!(out, "{}", path.last().map_or("/", String::as_str))?,
+                    }
+                }
+            }
+            "pwd" => writeln!(out, "{}", self.pwd())?,
+            "tree" => {
+                let args = if args.is_empty() { &["."][..] } else { args };
+                for (_, path) in self.expand_all(args, out)? {
+                    if let Some(node) = self.root.get(&path) {
+                        let name = path.last().map_or("/", String::as_str);
+                        tree(name, node, 0, out)?;
+                    }
+                }
+            }
+            _ => writeln!(out, "{}: command not found, try help", command)?,
+        }
+        Ok(true)
+    }
+
+    // Every path an argument matches, reporting the ones that match nothing.
+    fn expand_all<W: Write>(
+        &self,
+        args: &[&str],
+        out: &mut W,
+    ) -> Result<Vec<(String, Vec<String>)>, Error> {
+        let mut paths = Vec::new();
+        for arg in args {
+            let expanded = self.expand(arg);
+            if expanded.is_empty() {
+                writeln!(out, "{}: No such file or directory", arg)?;
+            }
+            paths.extend(expanded.into_iter().map(|path| (arg.to_string(), path)));
+        }
+        Ok(paths)
+    }
+
+    // Resolves `.`, `..` and wildcards against the tree, relative paths
+    // starting from the working directory.
+    fn expand(&self, arg: &str) -> Vec<Vec<String>> {
+        let mut paths = vec![if arg.starts_with('/') {
+            vec![]
+        } else {
+            self.cwd.clone()
+        }];
+        for component in arg.split('/').filter(|c| !c.is_empty() && *c != ".") {
+            paths = paths
+                .into_iter()
+                .flat_map(|mut path| {
+                    if component == ".." {
+                        path.pop();
+                        return vec![path];
+                    }
+                    let Some(Node::Directory(children)) = self.root.get(&path) else {
+                        return vec![];
+                    };
+                    children
+                        .keys()
+                        .filter(|name| glob_matches(component, name))
+                        .map(|name| {
+                            let mut path = path.clone();
+                            path.push(name.clone());
+                            path
+                        })
+                        .collect()
+                })
+                .collect();
+        }
+        paths
+    }
+}
+
+fn find<W: Write>(path: &str, node: &Node, out: &mut W) -> Result<(), Error> {
+    writeln!(out, "{}", path)?;
+    if let Node::Directory(children) = node {
+        for (name, child) in children {
+            find(
+                &format!("{}/{}", path.trim_end_matches('/'), name),
+                child,
+                out,
+            )?;
+        }
+    }
+    Ok(())
+}
+
+fn tree<W: Write>(name: &str, node: &Node, depth: usize, out: &mut W) -> Result<(), Error> {
+    let indent = "  ".repeat(depth);
+    match node {
+        Node::Directory(children) => {
+            writeln!(out, "{}{}:", indent, name)?;
+            for (name, child) in children {
+                tree(name, child, depth + 1, out)?;
+            }
+        }
+        Node::File(contents) => {
+            let contents = contents.replace('\n', &format!("\n{}  ", indent));
+            writeln!(out, "{}{}: {}", indent, name, contents)?;
+        }
+    }
+    Ok(())
+}
+
+// Shell-style matching where `*` is any run of characters and `?` any one.
+fn glob_matches(pattern: &str, name: &str) -> bool {
+    let pattern = pattern.chars().collect::<Vec<_>>();
+    let name = name.chars().collect::<Vec<_>>();
+    let (mut p, mut n) = (0, 0);
+    let mut star = None;
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p, n));
+                p += 1;
+            }
+            Some(c) if *c == '?' || *c == name[n] => {
+                p += 1;
+                n += 1;
+            }
+            _ => match star {
+                Some((star_p, star_n)) => {
+                    p = star_p + 1;
+                    n = star_n + 1;
+                    star = Some((star_p, star_n + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+// The name of an ACL enum value, or its id if this broker doesn't know it.
+fn named<T: Debug>(id: i8, value: Option<T>) -> String {
+    value.map_or_else(|| id.to_string(), |value| format!("{:?}", value))
+}
+
+fn pretty(value: &serde_json::Value) -> String {
+    serde_json::to_string_pretty(value).unwrap_or_default()
+}
@@ -0,0 +1,37 @@
+pub mod dump_log;
+pub mod format;
+pub mod metadata_shell;
+
+use std::{io, path::PathBuf};
+
+use crate::{config, protocol, storage};
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("{0}")]
+    InvalidArgument(String),
+
+    #[error(transparent)]
+    Config(#[from] config::Error),
+
+    #[error(transparent)]
+    Storage(#[from] storage::Error),
+
+    #[error("metadata log error: {0}")]
+    Metadata(protocol::Error),
+
+    #[error("io error on {path}: {source}")]
+    IOError { path: PathBuf, source: io::Error },
+
+    #[error("failed to write output: {0}")]
+    Output(#[from] io::Error),
+}
+
+// The value following `option`, e.g. the path after `--config`.
+fn value<I: Iterator<Item = String>>(args: &mut I, option: &str) -> Result<String, Error> {
+    args.next()
+        .ok_or_else(|| Error::InvalidArgument(format!("{} needs a value", option)))
+}
+
+#[cfg(test)]
+mod tests;
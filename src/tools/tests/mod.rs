@@ -0,0 +1,249 @@
+use std::path::PathBuf;
+
+use bytes::BytesMut;
+use sha2::Sha256;
+
+use crate::{
+    config::BrokerConfig,
+    metadata::{
+        metadata_value,
+        records::{
+            config_record, partition_record, topic_record, ConfigRecord, PartitionRecord,
+            TopicRecord,
+        },
+        MetadataCache, MetadataLoader, MetadataWriter, Record, RecordBatch, ValueRecord,
+    },
+    model::ConfigResource,
+    protocol::Writable,
+    security::ScramMechanism,
+    storage::meta_properties,
+};
+
+use super::{dump_log::DumpLog, format::Format, metadata_shell::MetadataShell, Error};
+
+fn log_dir() -> PathBuf {
+    std::env::temp_dir().join(format!("format-{}", uuid::Uuid::new_v4()))
+}
+
+#[test]
+fn test_format_bootstraps_metadata_log() {
+    let (first, second) = (log_dir(), log_dir());
+    let args = |extra: &[&str]| {
+        [
+            "--cluster-id",
+            "MkU3OEVBNTcwNTJENDM2Qk",
+            "--override",
+            "node.id=1",
+            "--override",
+        ]
+        .into_iter()
+        .map(str::to_string)
+        .chain([format!("log.dirs={},{}", first.display(), second.display())])
+        .chain(extra.iter().map(|arg| arg.to_string()))
+        .collect::<Vec<_>>()
+    };
+
+    let format = Format::from_args(args(&[
+        "--feature",
+        "metadata.version=20",
+        "--add-scram",
+        "SCRAM-SHA-256=[name=alice,password=alice-secret,salt=c2FsdA==,iterations=4096]",
+        "--add-scram",
+        r#"SCRAM-SHA-256=[name=bob,password="bob,secret=1"]"#,
+    ]))
+    .unwrap();
+    assert_eq!(vec![first.clone(), second.clone()], format.run().unwrap());
+
+    let config = BrokerConfig::from_args(args(&[])[2..].to_vec()).unwrap();
+    let properties = meta_properties::load(&config).unwrap().unwrap();
+    assert_eq!("MkU3OEVBNTcwNTJENDM2Qk", properties.cluster_id());
+    assert_eq!(1, properties.node_id());
+
+    let metadata = MetadataCache::new();
+    MetadataLoader::new(&first, u64::MAX)
+        .poll(&metadata)
+        .unwrap();
+    let image = metadata.image();
+    assert_eq!(Some(&20), image.features().get("metadata.version"));
+    let credential = &image.scram_credentials()[&("alice".to_string(), 1)];
+    let mut salted_password = [0u8; 32];
+    pbkdf2::pbkdf2_hmac::<Sha256>(b"alice-secret", b"salt", 4096, &mut salted_password);
+    let expected =
+        ScramMechanism::Sha256.credential(credential.salt().clone(), &salted_password, 4096);
+    assert_eq!(expected.stored_key(), credential.stored_key());
+    let credential = &image.scram_credentials()[&("bob".to_string(), 1)];
+    pbkdf2::pbkdf2_hmac::<Sha256>(
+        b"bob,secret=1",
+        credential.salt(),
+        4096,
+        &mut salted_password,
+    );
+    let expected =
+        ScramMechanism::Sha256.credential(credential.salt().clone(), &salted_password, 4096);
+    assert_eq!(expected.stored_key(), credential.stored_key());
+
+    assert!(matches!(
+        Format::from_args(args(&[])).unwrap().run(),
+        Err(Error::InvalidArgument(_))
+    ));
+    assert!(Format::from_args(args(&["--ignore-formatted"]))
+        .unwrap()
+        .run()
+        .unwrap()
+        .is_empty());
+    assert!(
+        Format::from_args(args(&["--feature", "metadata.version=0"])).is_err(),
+        "level out of range"
+    );
+}
+
+#[test]
+fn test_dump_log_reports_corrupt_batches() {
+    let topic_id = uuid::Uuid::new_v4();
+    let value = metadata_value(topic_record::API_KEY, 0, &TopicRecord::new("foo", topic_id));
+    let mut data = BytesMut::new();
+    for base_offset in [0, 1] {
+        RecordBatch::new(
+            base_offset,
+            3,
+            2,
+            0,
+            0,
+            1000,
+            1000,
+            -1,
+            -1,
+            -1,
+            vec![Record::Value(ValueRecord::new(
+                0,
+                0,
+                0,
+                None,
+                value.clone(),
+                vec![],
+            ))],
+        )
+        .write(&mut data);
+    }
+    let size = data.len() / 2;
+    let last = data.len() - 1;
+    data[last] ^= 0xff;
+    data.extend_from_slice(b"torn");
+    let dir = log_dir();
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("00000000000000000000.log");
+    std::fs::write(&path, &data).unwrap();
+
+    let dump = |args: &[&str]| {
+        let args = ["--files", path.to_str().unwrap()]
+            .into_iter()
+            .chain(args.iter().copied())
+            .map(str::to_string);
+        let mut out = Vec::new();
+        DumpLog::from_args(args).unwrap().run(&mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    };
+
+    let text = dump(&["--cluster-metadata-decoder"]);
+    let lines = text.lines().collect::<Vec<_>>();
+    assert_eq!(6, lines.len(), "{}", text);
+    assert!(lines[2].starts_with("baseOffset: 0 lastOffset: 0 count: 1"));
+    assert!(lines[2].ends_with("isvalid: true"));
+    assert!(lines[3].contains(&format!(
+        r#""data":{{"name":"foo","topicId":"{}"}},"type":"TOPIC_RECORD""#,
+        meta_properties::encode_uuid(topic_id)
+    )));
+    assert!(
+        lines[4].ends_with("isvalid: false"),
+        "no records for a bad CRC"
+    );
+    assert!(lines[5].starts_with(&format!(
+        "Found 4 invalid bytes at the end of {} starting at position {}",
+        path.display(),
+        2 * size
+    )));
+
+    let json = dump(&["--json"])
+        .lines()
+        .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(3, json.len());
+    assert_eq!(1, json[1]["baseOffset"]);
+    assert_eq!(size, json[1]["position"]);
+    assert_eq!(false, json[1]["isValid"]);
+    assert_eq!(4, json[2]["invalidBytes"]);
+
+    let mut data = BytesMut::new();
+    RecordBatch::new(2, 3, 2, 4, 0, 1000, 1000, -1, -1, -1, vec![]).write(&mut data);
+    std::fs::write(&path, &data).unwrap();
+    let text = dump(&["--print-data-log"]);
+    assert!(text.contains("compresscodec: zstd"), "{}", text);
+    assert!(text.ends_with("| records not shown: compressed with zstd\n"));
+}
+
+#[test]
+fn test_metadata_shell_navigates_image() {
+    let dir = log_dir();
+    let topic_id = uuid::Uuid::new_v4();
+    MetadataWriter::new(&dir)
+        .append(vec![
+            metadata_value(topic_record::API_KEY, 0, &TopicRecord::new("foo", topic_id)),
+            metadata_value(
+                partition_record::API_KEY,
+                0,
+                &PartitionRecord::v0(0, topic_id, vec![1], vec![1], vec![], vec![], 1, 4, 5, 0),
+            ),
+            metadata_value(
+                config_record::API_KEY,
+                0,
+                &ConfigRecord::new(
+                    ConfigResource::TOPIC,
+                    "foo",
+                    "cleanup.policy",
+                    Some("compact"),
+                ),
+            ),
+        ])
+        .unwrap();
+
+    let shell = |args: &[&str], input: &str| {
+        let args = ["--directory", dir.to_str().unwrap()]
+            .into_iter()
+            .chain(args.iter().copied())
+            .map(str::to_string);
+        let mut out = Vec::new();
+        MetadataShell::from_args(args)
+            .unwrap()
+            .run(input.as_bytes(), &mut out, false)
+            .unwrap();
+        String::from_utf8(out).unwrap()
+    };
+
+    assert_eq!("0\nid\n", shell(&["ls", "/topics/foo"], ""));
+    let out = shell(
+        &[],
+        "cd /topics/foo\ncat id 0/data\ncat /configs/*/foo/cleanup.policy\nfind ../f?o\nexit\npwd\n",
+    );
+    let lines = out.lines().collect::<Vec<_>>();
+    assert_eq!(meta_properties::encode_uuid(topic_id), lines[0]);
+    let data = lines[1..]
+        .iter()
+        .take_while(|line| **line != "compact")
+        .copied()
+        .collect::<String>();
+    let data = serde_json::from_str::<serde_json::Value>(&data).unwrap();
+    assert_eq!(1, data["leader"]);
+    assert_eq!(4, data["leaderEpoch"]);
+    assert_eq!(serde_json::json!([1]), data["isr"]);
+    assert_eq!(
+        vec![
+            "compact",
+            "/topics/foo",
+            "/topics/foo/0",
+            "/topics/foo/0/data",
+            "/topics/foo/id"
+        ],
+        lines[lines.len() - 5..],
+        "nothing runs after exit"
+    );
+}